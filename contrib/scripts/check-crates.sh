@@ -41,6 +41,7 @@ buildargs=(
     "-p nostr --all-features"                                     # All features
     "-p nostr-database"
     "-p nostr-lmdb"
+    "-p nostr-sqlite"
     "-p nostr-mls-storage"
     "-p nostr-mls-memory-storage"
     "-p nostr-mls-sqlite-storage"
@@ -62,6 +63,7 @@ buildargs=(
 
 skip_msrv=(
     "-p nostr-lmdb"                       # MSRV: 1.72.0
+    "-p nostr-sqlite"                     # MSRV: 1.75.0
     "-p nostr-mls-storage"                # MSRV: 1.74.0
     "-p nostr-mls-memory-storage"         # MSRV: 1.74.0
     "-p nostr-mls-sqlite-storage"         # MSRV: 1.74.0
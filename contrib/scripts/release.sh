@@ -6,6 +6,7 @@ args=(
     "-p nostr"
     "-p nostr-database"
     "-p nostr-lmdb"
+    "-p nostr-sqlite"
     "-p nostr-mls-storage"
     "-p nostr-mls-memory-storage"
     "-p nostr-mls-sqlite-storage"
@@ -0,0 +1,252 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Database changes
+
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use async_utility::futures_util::Stream;
+use nostr::{Event, Filter};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Database change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatabaseChange {
+    /// A new event has been saved
+    Saved(Event),
+    /// A replaceable or addressable event has been replaced by a newer one
+    Replaced {
+        /// Removed event
+        old: Event,
+        /// New event
+        new: Event,
+    },
+    /// An event has been removed (NIP09 deletion, delete by filter, etc.)
+    Deleted(Event),
+    /// An event has been evicted to respect the capacity limit of the database
+    Evicted(Event),
+    /// The database has been wiped
+    Wiped,
+}
+
+impl DatabaseChange {
+    /// Check if the change involves an event that match the [`Filter`]
+    pub fn match_filter(&self, filter: &Filter) -> bool {
+        match self {
+            Self::Saved(event) | Self::Deleted(event) | Self::Evicted(event) => {
+                filter.match_event(event)
+            }
+            Self::Replaced { old, new } => filter.match_event(new) || filter.match_event(old),
+            // All the events have been removed
+            Self::Wiped => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    filter: Filter,
+    tx: UnboundedSender<DatabaseChange>,
+}
+
+/// Database change notifier
+///
+/// Keep track of the subscribers and dispatch the [`DatabaseChange`]s to the ones with a matching filter.
+/// Used by the database backends to implement [`NostrEventsDatabase::subscribe`](crate::NostrEventsDatabase::subscribe).
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseChangeNotifier {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl DatabaseChangeNotifier {
+    /// New notifier
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to the changes that match the [`Filter`]
+    pub fn subscribe(&self, filter: Filter) -> DatabaseChangeStream {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        subscribers.push(Subscriber { filter, tx });
+
+        DatabaseChangeStream { inner: rx }
+    }
+
+    /// Check if there are active subscribers
+    ///
+    /// Useful to avoid collecting the changes if no one is listening.
+    pub fn has_subscribers(&self) -> bool {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        subscribers.retain(|s| !s.tx.is_closed());
+        !subscribers.is_empty()
+    }
+
+    /// Send the changes to the subscribers with a matching filter
+    ///
+    /// The subscribers that dropped the stream are removed.
+    pub fn notify<I>(&self, changes: I)
+    where
+        I: IntoIterator<Item = DatabaseChange>,
+    {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        for change in changes.into_iter() {
+            subscribers.retain(|s| {
+                if change.match_filter(&s.filter) {
+                    s.tx.send(change.clone()).is_ok()
+                } else {
+                    !s.tx.is_closed()
+                }
+            });
+        }
+    }
+
+    /// Notify a saved event
+    ///
+    /// `removed` must contain the events removed while saving the new one
+    /// (i.e., the replaced event or the targets of a NIP09 deletion).
+    pub fn notify_saved(&self, event: &Event, removed: Vec<Event>) {
+        let mut changes: Vec<DatabaseChange> = Vec::with_capacity(removed.len() + 1);
+        let mut replaced: bool = false;
+
+        for old in removed.into_iter() {
+            if is_replacement(event, &old) {
+                replaced = true;
+                changes.push(DatabaseChange::Replaced {
+                    old,
+                    new: event.clone(),
+                });
+            } else {
+                changes.push(DatabaseChange::Deleted(old));
+            }
+        }
+
+        if !replaced {
+            changes.push(DatabaseChange::Saved(event.clone()));
+        }
+
+        self.notify(changes);
+    }
+
+    /// Notify removed events
+    #[inline]
+    pub fn notify_deleted<I>(&self, events: I)
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        self.notify(events.into_iter().map(DatabaseChange::Deleted));
+    }
+}
+
+/// Check if `old` has been replaced by `new`
+fn is_replacement(new: &Event, old: &Event) -> bool {
+    if new.kind != old.kind || new.pubkey != old.pubkey {
+        return false;
+    }
+
+    if new.kind.is_replaceable() {
+        return true;
+    }
+
+    new.kind.is_addressable() && new.tags.identifier() == old.tags.identifier()
+}
+
+/// Stream of [`DatabaseChange`]s
+///
+/// The subscription is automatically removed when the stream is dropped.
+#[derive(Debug)]
+pub struct DatabaseChangeStream {
+    inner: UnboundedReceiver<DatabaseChange>,
+}
+
+impl DatabaseChangeStream {
+    /// Closes the stream without dropping it.
+    #[inline]
+    pub fn close(&mut self) {
+        self.inner.close();
+    }
+}
+
+impl Stream for DatabaseChangeStream {
+    type Item = DatabaseChange;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_utility::futures_util::StreamExt;
+    use nostr::{EventBuilder, Keys, Kind, Tag};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_notifier() {
+        let keys = Keys::generate();
+        let notifier = DatabaseChangeNotifier::new();
+
+        assert!(!notifier.has_subscribers());
+
+        let mut notes = notifier.subscribe(Filter::new().kind(Kind::TextNote));
+        let mut metadata = notifier.subscribe(Filter::new().kind(Kind::Metadata));
+
+        assert!(notifier.has_subscribers());
+
+        let note = EventBuilder::text_note("hello")
+            .sign_with_keys(&keys)
+            .unwrap();
+        notifier.notify_saved(&note, Vec::new());
+
+        let old = EventBuilder::new(Kind::Metadata, "{}")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let new = EventBuilder::new(Kind::Metadata, "{}")
+            .custom_created_at(old.created_at + 1)
+            .sign_with_keys(&keys)
+            .unwrap();
+        notifier.notify_saved(&new, vec![old.clone()]);
+
+        let deletion = EventBuilder::new(Kind::EventDeletion, "")
+            .tag(Tag::event(note.id))
+            .sign_with_keys(&keys)
+            .unwrap();
+        notifier.notify_saved(&deletion, vec![note.clone()]);
+
+        assert_eq!(
+            notes.next().await,
+            Some(DatabaseChange::Saved(note.clone()))
+        );
+        assert_eq!(notes.next().await, Some(DatabaseChange::Deleted(note)));
+        assert_eq!(
+            metadata.next().await,
+            Some(DatabaseChange::Replaced { old, new })
+        );
+
+        // Drop the subscriptions
+        notifier.notify([DatabaseChange::Wiped]);
+        assert_eq!(notes.next().await, Some(DatabaseChange::Wiped));
+        assert_eq!(metadata.next().await, Some(DatabaseChange::Wiped));
+
+        drop(notes);
+        drop(metadata);
+
+        assert!(!notifier.has_subscribers());
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Sync checkpoint trait
+
+use std::fmt;
+
+use nostr::prelude::*;
+
+use crate::error::DatabaseError;
+
+/// Nostr Database sync checkpoint trait
+///
+/// Keep the time of the last successful negentropy reconciliation of a filter with a relay,
+/// to narrow the next ones.
+///
+/// Not part of [`NostrDatabase`](crate::NostrDatabase): pass it to the sync scheduler to resume after a restart.
+pub trait NostrDatabaseCheckpoint: fmt::Debug + Send + Sync {
+    /// Get the sync checkpoint of a filter with a relay
+    ///
+    /// Returns `None` if the checkpoint has never been saved.
+    fn sync_checkpoint<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        filter: &'a Filter,
+    ) -> BoxedFuture<'a, Result<Option<Timestamp>, DatabaseError>>;
+
+    /// Save the sync checkpoint of a filter with a relay
+    ///
+    /// Any previous checkpoint of the same relay and filter is overwritten.
+    fn save_sync_checkpoint<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        filter: &'a Filter,
+        timestamp: Timestamp,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>>;
+}
@@ -138,6 +138,10 @@ pub struct DatabaseEventResult {
     pub status: SaveEventStatus,
     /// List of events that should be removed from database
     pub to_discard: HashSet<EventId>,
+    /// Events removed from the helper (replaced or deleted)
+    pub removed: Vec<Event>,
+    /// Event evicted from the helper to respect the max capacity
+    pub evicted: Option<Event>,
 }
 
 enum InternalQueryResult<'a> {
@@ -199,6 +203,8 @@ impl InternalDatabaseHelper {
             return DatabaseEventResult {
                 status: SaveEventStatus::Rejected(RejectedReason::Duplicate),
                 to_discard: HashSet::new(),
+                removed: Vec::new(),
+                evicted: None,
             };
         }
 
@@ -209,6 +215,8 @@ impl InternalDatabaseHelper {
             return DatabaseEventResult {
                 status: SaveEventStatus::Rejected(RejectedReason::Deleted),
                 to_discard,
+                removed: Vec::new(),
+                evicted: None,
             };
         }
 
@@ -218,6 +226,8 @@ impl InternalDatabaseHelper {
             return DatabaseEventResult {
                 status: SaveEventStatus::Rejected(RejectedReason::Expired),
                 to_discard,
+                removed: Vec::new(),
+                evicted: None,
             };
         }
 
@@ -319,7 +329,8 @@ impl InternalDatabaseHelper {
         }
 
         // Remove events
-        self.discard_events(&to_discard);
+        let removed: Vec<Event> = self.discard_events(&to_discard);
+        let mut evicted: Option<Event> = None;
 
         // Insert event
         if status.is_success() {
@@ -357,14 +368,22 @@ impl InternalDatabaseHelper {
 
             if let Some(event) = pop {
                 to_discard.insert(event.id);
+                evicted = Some(event.as_ref().clone());
                 self.discard_event(event);
             }
         }
 
-        DatabaseEventResult { status, to_discard }
+        DatabaseEventResult {
+            status,
+            to_discard,
+            removed,
+            evicted,
+        }
     }
 
-    fn discard_events(&mut self, ids: &HashSet<EventId>) {
+    fn discard_events(&mut self, ids: &HashSet<EventId>) -> Vec<Event> {
+        let mut removed: Vec<Event> = Vec::new();
+
         for id in ids.iter() {
             if let Some(ev) = self.ids.remove(id) {
                 self.events.remove(&ev);
@@ -386,9 +405,13 @@ impl InternalDatabaseHelper {
                 if let Some(set) = self.kind_author_index.get_mut(&(ev.kind, ev.pubkey)) {
                     set.remove(&ev);
                 }
+
+                removed.push(ev.as_ref().clone());
             }
             self.deleted_ids.insert(*id);
         }
+
+        removed
     }
 
     fn discard_event(&mut self, ev: DatabaseEvent) {
@@ -419,6 +442,8 @@ impl InternalDatabaseHelper {
             return DatabaseEventResult {
                 status: SaveEventStatus::Rejected(RejectedReason::Ephemeral),
                 to_discard: HashSet::new(),
+                removed: Vec::new(),
+                evicted: None,
             };
         }
         let now = Timestamp::now();
@@ -10,7 +10,7 @@ use nostr::prelude::*;
 
 pub mod helper;
 
-use crate::{DatabaseError, Events, Profile};
+use crate::{DatabaseChangeStream, DatabaseError, Events, Profile};
 
 /// NIP65 relays map
 pub type RelaysMap = HashMap<RelayUrl, Option<RelayMetadata>>;
@@ -146,6 +146,18 @@ pub trait NostrEventsDatabase: fmt::Debug + Send + Sync {
 
     /// Delete all events that match the [Filter]
     fn delete(&self, filter: Filter) -> BoxedFuture<Result<(), DatabaseError>>;
+
+    /// Subscribe to the changes of the events that match the [`Filter`]
+    ///
+    /// The stream receives a [`DatabaseChange`](crate::DatabaseChange) every time a matching event is saved,
+    /// replaced or deleted, regardless of the source of the write.
+    /// The subscription ends when the stream is dropped.
+    ///
+    /// By default returns [`DatabaseError::NotSupported`].
+    fn subscribe(&self, filter: Filter) -> Result<DatabaseChangeStream, DatabaseError> {
+        let _ = filter;
+        Err(DatabaseError::NotSupported)
+    }
 }
 
 /// Nostr Event Store Extension
@@ -0,0 +1,42 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Gossip trait
+
+use std::collections::HashMap;
+use std::fmt;
+
+use nostr::prelude::*;
+
+use crate::error::DatabaseError;
+
+/// Gossip status of a public key
+///
+/// The relay lists are stored as regular events (NIP-65 and NIP-17),
+/// so this keeps track only of the timestamps needed to know when they have to be refreshed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GossipPublicKeyStatus {
+    /// Timestamp of the last outdated check
+    pub last_check: Timestamp,
+    /// Timestamp of the last NIP-17 relay list update
+    pub nip17_last_update: Timestamp,
+    /// Timestamp of the last NIP-65 relay list update
+    pub nip65_last_update: Timestamp,
+}
+
+/// Nostr Database gossip trait
+///
+/// Not part of [`NostrDatabase`](crate::NostrDatabase): pass it to the client to persist the gossip statuses across restarts.
+pub trait NostrDatabaseGossip: fmt::Debug + Send + Sync {
+    /// Get the gossip status of all the tracked public keys
+    fn gossip_statuses(
+        &self,
+    ) -> BoxedFuture<Result<HashMap<PublicKey, GossipPublicKeyStatus>, DatabaseError>>;
+
+    /// Save (or replace) the gossip status of some public keys
+    fn save_gossip_statuses(
+        &self,
+        statuses: HashMap<PublicKey, GossipPublicKeyStatus>,
+    ) -> BoxedFuture<Result<(), DatabaseError>>;
+}
@@ -0,0 +1,35 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay health trait
+
+use std::fmt;
+
+use nostr::prelude::*;
+
+use crate::error::DatabaseError;
+
+/// Nostr Database relay health trait
+///
+/// Keep the relay health scores computed by the relay pool, to restore them after a restart.
+///
+/// Not part of [`NostrDatabase`](crate::NostrDatabase): pass it to the relay pool to persist the scores.
+pub trait NostrDatabaseHealth: fmt::Debug + Send + Sync {
+    /// Get the health score of a relay
+    ///
+    /// Returns `None` if the score of the relay has never been saved.
+    fn relay_health_score<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+    ) -> BoxedFuture<'a, Result<Option<f64>, DatabaseError>>;
+
+    /// Save the health score of a relay
+    ///
+    /// The score is in the `0.0..=1.0` range. Any previous score of the relay is overwritten.
+    fn save_relay_health_score<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        score: f64,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>>;
+}
@@ -13,16 +13,24 @@ use std::sync::Arc;
 
 pub use nostr;
 
+mod change;
+mod checkpoint;
 mod collections;
 mod error;
 mod events;
 #[cfg(feature = "flatbuf")]
 pub mod flatbuffers;
+mod gossip;
+mod health;
 pub mod memory;
+mod outbox;
 pub mod prelude;
 pub mod profile;
+mod thread;
 mod wipe;
 
+pub use self::change::{DatabaseChange, DatabaseChangeNotifier, DatabaseChangeStream};
+pub use self::checkpoint::NostrDatabaseCheckpoint;
 pub use self::collections::events::Events;
 pub use self::error::DatabaseError;
 pub use self::events::helper::{DatabaseEventResult, DatabaseHelper};
@@ -32,8 +40,12 @@ pub use self::events::{
 };
 #[cfg(feature = "flatbuf")]
 pub use self::flatbuffers::{FlatBufferBuilder, FlatBufferDecode, FlatBufferEncode};
+pub use self::gossip::{GossipPublicKeyStatus, NostrDatabaseGossip};
+pub use self::health::NostrDatabaseHealth;
 pub use self::memory::{MemoryDatabase, MemoryDatabaseOptions};
+pub use self::outbox::NostrDatabaseOutbox;
 pub use self::profile::Profile;
+pub use self::thread::{Thread, ThreadNode};
 pub use self::wipe::NostrDatabaseWipe;
 
 /// Backend
@@ -4,6 +4,7 @@
 
 //! Memory (RAM) Storage backend for Nostr apps
 
+use std::collections::HashMap;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 
@@ -12,8 +13,10 @@ use nostr::prelude::*;
 use tokio::sync::RwLock;
 
 use crate::{
-    Backend, DatabaseError, DatabaseEventResult, DatabaseEventStatus, DatabaseHelper, Events,
-    NostrDatabase, NostrDatabaseWipe, NostrEventsDatabase, SaveEventStatus,
+    Backend, DatabaseChange, DatabaseChangeNotifier, DatabaseChangeStream, DatabaseError,
+    DatabaseEventResult, DatabaseEventStatus, DatabaseHelper, Events, GossipPublicKeyStatus,
+    NostrDatabase, NostrDatabaseCheckpoint, NostrDatabaseGossip, NostrDatabaseHealth,
+    NostrDatabaseOutbox, NostrDatabaseWipe, NostrEventsDatabase, SaveEventStatus,
 };
 
 const MAX_EVENTS: usize = 35_000;
@@ -59,6 +62,11 @@ enum InnerMemoryDatabase {
 #[derive(Debug, Clone)]
 pub struct MemoryDatabase {
     inner: InnerMemoryDatabase,
+    checkpoints: Arc<RwLock<HashMap<(RelayUrl, Filter), Timestamp>>>,
+    gossip: Arc<RwLock<HashMap<PublicKey, GossipPublicKeyStatus>>>,
+    health: Arc<RwLock<HashMap<RelayUrl, f64>>>,
+    outbox: Arc<RwLock<HashMap<RelayUrl, HashMap<EventId, Event>>>>,
+    notifier: DatabaseChangeNotifier,
 }
 
 impl Default for MemoryDatabase {
@@ -99,7 +107,14 @@ impl MemoryDatabase {
             InnerMemoryDatabase::Tracker(Arc::new(RwLock::new(cache)))
         };
 
-        Self { inner }
+        Self {
+            inner,
+            checkpoints: Arc::new(RwLock::new(HashMap::new())),
+            gossip: Arc::new(RwLock::new(HashMap::new())),
+            health: Arc::new(RwLock::new(HashMap::new())),
+            outbox: Arc::new(RwLock::new(HashMap::new())),
+            notifier: DatabaseChangeNotifier::new(),
+        }
     }
 }
 
@@ -124,7 +139,21 @@ impl NostrEventsDatabase for MemoryDatabase {
                     Ok(SaveEventStatus::Success)
                 }
                 InnerMemoryDatabase::Full(helper) => {
-                    let DatabaseEventResult { status, .. } = helper.index_event(event).await;
+                    let DatabaseEventResult {
+                        status,
+                        removed,
+                        evicted,
+                        ..
+                    } = helper.index_event(event).await;
+
+                    if status.is_success() {
+                        self.notifier.notify_saved(event, removed);
+
+                        if let Some(evicted) = evicted {
+                            self.notifier.notify([DatabaseChange::Evicted(evicted)]);
+                        }
+                    }
+
                     Ok(status)
                 }
             }
@@ -221,17 +250,166 @@ impl NostrEventsDatabase for MemoryDatabase {
             match &self.inner {
                 InnerMemoryDatabase::Tracker(..) => Ok(()),
                 InnerMemoryDatabase::Full(helper) => {
+                    // Collect the events to remove only if someone is listening
+                    let events: Option<Events> = if self.notifier.has_subscribers() {
+                        Some(helper.query(filter.clone()).await)
+                    } else {
+                        None
+                    };
+
                     helper.delete(filter).await;
+
+                    if let Some(events) = events {
+                        self.notifier.notify_deleted(events);
+                    }
+
                     Ok(())
                 }
             }
         })
     }
+
+    fn subscribe(&self, filter: Filter) -> Result<DatabaseChangeStream, DatabaseError> {
+        match &self.inner {
+            // Events aren't stored
+            InnerMemoryDatabase::Tracker(..) => Err(DatabaseError::NotSupported),
+            InnerMemoryDatabase::Full(..) => Ok(self.notifier.subscribe(filter)),
+        }
+    }
+}
+
+impl NostrDatabaseCheckpoint for MemoryDatabase {
+    fn sync_checkpoint<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        filter: &'a Filter,
+    ) -> BoxedFuture<'a, Result<Option<Timestamp>, DatabaseError>> {
+        Box::pin(async move {
+            let checkpoints = self.checkpoints.read().await;
+            Ok(checkpoints
+                .get(&(relay_url.clone(), filter.clone()))
+                .copied())
+        })
+    }
+
+    fn save_sync_checkpoint<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        filter: &'a Filter,
+        timestamp: Timestamp,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            let mut checkpoints = self.checkpoints.write().await;
+            checkpoints.insert((relay_url.clone(), filter.clone()), timestamp);
+            Ok(())
+        })
+    }
+}
+
+impl NostrDatabaseGossip for MemoryDatabase {
+    fn gossip_statuses(
+        &self,
+    ) -> BoxedFuture<Result<HashMap<PublicKey, GossipPublicKeyStatus>, DatabaseError>> {
+        Box::pin(async move {
+            let gossip = self.gossip.read().await;
+            Ok(gossip.clone())
+        })
+    }
+
+    fn save_gossip_statuses(
+        &self,
+        statuses: HashMap<PublicKey, GossipPublicKeyStatus>,
+    ) -> BoxedFuture<Result<(), DatabaseError>> {
+        Box::pin(async move {
+            let mut gossip = self.gossip.write().await;
+            gossip.extend(statuses);
+            Ok(())
+        })
+    }
+}
+
+impl NostrDatabaseHealth for MemoryDatabase {
+    fn relay_health_score<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+    ) -> BoxedFuture<'a, Result<Option<f64>, DatabaseError>> {
+        Box::pin(async move {
+            let health = self.health.read().await;
+            Ok(health.get(relay_url).copied())
+        })
+    }
+
+    fn save_relay_health_score<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        score: f64,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            let mut health = self.health.write().await;
+            health.insert(relay_url.clone(), score);
+            Ok(())
+        })
+    }
+}
+
+impl NostrDatabaseOutbox for MemoryDatabase {
+    fn outbox_events<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+    ) -> BoxedFuture<'a, Result<Vec<Event>, DatabaseError>> {
+        Box::pin(async move {
+            let outbox = self.outbox.read().await;
+            let mut events: Vec<Event> = outbox
+                .get(relay_url)
+                .map(|events| events.values().cloned().collect())
+                .unwrap_or_default();
+            events.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+            Ok(events)
+        })
+    }
+
+    fn save_outbox_event<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        event: &'a Event,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            let mut outbox = self.outbox.write().await;
+            outbox
+                .entry(relay_url.clone())
+                .or_default()
+                .insert(event.id, event.clone());
+            Ok(())
+        })
+    }
+
+    fn remove_outbox_event<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        event_id: &'a EventId,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            let mut outbox = self.outbox.write().await;
+            if let Some(events) = outbox.get_mut(relay_url) {
+                events.remove(event_id);
+
+                if events.is_empty() {
+                    outbox.remove(relay_url);
+                }
+            }
+            Ok(())
+        })
+    }
 }
 
 impl NostrDatabaseWipe for MemoryDatabase {
     fn wipe(&self) -> BoxedFuture<Result<(), DatabaseError>> {
         Box::pin(async move {
+            self.checkpoints.write().await.clear();
+            self.gossip.write().await.clear();
+            self.health.write().await.clear();
+            self.outbox.write().await.clear();
+
             match &self.inner {
                 InnerMemoryDatabase::Tracker(tracker) => {
                     let mut seen_event_ids = tracker.write().await;
@@ -239,6 +417,7 @@ impl NostrDatabaseWipe for MemoryDatabase {
                 }
                 InnerMemoryDatabase::Full(helper) => {
                     helper.clear().await;
+                    self.notifier.notify([DatabaseChange::Wiped]);
                 }
             }
 
@@ -246,3 +425,42 @@ impl NostrDatabaseWipe for MemoryDatabase {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_utility::futures_util::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribe_evicted_and_wiped() {
+        let db = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: Some(1),
+        });
+        let keys = Keys::generate();
+
+        let mut notes = db.subscribe(Filter::new().kind(Kind::TextNote)).unwrap();
+
+        let first = EventBuilder::text_note("first")
+            .custom_created_at(Timestamp::from_secs(1_700_000_000))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let second = EventBuilder::text_note("second")
+            .custom_created_at(Timestamp::from_secs(1_700_000_100))
+            .sign_with_keys(&keys)
+            .unwrap();
+        db.save_event(&first).await.unwrap();
+        db.save_event(&second).await.unwrap();
+
+        assert_eq!(
+            notes.next().await,
+            Some(DatabaseChange::Saved(first.clone()))
+        );
+        assert_eq!(notes.next().await, Some(DatabaseChange::Saved(second)));
+        assert_eq!(notes.next().await, Some(DatabaseChange::Evicted(first)));
+
+        db.wipe().await.unwrap();
+        assert_eq!(notes.next().await, Some(DatabaseChange::Wiped));
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Outbox trait
+
+use std::fmt;
+
+use nostr::prelude::*;
+
+use crate::error::DatabaseError;
+
+/// Nostr Database outbox trait
+///
+/// Keep the events that couldn't be sent to a relay (i.e., because it was unreachable),
+/// to replay them when the connection is established again.
+///
+/// Not part of [`NostrDatabase`](crate::NostrDatabase): pass it to the relay pool to enable the offline outbox.
+pub trait NostrDatabaseOutbox: fmt::Debug + Send + Sync {
+    /// Get the pending events of a relay, sorted by `created_at` (oldest first)
+    fn outbox_events<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+    ) -> BoxedFuture<'a, Result<Vec<Event>, DatabaseError>>;
+
+    /// Add an event to the outbox of a relay
+    ///
+    /// Adding an event already in the outbox is a no-op.
+    fn save_outbox_event<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        event: &'a Event,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>>;
+
+    /// Remove an event from the outbox of a relay
+    fn remove_outbox_event<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        event_id: &'a EventId,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>>;
+}
@@ -0,0 +1,494 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Thread
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use nostr::nips::nip01::Coordinate;
+use nostr::nips::nip10;
+use nostr::nips::nip22::{self, Comment};
+use nostr::{Event, EventId, Kind, TagKind, TagStandard, Timestamp};
+
+/// Node of a [`Thread`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadNode {
+    /// Event ID
+    pub id: EventId,
+    /// Event
+    ///
+    /// `None` if the event is referenced by the thread but missing (placeholder).
+    pub event: Option<Event>,
+    /// Replies (NIP10) and comments (NIP22), oldest first
+    pub replies: Vec<ThreadNode>,
+    /// Text notes quoting this event without replying to it (NIP18), oldest first
+    pub quotes: Vec<ThreadNode>,
+}
+
+impl ThreadNode {
+    /// Check if the event is missing
+    #[inline]
+    pub fn is_missing(&self) -> bool {
+        self.event.is_none()
+    }
+
+    #[inline]
+    fn created_at(&self) -> Option<Timestamp> {
+        self.event.as_ref().map(|event| event.created_at)
+    }
+}
+
+/// Conversation tree
+///
+/// Built from a set of events (i.e., [`Events`](crate::Events) from the database or from a fetch),
+/// resolving:
+/// * the NIP10 `root` and `reply` markers and the deprecated positional `e` tags of the text notes;
+/// * the NIP22 root and parent scopes of the comments (`E`/`e` and `A`/`a` tags);
+/// * the NIP18 `q` tags of the text notes that aren't replies.
+///
+/// The events referenced as parent but not included in the set are added as placeholders
+/// (see [`ThreadNode::is_missing`]) and listed in [`Thread::missing`], to fetch them and rebuild the thread.
+/// The replies of a missing event are attached to it and, if known, the missing event is attached to the root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Thread {
+    roots: Vec<ThreadNode>,
+    missing: Vec<EventId>,
+    missing_coordinates: Vec<Coordinate>,
+}
+
+/// Link between two nodes
+#[derive(Debug, Clone, Copy)]
+enum Link {
+    Reply(EventId),
+    Quote(EventId),
+}
+
+impl Link {
+    /// Linked event ID
+    #[inline]
+    fn id(&self) -> &EventId {
+        match self {
+            Self::Reply(id) | Self::Quote(id) => id,
+        }
+    }
+}
+
+impl Thread {
+    /// Build the thread
+    pub fn build<I>(events: I) -> Self
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        let mut events: HashMap<EventId, Event> =
+            events.into_iter().map(|event| (event.id, event)).collect();
+
+        // Index the replaceable and addressable events, to resolve the NIP22 coordinates
+        let mut addresses: HashMap<Coordinate, &Event> = HashMap::new();
+        for event in events.values() {
+            if let Some(coordinate) = event.coordinate() {
+                addresses
+                    .entry(coordinate.into_owned())
+                    .and_modify(|current| {
+                        if event.created_at > current.created_at {
+                            *current = event;
+                        }
+                    })
+                    .or_insert(event);
+            }
+        }
+
+        // Sort the events, to have a deterministic output
+        let mut sorted: Vec<&Event> = events.values().collect();
+        sorted.sort_by_key(|event| (event.created_at, event.id));
+
+        // Links to the parent nodes
+        let mut links: HashMap<EventId, Link> = HashMap::new();
+        let mut missing: Vec<EventId> = Vec::new();
+        let mut missing_coordinates: Vec<Coordinate> = Vec::new();
+
+        for event in sorted.into_iter() {
+            let (parent, root) = match event.kind {
+                Kind::TextNote => (
+                    nip10::extract_reply(event).copied(),
+                    nip10::extract_root(event).copied(),
+                ),
+                Kind::Comment => (
+                    resolve_comment(
+                        nip22::extract_parent(event),
+                        &addresses,
+                        &mut missing_coordinates,
+                    ),
+                    resolve_comment(
+                        nip22::extract_root(event),
+                        &addresses,
+                        &mut missing_coordinates,
+                    ),
+                ),
+                _ => (None, None),
+            };
+
+            match parent {
+                Some(parent) if parent != event.id => {
+                    links.insert(event.id, Link::Reply(parent));
+
+                    // Add a placeholder for the missing parent, attached to the root (if known)
+                    if !events.contains_key(&parent) && !missing.contains(&parent) {
+                        missing.push(parent);
+
+                        if let Some(root) = root {
+                            if root != parent && root != event.id {
+                                links.insert(parent, Link::Reply(root));
+
+                                if !events.contains_key(&root) && !missing.contains(&root) {
+                                    missing.push(root);
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    // Not a reply: check if it's quoting an event of the thread
+                    if let Some(quoted) = extract_quote(event, &events) {
+                        links.insert(event.id, Link::Quote(quoted));
+                    }
+                }
+            }
+        }
+
+        // Collect the children of each node, reversing the links
+        let mut children: HashMap<EventId, Vec<Link>> = HashMap::new();
+        for (id, link) in links.iter() {
+            let child: Link = match link {
+                Link::Reply(..) => Link::Reply(*id),
+                Link::Quote(..) => Link::Quote(*id),
+            };
+            children.entry(*link.id()).or_default().push(child);
+        }
+
+        // Walk the tree from the top-level nodes.
+        // The nodes not reached are in a reference cycle: break it, walking from them too.
+        let mut nodes: Vec<(Option<Timestamp>, EventId)> = events
+            .values()
+            .map(|event| (Some(event.created_at), event.id))
+            .chain(missing.iter().map(|id| (None, *id)))
+            .collect();
+        nodes.sort();
+        nodes.sort_by_key(|(_, id)| links.contains_key(id));
+
+        let mut top_level: Vec<EventId> = Vec::new();
+        let mut visited: HashSet<EventId> = HashSet::with_capacity(nodes.len());
+        let mut order: Vec<EventId> = Vec::with_capacity(nodes.len());
+        let mut tree: HashMap<EventId, Vec<Link>> = HashMap::new();
+
+        for (_, id) in nodes.into_iter() {
+            if !visited.insert(id) {
+                continue;
+            }
+
+            top_level.push(id);
+
+            let mut queue: VecDeque<EventId> = VecDeque::from([id]);
+            while let Some(id) = queue.pop_front() {
+                order.push(id);
+
+                for child in children.remove(&id).unwrap_or_default().into_iter() {
+                    let child_id: EventId = *child.id();
+                    if visited.insert(child_id) {
+                        tree.entry(id).or_default().push(child);
+                        queue.push_back(child_id);
+                    }
+                }
+            }
+        }
+
+        // Build the nodes bottom-up
+        let mut built: HashMap<EventId, ThreadNode> = HashMap::with_capacity(order.len());
+        for id in order.into_iter().rev() {
+            let mut node = ThreadNode {
+                id,
+                event: events.remove(&id),
+                replies: Vec::new(),
+                quotes: Vec::new(),
+            };
+
+            for child in tree.remove(&id).unwrap_or_default().into_iter() {
+                match child {
+                    Link::Reply(child) => node.replies.extend(built.remove(&child)),
+                    Link::Quote(child) => node.quotes.extend(built.remove(&child)),
+                }
+            }
+
+            sort(&mut node.replies);
+            sort(&mut node.quotes);
+
+            built.insert(id, node);
+        }
+
+        let mut roots: Vec<ThreadNode> = top_level
+            .into_iter()
+            .filter_map(|id| built.remove(&id))
+            .collect();
+        sort(&mut roots);
+
+        Self {
+            roots,
+            missing,
+            missing_coordinates,
+        }
+    }
+
+    /// Top-level nodes, oldest first
+    ///
+    /// Usually a single root, but the set may include more threads or unrelated events.
+    #[inline]
+    pub fn roots(&self) -> &[ThreadNode] {
+        &self.roots
+    }
+
+    /// Consume the thread and get the top-level nodes
+    #[inline]
+    pub fn into_roots(self) -> Vec<ThreadNode> {
+        self.roots
+    }
+
+    /// IDs of the events referenced by the thread but missing
+    #[inline]
+    pub fn missing(&self) -> &[EventId] {
+        &self.missing
+    }
+
+    /// Coordinates referenced by the NIP22 comments but missing
+    ///
+    /// The comments replying to them are added as top-level nodes.
+    #[inline]
+    pub fn missing_coordinates(&self) -> &[Coordinate] {
+        &self.missing_coordinates
+    }
+
+    /// Check if the thread is complete (no missing events)
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.missing_coordinates.is_empty()
+    }
+}
+
+fn resolve_comment(
+    comment: Option<Comment>,
+    addresses: &HashMap<Coordinate, &Event>,
+    missing_coordinates: &mut Vec<Coordinate>,
+) -> Option<EventId> {
+    match comment? {
+        Comment::Event { id, .. } => Some(*id),
+        Comment::Coordinate { address, .. } => match addresses.get(address) {
+            Some(event) => Some(event.id),
+            None => {
+                if !missing_coordinates.contains(address) {
+                    missing_coordinates.push(address.clone());
+                }
+                None
+            }
+        },
+        // External content isn't an event
+        Comment::External { .. } => None,
+    }
+}
+
+/// Get the first quoted event included in the set
+fn extract_quote(event: &Event, events: &HashMap<EventId, Event>) -> Option<EventId> {
+    if event.kind != Kind::TextNote {
+        return None;
+    }
+
+    event
+        .tags
+        .filter_standardized(TagKind::q())
+        .find_map(|tag| match tag {
+            TagStandard::Quote { event_id, .. } if events.contains_key(event_id) => Some(*event_id),
+            _ => None,
+        })
+}
+
+/// Sort the nodes, oldest first (missing events before their replies)
+fn sort(nodes: &mut [ThreadNode]) {
+    nodes.sort_by_key(|node| (node.created_at(), node.id));
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Tag};
+
+    use super::*;
+
+    fn note(keys: &Keys, builder: EventBuilder, created_at: u64) -> Event {
+        builder
+            .custom_created_at(Timestamp::from_secs(created_at))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_build_nip10_thread() {
+        let keys = Keys::generate();
+        let root = note(&keys, EventBuilder::text_note("root"), 10);
+        let a = note(
+            &keys,
+            EventBuilder::text_note_reply("a", &root, None, None),
+            20,
+        );
+        let b = note(
+            &keys,
+            EventBuilder::text_note_reply("b", &root, None, None),
+            30,
+        );
+        let c = note(
+            &keys,
+            EventBuilder::text_note_reply("c", &a, Some(&root), None),
+            40,
+        );
+        let positional = note(
+            &keys,
+            EventBuilder::text_note("positional").tags([Tag::event(root.id), Tag::event(b.id)]),
+            50,
+        );
+        let quote = note(
+            &keys,
+            EventBuilder::text_note("quote").tag(Tag::from_standardized_without_cell(
+                TagStandard::Quote {
+                    event_id: c.id,
+                    relay_url: None,
+                    public_key: None,
+                },
+            )),
+            60,
+        );
+
+        let thread = Thread::build(vec![
+            quote.clone(),
+            positional.clone(),
+            c.clone(),
+            b.clone(),
+            a.clone(),
+            root.clone(),
+        ]);
+        assert!(thread.is_complete());
+
+        let roots = thread.roots();
+        assert_eq!(roots.len(), 1);
+
+        let root_node = &roots[0];
+        assert_eq!(root_node.id, root.id);
+        assert_eq!(
+            root_node.replies.iter().map(|n| n.id).collect::<Vec<_>>(),
+            vec![a.id, b.id]
+        );
+
+        let a_node = &root_node.replies[0];
+        assert_eq!(a_node.replies[0].id, c.id);
+        assert_eq!(a_node.replies[0].quotes[0].id, quote.id);
+
+        let b_node = &root_node.replies[1];
+        assert_eq!(b_node.replies[0].id, positional.id);
+    }
+
+    #[test]
+    fn test_build_missing_parents() {
+        let keys = Keys::generate();
+        let root = note(&keys, EventBuilder::text_note("root"), 10);
+        let a = note(
+            &keys,
+            EventBuilder::text_note_reply("a", &root, None, None),
+            20,
+        );
+        let b = note(
+            &keys,
+            EventBuilder::text_note_reply("b", &a, Some(&root), None),
+            30,
+        );
+
+        // Both the root and the parent are missing
+        let thread = Thread::build(vec![b.clone()]);
+        assert_eq!(thread.missing(), &[a.id, root.id]);
+
+        let roots = thread.roots();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].id, root.id);
+        assert!(roots[0].is_missing());
+        assert_eq!(roots[0].replies[0].id, a.id);
+        assert!(roots[0].replies[0].is_missing());
+        assert_eq!(roots[0].replies[0].replies[0].id, b.id);
+
+        // Only the parent is missing
+        let thread = Thread::build(vec![root.clone(), b.clone()]);
+        assert_eq!(thread.missing(), &[a.id]);
+        assert_eq!(thread.roots().len(), 1);
+        assert_eq!(thread.roots()[0].replies[0].replies[0].id, b.id);
+    }
+
+    #[test]
+    fn test_build_nip22_comments() {
+        let keys = Keys::generate();
+        let article = note(
+            &keys,
+            EventBuilder::long_form_text_note("article").tag(Tag::identifier("id")),
+            10,
+        );
+        let comment = note(
+            &keys,
+            EventBuilder::comment("comment", &article, Some(&article), None),
+            20,
+        );
+        let reply = note(
+            &keys,
+            EventBuilder::comment("reply", &comment, Some(&article), None),
+            30,
+        );
+
+        let thread = Thread::build(vec![reply.clone(), comment.clone(), article.clone()]);
+        assert!(thread.is_complete());
+        assert_eq!(thread.roots().len(), 1);
+        assert_eq!(thread.roots()[0].id, article.id);
+        assert_eq!(thread.roots()[0].replies[0].id, comment.id);
+        assert_eq!(thread.roots()[0].replies[0].replies[0].id, reply.id);
+
+        // Missing article
+        let thread = Thread::build(vec![reply.clone(), comment.clone()]);
+        assert!(thread.missing().is_empty());
+        assert_eq!(
+            thread.missing_coordinates(),
+            &[article.coordinate().unwrap().into_owned()]
+        );
+        assert_eq!(thread.roots().len(), 1);
+        assert_eq!(thread.roots()[0].id, comment.id);
+    }
+
+    #[test]
+    fn test_build_cycle() {
+        let keys = Keys::generate();
+        let missing = EventId::from_byte_array([1; 32]);
+
+        // `a` replies to the missing event, `b` replies to the missing event with `a` as root:
+        // the placeholder is attached to `a`, that is attached to the placeholder.
+        let a = note(
+            &keys,
+            EventBuilder::text_note("a").tag(Tag::event(missing)),
+            20,
+        );
+        let b = note(
+            &keys,
+            EventBuilder::text_note("b").tags([Tag::event(a.id), Tag::event(missing)]),
+            10,
+        );
+
+        let thread = Thread::build(vec![a.clone(), b.clone()]);
+        assert_eq!(thread.missing(), &[missing]);
+
+        let roots = thread.roots();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].id, missing);
+        assert_eq!(
+            roots[0].replies.iter().map(|n| n.id).collect::<Vec<_>>(),
+            vec![b.id, a.id]
+        );
+    }
+}
@@ -228,7 +228,9 @@ impl WebDatabase {
 
     async fn _save_event(&self, event: &Event) -> Result<SaveEventStatus, IndexedDBError> {
         // Index event
-        let DatabaseEventResult { status, to_discard } = self.helper.index_event(event).await;
+        let DatabaseEventResult {
+            status, to_discard, ..
+        } = self.helper.index_event(event).await;
 
         if status.is_success() {
             let tx = self
@@ -10,6 +10,7 @@
 #![warn(rustdoc::bare_urls)]
 #![allow(clippy::mutable_key_type)]
 
+use std::collections::HashMap;
 use std::path::Path;
 
 use nostr_database::prelude::*;
@@ -131,6 +132,124 @@ impl NostrEventsDatabase for NostrLMDB {
     fn delete(&self, filter: Filter) -> BoxedFuture<Result<(), DatabaseError>> {
         Box::pin(async move { self.db.delete(filter).await.map_err(DatabaseError::backend) })
     }
+
+    #[inline]
+    fn subscribe(&self, filter: Filter) -> Result<DatabaseChangeStream, DatabaseError> {
+        Ok(self.db.subscribe(filter))
+    }
+}
+
+impl NostrDatabaseCheckpoint for NostrLMDB {
+    fn sync_checkpoint<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        filter: &'a Filter,
+    ) -> BoxedFuture<'a, Result<Option<Timestamp>, DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .sync_checkpoint(relay_url, filter)
+                .map_err(DatabaseError::backend)
+        })
+    }
+
+    fn save_sync_checkpoint<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        filter: &'a Filter,
+        timestamp: Timestamp,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .save_sync_checkpoint(relay_url.clone(), filter.clone(), timestamp)
+                .await
+                .map_err(DatabaseError::backend)
+        })
+    }
+}
+
+impl NostrDatabaseGossip for NostrLMDB {
+    fn gossip_statuses(
+        &self,
+    ) -> BoxedFuture<Result<HashMap<PublicKey, GossipPublicKeyStatus>, DatabaseError>> {
+        Box::pin(async move { self.db.gossip_statuses().map_err(DatabaseError::backend) })
+    }
+
+    fn save_gossip_statuses(
+        &self,
+        statuses: HashMap<PublicKey, GossipPublicKeyStatus>,
+    ) -> BoxedFuture<Result<(), DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .save_gossip_statuses(statuses)
+                .await
+                .map_err(DatabaseError::backend)
+        })
+    }
+}
+
+impl NostrDatabaseHealth for NostrLMDB {
+    fn relay_health_score<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+    ) -> BoxedFuture<'a, Result<Option<f64>, DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .relay_health_score(relay_url)
+                .map_err(DatabaseError::backend)
+        })
+    }
+
+    fn save_relay_health_score<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        score: f64,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .save_relay_health_score(relay_url.clone(), score)
+                .await
+                .map_err(DatabaseError::backend)
+        })
+    }
+}
+
+impl NostrDatabaseOutbox for NostrLMDB {
+    fn outbox_events<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+    ) -> BoxedFuture<'a, Result<Vec<Event>, DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .outbox_events(relay_url)
+                .map_err(DatabaseError::backend)
+        })
+    }
+
+    fn save_outbox_event<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        event: &'a Event,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .save_outbox_event(relay_url.clone(), event.clone())
+                .await
+                .map_err(DatabaseError::backend)
+        })
+    }
+
+    fn remove_outbox_event<'a>(
+        &'a self,
+        relay_url: &'a RelayUrl,
+        event_id: &'a EventId,
+    ) -> BoxedFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            self.db
+                .remove_outbox_event(relay_url.clone(), *event_id)
+                .await
+                .map_err(DatabaseError::backend)
+        })
+    }
 }
 
 impl NostrDatabaseWipe for NostrLMDB {
@@ -145,6 +264,7 @@ mod tests {
     use std::ops::Deref;
     use std::time::Duration;
 
+    use async_utility::futures_util::StreamExt;
     use tempfile::TempDir;
 
     use super::*;
@@ -466,4 +586,214 @@ mod tests {
 
         assert_eq!(db.count_all().await, 2);
     }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let db = TempDatabase::new();
+        let keys = Keys::generate();
+
+        let mut notes = db.subscribe(Filter::new().kind(Kind::TextNote)).unwrap();
+        let mut metadata = db.subscribe(Filter::new().kind(Kind::Metadata)).unwrap();
+
+        // Save
+        let note = EventBuilder::text_note("Text Note")
+            .sign_with_keys(&keys)
+            .unwrap();
+        db.save_event(&note).await.unwrap();
+        assert_eq!(
+            notes.next().await,
+            Some(DatabaseChange::Saved(note.clone()))
+        );
+
+        // Replace
+        let old = EventBuilder::metadata(&Metadata::new().name("old"))
+            .custom_created_at(Timestamp::from_secs(1_700_000_000))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let new = EventBuilder::metadata(&Metadata::new().name("new"))
+            .custom_created_at(Timestamp::from_secs(1_700_000_100))
+            .sign_with_keys(&keys)
+            .unwrap();
+        db.save_event(&old).await.unwrap();
+        db.save_event(&new).await.unwrap();
+        assert_eq!(
+            metadata.next().await,
+            Some(DatabaseChange::Saved(old.clone()))
+        );
+        assert_eq!(
+            metadata.next().await,
+            Some(DatabaseChange::Replaced {
+                old,
+                new: new.clone()
+            })
+        );
+
+        // NIP09 deletion
+        let deletion = EventBuilder::delete(EventDeletionRequest::new().id(note.id))
+            .sign_with_keys(&keys)
+            .unwrap();
+        db.save_event(&deletion).await.unwrap();
+        assert_eq!(notes.next().await, Some(DatabaseChange::Deleted(note)));
+
+        // Delete by filter
+        db.delete(Filter::new().kind(Kind::Metadata)).await.unwrap();
+        assert_eq!(metadata.next().await, Some(DatabaseChange::Deleted(new)));
+
+        // Wipe
+        db.wipe().await.unwrap();
+        assert_eq!(notes.next().await, Some(DatabaseChange::Wiped));
+        assert_eq!(metadata.next().await, Some(DatabaseChange::Wiped));
+    }
+
+    #[tokio::test]
+    async fn test_gossip_statuses() {
+        let db = TempDatabase::new();
+
+        assert!(db.gossip_statuses().await.unwrap().is_empty());
+
+        let public_key = Keys::generate().public_key;
+        let status = GossipPublicKeyStatus {
+            last_check: Timestamp::from_secs(1_700_000_000),
+            nip17_last_update: Timestamp::from_secs(1_700_000_100),
+            nip65_last_update: Timestamp::from_secs(1_700_000_200),
+        };
+
+        let mut statuses = HashMap::new();
+        statuses.insert(public_key, status);
+        db.save_gossip_statuses(statuses).await.unwrap();
+
+        let stored = db.gossip_statuses().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored.get(&public_key), Some(&status));
+
+        // Replace
+        let status = GossipPublicKeyStatus {
+            last_check: Timestamp::from_secs(1_700_001_000),
+            ..status
+        };
+        let mut statuses = HashMap::new();
+        statuses.insert(public_key, status);
+        db.save_gossip_statuses(statuses).await.unwrap();
+
+        let stored = db.gossip_statuses().await.unwrap();
+        assert_eq!(stored.get(&public_key), Some(&status));
+
+        // Wipe
+        db.wipe().await.unwrap();
+        assert!(db.gossip_statuses().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sync_checkpoint() {
+        let db = TempDatabase::new();
+
+        let relay_a = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let relay_b = RelayUrl::parse("wss://relay.example.org").unwrap();
+        let filter_a = Filter::new().kind(Kind::TextNote);
+        let filter_b = Filter::new().kind(Kind::Metadata);
+
+        assert_eq!(db.sync_checkpoint(&relay_a, &filter_a).await.unwrap(), None);
+
+        db.save_sync_checkpoint(&relay_a, &filter_a, Timestamp::from_secs(10))
+            .await
+            .unwrap();
+        db.save_sync_checkpoint(&relay_b, &filter_a, Timestamp::from_secs(20))
+            .await
+            .unwrap();
+        assert_eq!(
+            db.sync_checkpoint(&relay_a, &filter_a).await.unwrap(),
+            Some(Timestamp::from_secs(10))
+        );
+        assert_eq!(
+            db.sync_checkpoint(&relay_b, &filter_a).await.unwrap(),
+            Some(Timestamp::from_secs(20))
+        );
+        assert_eq!(db.sync_checkpoint(&relay_a, &filter_b).await.unwrap(), None);
+
+        // Overwrite
+        db.save_sync_checkpoint(&relay_a, &filter_a, Timestamp::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(
+            db.sync_checkpoint(&relay_a, &filter_a).await.unwrap(),
+            Some(Timestamp::from_secs(30))
+        );
+
+        // Wipe
+        db.wipe().await.unwrap();
+        assert_eq!(db.sync_checkpoint(&relay_a, &filter_a).await.unwrap(), None);
+        assert_eq!(db.sync_checkpoint(&relay_b, &filter_a).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_relay_health_score() {
+        let db = TempDatabase::new();
+
+        let relay_a = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let relay_b = RelayUrl::parse("wss://relay.example.org").unwrap();
+
+        assert_eq!(db.relay_health_score(&relay_a).await.unwrap(), None);
+
+        db.save_relay_health_score(&relay_a, 0.25).await.unwrap();
+        db.save_relay_health_score(&relay_b, 1.0).await.unwrap();
+        assert_eq!(db.relay_health_score(&relay_a).await.unwrap(), Some(0.25));
+        assert_eq!(db.relay_health_score(&relay_b).await.unwrap(), Some(1.0));
+
+        // Overwrite
+        db.save_relay_health_score(&relay_a, 0.75).await.unwrap();
+        assert_eq!(db.relay_health_score(&relay_a).await.unwrap(), Some(0.75));
+
+        // Wipe
+        db.wipe().await.unwrap();
+        assert_eq!(db.relay_health_score(&relay_a).await.unwrap(), None);
+        assert_eq!(db.relay_health_score(&relay_b).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_outbox() {
+        let db = TempDatabase::new();
+
+        let relay_a = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let relay_b = RelayUrl::parse("wss://relay.example.com.evil").unwrap();
+
+        assert!(db.outbox_events(&relay_a).await.unwrap().is_empty());
+
+        let keys = Keys::generate();
+        let first = EventBuilder::text_note("first")
+            .custom_created_at(Timestamp::from_secs(1_700_000_000))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let second = EventBuilder::text_note("second")
+            .custom_created_at(Timestamp::from_secs(1_700_000_100))
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        db.save_outbox_event(&relay_a, &second).await.unwrap();
+        db.save_outbox_event(&relay_a, &first).await.unwrap();
+        db.save_outbox_event(&relay_a, &first).await.unwrap();
+        db.save_outbox_event(&relay_b, &second).await.unwrap();
+
+        // Sorted by creation time, without duplicates
+        assert_eq!(
+            db.outbox_events(&relay_a).await.unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+        assert_eq!(
+            db.outbox_events(&relay_b).await.unwrap(),
+            vec![second.clone()]
+        );
+
+        // Remove
+        db.remove_outbox_event(&relay_a, &first.id).await.unwrap();
+        assert_eq!(
+            db.outbox_events(&relay_a).await.unwrap(),
+            vec![second.clone()]
+        );
+        assert_eq!(db.outbox_events(&relay_b).await.unwrap(), vec![second]);
+
+        // Wipe
+        db.wipe().await.unwrap();
+        assert!(db.outbox_events(&relay_a).await.unwrap().is_empty());
+        assert!(db.outbox_events(&relay_b).await.unwrap().is_empty());
+    }
 }
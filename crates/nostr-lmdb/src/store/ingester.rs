@@ -8,7 +8,7 @@ use std::thread;
 use heed::RwTxn;
 use nostr::nips::nip01::Coordinate;
 use nostr::{Event, Kind, Timestamp};
-use nostr_database::{FlatBufferBuilder, RejectedReason, SaveEventStatus};
+use nostr_database::{DatabaseChangeNotifier, FlatBufferBuilder, RejectedReason, SaveEventStatus};
 use tokio::sync::oneshot;
 
 use super::error::Error;
@@ -43,17 +43,18 @@ impl IngesterItem {
 #[derive(Debug)]
 pub(super) struct Ingester {
     db: Lmdb,
+    notifier: DatabaseChangeNotifier,
     rx: Receiver<IngesterItem>,
 }
 
 impl Ingester {
     /// Build and spawn a new ingester
-    pub(super) fn run(db: Lmdb) -> Sender<IngesterItem> {
+    pub(super) fn run(db: Lmdb, notifier: DatabaseChangeNotifier) -> Sender<IngesterItem> {
         // Create new asynchronous channel
         let (tx, rx) = std::sync::mpsc::channel();
 
         // Construct and spawn ingester
-        let ingester = Self { db, rx };
+        let ingester = Self { db, notifier, rx };
         ingester.spawn_ingester();
 
         // Return ingester sender
@@ -132,6 +133,9 @@ impl Ingester {
         // Acquire write transaction
         let mut txn = self.db.write_txn()?;
 
+        // Events removed while ingesting this one (collected only if someone is listening)
+        let mut removed: Option<Vec<Event>> = self.notifier.has_subscribers().then(Vec::new);
+
         // Remove replaceable events being replaced
         if event.kind.is_replaceable() {
             // Find replaceable event
@@ -148,8 +152,13 @@ impl Ingester {
                 let read_txn = self.db.read_txn()?;
 
                 let coordinate: Coordinate = Coordinate::new(event.kind, event.pubkey);
-                self.db
-                    .remove_replaceable(&read_txn, &mut txn, &coordinate, event.created_at)?;
+                self.db.remove_replaceable(
+                    &read_txn,
+                    &mut txn,
+                    &coordinate,
+                    event.created_at,
+                    removed.as_mut(),
+                )?;
 
                 read_txn.commit()?;
             }
@@ -176,6 +185,7 @@ impl Ingester {
                         &mut txn,
                         &coordinate,
                         Timestamp::max(),
+                        removed.as_mut(),
                     )?;
 
                     read_txn.commit()?;
@@ -185,7 +195,7 @@ impl Ingester {
 
         // Handle deletion events
         if let Kind::EventDeletion = event.kind {
-            let invalid: bool = self.handle_deletion_event(&mut txn, &event)?;
+            let invalid: bool = self.handle_deletion_event(&mut txn, &event, removed.as_mut())?;
 
             if invalid {
                 txn.abort();
@@ -199,10 +209,20 @@ impl Ingester {
         // Commit
         txn.commit()?;
 
+        // Notify the subscribers
+        if let Some(removed) = removed {
+            self.notifier.notify_saved(&event, removed);
+        }
+
         Ok(SaveEventStatus::Success)
     }
 
-    fn handle_deletion_event(&self, txn: &mut RwTxn, event: &Event) -> nostr::Result<bool, Error> {
+    fn handle_deletion_event(
+        &self,
+        txn: &mut RwTxn,
+        event: &Event,
+        mut removed: Option<&mut Vec<Event>>,
+    ) -> nostr::Result<bool, Error> {
         // Acquire read txn
         let read_txn = self.db.read_txn()?;
 
@@ -216,6 +236,9 @@ impl Ingester {
                 // Mark as deleted and remove event
                 self.db.mark_deleted(txn, id)?;
                 self.db.remove(txn, &target)?;
+                if let Some(removed) = removed.as_deref_mut() {
+                    removed.push(target.into_owned());
+                }
             }
         }
 
@@ -231,11 +254,21 @@ impl Ingester {
 
             // Remove events (up to the created_at of the deletion event)
             if coordinate.kind.is_replaceable() {
-                self.db
-                    .remove_replaceable(&read_txn, txn, coordinate, event.created_at)?;
+                self.db.remove_replaceable(
+                    &read_txn,
+                    txn,
+                    coordinate,
+                    event.created_at,
+                    removed.as_deref_mut(),
+                )?;
             } else if coordinate.kind.is_addressable() {
-                self.db
-                    .remove_addressable(&read_txn, txn, coordinate, event.created_at)?;
+                self.db.remove_addressable(
+                    &read_txn,
+                    txn,
+                    coordinate,
+                    event.created_at,
+                    removed.as_deref_mut(),
+                )?;
             }
         }
 
@@ -3,17 +3,19 @@
 // Copyright (c) 2023-2025 Rust Nostr Developers
 // Distributed under the MIT software license
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::iter;
-use std::ops::Bound;
+use std::ops::{Bound, Range};
 use std::path::Path;
 
 use heed::byteorder::NativeEndian;
 use heed::types::{Bytes, Unit, U64};
 use heed::{Database, Env, EnvFlags, EnvOpenOptions, RoRange, RoTxn, RwTxn};
+use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::hashes::Hash;
 use nostr::prelude::*;
 use nostr_database::flatbuffers::FlatBufferDecodeBorrowed;
-use nostr_database::{FlatBufferBuilder, FlatBufferEncode};
+use nostr_database::{FlatBufferBuilder, FlatBufferEncode, GossipPublicKeyStatus};
 
 mod index;
 
@@ -22,6 +24,7 @@ use super::types::DatabaseFilter;
 
 const EVENT_ID_ALL_ZEROS: [u8; 32] = [0; 32];
 const EVENT_ID_ALL_255: [u8; 32] = [255; 32];
+const GOSSIP_STATUS_LEN: usize = 24;
 
 // 64-bit
 #[cfg(target_pointer_width = "64")]
@@ -53,6 +56,14 @@ pub(crate) struct Lmdb {
     deleted_ids: Database<Bytes, Unit>, // Event ID
     /// Deleted coordinates
     deleted_coordinates: Database<Bytes, U64<NativeEndian>>, // Coordinate, UNIX timestamp
+    /// Sync checkpoints
+    checkpoints: Database<Bytes, U64<NativeEndian>>, // Relay URL + 0x00 + SHA256(Filter), UNIX timestamp
+    /// Gossip status
+    gossip: Database<Bytes, Bytes>, // Public Key, <Gossip status>
+    /// Relay health scores
+    health: Database<Bytes, Bytes>, // Relay URL, Score
+    /// Outbox
+    outbox: Database<Bytes, Bytes>, // Relay URL + 0x00 + Event ID, Event
 }
 
 impl Lmdb {
@@ -64,7 +75,7 @@ impl Lmdb {
         let env: Env = unsafe {
             EnvOpenOptions::new()
                 .flags(EnvFlags::NO_TLS)
-                .max_dbs(9)
+                .max_dbs(16)
                 .map_size(MAP_SIZE)
                 .open(path)?
         };
@@ -117,6 +128,26 @@ impl Lmdb {
             .types::<Bytes, U64<NativeEndian>>()
             .name("deleted-coordinates")
             .create(&mut txn)?;
+        let checkpoints = env
+            .database_options()
+            .types::<Bytes, U64<NativeEndian>>()
+            .name("checkpoints")
+            .create(&mut txn)?;
+        let gossip = env
+            .database_options()
+            .types::<Bytes, Bytes>()
+            .name("gossip")
+            .create(&mut txn)?;
+        let health = env
+            .database_options()
+            .types::<Bytes, Bytes>()
+            .name("health")
+            .create(&mut txn)?;
+        let outbox = env
+            .database_options()
+            .types::<Bytes, Bytes>()
+            .name("outbox")
+            .create(&mut txn)?;
 
         // Commit changes
         txn.commit()?;
@@ -132,6 +163,10 @@ impl Lmdb {
             ktc_index,
             deleted_ids,
             deleted_coordinates,
+            checkpoints,
+            gossip,
+            health,
+            outbox,
         })
     }
 
@@ -278,6 +313,134 @@ impl Lmdb {
         self.ktc_index.clear(txn)?;
         self.deleted_ids.clear(txn)?;
         self.deleted_coordinates.clear(txn)?;
+        self.checkpoints.clear(txn)?;
+        self.gossip.clear(txn)?;
+        self.health.clear(txn)?;
+        self.outbox.clear(txn)?;
+        Ok(())
+    }
+
+    pub(crate) fn sync_checkpoint(
+        &self,
+        txn: &RoTxn,
+        relay_url: &RelayUrl,
+        filter: &Filter,
+    ) -> Result<Option<Timestamp>, Error> {
+        let key: Vec<u8> = make_checkpoint_key(relay_url, filter);
+        let value: Option<u64> = self.checkpoints.get(txn, &key)?;
+        Ok(value.map(Timestamp::from_secs))
+    }
+
+    pub(crate) fn save_sync_checkpoint(
+        &self,
+        txn: &mut RwTxn,
+        relay_url: &RelayUrl,
+        filter: &Filter,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        let key: Vec<u8> = make_checkpoint_key(relay_url, filter);
+        self.checkpoints.put(txn, &key, &timestamp.as_u64())?;
+        Ok(())
+    }
+
+    pub(crate) fn gossip_statuses(
+        &self,
+        txn: &RoTxn,
+    ) -> Result<HashMap<PublicKey, GossipPublicKeyStatus>, Error> {
+        let mut statuses: HashMap<PublicKey, GossipPublicKeyStatus> = HashMap::new();
+
+        for result in self.gossip.iter(txn)? {
+            let (key, value) = result?;
+
+            // Skip malformed entries
+            let Some(status) = decode_gossip_status(value) else {
+                continue;
+            };
+
+            let public_key: PublicKey = PublicKey::from_slice(key)?;
+            statuses.insert(public_key, status);
+        }
+
+        Ok(statuses)
+    }
+
+    pub(crate) fn save_gossip_statuses(
+        &self,
+        txn: &mut RwTxn,
+        statuses: HashMap<PublicKey, GossipPublicKeyStatus>,
+    ) -> Result<(), Error> {
+        for (public_key, status) in statuses.into_iter() {
+            self.gossip
+                .put(txn, public_key.as_bytes(), &encode_gossip_status(&status))?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn relay_health_score(
+        &self,
+        txn: &RoTxn,
+        relay_url: &RelayUrl,
+    ) -> Result<Option<f64>, Error> {
+        let value: Option<&[u8]> = self.health.get(txn, relay_url.as_str().as_bytes())?;
+
+        // Skip malformed values
+        Ok(value
+            .and_then(|value| value.try_into().ok())
+            .map(f64::from_be_bytes))
+    }
+
+    pub(crate) fn save_relay_health_score(
+        &self,
+        txn: &mut RwTxn,
+        relay_url: &RelayUrl,
+        score: f64,
+    ) -> Result<(), Error> {
+        self.health
+            .put(txn, relay_url.as_str().as_bytes(), &score.to_be_bytes())?;
+        Ok(())
+    }
+
+    pub(crate) fn outbox_events(
+        &self,
+        txn: &RoTxn,
+        relay_url: &RelayUrl,
+    ) -> Result<Vec<Event>, Error> {
+        let prefix: Vec<u8> = make_outbox_key_prefix(relay_url);
+
+        let mut events: Vec<Event> = Vec::new();
+
+        for result in self.outbox.prefix_iter(txn, &prefix)? {
+            let (_, value) = result?;
+            let event: EventBorrow = EventBorrow::decode(value)?;
+            events.push(event.into_owned());
+        }
+
+        events.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+        Ok(events)
+    }
+
+    pub(crate) fn save_outbox_event(
+        &self,
+        txn: &mut RwTxn,
+        fbb: &mut FlatBufferBuilder,
+        relay_url: &RelayUrl,
+        event: &Event,
+    ) -> Result<(), Error> {
+        let key: Vec<u8> = make_outbox_key(relay_url, &event.id);
+        self.outbox.put(txn, &key, event.encode(fbb))?;
+        Ok(())
+    }
+
+    pub(crate) fn remove_outbox_event(
+        &self,
+        txn: &mut RwTxn,
+        relay_url: &RelayUrl,
+        event_id: &EventId,
+    ) -> Result<(), Error> {
+        let key: Vec<u8> = make_outbox_key(relay_url, event_id);
+        self.outbox.delete(txn, &key)?;
         Ok(())
     }
 
@@ -298,10 +461,22 @@ impl Lmdb {
         }
     }
 
-    pub fn delete(&self, read_txn: &RoTxn, txn: &mut RwTxn, filter: Filter) -> Result<(), Error> {
+    /// Delete all the events that match the filter
+    ///
+    /// The removed events are collected into `removed`, if any.
+    pub fn delete(
+        &self,
+        read_txn: &RoTxn,
+        txn: &mut RwTxn,
+        filter: Filter,
+        mut removed: Option<&mut Vec<Event>>,
+    ) -> Result<(), Error> {
         let events = self.query(read_txn, filter)?;
         for event in events.into_iter() {
             self.remove(txn, &event)?;
+            if let Some(removed) = removed.as_deref_mut() {
+                removed.push(event.into_owned());
+            }
         }
         Ok(())
     }
@@ -611,12 +786,14 @@ impl Lmdb {
 
     // Remove all replaceable events with the matching author-kind
     // Kind must be a replaceable (not parameterized replaceable) event kind
+    // The removed events are collected into `removed`, if any
     pub fn remove_replaceable(
         &self,
         read_txn: &RoTxn,
         txn: &mut RwTxn,
         coordinate: &Coordinate,
         until: Timestamp,
+        mut removed: Option<&mut Vec<Event>>,
     ) -> Result<(), Error> {
         if !coordinate.kind.is_replaceable() {
             return Err(Error::WrongEventKind);
@@ -635,6 +812,9 @@ impl Lmdb {
 
             if let Some(event) = self.get_event_by_id(read_txn, id)? {
                 self.remove(txn, &event)?;
+                if let Some(removed) = removed.as_deref_mut() {
+                    removed.push(event.into_owned());
+                }
             }
         }
 
@@ -643,12 +823,14 @@ impl Lmdb {
 
     // Remove all parameterized-replaceable events with the matching author-kind-d
     // Kind must be a parameterized-replaceable event kind
+    // The removed events are collected into `removed`, if any
     pub fn remove_addressable(
         &self,
         read_txn: &RoTxn,
         txn: &mut RwTxn,
         coordinate: &Coordinate,
         until: Timestamp,
+        mut removed: Option<&mut Vec<Event>>,
     ) -> Result<(), Error> {
         if !coordinate.kind.is_addressable() {
             return Err(Error::WrongEventKind);
@@ -671,6 +853,9 @@ impl Lmdb {
 
             if event.kind == coordinate.kind.as_u16() {
                 self.remove(txn, &event)?;
+                if let Some(removed) = removed.as_deref_mut() {
+                    removed.push(event.into_owned());
+                }
             }
         }
 
@@ -830,3 +1015,57 @@ impl Lmdb {
         Ok(self.ktc_index.range(txn, &range)?)
     }
 }
+
+/// Outbox key prefix: relay URL + 0x00
+///
+/// The separator avoids matching the relays with a URL that starts with the same characters.
+fn make_outbox_key_prefix(relay_url: &RelayUrl) -> Vec<u8> {
+    let url: &[u8] = relay_url.as_str().as_bytes();
+    let mut prefix: Vec<u8> = Vec::with_capacity(url.len() + 1 + 32);
+    prefix.extend_from_slice(url);
+    prefix.push(0);
+    prefix
+}
+
+fn make_outbox_key(relay_url: &RelayUrl, event_id: &EventId) -> Vec<u8> {
+    let mut key: Vec<u8> = make_outbox_key_prefix(relay_url);
+    key.extend_from_slice(event_id.as_bytes());
+    key
+}
+
+/// The filter is hashed to keep the key within the LMDB max key size
+fn make_checkpoint_key(relay_url: &RelayUrl, filter: &Filter) -> Vec<u8> {
+    let url: &[u8] = relay_url.as_str().as_bytes();
+    let hash: Sha256Hash = Sha256Hash::hash(filter.as_json().as_bytes());
+
+    let mut key: Vec<u8> = Vec::with_capacity(url.len() + 1 + 32);
+    key.extend_from_slice(url);
+    key.push(0);
+    key.extend_from_slice(hash.as_byte_array());
+    key
+}
+
+fn encode_gossip_status(status: &GossipPublicKeyStatus) -> [u8; GOSSIP_STATUS_LEN] {
+    let mut value: [u8; GOSSIP_STATUS_LEN] = [0; GOSSIP_STATUS_LEN];
+    value[..8].copy_from_slice(&status.last_check.as_u64().to_be_bytes());
+    value[8..16].copy_from_slice(&status.nip17_last_update.as_u64().to_be_bytes());
+    value[16..].copy_from_slice(&status.nip65_last_update.as_u64().to_be_bytes());
+    value
+}
+
+fn decode_gossip_status(value: &[u8]) -> Option<GossipPublicKeyStatus> {
+    if value.len() != GOSSIP_STATUS_LEN {
+        return None;
+    }
+
+    let timestamp = |range: Range<usize>| -> Option<Timestamp> {
+        let bytes: [u8; 8] = value.get(range)?.try_into().ok()?;
+        Some(Timestamp::from_secs(u64::from_be_bytes(bytes)))
+    };
+
+    Some(GossipPublicKeyStatus {
+        last_check: timestamp(0..8)?,
+        nip17_last_update: timestamp(8..16)?,
+        nip65_last_update: timestamp(16..24)?,
+    })
+}
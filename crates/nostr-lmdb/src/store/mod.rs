@@ -3,6 +3,7 @@
 // Copyright (c) 2023-2025 Rust Nostr Developers
 // Distributed under the MIT software license
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::sync::mpsc::Sender;
@@ -24,6 +25,7 @@ use self::lmdb::Lmdb;
 pub struct Store {
     db: Lmdb,
     ingester: Sender<IngesterItem>,
+    notifier: DatabaseChangeNotifier,
 }
 
 impl Store {
@@ -37,9 +39,14 @@ impl Store {
         fs::create_dir_all(path)?;
 
         let db: Lmdb = Lmdb::new(path)?;
-        let ingester: Sender<IngesterItem> = Ingester::run(db.clone());
+        let notifier: DatabaseChangeNotifier = DatabaseChangeNotifier::new();
+        let ingester: Sender<IngesterItem> = Ingester::run(db.clone(), notifier.clone());
 
-        Ok(Self { db, ingester })
+        Ok(Self {
+            db,
+            ingester,
+            notifier,
+        })
     }
 
     #[inline]
@@ -134,25 +141,143 @@ impl Store {
     }
 
     pub async fn delete(&self, filter: Filter) -> Result<(), Error> {
+        let notifier: DatabaseChangeNotifier = self.notifier.clone();
         self.interact(move |db| {
             let read_txn = db.read_txn()?;
             let mut txn = db.write_txn()?;
 
-            db.delete(&read_txn, &mut txn, filter)?;
+            // Collect the removed events only if someone is listening
+            let mut removed: Option<Vec<Event>> = notifier.has_subscribers().then(Vec::new);
+
+            db.delete(&read_txn, &mut txn, filter, removed.as_mut())?;
 
             read_txn.commit()?;
             txn.commit()?;
 
+            // Notify the subscribers
+            if let Some(removed) = removed {
+                notifier.notify_deleted(removed);
+            }
+
+            Ok(())
+        })
+        .await?
+    }
+
+    #[inline]
+    pub fn subscribe(&self, filter: Filter) -> DatabaseChangeStream {
+        self.notifier.subscribe(filter)
+    }
+
+    pub fn gossip_statuses(&self) -> Result<HashMap<PublicKey, GossipPublicKeyStatus>, Error> {
+        let txn = self.db.read_txn()?;
+        let statuses = self.db.gossip_statuses(&txn)?;
+        txn.commit()?;
+        Ok(statuses)
+    }
+
+    pub async fn save_gossip_statuses(
+        &self,
+        statuses: HashMap<PublicKey, GossipPublicKeyStatus>,
+    ) -> Result<(), Error> {
+        self.interact(move |db| {
+            let mut txn = db.write_txn()?;
+            db.save_gossip_statuses(&mut txn, statuses)?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub fn sync_checkpoint(
+        &self,
+        relay_url: &RelayUrl,
+        filter: &Filter,
+    ) -> Result<Option<Timestamp>, Error> {
+        let txn = self.db.read_txn()?;
+        let timestamp = self.db.sync_checkpoint(&txn, relay_url, filter)?;
+        txn.commit()?;
+        Ok(timestamp)
+    }
+
+    pub async fn save_sync_checkpoint(
+        &self,
+        relay_url: RelayUrl,
+        filter: Filter,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        self.interact(move |db| {
+            let mut txn = db.write_txn()?;
+            db.save_sync_checkpoint(&mut txn, &relay_url, &filter, timestamp)?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub fn relay_health_score(&self, relay_url: &RelayUrl) -> Result<Option<f64>, Error> {
+        let txn = self.db.read_txn()?;
+        let score = self.db.relay_health_score(&txn, relay_url)?;
+        txn.commit()?;
+        Ok(score)
+    }
+
+    pub async fn save_relay_health_score(
+        &self,
+        relay_url: RelayUrl,
+        score: f64,
+    ) -> Result<(), Error> {
+        self.interact(move |db| {
+            let mut txn = db.write_txn()?;
+            db.save_relay_health_score(&mut txn, &relay_url, score)?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub fn outbox_events(&self, relay_url: &RelayUrl) -> Result<Vec<Event>, Error> {
+        let txn = self.db.read_txn()?;
+        let events = self.db.outbox_events(&txn, relay_url)?;
+        txn.commit()?;
+        Ok(events)
+    }
+
+    pub async fn save_outbox_event(&self, relay_url: RelayUrl, event: Event) -> Result<(), Error> {
+        self.interact(move |db| {
+            let mut fbb = FlatBufferBuilder::new();
+            let mut txn = db.write_txn()?;
+            db.save_outbox_event(&mut txn, &mut fbb, &relay_url, &event)?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn remove_outbox_event(
+        &self,
+        relay_url: RelayUrl,
+        event_id: EventId,
+    ) -> Result<(), Error> {
+        self.interact(move |db| {
+            let mut txn = db.write_txn()?;
+            db.remove_outbox_event(&mut txn, &relay_url, &event_id)?;
+            txn.commit()?;
             Ok(())
         })
         .await?
     }
 
     pub async fn wipe(&self) -> Result<(), Error> {
+        let notifier: DatabaseChangeNotifier = self.notifier.clone();
         self.interact(move |db| {
             let mut txn = db.write_txn()?;
             db.wipe(&mut txn)?;
             txn.commit()?;
+
+            // Notify the subscribers
+            notifier.notify([DatabaseChange::Wiped]);
+
             Ok(())
         })
         .await?
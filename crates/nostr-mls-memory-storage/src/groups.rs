@@ -2,7 +2,7 @@
 
 use std::collections::BTreeSet;
 
-use nostr::PublicKey;
+use nostr::{PublicKey, RelayUrl};
 use nostr_mls_storage::groups::error::{GroupError, InvalidGroupState};
 use nostr_mls_storage::groups::types::*;
 use nostr_mls_storage::groups::GroupStorage;
@@ -14,14 +14,22 @@ use crate::NostrMlsMemoryStorage;
 impl GroupStorage for NostrMlsMemoryStorage {
     fn save_group(&self, group: Group) -> Result<(), GroupError> {
         // Store in the MLS group ID cache
-        {
+        let old_group: Option<Group> = {
             let mut cache = self.groups_cache.write();
-            cache.put(group.mls_group_id.clone(), group.clone());
-        }
+            cache.put(group.mls_group_id.clone(), group.clone())
+        };
 
         // Store in the Nostr group ID cache
         {
             let mut cache = self.groups_by_nostr_id_cache.write();
+
+            // The Nostr group ID can change over time: remove the stale entry
+            if let Some(old_group) = old_group {
+                if old_group.nostr_group_id != group.nostr_group_id {
+                    cache.pop(&old_group.nostr_group_id);
+                }
+            }
+
             cache.put(group.nostr_group_id, group);
         }
 
@@ -107,6 +115,28 @@ impl GroupStorage for NostrMlsMemoryStorage {
         Ok(())
     }
 
+    fn replace_group_relays(
+        &self,
+        mls_group_id: &GroupId,
+        relays: BTreeSet<RelayUrl>,
+    ) -> Result<(), GroupError> {
+        // Check if the group exists first
+        self.find_group_by_mls_group_id(mls_group_id)?;
+
+        let group_relays: BTreeSet<GroupRelay> = relays
+            .into_iter()
+            .map(|relay_url| GroupRelay {
+                relay_url,
+                mls_group_id: mls_group_id.clone(),
+            })
+            .collect();
+
+        let mut cache = self.group_relays_cache.write();
+        cache.put(mls_group_id.clone(), group_relays);
+
+        Ok(())
+    }
+
     fn get_group_exporter_secret(
         &self,
         mls_group_id: &GroupId,
@@ -326,6 +326,18 @@ mod tests {
             .unwrap();
         let found_relays_after_duplicate = nostr_storage.group_relays(&mls_group_id).unwrap();
         assert_eq!(found_relays_after_duplicate.len(), 2);
+
+        // Replace all the relays
+        let relay_url3 = RelayUrl::parse("wss://relay3.example.com").unwrap();
+        nostr_storage
+            .replace_group_relays(&mls_group_id, BTreeSet::from([relay_url3.clone()]))
+            .unwrap();
+        let found_relays_after_replace = nostr_storage.group_relays(&mls_group_id).unwrap();
+        assert_eq!(found_relays_after_replace.len(), 1);
+        assert_eq!(
+            found_relays_after_replace.first().unwrap().relay_url,
+            relay_url3
+        );
     }
 
     #[test]
@@ -2,7 +2,7 @@
 
 use std::collections::BTreeSet;
 
-use nostr::PublicKey;
+use nostr::{PublicKey, RelayUrl};
 use nostr_mls_storage::groups::error::GroupError;
 use nostr_mls_storage::groups::types::{Group, GroupExporterSecret, GroupRelay};
 use nostr_mls_storage::groups::GroupStorage;
@@ -215,6 +215,42 @@ impl GroupStorage for NostrMlsSqliteStorage {
         Ok(())
     }
 
+    fn replace_group_relays(
+        &self,
+        mls_group_id: &GroupId,
+        relays: BTreeSet<RelayUrl>,
+    ) -> Result<(), GroupError> {
+        // First verify the group exists
+        if self.find_group_by_mls_group_id(mls_group_id)?.is_none() {
+            return Err(GroupError::InvalidParameters(format!(
+                "Group with MLS ID {:?} not found",
+                mls_group_id
+            )));
+        }
+
+        let mut conn_guard = self.db_connection.lock().map_err(into_group_err)?;
+
+        let tx = conn_guard.transaction().map_err(into_group_err)?;
+
+        tx.execute(
+            "DELETE FROM group_relays WHERE mls_group_id = ?",
+            params![mls_group_id.as_slice()],
+        )
+        .map_err(into_group_err)?;
+
+        for relay_url in relays.iter() {
+            tx.execute(
+                "INSERT INTO group_relays (mls_group_id, relay_url) VALUES (?, ?)",
+                params![mls_group_id.as_slice(), relay_url.as_str()],
+            )
+            .map_err(into_group_err)?;
+        }
+
+        tx.commit().map_err(into_group_err)?;
+
+        Ok(())
+    }
+
     fn get_group_exporter_secret(
         &self,
         mls_group_id: &GroupId,
@@ -270,7 +306,6 @@ impl GroupStorage for NostrMlsSqliteStorage {
 
 #[cfg(test)]
 mod tests {
-    use nostr::RelayUrl;
     use nostr_mls_storage::groups::types::{GroupState, GroupType};
 
     use super::*;
@@ -364,6 +399,23 @@ mod tests {
             relays.first().unwrap().relay_url.to_string(),
             "wss://relay.example.com"
         );
+
+        // Replace all the relays
+        let relay_url1 = RelayUrl::parse("wss://relay1.example.com").unwrap();
+        let relay_url2 = RelayUrl::parse("wss://relay2.example.com").unwrap();
+        storage
+            .replace_group_relays(
+                &mls_group_id,
+                BTreeSet::from([relay_url1.clone(), relay_url2.clone()]),
+            )
+            .unwrap();
+        let relays: BTreeSet<RelayUrl> = storage
+            .group_relays(&mls_group_id)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.relay_url)
+            .collect();
+        assert_eq!(relays, BTreeSet::from([relay_url1, relay_url2]));
     }
 
     #[test]
@@ -9,7 +9,7 @@
 
 use std::collections::BTreeSet;
 
-use nostr::PublicKey;
+use nostr::{PublicKey, RelayUrl};
 use openmls::group::GroupId;
 
 pub mod error;
@@ -48,6 +48,13 @@ pub trait GroupStorage {
     /// Save a group relay
     fn save_group_relay(&self, group_relay: GroupRelay) -> Result<(), GroupError>;
 
+    /// Replace all the relays of a group
+    fn replace_group_relays(
+        &self,
+        group_id: &GroupId,
+        relays: BTreeSet<RelayUrl>,
+    ) -> Result<(), GroupError>;
+
     /// Get an exporter secret for a group and epoch
     fn get_group_exporter_secret(
         &self,
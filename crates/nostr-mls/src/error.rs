@@ -16,8 +16,9 @@ use openmls::error::LibraryError;
 use openmls::extensions::errors::InvalidExtensionError;
 use openmls::framing::errors::ProtocolMessageError;
 use openmls::group::{
-    AddMembersError, CreateMessageError, ExportSecretError, MergePendingCommitError, NewGroupError,
-    ProcessMessageError, SelfUpdateError, WelcomeError,
+    AddMembersError, CreateGroupContextExtProposalError, CreateMessageError, ExportSecretError,
+    MergeCommitError, MergePendingCommitError, NewGroupError, ProcessMessageError,
+    RemoveMembersError, SelfUpdateError, WelcomeError,
 };
 use openmls::key_packages::errors::{KeyPackageNewError, KeyPackageVerifyError};
 use openmls_traits::types::CryptoError;
@@ -71,6 +72,8 @@ pub enum Error {
     CannotDecryptOwnMessage,
     /// Merge pending commit error
     MergePendingCommit(String),
+    /// Merge staged commit error
+    MergeStagedCommit(String),
     /// Self update error
     SelfUpdate(String),
     /// Welcome error
@@ -87,6 +90,8 @@ pub enum Error {
     ProtocolGroupIdMismatch,
     /// Own leaf not found
     OwnLeafNotFound,
+    /// The operation requires group admin permissions
+    NotAdmin,
     /// Failed to load signer
     CantLoadSigner,
     /// Invalid Welcome message
@@ -137,6 +142,7 @@ impl fmt::Display for Error {
             }
             Self::ProcessedWelcomeNotFound => write!(f, "processed welcome not found"),
             Self::MergePendingCommit(e) => write!(f, "{e}"),
+            Self::MergeStagedCommit(e) => write!(f, "{e}"),
             Self::SelfUpdate(e) => write!(f, "{e}"),
             Self::Provider(e) => write!(f, "{e}"),
             Self::GroupNotFound => write!(f, "group not found"),
@@ -145,6 +151,7 @@ impl fmt::Display for Error {
                 "protocol message group ID doesn't match the current group ID"
             ),
             Self::OwnLeafNotFound => write!(f, "own leaf not found"),
+            Self::NotAdmin => write!(f, "only group admins can perform this operation"),
             Self::CantLoadSigner => write!(f, "can't load signer"),
             Self::InvalidWelcomeMessage => write!(f, "invalid welcome message"),
             Self::UnexpectedEvent { expected, received } => write!(
@@ -299,6 +306,24 @@ where
     }
 }
 
+impl<T> From<RemoveMembersError<T>> for Error
+where
+    T: fmt::Display,
+{
+    fn from(e: RemoveMembersError<T>) -> Self {
+        Self::Group(e.to_string())
+    }
+}
+
+impl<T> From<CreateGroupContextExtProposalError<T>> for Error
+where
+    T: fmt::Display,
+{
+    fn from(e: CreateGroupContextExtProposalError<T>) -> Self {
+        Self::Group(e.to_string())
+    }
+}
+
 impl<T> From<MergePendingCommitError<T>> for Error
 where
     T: fmt::Display,
@@ -308,6 +333,15 @@ where
     }
 }
 
+impl<T> From<MergeCommitError<T>> for Error
+where
+    T: fmt::Display,
+{
+    fn from(e: MergeCommitError<T>) -> Self {
+        Self::MergeStagedCommit(e.to_string())
+    }
+}
+
 impl<T> From<SelfUpdateError<T>> for Error
 where
     T: fmt::Display,
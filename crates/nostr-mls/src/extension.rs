@@ -12,7 +12,7 @@ use nostr::secp256k1::rand::rngs::OsRng;
 use nostr::secp256k1::rand::Rng;
 use nostr::util::hex;
 use nostr::{PublicKey, RelayUrl};
-use openmls::extensions::{Extension, ExtensionType};
+use openmls::extensions::{Extension, ExtensionType, UnknownExtension};
 use openmls::group::{GroupContext, MlsGroup};
 use tls_codec::{
     DeserializeBytes, TlsDeserialize, TlsDeserializeBytes, TlsSerialize, TlsSerializeBytes, TlsSize,
@@ -231,6 +231,15 @@ impl NostrGroupDataExtension {
         self.relays.remove(relay);
     }
 
+    /// Serializes the data as an MLS group context [`Extension`]
+    pub(crate) fn as_extension(&self) -> Result<Extension, Error> {
+        let serialized: Vec<u8> = tls_codec::Serialize::tls_serialize_detached(&self.as_raw())?;
+        Ok(Extension::Unknown(
+            self.extension_type(),
+            UnknownExtension(serialized),
+        ))
+    }
+
     pub(crate) fn as_raw(&self) -> RawNostrGroupDataExtension {
         RawNostrGroupDataExtension {
             nostr_group_id: self.nostr_group_id,
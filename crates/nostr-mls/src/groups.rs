@@ -14,8 +14,10 @@
 use std::collections::BTreeSet;
 use std::str;
 
-use nostr::{PublicKey, RelayUrl};
+use nostr::util::hex;
+use nostr::{Event, EventBuilder, Kind, PublicKey, RelayUrl, Tag, Timestamp, UnsignedEvent};
 use nostr_mls_storage::groups::types as group_types;
+use nostr_mls_storage::messages::types as message_types;
 use nostr_mls_storage::NostrMlsStorageProvider;
 use openmls::group::GroupId;
 use openmls::prelude::*;
@@ -46,6 +48,17 @@ pub struct SelfUpdateResult {
     pub new_secret: group_types::GroupExporterSecret,
 }
 
+/// Result of a commit that changes the roster of an MLS group
+#[derive(Debug)]
+pub struct UpdateGroupResult {
+    /// kind:445 event carrying the commit, to be published to the group relays
+    pub evolution_event: Event,
+    /// kind:444 welcome rumors for the new members (one per key package event)
+    ///
+    /// Each rumor must be gift-wrapped to its recipient before publishing.
+    pub welcome_rumors: Option<Vec<UnsignedEvent>>,
+}
+
 impl<Storage> NostrMls<Storage>
 where
     Storage: NostrMlsStorageProvider,
@@ -87,6 +100,82 @@ where
         .ok_or(Error::CantLoadSigner)
     }
 
+    /// Gets the Nostr public key of the current member in an MLS group
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Reference to the MLS group
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PublicKey)` - The public key stored in the member's credential
+    /// * `Err(Error)` - If the leaf node is not found or the credential is invalid
+    pub(crate) fn get_own_pubkey(&self, group: &MlsGroup) -> Result<PublicKey, Error> {
+        let own_leaf: &LeafNode = self.get_own_leaf(group)?;
+        pubkey_from_credential(own_leaf.credential().clone())
+    }
+
+    /// Checks that the current member is an admin of the MLS group
+    ///
+    /// The admin list is read from the group's [`NostrGroupDataExtension`].
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Reference to the MLS group
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the current member is an admin
+    /// * `Err(Error::NotAdmin)` - If the current member isn't an admin
+    pub(crate) fn ensure_own_admin(&self, group: &MlsGroup) -> Result<(), Error> {
+        let own_pubkey: PublicKey = self.get_own_pubkey(group)?;
+        let group_data = NostrGroupDataExtension::from_group(group)?;
+
+        if group_data.admins.contains(&own_pubkey) {
+            Ok(())
+        } else {
+            Err(Error::NotAdmin)
+        }
+    }
+
+    /// Checks that the sender of a message is an admin of the MLS group
+    ///
+    /// The admin list is read from the group's [`NostrGroupDataExtension`], so this must be
+    /// called before merging the sender's commit.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Reference to the MLS group
+    /// * `sender` - The sender of the message
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the sender is an admin
+    /// * `Err(Error::NotAdmin)` - If the sender isn't a member or isn't an admin
+    pub(crate) fn ensure_sender_admin(
+        &self,
+        group: &MlsGroup,
+        sender: &Sender,
+    ) -> Result<(), Error> {
+        let leaf_index: LeafNodeIndex = match sender {
+            Sender::Member(leaf_index) => *leaf_index,
+            _ => return Err(Error::NotAdmin),
+        };
+
+        let member = group
+            .members()
+            .find(|member| member.index == leaf_index)
+            .ok_or(Error::NotAdmin)?;
+        let public_key: PublicKey = pubkey_from_credential(member.credential)?;
+        let group_data = NostrGroupDataExtension::from_group(group)?;
+
+        if group_data.admins.contains(&public_key) {
+            Ok(())
+        } else {
+            Err(Error::NotAdmin)
+        }
+    }
+
     /// Loads an MLS group from storage by its ID
     ///
     /// # Arguments
@@ -192,14 +281,15 @@ where
     /// * `Err(Error)` - If the group is not found or there is an error accessing member data
     pub fn get_members(&self, group_id: &GroupId) -> Result<BTreeSet<PublicKey>, Error> {
         let group = self.load_mls_group(group_id)?.ok_or(Error::GroupNotFound)?;
+        self.group_members(&group)
+    }
 
+    /// Gets the public keys of all members of a loaded MLS group
+    pub(crate) fn group_members(&self, group: &MlsGroup) -> Result<BTreeSet<PublicKey>, Error> {
         // Store members in a variable to extend its lifetime
         let mut members = group.members();
         members.try_fold(BTreeSet::new(), |mut acc, m| {
-            let credentials: BasicCredential = BasicCredential::try_from(m.credential)?;
-            let hex_bytes: &[u8] = credentials.identity();
-            let hex_str: &str = str::from_utf8(hex_bytes)?;
-            let public_key = PublicKey::from_hex(hex_str)?;
+            let public_key: PublicKey = pubkey_from_credential(m.credential)?;
             acc.insert(public_key);
             Ok(acc)
         })
@@ -287,15 +377,7 @@ where
             group_data
         );
 
-        let serialized_group_data = group_data
-            .as_raw()
-            .tls_serialize_detached()
-            .expect("Failed to serialize group data");
-
-        let extensions = vec![Extension::Unknown(
-            group_data.extension_type(),
-            UnknownExtension(serialized_group_data),
-        )];
+        let extensions = vec![group_data.as_extension()?];
         let extensions =
             Extensions::from_vec(extensions).expect("Couldn't convert extensions vec to Object");
 
@@ -468,6 +550,326 @@ where
         })
     }
 
+    /// Adds new members to an existing MLS group
+    ///
+    /// This function:
+    /// 1. Parses the key packages from the kind:443 events
+    /// 2. Creates a commit adding the members
+    /// 3. Wraps the commit in a kind:445 event, encrypted with the current epoch's secret
+    /// 4. Merges the commit and exports the new epoch's secret
+    /// 5. Builds a kind:444 welcome rumor for each new member
+    ///
+    /// Only group admins can add members.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The MLS group ID
+    /// * `key_package_events` - The kind:443 key package events of the members to add
+    ///
+    /// # Returns
+    ///
+    /// An [`UpdateGroupResult`] containing the evolution event and the welcome rumors
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Error` if:
+    /// - The group is not found
+    /// - The current member is not an admin
+    /// - A key package event is invalid
+    /// - Adding members or merging the commit fails
+    pub fn add_members(
+        &self,
+        group_id: &GroupId,
+        key_package_events: &[Event],
+    ) -> Result<UpdateGroupResult, Error> {
+        let mut mls_group = self.load_mls_group(group_id)?.ok_or(Error::GroupNotFound)?;
+        let group = self.get_group(group_id)?.ok_or(Error::GroupNotFound)?;
+
+        self.ensure_own_admin(&mls_group)?;
+
+        let key_packages: Vec<KeyPackage> = key_package_events
+            .iter()
+            .map(|event| self.parse_key_package(event))
+            .collect::<Result<_, _>>()?;
+
+        let signer: SignatureKeyPair = self.load_mls_signer(&mls_group)?;
+        let (commit_message, welcome_message, _group_info) =
+            mls_group.add_members(&self.provider, &signer, &key_packages)?;
+
+        let serialized_commit_message = commit_message.tls_serialize_detached()?;
+        let serialized_welcome_message = welcome_message.tls_serialize_detached()?;
+
+        // Must be wrapped before merging, the other members are still in the current epoch
+        let evolution_event: Event = self.build_encrypted_message_event(
+            group_id,
+            &group.nostr_group_id,
+            &serialized_commit_message,
+        )?;
+
+        mls_group.merge_pending_commit(&self.provider)?;
+        self.commit_created(&evolution_event, &mls_group, group)?;
+
+        // Build a welcome rumor for each new member
+        let own_pubkey: PublicKey = self.get_own_pubkey(&mls_group)?;
+        let relays: BTreeSet<RelayUrl> = self.get_relays(group_id)?;
+        let welcome_rumors: Vec<UnsignedEvent> = key_package_events
+            .iter()
+            .map(|event| {
+                EventBuilder::new(Kind::MlsWelcome, hex::encode(&serialized_welcome_message))
+                    .tags([Tag::relays(relays.clone()), Tag::event(event.id)])
+                    .build(own_pubkey)
+            })
+            .collect();
+
+        Ok(UpdateGroupResult {
+            evolution_event,
+            welcome_rumors: Some(welcome_rumors),
+        })
+    }
+
+    /// Removes members from an existing MLS group
+    ///
+    /// This function creates a commit removing the members, wraps it in a kind:445 event
+    /// (encrypted with the current epoch's secret), merges it and exports the new epoch's secret.
+    /// Since the removed members don't know the new exporter secret, they can't read any
+    /// message sent after the commit.
+    ///
+    /// Only group admins can remove members.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The MLS group ID
+    /// * `public_keys` - The public keys of the members to remove
+    ///
+    /// # Returns
+    ///
+    /// An [`UpdateGroupResult`] containing the evolution event
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Error` if:
+    /// - The group is not found
+    /// - The current member is not an admin
+    /// - A public key doesn't belong to a member of the group
+    /// - Removing members or merging the commit fails
+    pub fn remove_members(
+        &self,
+        group_id: &GroupId,
+        public_keys: &[PublicKey],
+    ) -> Result<UpdateGroupResult, Error> {
+        let mut mls_group = self.load_mls_group(group_id)?.ok_or(Error::GroupNotFound)?;
+        let group = self.get_group(group_id)?.ok_or(Error::GroupNotFound)?;
+
+        self.ensure_own_admin(&mls_group)?;
+
+        // Find the leaf indices of the members to remove
+        let mut leaf_indices: Vec<LeafNodeIndex> = Vec::with_capacity(public_keys.len());
+        for public_key in public_keys.iter() {
+            let index: LeafNodeIndex = mls_group
+                .members()
+                .find(|member| {
+                    pubkey_from_credential(member.credential.clone())
+                        .is_ok_and(|pk| &pk == public_key)
+                })
+                .map(|member| member.index)
+                .ok_or_else(|| Error::Group(format!("{public_key} is not a member")))?;
+            leaf_indices.push(index);
+        }
+
+        let signer: SignatureKeyPair = self.load_mls_signer(&mls_group)?;
+        let (commit_message, _welcome_message, _group_info) =
+            mls_group.remove_members(&self.provider, &signer, &leaf_indices)?;
+
+        let serialized_commit_message = commit_message.tls_serialize_detached()?;
+
+        // Must be wrapped before merging, the other members are still in the current epoch
+        let evolution_event: Event = self.build_encrypted_message_event(
+            group_id,
+            &group.nostr_group_id,
+            &serialized_commit_message,
+        )?;
+
+        mls_group.merge_pending_commit(&self.provider)?;
+        self.commit_created(&evolution_event, &mls_group, group)?;
+
+        Ok(UpdateGroupResult {
+            evolution_event,
+            welcome_rumors: None,
+        })
+    }
+
+    /// Gets the Nostr group data (name, description, admins, relays, ...) of an MLS group
+    ///
+    /// The data is read from the group's [`NostrGroupDataExtension`]. The returned value can be
+    /// edited with its setters and passed to [`NostrMls::update_group_data`].
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The MLS group ID
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(NostrGroupDataExtension)` - The current group data
+    /// * `Err(Error)` - If the group is not found or the extension can't be parsed
+    pub fn get_group_data(&self, group_id: &GroupId) -> Result<NostrGroupDataExtension, Error> {
+        let mls_group = self.load_mls_group(group_id)?.ok_or(Error::GroupNotFound)?;
+        NostrGroupDataExtension::from_group(&mls_group)
+    }
+
+    /// Updates the Nostr group data (name, description, admins, relays, ...) of an MLS group
+    ///
+    /// This function creates a commit with a GroupContextExtensions proposal replacing the
+    /// group's [`NostrGroupDataExtension`], wraps it in a kind:445 event (encrypted with the
+    /// current epoch's secret), merges it and updates the stored group and relays.
+    ///
+    /// Only group admins can update the group data.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The MLS group ID
+    /// * `group_data` - The new group data, usually obtained with [`NostrMls::get_group_data`]
+    ///
+    /// # Returns
+    ///
+    /// An [`UpdateGroupResult`] containing the evolution event
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Error` if:
+    /// - The group is not found
+    /// - The current member is not an admin
+    /// - The group data has no admins or an admin isn't a member
+    /// - Creating or merging the commit fails
+    pub fn update_group_data(
+        &self,
+        group_id: &GroupId,
+        group_data: NostrGroupDataExtension,
+    ) -> Result<UpdateGroupResult, Error> {
+        let mut mls_group = self.load_mls_group(group_id)?.ok_or(Error::GroupNotFound)?;
+        let group = self.get_group(group_id)?.ok_or(Error::GroupNotFound)?;
+
+        self.ensure_own_admin(&mls_group)?;
+
+        // Validate admins
+        if group_data.admins.is_empty() {
+            return Err(Error::Group(
+                "Group must have at least one admin".to_string(),
+            ));
+        }
+
+        let members: BTreeSet<PublicKey> = self.group_members(&mls_group)?;
+        if !group_data.admins.is_subset(&members) {
+            return Err(Error::Group("Admin must be a member".to_string()));
+        }
+
+        let extensions = Extensions::from_vec(vec![group_data.as_extension()?])?;
+
+        let signer: SignatureKeyPair = self.load_mls_signer(&mls_group)?;
+        let (commit_message, _welcome_message, _group_info) =
+            mls_group.update_group_context_extensions(&self.provider, extensions, &signer)?;
+
+        let serialized_commit_message = commit_message.tls_serialize_detached()?;
+
+        // Must be wrapped before merging, the other members are still in the current epoch
+        let evolution_event: Event = self.build_encrypted_message_event(
+            group_id,
+            &group.nostr_group_id,
+            &serialized_commit_message,
+        )?;
+
+        mls_group.merge_pending_commit(&self.provider)?;
+        self.commit_created(&evolution_event, &mls_group, group)?;
+
+        Ok(UpdateGroupResult {
+            evolution_event,
+            welcome_rumors: None,
+        })
+    }
+
+    /// Stores the state resulting from a commit created (and merged) by the current member
+    ///
+    /// The evolution event is marked as processed, so that it's skipped when it comes back
+    /// from the relays, and the exporter secret of the new epoch is exported and cached.
+    fn commit_created(
+        &self,
+        evolution_event: &Event,
+        mls_group: &MlsGroup,
+        group: group_types::Group,
+    ) -> Result<(), Error> {
+        let processed_message = message_types::ProcessedMessage {
+            wrapper_event_id: evolution_event.id,
+            message_event_id: None,
+            processed_at: Timestamp::now(),
+            state: message_types::ProcessedMessageState::Processed,
+            failure_reason: None,
+        };
+
+        self.storage()
+            .save_processed_message(processed_message)
+            .map_err(|e| Error::Message(e.to_string()))?;
+
+        self.sync_group_with_mls_group(mls_group, group)?;
+
+        // Rotate the exporter secret
+        let new_secret = self.exporter_secret(mls_group.group_id())?;
+
+        tracing::debug!(target: "nostr_mls::groups::commit_created", "New epoch: {:?}", new_secret.epoch);
+
+        Ok(())
+    }
+
+    /// Updates the stored group with the current state of the MLS group
+    ///
+    /// This must be called after merging a commit. It updates:
+    /// - The epoch
+    /// - The Nostr group ID, name and description (from the [`NostrGroupDataExtension`])
+    /// - The admins (the admins in the [`NostrGroupDataExtension`] that are still members)
+    /// - The relays (from the [`NostrGroupDataExtension`])
+    /// - The group type
+    /// - The state, if the current member has been removed from the group
+    pub(crate) fn sync_group_with_mls_group(
+        &self,
+        mls_group: &MlsGroup,
+        mut group: group_types::Group,
+    ) -> Result<group_types::Group, Error> {
+        group.epoch = mls_group.epoch().as_u64();
+
+        if !mls_group.is_active() {
+            // We've been removed from the group
+            group.state = group_types::GroupState::Inactive;
+
+            self.storage()
+                .save_group(group.clone())
+                .map_err(|e| Error::Group(e.to_string()))?;
+
+            return Ok(group);
+        }
+
+        let group_data = NostrGroupDataExtension::from_group(mls_group)?;
+        let members: BTreeSet<PublicKey> = self.group_members(mls_group)?;
+
+        group.nostr_group_id = group_data.nostr_group_id;
+        group.name = group_data.name;
+        group.description = group_data.description;
+        group.admin_pubkeys = group_data.admins.intersection(&members).copied().collect();
+        group.group_type = if members.len() > 2 {
+            group_types::GroupType::Group
+        } else {
+            group_types::GroupType::DirectMessage
+        };
+
+        self.storage()
+            .save_group(group.clone())
+            .map_err(|e| Error::Group(e.to_string()))?;
+
+        // Always (re-)save the group relays after saving the group
+        self.storage()
+            .replace_group_relays(&group.mls_group_id, group_data.relays)
+            .map_err(|e| Error::Group(e.to_string()))?;
+
+        Ok(group)
+    }
+
     /// Validates the members and admins of a group during creation
     ///
     /// # Arguments
@@ -516,12 +918,41 @@ where
     }
 }
 
+/// Extracts the Nostr public key stored (hex-encoded) in a basic credential
+fn pubkey_from_credential(credential: Credential) -> Result<PublicKey, Error> {
+    let credentials: BasicCredential = BasicCredential::try_from(credential)?;
+    let hex_bytes: &[u8] = credentials.identity();
+    let hex_str: &str = str::from_utf8(hex_bytes)?;
+    Ok(PublicKey::from_hex(hex_str)?)
+}
+
 #[cfg(test)]
 mod tests {
-    use nostr::{Keys, PublicKey};
+    use nostr::{EventId, Keys, PublicKey};
+    use nostr_mls_memory_storage::NostrMlsMemoryStorage;
+    use nostr_mls_storage::groups::GroupStorage;
 
+    use super::*;
     use crate::tests::create_test_nostr_mls;
 
+    fn create_key_package_event(nostr_mls: &NostrMls<NostrMlsMemoryStorage>, keys: &Keys) -> Event {
+        let relay_url = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let (key_package, tags) = nostr_mls
+            .create_key_package_for_event(&keys.public_key(), [relay_url])
+            .unwrap();
+        EventBuilder::new(Kind::MlsKeyPackage, key_package)
+            .tags(tags)
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    fn join_group(nostr_mls: &NostrMls<NostrMlsMemoryStorage>, welcome_rumor: &UnsignedEvent) {
+        let welcome = nostr_mls
+            .process_welcome(&EventId::all_zeros(), welcome_rumor)
+            .unwrap();
+        nostr_mls.accept_welcome(&welcome).unwrap();
+    }
+
     fn create_test_group_members() -> (PublicKey, Vec<PublicKey>, Vec<PublicKey>) {
         let creator = Keys::generate();
         let member1 = Keys::generate();
@@ -563,4 +994,233 @@ mod tests {
             .validate_group_members(&creator_pk, &members, &bad_admins)
             .is_err());
     }
+
+    /// Alice creates a group with Bob, and Bob joins it
+    fn create_two_member_group(
+        alice_nostr_mls: &NostrMls<NostrMlsMemoryStorage>,
+        alice_keys: &Keys,
+        bob_nostr_mls: &NostrMls<NostrMlsMemoryStorage>,
+        bob_keys: &Keys,
+    ) -> CreateGroupResult {
+        let bob_key_package_event = create_key_package_event(bob_nostr_mls, bob_keys);
+        let bob_key_package = alice_nostr_mls
+            .parse_key_package(&bob_key_package_event)
+            .unwrap();
+        let create_result = alice_nostr_mls
+            .create_group(
+                "Test group",
+                "Test description",
+                &alice_keys.public_key(),
+                vec![bob_keys.public_key()],
+                vec![bob_key_package],
+                vec![alice_keys.public_key()],
+                vec![RelayUrl::parse("wss://relay.example.com").unwrap()],
+            )
+            .unwrap();
+        let bob_welcome_rumor = EventBuilder::new(
+            Kind::MlsWelcome,
+            hex::encode(&create_result.serialized_welcome_message),
+        )
+        .build(alice_keys.public_key());
+        join_group(bob_nostr_mls, &bob_welcome_rumor);
+        create_result
+    }
+
+    #[test]
+    fn test_add_and_remove_members() {
+        let alice_keys = Keys::generate();
+        let bob_keys = Keys::generate();
+        let carol_keys = Keys::generate();
+        let alice_nostr_mls = create_test_nostr_mls();
+        let bob_nostr_mls = create_test_nostr_mls();
+        let carol_nostr_mls = create_test_nostr_mls();
+
+        // Alice creates a group with Bob
+        let create_result =
+            create_two_member_group(&alice_nostr_mls, &alice_keys, &bob_nostr_mls, &bob_keys);
+        let group_id = create_result.group.mls_group_id.clone();
+
+        // Alice adds Carol
+        let carol_key_package_event = create_key_package_event(&carol_nostr_mls, &carol_keys);
+        let add_result = alice_nostr_mls
+            .add_members(&group_id, &[carol_key_package_event.clone()])
+            .unwrap();
+        assert_eq!(add_result.evolution_event.kind, Kind::MlsGroupMessage);
+        let welcome_rumors = add_result.welcome_rumors.unwrap();
+        assert_eq!(welcome_rumors.len(), 1);
+        assert_eq!(welcome_rumors[0].kind, Kind::MlsWelcome);
+        assert!(welcome_rumors[0]
+            .tags
+            .event_ids()
+            .any(|id| id == &carol_key_package_event.id));
+        assert_eq!(alice_nostr_mls.get_members(&group_id).unwrap().len(), 3);
+
+        let alice_group = alice_nostr_mls.get_group(&group_id).unwrap().unwrap();
+        assert_eq!(alice_group.epoch, create_result.group.epoch + 1);
+        assert_eq!(alice_group.group_type, group_types::GroupType::Group);
+
+        // Alice skips her own commit when it comes back from the relays
+        assert!(alice_nostr_mls
+            .process_message(&add_result.evolution_event)
+            .unwrap()
+            .is_none());
+
+        // Bob processes the commit, Carol joins
+        bob_nostr_mls
+            .process_message(&add_result.evolution_event)
+            .unwrap();
+        assert_eq!(bob_nostr_mls.get_members(&group_id).unwrap().len(), 3);
+        assert_eq!(
+            bob_nostr_mls.get_group(&group_id).unwrap().unwrap().epoch,
+            alice_group.epoch
+        );
+        join_group(&carol_nostr_mls, &welcome_rumors[0]);
+        assert_eq!(carol_nostr_mls.get_members(&group_id).unwrap().len(), 3);
+
+        // Bob is not an admin
+        let dave_keys = Keys::generate();
+        let dave_nostr_mls = create_test_nostr_mls();
+        let dave_key_package_event = create_key_package_event(&dave_nostr_mls, &dave_keys);
+        assert_eq!(
+            bob_nostr_mls
+                .add_members(&group_id, &[dave_key_package_event])
+                .unwrap_err(),
+            Error::NotAdmin
+        );
+        assert_eq!(
+            bob_nostr_mls
+                .remove_members(&group_id, &[carol_keys.public_key()])
+                .unwrap_err(),
+            Error::NotAdmin
+        );
+
+        // Alice removes Carol
+        let remove_result = alice_nostr_mls
+            .remove_members(&group_id, &[carol_keys.public_key()])
+            .unwrap();
+        assert!(remove_result.welcome_rumors.is_none());
+        let alice_members = alice_nostr_mls.get_members(&group_id).unwrap();
+        assert_eq!(alice_members.len(), 2);
+        assert!(!alice_members.contains(&carol_keys.public_key()));
+
+        // The exporter secret has been rotated
+        let old_secret = alice_nostr_mls
+            .storage()
+            .get_group_exporter_secret(&group_id, alice_group.epoch)
+            .unwrap()
+            .unwrap();
+        let new_secret = alice_nostr_mls.exporter_secret(&group_id).unwrap();
+        assert_eq!(new_secret.epoch, alice_group.epoch + 1);
+        assert_ne!(new_secret.secret, old_secret.secret);
+
+        // Bob and Carol process the commit
+        bob_nostr_mls
+            .process_message(&remove_result.evolution_event)
+            .unwrap();
+        assert_eq!(bob_nostr_mls.get_members(&group_id).unwrap(), alice_members);
+        assert_eq!(
+            bob_nostr_mls
+                .get_group(&group_id)
+                .unwrap()
+                .unwrap()
+                .group_type,
+            group_types::GroupType::DirectMessage
+        );
+
+        carol_nostr_mls
+            .process_message(&remove_result.evolution_event)
+            .unwrap();
+        assert_eq!(
+            carol_nostr_mls.get_group(&group_id).unwrap().unwrap().state,
+            group_types::GroupState::Inactive
+        );
+
+        // Removing a non-member fails
+        assert!(alice_nostr_mls
+            .remove_members(&group_id, &[carol_keys.public_key()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_group_data() {
+        let alice_keys = Keys::generate();
+        let bob_keys = Keys::generate();
+        let alice_nostr_mls = create_test_nostr_mls();
+        let bob_nostr_mls = create_test_nostr_mls();
+
+        let create_result =
+            create_two_member_group(&alice_nostr_mls, &alice_keys, &bob_nostr_mls, &bob_keys);
+        let group_id = create_result.group.mls_group_id.clone();
+
+        // Bob is not an admin
+        let group_data = bob_nostr_mls.get_group_data(&group_id).unwrap();
+        assert_eq!(
+            bob_nostr_mls
+                .update_group_data(&group_id, group_data)
+                .unwrap_err(),
+            Error::NotAdmin
+        );
+
+        // Admins must be members
+        let mut group_data = alice_nostr_mls.get_group_data(&group_id).unwrap();
+        group_data.add_admin(Keys::generate().public_key());
+        assert!(alice_nostr_mls
+            .update_group_data(&group_id, group_data)
+            .is_err());
+
+        // Alice renames the group, moves it to another relay and promotes Bob
+        let new_relay = RelayUrl::parse("wss://new-relay.example.com").unwrap();
+        let mut group_data = alice_nostr_mls.get_group_data(&group_id).unwrap();
+        group_data.set_name("New name".to_string());
+        group_data.set_description("New description".to_string());
+        group_data.relays.clear();
+        group_data.add_relay(new_relay.clone());
+        group_data.add_admin(bob_keys.public_key());
+        let update_result = alice_nostr_mls
+            .update_group_data(&group_id, group_data.clone())
+            .unwrap();
+        assert!(update_result.welcome_rumors.is_none());
+        assert_eq!(
+            alice_nostr_mls.get_group_data(&group_id).unwrap(),
+            group_data
+        );
+
+        // Bob processes the commit
+        bob_nostr_mls
+            .process_message(&update_result.evolution_event)
+            .unwrap();
+
+        for nostr_mls in [&alice_nostr_mls, &bob_nostr_mls] {
+            let group = nostr_mls.get_group(&group_id).unwrap().unwrap();
+            assert_eq!(group.name, "New name");
+            assert_eq!(group.description, "New description");
+            assert_eq!(
+                group.admin_pubkeys,
+                BTreeSet::from([alice_keys.public_key(), bob_keys.public_key()])
+            );
+            assert_eq!(group.epoch, create_result.group.epoch + 1);
+            assert_eq!(
+                nostr_mls.get_relays(&group_id).unwrap(),
+                BTreeSet::from([new_relay.clone()])
+            );
+        }
+
+        // Bob is now an admin
+        let mut group_data = bob_nostr_mls.get_group_data(&group_id).unwrap();
+        group_data.remove_admin(&alice_keys.public_key());
+        let update_result = bob_nostr_mls
+            .update_group_data(&group_id, group_data)
+            .unwrap();
+        alice_nostr_mls
+            .process_message(&update_result.evolution_event)
+            .unwrap();
+        assert_eq!(
+            alice_nostr_mls
+                .get_group(&group_id)
+                .unwrap()
+                .unwrap()
+                .admin_pubkeys,
+            BTreeSet::from([bob_keys.public_key()])
+        );
+    }
 }
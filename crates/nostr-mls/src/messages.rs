@@ -105,6 +105,56 @@ where
         Ok(serialized_message)
     }
 
+    /// Wraps a serialized MLS message in a kind:445 Nostr event
+    ///
+    /// The message is NIP-44 encrypted with keys derived from the exporter secret of the
+    /// group's **current** epoch and signed with a freshly generated ephemeral key.
+    ///
+    /// When wrapping a commit, this must be called before merging it, so that the other
+    /// members (who are still in the previous epoch) can decrypt it.
+    ///
+    /// # Arguments
+    ///
+    /// * `mls_group_id` - The MLS group ID
+    /// * `nostr_group_id` - The Nostr group ID, used for the `h` tag
+    /// * `serialized_message` - The serialized MLS message
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Event)` - The signed Nostr event ready for relay publication
+    /// * `Err(Error)` - If the secret can't be exported or encryption fails
+    pub(crate) fn build_encrypted_message_event(
+        &self,
+        mls_group_id: &GroupId,
+        nostr_group_id: &[u8; 32],
+        serialized_message: &[u8],
+    ) -> Result<Event, Error> {
+        // Export secret
+        let secret: group_types::GroupExporterSecret = self.exporter_secret(mls_group_id)?;
+
+        // Convert that secret to nostr keys
+        let secret_key: SecretKey = SecretKey::from_slice(&secret.secret)?;
+        let export_nostr_keys: Keys = Keys::new(secret_key);
+
+        // Encrypt the message content
+        let encrypted_content: String = nip44::encrypt(
+            export_nostr_keys.secret_key(),
+            &export_nostr_keys.public_key,
+            serialized_message,
+            nip44::Version::default(),
+        )?;
+
+        // Generate ephemeral key
+        let ephemeral_nostr_keys: Keys = Keys::generate();
+
+        let tag: Tag = Tag::custom(TagKind::h(), [hex::encode(nostr_group_id)]);
+        let event = EventBuilder::new(Kind::MlsGroupMessage, encrypted_content)
+            .tag(tag)
+            .sign_with_keys(&ephemeral_nostr_keys)?;
+
+        Ok(event)
+    }
+
     /// Creates a complete encrypted Nostr event for an MLS group message
     ///
     /// This is the main entry point for creating group messages. The function:
@@ -142,28 +192,9 @@ where
         // Create message
         let message: Vec<u8> = self.create_message_for_event(&mut mls_group, rumor.clone())?;
 
-        // Export secret
-        let secret: group_types::GroupExporterSecret = self.exporter_secret(mls_group_id)?;
-
-        // Convert that secret to nostr keys
-        let secret_key: SecretKey = SecretKey::from_slice(&secret.secret)?;
-        let export_nostr_keys: Keys = Keys::new(secret_key);
-
-        // Encrypt the message content
-        let encrypted_content: String = nip44::encrypt(
-            export_nostr_keys.secret_key(),
-            &export_nostr_keys.public_key,
-            &message,
-            nip44::Version::default(),
-        )?;
-
-        // Generate ephemeral key
-        let ephemeral_nostr_keys: Keys = Keys::generate();
-
-        let tag: Tag = Tag::custom(TagKind::h(), [hex::encode(group.nostr_group_id)]);
-        let event = EventBuilder::new(Kind::MlsGroupMessage, encrypted_content)
-            .tag(tag)
-            .sign_with_keys(&ephemeral_nostr_keys)?;
+        // Wrap the message in a kind:445 event
+        let event: Event =
+            self.build_encrypted_message_event(mls_group_id, &group.nostr_group_id, &message)?;
 
         // Create message to save to storage
         let message: message_types::Message = message_types::Message {
@@ -261,6 +292,8 @@ where
             "Processed message: {:?}",
             processed_message
         );
+        let sender: Sender = processed_message.sender().clone();
+
         // Handle the processed message based on its type
         match processed_message.into_content() {
             ProcessedMessageContent::ApplicationMessage(application_message) => {
@@ -278,7 +311,17 @@ where
             ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
                 // This is a commit message
                 tracing::debug!(target: "nostr_mls::messages::process_message_for_group", "Received commit message: {:?}", staged_commit);
-                // TODO: Handle commit message
+                // Only admins can add or remove members and change the group data,
+                // while any member can update its own leaf
+                if staged_commit
+                    .queued_proposals()
+                    .any(|proposal| !matches!(proposal.proposal(), Proposal::Update(..)))
+                {
+                    self.ensure_sender_admin(group, &sender)?;
+                }
+
+                // Move the group to the next epoch
+                group.merge_staged_commit(&self.provider, *staged_commit)?;
                 Ok(None)
             }
             ProcessedMessageContent::ExternalJoinProposalMessage(external_join_proposal) => {
@@ -330,6 +373,21 @@ where
         .try_into()
         .map_err(|_e| Error::Message("Failed to convert nostr group id to [u8; 32]".to_string()))?;
 
+        // Protocol messages (i.e., commits) that we created ourselves are saved as processed
+        // and merged when they are created, so there is nothing left to do.
+        if let Some(processed_message) = self
+            .storage()
+            .find_processed_message_by_event_id(&event.id)
+            .map_err(|e| Error::Message(e.to_string()))?
+        {
+            if processed_message.state == message_types::ProcessedMessageState::Processed
+                && processed_message.message_event_id.is_none()
+            {
+                tracing::debug!(target: "nostr_mls::messages::process_message", "Protocol message already processed");
+                return Ok(None);
+            }
+        }
+
         let mut group = self
             .storage()
             .find_group_by_nostr_group_id(&nostr_group_id)
@@ -415,6 +473,9 @@ where
                     .save_processed_message(processed_message)
                     .map_err(|e| Error::Message(e.to_string()))?;
 
+                // A commit may have changed the epoch, the roster or the admins
+                self.sync_group_with_mls_group(&mls_group, group)?;
+
                 Ok(None)
             }
             Err(e) => {
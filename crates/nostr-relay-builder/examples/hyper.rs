@@ -8,7 +8,7 @@ use std::pin::Pin;
 
 use base64::prelude::*;
 use hyper::body::Incoming;
-use hyper::header::{CONNECTION, SEC_WEBSOCKET_ACCEPT, UPGRADE};
+use hyper::header::{ACCEPT, CONNECTION, CONTENT_TYPE, SEC_WEBSOCKET_ACCEPT, UPGRADE};
 use hyper::server::conn::http1;
 use hyper::service::Service;
 use hyper::{Request, Response};
@@ -96,6 +96,26 @@ impl Service<Request<Incoming>> for HttpServer {
             }
         }
 
+        // serve NIP11 document
+        if req
+            .headers()
+            .get(ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.contains("application/nostr+json"))
+            .unwrap_or(false)
+        {
+            let relay: LocalRelay = self.relay.clone();
+            return Box::pin(async move {
+                let json: String = nostr::serde_json::to_string(&relay.nip11().await).unwrap();
+                Ok(base
+                    .status(200)
+                    .header(CONTENT_TYPE, "application/nostr+json")
+                    .header("access-control-allow-origin", "*")
+                    .body(json)
+                    .unwrap())
+            });
+        }
+
         // serve landing page otherwise
         Box::pin(async move {
             Ok(base
@@ -4,6 +4,7 @@
 
 //! Relay Builder
 
+use std::collections::HashSet;
 use std::fmt;
 use std::net::{IpAddr, SocketAddr};
 #[cfg(all(feature = "tor", any(target_os = "android", target_os = "ios")))]
@@ -11,7 +12,7 @@ use std::path::Path;
 #[cfg(feature = "tor")]
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use nostr_database::prelude::*;
 
@@ -164,6 +165,79 @@ pub struct RelayBuilderNip42 {
     // pub allowed: HashSet<PublicKey>,
 }
 
+/// NIP86 options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayBuilderNip86 {
+    /// Public URL of the relay management API (i.e., `https://relay.example.com`)
+    ///
+    /// The URL authorized by the NIP98 requests must match it exactly.
+    pub url: Url,
+    /// Public keys allowed to use the relay management API
+    pub admins: HashSet<PublicKey>,
+}
+
+impl RelayBuilderNip86 {
+    /// New NIP86 options
+    #[inline]
+    pub fn new<I>(url: Url, admins: I) -> Self
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        Self {
+            url,
+            admins: admins.into_iter().collect(),
+        }
+    }
+}
+
+/// Relay clock
+///
+/// Wraps a [`TimeSupplier`], used to know the current time (i.e., for the NIP40 expiration).
+#[derive(Clone)]
+pub(crate) struct RelayClock {
+    now: Arc<dyn Fn() -> Timestamp + Send + Sync>,
+}
+
+impl fmt::Debug for RelayClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelayClock").finish()
+    }
+}
+
+impl Default for RelayClock {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl RelayClock {
+    fn new<T>(supplier: T) -> Self
+    where
+        T: TimeSupplier + Send + Sync + 'static,
+    {
+        Self {
+            now: Arc::new(move || Timestamp::now_with_supplier(&supplier)),
+        }
+    }
+
+    /// Get current timestamp
+    #[inline]
+    pub(crate) fn now(&self) -> Timestamp {
+        (self.now)()
+    }
+}
+
+#[cfg(test)]
+impl RelayClock {
+    /// Clock that returns the timestamp of the provided function
+    pub(crate) fn from_fn<F>(now: F) -> Self
+    where
+        F: Fn() -> Timestamp + Send + Sync + 'static,
+    {
+        Self { now: Arc::new(now) }
+    }
+}
+
 /// Relay builder
 #[derive(Debug)]
 pub struct RelayBuilder {
@@ -171,6 +245,8 @@ pub struct RelayBuilder {
     pub(crate) addr: Option<IpAddr>,
     /// Port
     pub(crate) port: Option<u16>,
+    /// Public URL
+    pub(crate) public_url: Option<RelayUrl>,
     /// Database
     pub(crate) database: Arc<dyn NostrEventsDatabase>,
     /// Mode
@@ -179,6 +255,8 @@ pub struct RelayBuilder {
     pub(crate) rate_limit: RateLimit,
     /// NIP42 options
     pub(crate) nip42: Option<RelayBuilderNip42>,
+    /// NIP86 options
+    pub(crate) nip86: Option<RelayBuilderNip86>,
     /// Tor hidden service
     #[cfg(feature = "tor")]
     pub(crate) tor: Option<RelayBuilderHiddenService>,
@@ -190,6 +268,12 @@ pub struct RelayBuilder {
     pub(crate) write_plugins: Vec<Arc<dyn WritePolicy>>,
     /// Query policy plugins
     pub(crate) query_plugins: Vec<Arc<dyn QueryPolicy>>,
+    /// NIP11 relay information document
+    pub(crate) nip11: RelayInformationDocument,
+    /// Clock
+    pub(crate) clock: RelayClock,
+    /// Interval of the expired events sweeper
+    pub(crate) expiration_sweep_interval: Duration,
     /// Test options
     pub(crate) test: RelayTestOptions,
 }
@@ -199,6 +283,7 @@ impl Default for RelayBuilder {
         Self {
             addr: None,
             port: None,
+            public_url: None,
             database: Arc::new(MemoryDatabase::with_opts(MemoryDatabaseOptions {
                 events: true,
                 max_events: Some(75_000),
@@ -206,12 +291,16 @@ impl Default for RelayBuilder {
             mode: RelayBuilderMode::default(),
             rate_limit: RateLimit::default(),
             nip42: None,
+            nip86: None,
             #[cfg(feature = "tor")]
             tor: None,
             max_connections: None,
             min_pow: None,
             write_plugins: Vec::new(),
             query_plugins: Vec::new(),
+            nip11: RelayInformationDocument::default(),
+            clock: RelayClock::default(),
+            expiration_sweep_interval: Duration::from_secs(60),
             test: RelayTestOptions::default(),
         }
     }
@@ -232,6 +321,16 @@ impl RelayBuilder {
         self
     }
 
+    /// Set the public URL of the relay (i.e., `wss://relay.example.com`)
+    ///
+    /// Used to check if the NIP62 requests to vanish are addressed to this relay.
+    /// If not set, only the requests to vanish from all the relays are processed.
+    #[inline]
+    pub fn public_url(mut self, url: RelayUrl) -> Self {
+        self.public_url = Some(url);
+        self
+    }
+
     /// Set database
     #[inline]
     pub fn database<D>(mut self, database: D) -> Self
@@ -263,6 +362,19 @@ impl RelayBuilder {
         self
     }
 
+    /// Enable the NIP86 relay management API
+    ///
+    /// The API is served on the same port of the relay (HTTP `POST` with `Content-Type: application/nostr+json+rpc`)
+    /// and the requests must be authorized (NIP98) by one of the admins.
+    /// All the changes are applied immediately to the running relay.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/86.md>
+    #[inline]
+    pub fn nip86(mut self, opts: RelayBuilderNip86) -> Self {
+        self.nip86 = Some(opts);
+        self
+    }
+
     /// Set tor options
     #[inline]
     #[cfg(feature = "tor")]
@@ -305,6 +417,41 @@ impl RelayBuilder {
         self
     }
 
+    /// Set NIP11 relay information document
+    ///
+    /// The `supported_nips` and the `limitation` are automatically filled from the builder settings
+    /// (i.e., [`RelayBuilder::min_pow`] and [`RelayBuilder::nip42`]).
+    /// The `max_subscriptions` limitation is filled from the [`RateLimit`] only if not already set.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/11.md>
+    #[inline]
+    pub fn nip11(mut self, document: RelayInformationDocument) -> Self {
+        self.nip11 = document;
+        self
+    }
+
+    /// Set a custom time supplier
+    ///
+    /// Used to check the NIP40 expiration of the events.
+    /// By default, the system time is used.
+    #[inline]
+    pub fn time_supplier<T>(mut self, supplier: T) -> Self
+    where
+        T: TimeSupplier + Send + Sync + 'static,
+    {
+        self.clock = RelayClock::new(supplier);
+        self
+    }
+
+    /// Set the interval at which the expired events (NIP40) are purged from the database (default: 60 secs)
+    ///
+    /// Expired events are anyway never served, also between two sweeps.
+    #[inline]
+    pub fn expiration_sweep_interval(mut self, interval: Duration) -> Self {
+        self.expiration_sweep_interval = interval;
+        self
+    }
+
     /// Testing options
     #[inline]
     pub(crate) fn test(mut self, test: RelayTestOptions) -> Self {
@@ -8,12 +8,15 @@ use std::{fmt, io};
 
 #[cfg(feature = "tor")]
 use async_wsocket::native::tor;
+use nostr_database::DatabaseError;
 
 /// Relay builder error
 #[derive(Debug)]
 pub enum Error {
     /// I/O error
     IO(io::Error),
+    /// Database error
+    Database(DatabaseError),
     /// Tor error
     #[cfg(feature = "tor")]
     Tor(tor::Error),
@@ -25,6 +28,7 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::IO(e) => write!(f, "{e}"),
+            Self::Database(e) => write!(f, "{e}"),
             #[cfg(feature = "tor")]
             Self::Tor(e) => write!(f, "{e}"),
         }
@@ -37,6 +41,12 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<DatabaseError> for Error {
+    fn from(e: DatabaseError) -> Self {
+        Self::Database(e)
+    }
+}
+
 #[cfg(feature = "tor")]
 impl From<tor::Error> for Error {
     fn from(e: tor::Error) -> Self {
@@ -0,0 +1,170 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP40 expiration index
+
+use std::collections::BTreeSet;
+
+use nostr_database::prelude::*;
+
+/// Number of stored events queried at a time to build the index
+const LOAD_PAGE_SIZE: usize = 1000;
+
+/// Index of the stored events with an expiration (NIP40)
+///
+/// Used to find the expired events without scanning the database.
+#[derive(Debug, Default)]
+pub(super) struct ExpirationIndex {
+    expirations: BTreeSet<(Timestamp, EventId)>,
+}
+
+impl ExpirationIndex {
+    /// Build the index from the stored events
+    pub async fn load(database: &dyn NostrEventsDatabase) -> Result<Self, DatabaseError> {
+        Self::load_paginated(database, LOAD_PAGE_SIZE).await
+    }
+
+    /// Walk the stored events in pages, from the newest to the oldest,
+    /// so that the whole database is never loaded in memory.
+    async fn load_paginated(
+        database: &dyn NostrEventsDatabase,
+        page_size: usize,
+    ) -> Result<Self, DatabaseError> {
+        let mut index: Self = Self::default();
+        let mut until: Option<Timestamp> = None;
+
+        loop {
+            let mut filter: Filter = Filter::new().limit(page_size);
+            if let Some(until) = until {
+                filter = filter.until(until);
+            }
+
+            let events: Events = database.query(filter).await?;
+
+            let oldest: Timestamp = match events.last() {
+                Some(event) => event.created_at,
+                None => break,
+            };
+            let saturated: bool = events.len() >= page_size;
+
+            for event in events.iter() {
+                index.insert(event);
+            }
+
+            if !saturated || oldest == Timestamp::zero() {
+                break;
+            }
+
+            // The page may end in the middle of a second: drain the oldest second entirely
+            let filter: Filter = Filter::new().since(oldest).until(oldest);
+            for event in database.query(filter).await?.iter() {
+                index.insert(event);
+            }
+
+            until = Some(oldest - 1);
+        }
+
+        Ok(index)
+    }
+
+    /// Index the event, if it has an expiration
+    pub fn insert(&mut self, event: &Event) {
+        if let Some(expiration) = event.tags.expiration() {
+            self.expirations.insert((*expiration, event.id));
+        }
+    }
+
+    /// Get the IDs of the events expired at the given timestamp
+    pub fn expired(&self, now: &Timestamp) -> Vec<EventId> {
+        self.expirations
+            .iter()
+            .take_while(|(expiration, ..)| expiration < now)
+            .map(|(.., id)| *id)
+            .collect()
+    }
+
+    /// Remove the events expired at the given timestamp
+    pub fn remove_expired(&mut self, now: &Timestamp) {
+        while let Some((expiration, ..)) = self.expirations.first() {
+            if expiration >= now {
+                break;
+            }
+
+            self.expirations.pop_first();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(keys: &Keys, expiration: Option<u64>) -> Event {
+        event_at(keys, Timestamp::now(), expiration)
+    }
+
+    fn event_at(keys: &Keys, created_at: Timestamp, expiration: Option<u64>) -> Event {
+        let mut builder = EventBuilder::text_note("").custom_created_at(created_at);
+        if let Some(expiration) = expiration {
+            builder = builder.tag(Tag::expiration(Timestamp::from(expiration)));
+        }
+        builder.sign_with_keys(keys).unwrap()
+    }
+
+    #[test]
+    fn test_expired() {
+        let keys = Keys::generate();
+        let first = event(&keys, Some(10));
+        let second = event(&keys, Some(20));
+
+        let mut index = ExpirationIndex::default();
+        index.insert(&second);
+        index.insert(&first);
+        index.insert(&event(&keys, None));
+
+        assert!(index.expired(&Timestamp::from(10)).is_empty());
+        assert_eq!(index.expired(&Timestamp::from(11)), vec![first.id]);
+        assert_eq!(
+            index.expired(&Timestamp::from(21)),
+            vec![first.id, second.id]
+        );
+
+        index.remove_expired(&Timestamp::from(11));
+        assert_eq!(index.expired(&Timestamp::from(21)), vec![second.id]);
+
+        index.remove_expired(&Timestamp::from(21));
+        assert!(index.expired(&Timestamp::from(u64::MAX)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_paginated() {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let keys = Keys::generate();
+
+        // More events than the page size in the same second
+        // The expirations are in the future, or the database rejects the events
+        let expirations = [
+            (1, Some(1)),
+            (2, Some(2)),
+            (2, Some(3)),
+            (2, None),
+            (3, Some(4)),
+        ];
+        let mut expected: Vec<EventId> = Vec::new();
+        for (created_at, expiration) in expirations {
+            let expiration: Option<u64> = expiration.map(|e| 10_000_000_000 + e);
+            let event = event_at(&keys, Timestamp::from(created_at), expiration);
+            database.save_event(&event).await.unwrap();
+            if expiration.is_some() {
+                expected.push(event.id);
+            }
+        }
+
+        let index = ExpirationIndex::load_paginated(&database, 2).await.unwrap();
+        assert_eq!(index.expired(&Timestamp::from(u64::MAX)), expected);
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Minimal HTTP handling, needed to serve the NIP11 document and the NIP86 API before the websocket upgrade
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use nostr::nips::nip86;
+use nostr::JsonUtil;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+/// Max size of the HTTP request head
+const MAX_HEAD_SIZE: usize = 8 * 1024;
+/// Max size of the NIP86 request body
+const MAX_BODY_SIZE: usize = 64 * 1024;
+const NIP11_CONTENT_TYPE: &str = "application/nostr+json";
+
+/// HTTP request head
+pub(super) struct RequestHead {
+    /// Raw bytes read from the stream (may also include the beginning of the body)
+    raw: Vec<u8>,
+}
+
+impl RequestHead {
+    /// Read the HTTP request head from the stream
+    pub async fn read<S>(stream: &mut S) -> io::Result<Self>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let mut raw: Vec<u8> = Vec::with_capacity(1024);
+        let mut buf: [u8; 1024] = [0u8; 1024];
+
+        while !raw.windows(4).any(|w| w == b"\r\n\r\n") {
+            if raw.len() >= MAX_HEAD_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "HTTP request head too large",
+                ));
+            }
+
+            let n: usize = stream.read(&mut buf).await?;
+
+            if n == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+
+            raw.extend_from_slice(&buf[..n]);
+        }
+
+        Ok(Self { raw })
+    }
+
+    fn lines(&self) -> impl Iterator<Item = &str> {
+        self.raw
+            .split(|b| *b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| std::str::from_utf8(line).ok())
+    }
+
+    fn method(&self) -> Option<&str> {
+        self.lines().next()?.split_whitespace().next()
+    }
+
+    /// Get header value (case-insensitive name)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            if key.trim().eq_ignore_ascii_case(name) {
+                Some(value.trim())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Check if it's a websocket upgrade request
+    pub fn is_websocket_upgrade(&self) -> bool {
+        self.header("upgrade")
+            .map(|v| v.eq_ignore_ascii_case("websocket"))
+            .unwrap_or(false)
+    }
+
+    /// Check if it's a NIP11 request
+    ///
+    /// Also the CORS preflight requests are considered NIP11 requests.
+    pub fn is_nip11(&self) -> bool {
+        if self.is_websocket_upgrade() {
+            return false;
+        }
+
+        match self.method() {
+            Some("GET") => self
+                .header("accept")
+                .map(|v| v.contains(NIP11_CONTENT_TYPE))
+                .unwrap_or(false),
+            Some("OPTIONS") => true,
+            _ => false,
+        }
+    }
+
+    /// Check if it's a NIP86 request
+    pub fn is_nip86(&self) -> bool {
+        self.method() == Some("POST")
+            && self
+                .header("content-type")
+                .map(|v| v.starts_with(nip86::CONTENT_TYPE))
+                .unwrap_or(false)
+    }
+
+    /// Read the request body, according to the `Content-Length` header
+    pub async fn read_body<S>(&self, stream: &mut S) -> io::Result<Vec<u8>>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let len: usize = self
+            .header("content-length")
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing content length"))?;
+
+        if len > MAX_BODY_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "HTTP request body too large",
+            ));
+        }
+
+        // Bytes of the body already read together with the head
+        let start: usize = self
+            .raw
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .map(|pos| pos + 4)
+            .unwrap_or(self.raw.len());
+        let mut body: Vec<u8> = self.raw[start..].to_vec();
+        body.truncate(len);
+
+        // Read the remaining bytes
+        if body.len() < len {
+            let offset: usize = body.len();
+            body.resize(len, 0);
+            stream.read_exact(&mut body[offset..]).await?;
+        }
+
+        Ok(body)
+    }
+
+    /// Consume the head and return a stream that replays the already read bytes
+    #[inline]
+    pub fn into_stream<S>(self, stream: S) -> Rewind<S> {
+        Rewind {
+            prefix: self.raw,
+            pos: 0,
+            inner: stream,
+        }
+    }
+}
+
+/// Write the NIP11 response
+pub(super) async fn send_nip11_response<S>(stream: &mut S, json: &str) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let response: String = format!(
+        "HTTP/1.1 200 OK\r\n\
+        Content-Type: {NIP11_CONTENT_TYPE}\r\n\
+        Access-Control-Allow-Origin: *\r\n\
+        Access-Control-Allow-Headers: *\r\n\
+        Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+        Content-Length: {}\r\n\
+        Connection: close\r\n\
+        \r\n\
+        {json}",
+        json.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    stream.shutdown().await
+}
+
+/// Write the NIP86 response
+pub(super) async fn send_nip86_response<S>(
+    stream: &mut S,
+    status: &str,
+    response: &nip86::Response,
+) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let json: String = response.as_json();
+    let response: String = format!(
+        "HTTP/1.1 {status}\r\n\
+        Content-Type: application/json\r\n\
+        Access-Control-Allow-Origin: *\r\n\
+        Content-Length: {}\r\n\
+        Connection: close\r\n\
+        \r\n\
+        {json}",
+        json.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    stream.shutdown().await
+}
+
+/// Stream that first returns the bytes already consumed from the inner stream
+pub(super) struct Rewind<S> {
+    prefix: Vec<u8>,
+    pos: usize,
+    inner: S,
+}
+
+impl<S> AsyncRead for Rewind<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.pos < self.prefix.len() {
+            let remaining: &[u8] = &self.prefix[self.pos..];
+            let len: usize = remaining.len().min(buf.remaining());
+            buf.put_slice(&remaining[..len]);
+            self.pos += len;
+            return Poll::Ready(Ok(()));
+        }
+
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S> AsyncWrite for Rewind<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NIP11_REQUEST: &[u8] =
+        b"GET / HTTP/1.1\r\nHost: relay.example.com\r\nAccept: application/nostr+json\r\n\r\n";
+
+    #[tokio::test]
+    async fn test_read_head() {
+        let mut stream: &[u8] = NIP11_REQUEST;
+        let head = RequestHead::read(&mut stream).await.unwrap();
+
+        assert_eq!(head.method(), Some("GET"));
+        assert_eq!(head.header("ACCEPT"), Some(NIP11_CONTENT_TYPE));
+        assert!(head.is_nip11());
+        assert!(!head.is_nip86());
+        assert!(!head.is_websocket_upgrade());
+    }
+
+    #[tokio::test]
+    async fn test_read_incomplete_head() {
+        let mut stream: &[u8] = b"GET / HTTP/1.1\r\nHost: relay.example.com\r\n";
+        let err = RequestHead::read(&mut stream).await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_read_head_too_large() {
+        let raw: Vec<u8> = vec![b'a'; MAX_HEAD_SIZE + 1];
+        let mut stream: &[u8] = &raw;
+        let err = RequestHead::read(&mut stream).await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_upgrade() {
+        let mut stream: &[u8] =
+            b"GET / HTTP/1.1\r\nHost: relay.example.com\r\nUpgrade: websocket\r\nAccept: application/nostr+json\r\n\r\n";
+        let head = RequestHead::read(&mut stream).await.unwrap();
+        assert!(head.is_websocket_upgrade());
+        assert!(!head.is_nip11());
+    }
+
+    #[tokio::test]
+    async fn test_read_body() {
+        let mut stream: &[u8] = b"POST / HTTP/1.1\r\nContent-Type: application/nostr+json+rpc\r\nContent-Length: 11\r\n\r\nhello world";
+        let head = RequestHead::read(&mut stream).await.unwrap();
+        assert!(head.is_nip86());
+
+        let body = head.read_body(&mut stream).await.unwrap();
+        assert_eq!(body, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_rewind() {
+        let mut stream: &[u8] = NIP11_REQUEST;
+        let head = RequestHead::read(&mut stream).await.unwrap();
+
+        let mut replayed: Vec<u8> = Vec::new();
+        head.into_stream(stream)
+            .read_to_end(&mut replayed)
+            .await
+            .unwrap();
+        assert_eq!(replayed, NIP11_REQUEST);
+    }
+}
@@ -3,33 +3,40 @@
 // Distributed under the MIT software license
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 
-use async_utility::futures_util::stream::{self, SplitSink};
-use async_utility::futures_util::{SinkExt, StreamExt};
-use async_wsocket::native::{self, Message, WebSocketStream};
+use async_utility::futures_util::stream;
+use async_utility::futures_util::{Sink, SinkExt, Stream, StreamExt};
+use async_wsocket::native::{self, Message};
 use atomic_destructor::AtomicDestroyer;
 use negentropy::{Id, Negentropy, NegentropyStorageVector};
 use nostr_database::prelude::*;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
-use tokio::sync::{broadcast, Notify, Semaphore};
+use tokio::sync::{broadcast, Mutex, Notify, RwLock, Semaphore, SemaphorePermit};
 
+use super::expiration::ExpirationIndex;
+use super::http::{self, RequestHead};
+use super::management::{self, Moderation};
 use super::session::{Nip42Session, RateLimiterResponse, Session, Tokens};
 use super::util;
 use crate::builder::{
     PolicyResult, QueryPolicy, RateLimit, RelayBuilder, RelayBuilderMode, RelayBuilderNip42,
-    RelayTestOptions, WritePolicy,
+    RelayBuilderNip86, RelayClock, RelayTestOptions, WritePolicy,
 };
 use crate::error::Error;
 
-type WsTx<S> = SplitSink<WebSocketStream<S>, Message>;
+/// Max time to wait for the HTTP request (head and body) of a new connection
+const HTTP_READ_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone)]
 pub(super) struct InnerLocalRelay {
     addr: SocketAddr,
+    public_url: Option<RelayUrl>,
     database: Arc<dyn NostrEventsDatabase>,
     shutdown: Arc<Notify>,
     /// Channel to notify new event received
@@ -45,6 +52,15 @@ pub(super) struct InnerLocalRelay {
     write_policy: Vec<Arc<dyn WritePolicy>>,
     query_policy: Vec<Arc<dyn QueryPolicy>>,
     nip42: Option<RelayBuilderNip42>,
+    nip86: Option<RelayBuilderNip86>,
+    nip11: Arc<RwLock<RelayInformationDocument>>,
+    /// Moderation state, editable through the NIP86 API
+    moderation: Arc<RwLock<Moderation>>,
+    /// Stored events with an expiration (NIP40)
+    expirations: Arc<Mutex<ExpirationIndex>>,
+    /// Public keys that requested to vanish (NIP62), with the timestamp of the last request
+    vanished: Arc<RwLock<HashMap<PublicKey, Timestamp>>>,
+    clock: RelayClock,
     test: RelayTestOptions,
 }
 
@@ -91,9 +107,16 @@ impl InnerLocalRelay {
 
         let max_connections: usize = builder.max_connections.unwrap_or(Semaphore::MAX_PERMITS);
 
+        // Compose NIP11 document
+        let nip11: RelayInformationDocument = build_nip11_document(&builder);
+
+        // Index the stored events with an expiration
+        let expirations: ExpirationIndex = ExpirationIndex::load(builder.database.as_ref()).await?;
+
         // Compose relay
-        Ok(Self {
+        let relay: Self = Self {
             addr,
+            public_url: builder.public_url,
             database: builder.database,
             shutdown: Arc::new(Notify::new()),
             new_event,
@@ -106,8 +129,27 @@ impl InnerLocalRelay {
             write_policy: builder.write_plugins,
             query_policy: builder.query_plugins,
             nip42: builder.nip42,
+            nip86: builder.nip86,
+            nip11: Arc::new(RwLock::new(nip11)),
+            moderation: Arc::new(RwLock::new(Moderation::default())),
+            expirations: Arc::new(Mutex::new(expirations)),
+            vanished: Arc::new(RwLock::new(HashMap::new())),
+            clock: builder.clock,
             test: builder.test,
-        })
+        };
+
+        // Cache the public keys that requested to vanish
+        let filter: Filter = Filter::new().kind(Kind::RequestToVanish);
+        for event in relay.database.query(filter).await? {
+            if relay.is_vanish_target(&event) {
+                relay.mark_vanished(&event).await;
+            }
+        }
+
+        // Purge expired events
+        relay.spawn_expiration_sweeper(builder.expiration_sweep_interval);
+
+        Ok(relay)
     }
 
     pub async fn run(builder: RelayBuilder) -> Result<Self, Error> {
@@ -149,6 +191,48 @@ impl InnerLocalRelay {
         Ok(())
     }
 
+    /// Periodically delete the expired events (NIP40) from the database
+    fn spawn_expiration_sweeper(&self, interval: Duration) {
+        let relay: Self = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {
+                        match relay.delete_expired_events().await {
+                            Ok(0) => {}
+                            Ok(deleted) => tracing::debug!("Deleted {deleted} expired events."),
+                            Err(e) => tracing::error!("Can't delete expired events: {e}"),
+                        }
+                    }
+                    _ = relay.shutdown.notified() => break,
+                }
+            }
+
+            tracing::info!("Local relay expiration sweeper loop terminated.");
+        });
+    }
+
+    /// Delete the expired events (NIP40) from the database
+    ///
+    /// Returns the number of deleted events.
+    async fn delete_expired_events(&self) -> Result<usize, DatabaseError> {
+        let now: Timestamp = self.clock.now();
+        let ids: Vec<EventId> = self.expirations.lock().await.expired(&now);
+
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let deleted: usize = ids.len();
+        self.database.delete(Filter::new().ids(ids)).await?;
+
+        // Remove from the index only once deleted, to retry at the next sweep in case of error
+        self.expirations.lock().await.remove_expired(&now);
+
+        Ok(deleted)
+    }
+
     #[inline]
     pub fn url(&self) -> String {
         format!("ws://{}", self.addr)
@@ -160,6 +244,11 @@ impl InnerLocalRelay {
         self.hidden_service.as_deref()
     }
 
+    #[inline]
+    pub async fn nip11(&self) -> RelayInformationDocument {
+        self.nip11.read().await.clone()
+    }
+
     pub fn notify_event(&self, event: Event) -> bool {
         self.new_event.send(event).is_ok()
     }
@@ -183,16 +272,61 @@ impl InnerLocalRelay {
             tokio::time::sleep(unresponsive_connection).await;
         }
 
+        // Check if IP is blocked
+        if self.moderation.read().await.is_ip_blocked(&addr.ip()) {
+            tracing::debug!("Rejected connection from blocked IP: {addr}");
+            return Ok(());
+        }
+
+        // Try to acquire connection limit
+        let permit: SemaphorePermit<'_> = self.connections_limit.try_acquire()?;
+
         // Accept websocket
         let ws_stream = native::take_upgraded(stream).await;
 
-        self.handle_websocket(ws_stream, addr).await?;
+        let (tx, rx) = ws_stream.split();
+        self.handle_websocket(permit, tx, rx, addr).await?;
+
+        Ok(())
+    }
+
+    /// Handle a connection that exchanges the JSON messages directly (i.e. in-memory transports)
+    pub(crate) async fn handle_message_connection<T, E>(
+        &self,
+        connection: T,
+        addr: SocketAddr,
+    ) -> Result<()>
+    where
+        T: Stream<Item = String> + Sink<String, Error = E> + Unpin,
+        E: std::error::Error + 'static,
+    {
+        // Check if IP is blocked
+        if self.moderation.read().await.is_ip_blocked(&addr.ip()) {
+            tracing::debug!("Rejected connection from blocked IP: {addr}");
+            return Ok(());
+        }
+
+        // Try to acquire connection limit
+        let permit: SemaphorePermit<'_> = self.connections_limit.try_acquire()?;
+
+        let (tx, rx) = connection.split();
+
+        // Only text messages are sent by the relay
+        let tx = tx.with_flat_map(|msg: Message| {
+            stream::iter(match msg {
+                Message::Text(json) => Some(Ok(json.as_str().to_string())),
+                _ => None,
+            })
+        });
+        let rx = rx.map(|json| Ok::<Message, Infallible>(Message::Text(json.into())));
+
+        self.handle_websocket(permit, tx, rx, addr).await?;
 
         Ok(())
     }
 
     /// Pass bare [TcpStream] for handling
-    async fn handle_connection<S>(self, raw_stream: S, addr: SocketAddr) -> Result<()>
+    async fn handle_connection<S>(self, mut raw_stream: S, addr: SocketAddr) -> Result<()>
     where
         S: AsyncRead + AsyncWrite + Unpin,
     {
@@ -200,32 +334,135 @@ impl InnerLocalRelay {
             tokio::time::sleep(unresponsive_connection).await;
         }
 
+        // Check if IP is blocked
+        if self.moderation.read().await.is_ip_blocked(&addr.ip()) {
+            tracing::debug!("Rejected connection from blocked IP: {addr}");
+            return Ok(());
+        }
+
+        // Try to acquire connection limit
+        //
+        // Acquired before reading the HTTP request, so slow clients can't exceed the limit.
+        let permit: SemaphorePermit<'_> = self.connections_limit.try_acquire()?;
+
+        // Read HTTP request head
+        let head: RequestHead =
+            tokio::time::timeout(HTTP_READ_TIMEOUT, RequestHead::read(&mut raw_stream)).await??;
+
+        // Serve NIP11 document
+        if head.is_nip11() {
+            tracing::debug!("Serving NIP11 document to {addr}");
+            let json: String = nostr::serde_json::to_string(&*self.nip11.read().await)?;
+            http::send_nip11_response(&mut raw_stream, &json).await?;
+            return Ok(());
+        }
+
+        // Serve NIP86 API
+        if let Some(opts) = &self.nip86 {
+            if head.is_nip86() {
+                tracing::debug!("Handling NIP86 request from {addr}");
+                return self.handle_nip86(&mut raw_stream, &head, opts).await;
+            }
+        }
+
         // Accept websocket
-        let ws_stream = native::accept(raw_stream).await?;
+        let ws_stream = native::accept(head.into_stream(raw_stream)).await?;
 
-        self.handle_websocket(ws_stream, addr).await?;
+        let (tx, rx) = ws_stream.split();
+        self.handle_websocket(permit, tx, rx, addr).await?;
 
         Ok(())
     }
 
-    /// Handle websocket connection
-    async fn handle_websocket<S>(
+    /// Handle a NIP86 relay management request
+    async fn handle_nip86<S>(
         &self,
-        ws_stream: WebSocketStream<S>,
-        addr: SocketAddr,
+        stream: &mut S,
+        head: &RequestHead,
+        opts: &RelayBuilderNip86,
     ) -> Result<()>
     where
         S: AsyncRead + AsyncWrite + Unpin,
     {
-        // Try to acquire connection limit
-        let permit = self.connections_limit.try_acquire()?;
+        let body: Vec<u8> =
+            tokio::time::timeout(HTTP_READ_TIMEOUT, head.read_body(stream)).await??;
+
+        // Verify NIP98 authorization
+        match self.verify_http_auth(head, &body, &opts.url) {
+            Ok(public_key) if opts.admins.contains(&public_key) => {}
+            Ok(..) => {
+                let response: nip86::Response =
+                    nip86::Response::error("unauthorized: not an admin");
+                http::send_nip86_response(stream, "401 Unauthorized", &response).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                let response: nip86::Response =
+                    nip86::Response::error(format!("unauthorized: {e}"));
+                http::send_nip86_response(stream, "401 Unauthorized", &response).await?;
+                return Ok(());
+            }
+        }
 
+        let response: nip86::Response = match nip86::Request::from_json(&body) {
+            Ok(request) => {
+                tracing::debug!("Executing NIP86 '{}' request", request.method());
+                match management::handle_request(
+                    request,
+                    &self.moderation,
+                    &self.nip11,
+                    &self.database,
+                )
+                .await
+                {
+                    Ok(response) => response,
+                    Err(e) => nip86::Response::error(e.to_string()),
+                }
+            }
+            Err(e) => nip86::Response::error(e.to_string()),
+        };
+
+        http::send_nip86_response(stream, "200 OK", &response).await?;
+
+        Ok(())
+    }
+
+    /// Verify the NIP98 authorization of an HTTP `POST` request
+    ///
+    /// The authorized URL must match the configured one,
+    /// since the `Host` header of the request is controlled by the client.
+    ///
+    /// Returns the public key of the author.
+    fn verify_http_auth(
+        &self,
+        head: &RequestHead,
+        body: &[u8],
+        url: &Url,
+    ) -> Result<PublicKey, nip98::Error> {
+        let auth: &str = head.header("authorization").unwrap_or_default();
+        nip98::verify_auth_header(auth, url, HttpMethod::POST, self.clock.now(), Some(body))
+    }
+
+    /// Handle websocket connection
+    ///
+    /// The connection permit is released when the connection terminates.
+    async fn handle_websocket<S, R, E>(
+        &self,
+        permit: SemaphorePermit<'_>,
+        mut tx: S,
+        mut rx: R,
+        addr: SocketAddr,
+    ) -> Result<()>
+    where
+        S: Sink<Message> + Unpin,
+        <S as Sink<Message>>::Error: std::error::Error + 'static,
+        R: Stream<Item = Result<Message, E>> + Unpin,
+        E: std::fmt::Display,
+    {
         tracing::debug!("WebSocket connection established: {addr}");
 
         let mut new_event = self.new_event.subscribe();
 
-        let (mut tx, mut rx) = ws_stream.split();
-
         let mut session: Session = Session {
             subscriptions: HashMap::new(),
             negentropy_subscription: HashMap::new(),
@@ -264,8 +501,8 @@ impl InnerLocalRelay {
                 event = new_event.recv() => {
                     if let Ok(event) = event {
                          // Iter subscriptions
-                        for (subscription_id, filter) in session.subscriptions.iter() {
-                            if filter.match_event(&event) {
+                        for (subscription_id, filters) in session.subscriptions.iter() {
+                            if filters.iter().any(|filter| filter.match_event(&event)) {
                                 send_msg(&mut tx, RelayMessage::Event{
                                     subscription_id: Cow::Borrowed(subscription_id),
                                     event: Cow::Borrowed(&event)
@@ -286,15 +523,17 @@ impl InnerLocalRelay {
         Ok(())
     }
 
+    #[rustfmt::skip]
     async fn handle_client_msg<S>(
         &self,
         session: &mut Session<'_>,
-        ws_tx: &mut WsTx<S>,
+        ws_tx: &mut S,
         msg: ClientMessage<'_>,
         addr: &SocketAddr,
     ) -> Result<()>
     where
-        S: AsyncRead + AsyncWrite + Unpin,
+        S: Sink<Message> + Unpin,
+        <S as Sink<Message>>::Error: std::error::Error + 'static,
     {
         match msg {
             ClientMessage::Event(event) => {
@@ -334,6 +573,38 @@ impl InnerLocalRelay {
                     }
                 }
 
+                // Check NIP40 expiration
+                if event.is_expired_at(&self.clock.now()) {
+                    return send_msg(
+                        ws_tx,
+                        RelayMessage::Ok {
+                            event_id: event.id,
+                            status: false,
+                            message: Cow::Owned(format!(
+                                "{}: event is expired",
+                                MachineReadablePrefix::Invalid
+                            )),
+                        },
+                    )
+                    .await;
+                }
+
+                // Check NIP86 moderation
+                if let Some(msg) = self.moderation.read().await.check_event(&event) {
+                    return send_msg(
+                        ws_tx,
+                        RelayMessage::Ok {
+                            event_id: event.id,
+                            status: false,
+                            message: Cow::Owned(format!(
+                                "{}: {msg}",
+                                MachineReadablePrefix::Blocked
+                            )),
+                        },
+                    )
+                    .await;
+                }
+
                 // Check NIP42
                 if let Some(nip42) = &self.nip42 {
                     // TODO: check if public key allowed
@@ -365,6 +636,32 @@ impl InnerLocalRelay {
                     }
                 }
 
+                // Check NIP70: protected events can be published only by their authenticated author
+                if event.is_protected() && session.nip42.public_key != Some(event.pubkey) {
+                    // Generate and send AUTH challenge
+                    send_msg(
+                        ws_tx,
+                        RelayMessage::Auth {
+                            challenge: Cow::Owned(session.nip42.generate_challenge()),
+                        },
+                    )
+                    .await?;
+
+                    // Return error
+                    return send_msg(
+                        ws_tx,
+                        RelayMessage::Ok {
+                            event_id: event.id,
+                            status: false,
+                            message: Cow::Owned(format!(
+                                "{}: this event may only be published by its author",
+                                MachineReadablePrefix::AuthRequired
+                            )),
+                        },
+                    )
+                    .await;
+                }
+
                 // check write policy
                 for policy in self.write_policy.iter() {
                     let event_id = event.id;
@@ -466,6 +763,27 @@ impl InnerLocalRelay {
                         .await;
                 }
 
+                // Check NIP62: block the events of authors that requested to vanish
+                if self.is_vanished(&event).await {
+                    return send_msg(
+                        ws_tx,
+                        RelayMessage::Ok {
+                            event_id: event.id,
+                            status: false,
+                            message: Cow::Owned(format!(
+                                "{}: the author or the recipient requested to vanish from this relay",
+                                MachineReadablePrefix::Blocked
+                            )),
+                        },
+                    )
+                    .await;
+                }
+
+                // Process NIP62 request to vanish
+                if event.kind == Kind::RequestToVanish && self.is_vanish_target(&event) {
+                    self.vanish(&event).await?;
+                }
+
                 if event.kind.is_ephemeral() {
                     let event_id = event.id;
 
@@ -488,6 +806,9 @@ impl InnerLocalRelay {
                     Ok(status) => {
                         // TODO: match status
                         if status.is_success() {
+                            // Index the expiration (NIP40)
+                            self.expirations.lock().await.insert(&event);
+
                             let event_id = event.id;
 
                             // Broadcast to channel
@@ -523,126 +844,38 @@ impl InnerLocalRelay {
                 subscription_id,
                 filter,
             } => {
-                // Check number of subscriptions
-                if session.subscriptions.len() >= self.rate_limit.max_reqs
-                    && !session.subscriptions.contains_key(&subscription_id)
-                {
-                    return send_msg(
-                            ws_tx,
-                            RelayMessage::Closed {
-                                subscription_id,
-                                message: Cow::Owned(format!(
-                                    "{}: too many REQs",
-                                    MachineReadablePrefix::RateLimited
-                                )),
-                            },
-                        )
-                        .await;
-                }
-
-                // Check NIP42
-                if let Some(nip42) = &self.nip42 {
-                    // TODO: check if public key allowed
-
-                    // Check mode and if it's authenticated
-                    if nip42.mode.is_read() && !session.nip42.is_authenticated() {
-                        // Generate and send AUTH challenge
-                        send_msg(
-                            ws_tx,
-                            RelayMessage::Auth {
-                                challenge: Cow::Owned(session.nip42.generate_challenge()),
-                            },
-                        )
-                        .await?;
-
-                        // Return error
-                        return send_msg(
-                                ws_tx,
-                                RelayMessage::Closed {
-                                    subscription_id,
-                                    message: Cow::Owned(format!(
-                                        "{}: you must auth",
-                                        MachineReadablePrefix::AuthRequired
-                                    )),
-                                },
-                            )
-                            .await;
-                    }
-                }
-
-                // check query policy plugins
-                for plugin in self.query_policy.iter() {
-                    if let PolicyResult::Reject(msg) = plugin.admit_query(&filter, addr).await {
-                        return send_msg(
-                                ws_tx,
-                                RelayMessage::Closed {
-                                    subscription_id,
-                                    message: Cow::Owned(format!("{}: {}", MachineReadablePrefix::Error, msg)),
-                                },
-                            )
-                            .await;
-                    }
-                }
-
-                let filter: Filter = filter.into_owned();
-
-                // Check if subscription has IDs
-                let ids_len: Option<usize> = filter.ids.as_ref().map(|ids| ids.len());
-
-                // Query database
-                let events: Events = self.database.query(filter.clone()).await?;
-                let events_len: usize = events.len();
-
-                tracing::debug!(
-                    "Found {events_len} events for subscription '{subscription_id}'",
-                );
-
-                let mut json_msgs: Vec<String> = Vec::with_capacity(events_len + 1);
-
-                // Add events
-                json_msgs.extend(
-                    events
-                        .into_iter()
-                        .map(|event| RelayMessage::Event {subscription_id: Cow::Borrowed(subscription_id.as_ref()), event: Cow::Owned(event)}.as_json()),
-                );
-
-                // Add EOSE message
-                json_msgs.push(RelayMessage::EndOfStoredEvents(Cow::Borrowed(subscription_id.as_ref())).as_json());
-
-                match ids_len {
-                    // Requested IDs len is the same as the query output, close the subscription.
-                    Some(ids_len) if ids_len == events_len => {
-                        json_msgs.push(RelayMessage::Closed {
-                            subscription_id,
-                            message: Cow::Borrowed(""),
-                        }.as_json());
-                    },
-                    // The stored events are all served, but miss some: save the subscription.
-                    _ => {
-                        // Save the subscription
-                        session
-                            .subscriptions
-                            .insert(subscription_id.clone().into_owned(), filter);
-                    }
-                }
-
-                // Send JSON messages
-                send_json_msgs(ws_tx, json_msgs).await
-            }
-            ClientMessage::ReqMultiFilter { subscription_id, .. } => {
-                send_msg(
+                self.handle_req(
+                    session,
                     ws_tx,
-                    RelayMessage::Closed {
-                        subscription_id,
-                        message: Cow::Owned(format!("{}: multi-filter REQs aren't supported (https://github.com/nostr-protocol/nips/pull/1645)", MachineReadablePrefix::Unsupported)),
-                    },
-                ).await
+                    subscription_id,
+                    vec![filter.into_owned()],
+                    addr,
+                )
+                .await
+            }
+            ClientMessage::ReqMultiFilter {
+                subscription_id,
+                filters,
+            } => {
+                self.handle_req(session, ws_tx, subscription_id, filters, addr)
+                    .await
             }
             ClientMessage::Count {
                 subscription_id,
                 filter,
             } => {
-                let count: usize = self.database.count(filter.into_owned()).await?;
+                // Delete the expired events, to exclude them from the count
+                self.delete_expired_events().await?;
+
+                // Exclude also the expired events not indexed (i.e., saved into the database by someone else)
+                let now: Timestamp = self.clock.now();
+                let count: usize = self
+                    .database
+                    .query(filter.into_owned())
+                    .await?
+                    .into_iter()
+                    .filter(|event| !event.is_expired_at(&now))
+                    .count();
                 send_msg(ws_tx, RelayMessage::Count { subscription_id, count }).await
             }
             ClientMessage::Close(subscription_id) => {
@@ -683,6 +916,9 @@ impl InnerLocalRelay {
 
                 // TODO: check nip42?
 
+                // Delete the expired events, to exclude them from the reconciliation
+                self.delete_expired_events().await?;
+
                 // Query database
                 let items = self.database.negentropy_items(filter.into_owned()).await?;
 
@@ -764,24 +1000,887 @@ impl InnerLocalRelay {
             }
         }
     }
+
+    /// Check if the request to vanish (NIP62) is for this relay
+    fn is_vanish_target(&self, event: &Event) -> bool {
+        #[cfg(feature = "tor")]
+        let hidden_service: Option<RelayUrl> = self
+            .hidden_service()
+            .and_then(|url| RelayUrl::parse(url).ok());
+        #[cfg(not(feature = "tor"))]
+        let hidden_service: Option<RelayUrl> = None;
+
+        event.tags.iter().any(|tag| match tag.as_standardized() {
+            Some(TagStandard::AllRelays) => true,
+            Some(TagStandard::Relay(relay)) => {
+                self.public_url.as_ref() == Some(relay) || hidden_service.as_ref() == Some(relay)
+            }
+            _ => false,
+        })
+    }
+
+    /// Cache the public key of a request to vanish (NIP62)
+    async fn mark_vanished(&self, event: &Event) {
+        let mut vanished = self.vanished.write().await;
+        let vanished_at: &mut Timestamp = vanished.entry(event.pubkey).or_insert(event.created_at);
+        if event.created_at > *vanished_at {
+            *vanished_at = event.created_at;
+        }
+    }
+
+    /// Check if the event was deleted by a request to vanish (NIP62)
+    ///
+    /// Both the events of the author and the gift wraps that p-tag the author are blocked.
+    async fn is_vanished(&self, event: &Event) -> bool {
+        let vanished = self.vanished.read().await;
+
+        let is_vanished = |public_key: &PublicKey| match vanished.get(public_key) {
+            Some(vanished_at) => event.created_at <= *vanished_at,
+            None => false,
+        };
+
+        is_vanished(&event.pubkey)
+            || (event.kind == Kind::GiftWrap && event.tags.public_keys().any(is_vanished))
+    }
+
+    /// Delete all the events of the author, up to the request to vanish (NIP62),
+    /// including the gift wraps that p-tag the author.
+    async fn vanish(&self, event: &Event) -> Result<()> {
+        tracing::debug!("Processing request to vanish of {}", event.pubkey);
+
+        // Block the re-broadcast of the deleted events
+        self.mark_vanished(event).await;
+
+        // Delete events authored by the public key
+        let filter: Filter = Filter::new().author(event.pubkey).until(event.created_at);
+        self.database.delete(filter).await?;
+
+        // Delete gift wraps
+        let filter: Filter = Filter::new()
+            .kind(Kind::GiftWrap)
+            .pubkey(event.pubkey)
+            .until(event.created_at);
+        self.database.delete(filter).await?;
+
+        Ok(())
+    }
+
+    /// Handle single and multi-filter REQs
+    ///
+    /// The stored events are the union of the results of every filter (each one with its own limit)
+    /// and a single EOSE is sent at the end.
+    async fn handle_req<S>(
+        &self,
+        session: &mut Session<'_>,
+        ws_tx: &mut S,
+        subscription_id: Cow<'_, SubscriptionId>,
+        filters: Vec<Filter>,
+        addr: &SocketAddr,
+    ) -> Result<()>
+    where
+        S: Sink<Message> + Unpin,
+        <S as Sink<Message>>::Error: std::error::Error + 'static,
+    {
+        // Check number of subscriptions
+        if session.subscriptions.len() >= self.rate_limit.max_reqs
+            && !session.subscriptions.contains_key(&subscription_id)
+        {
+            return send_msg(
+                ws_tx,
+                RelayMessage::Closed {
+                    subscription_id,
+                    message: Cow::Owned(format!(
+                        "{}: too many REQs",
+                        MachineReadablePrefix::RateLimited
+                    )),
+                },
+            )
+            .await;
+        }
+
+        // Check NIP42
+        if let Some(nip42) = &self.nip42 {
+            // TODO: check if public key allowed
+
+            // Check mode and if it's authenticated
+            if nip42.mode.is_read() && !session.nip42.is_authenticated() {
+                // Generate and send AUTH challenge
+                send_msg(
+                    ws_tx,
+                    RelayMessage::Auth {
+                        challenge: Cow::Owned(session.nip42.generate_challenge()),
+                    },
+                )
+                .await?;
+
+                // Return error
+                return send_msg(
+                    ws_tx,
+                    RelayMessage::Closed {
+                        subscription_id,
+                        message: Cow::Owned(format!(
+                            "{}: you must auth",
+                            MachineReadablePrefix::AuthRequired
+                        )),
+                    },
+                )
+                .await;
+            }
+        }
+
+        // check query policy plugins
+        for filter in filters.iter() {
+            for plugin in self.query_policy.iter() {
+                if let PolicyResult::Reject(msg) = plugin.admit_query(filter, addr).await {
+                    return send_msg(
+                        ws_tx,
+                        RelayMessage::Closed {
+                            subscription_id,
+                            message: Cow::Owned(format!(
+                                "{}: {}",
+                                MachineReadablePrefix::Error,
+                                msg
+                            )),
+                        },
+                    )
+                    .await;
+                }
+            }
+        }
+
+        // Collect the requested IDs, only if all the filters have them
+        let ids: Option<HashSet<EventId>> = filters
+            .iter()
+            .map(|filter| filter.ids.as_ref())
+            .try_fold(HashSet::new(), |mut ids, filter_ids| {
+                ids.extend(filter_ids?.iter().copied());
+                Some(ids)
+            });
+
+        // Delete the expired events, so that they don't count toward the limits
+        self.delete_expired_events().await?;
+
+        // Query database
+        // Each filter is queried separately to respect its own limit
+        let mut events: Option<Events> = None;
+        for filter in filters.iter() {
+            let output: Events = self.database.query(filter.clone()).await?;
+            events = Some(match events {
+                Some(events) => events.merge(output),
+                None => output,
+            });
+        }
+        // Exclude the banned events and the expired ones not indexed (i.e., saved into the database by someone else)
+        let now: Timestamp = self.clock.now();
+        let moderation = self.moderation.read().await;
+        let events: Vec<Event> = events
+            .into_iter()
+            .flatten()
+            .filter(|event| !event.is_expired_at(&now) && moderation.is_visible(event))
+            .collect();
+        drop(moderation);
+        let events_len: usize = events.len();
+
+        tracing::debug!("Found {events_len} events for subscription '{subscription_id}'");
+
+        let mut json_msgs: Vec<String> = Vec::with_capacity(events_len + 1);
+
+        // Add events
+        json_msgs.extend(events.into_iter().map(|event| {
+            RelayMessage::Event {
+                subscription_id: Cow::Borrowed(subscription_id.as_ref()),
+                event: Cow::Owned(event),
+            }
+            .as_json()
+        }));
+
+        // Add EOSE message
+        json_msgs.push(
+            RelayMessage::EndOfStoredEvents(Cow::Borrowed(subscription_id.as_ref())).as_json(),
+        );
+
+        match ids {
+            // Requested IDs len is the same as the query output, close the subscription.
+            Some(ids) if !filters.is_empty() && ids.len() == events_len => {
+                json_msgs.push(
+                    RelayMessage::Closed {
+                        subscription_id,
+                        message: Cow::Borrowed(""),
+                    }
+                    .as_json(),
+                );
+            }
+            // The stored events are all served, but miss some: save the subscription.
+            _ => {
+                // Save the subscription
+                session
+                    .subscriptions
+                    .insert(subscription_id.into_owned(), filters);
+            }
+        }
+
+        // Send JSON messages
+        send_json_msgs(ws_tx, json_msgs).await
+    }
 }
 
 #[inline]
-async fn send_msg<S>(tx: &mut WsTx<S>, msg: RelayMessage<'_>) -> Result<()>
+async fn send_msg<S>(tx: &mut S, msg: RelayMessage<'_>) -> Result<()>
 where
-    S: AsyncRead + AsyncWrite + Unpin,
+    S: Sink<Message> + Unpin,
+    <S as Sink<Message>>::Error: std::error::Error + 'static,
 {
     tx.send(Message::Text(msg.as_json().into())).await?;
     Ok(())
 }
 
 #[inline]
-async fn send_json_msgs<I, S>(tx: &mut WsTx<S>, json_msgs: I) -> Result<()>
+async fn send_json_msgs<I, S>(tx: &mut S, json_msgs: I) -> Result<()>
 where
     I: IntoIterator<Item = String>,
-    S: AsyncRead + AsyncWrite + Unpin,
+    S: Sink<Message> + Unpin,
+    <S as Sink<Message>>::Error: std::error::Error + 'static,
 {
     let mut stream = stream::iter(json_msgs.into_iter()).map(|msg| Ok(Message::Text(msg.into())));
     tx.send_all(&mut stream).await?;
     Ok(())
 }
+
+/// Build the NIP11 document, filling the supported NIPs and the limitations from the builder settings
+fn build_nip11_document(builder: &RelayBuilder) -> RelayInformationDocument {
+    let mut document: RelayInformationDocument = builder.nip11.clone();
+
+    // Supported NIPs
+    let mut supported_nips: Vec<u16> = document.supported_nips.take().unwrap_or_default();
+    supported_nips.extend([1, 9, 11, 40, 45, 62, 70, 77]);
+
+    if builder.min_pow.is_some() {
+        supported_nips.push(13);
+    }
+
+    if builder.nip42.is_some() {
+        supported_nips.push(42);
+    }
+
+    if builder.nip86.is_some() {
+        supported_nips.push(86);
+    }
+
+    supported_nips.sort_unstable();
+    supported_nips.dedup();
+    document.supported_nips = Some(supported_nips);
+
+    // Software
+    if document.software.is_none() {
+        document.software = Some(String::from(env!("CARGO_PKG_REPOSITORY")));
+        document.version = Some(String::from(env!("CARGO_PKG_VERSION")));
+    }
+
+    // Limitation
+    let mut limitation: Limitation = document.limitation.take().unwrap_or_default();
+    if limitation.max_subscriptions.is_none() {
+        limitation.max_subscriptions = i32::try_from(builder.rate_limit.max_reqs).ok();
+    }
+    limitation.min_pow_difficulty = builder.min_pow.map(i32::from);
+    limitation.auth_required = Some(builder.nip42.is_some());
+    document.limitation = Some(limitation);
+
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use nostr::hashes::sha256::Hash as Sha256Hash;
+    use nostr::hashes::Hash;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use super::*;
+
+    const ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+    const NIP11_REQUEST: &[u8] = b"GET / HTTP/1.1\r\nAccept: application/nostr+json\r\n\r\n";
+
+    async fn get_nip11(addr: SocketAddr) -> Vec<u8> {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        // The relay may have already closed the connection
+        let _ = stream.write_all(NIP11_REQUEST).await;
+
+        let mut response: Vec<u8> = Vec::new();
+        let _ = stream.read_to_end(&mut response).await;
+        response
+    }
+
+    fn session(relay: &InnerLocalRelay) -> Session<'static> {
+        Session {
+            subscriptions: HashMap::new(),
+            negentropy_subscription: HashMap::new(),
+            nip42: Nip42Session::default(),
+            tokens: Tokens::new(relay.rate_limit.notes_per_minute),
+        }
+    }
+
+    fn parse(msgs: Vec<Message>) -> Vec<RelayMessage<'static>> {
+        msgs.into_iter()
+            .map(|msg| match msg {
+                Message::Text(json) => RelayMessage::from_json(json.as_str()).unwrap(),
+                _ => panic!("unexpected message"),
+            })
+            .collect()
+    }
+
+    async fn req(relay: &InnerLocalRelay, filters: Vec<Filter>) -> Vec<RelayMessage<'static>> {
+        let mut session: Session = session(relay);
+        let mut tx: Vec<Message> = Vec::new();
+
+        relay
+            .handle_req(
+                &mut session,
+                &mut tx,
+                Cow::Owned(SubscriptionId::new("test")),
+                filters,
+                &ADDR,
+            )
+            .await
+            .unwrap();
+
+        parse(tx)
+    }
+
+    async fn handle_msg(
+        relay: &InnerLocalRelay,
+        msg: ClientMessage<'_>,
+    ) -> Vec<RelayMessage<'static>> {
+        let mut session: Session = session(relay);
+        let mut tx: Vec<Message> = Vec::new();
+
+        relay
+            .handle_client_msg(&mut session, &mut tx, msg, &ADDR)
+            .await
+            .unwrap();
+
+        parse(tx)
+    }
+
+    async fn publish(relay: &InnerLocalRelay, event: Event) -> bool {
+        match handle_msg(relay, ClientMessage::event(event)).await.pop() {
+            Some(RelayMessage::Ok { status, .. }) => status,
+            msg => panic!("unexpected message: {msg:?}"),
+        }
+    }
+
+    async fn count(relay: &InnerLocalRelay, filter: Filter) -> usize {
+        let msg = ClientMessage::count(SubscriptionId::new("test"), filter);
+        match handle_msg(relay, msg).await.pop() {
+            Some(RelayMessage::Count { count, .. }) => count,
+            msg => panic!("unexpected message: {msg:?}"),
+        }
+    }
+
+    /// Relay with a clock that can be moved forward
+    ///
+    /// The clock starts at the current time: the database rejects the events already expired.
+    async fn relay_with_clock(mut builder: RelayBuilder) -> (InnerLocalRelay, Arc<AtomicU64>) {
+        let now: Arc<AtomicU64> = Arc::new(AtomicU64::new(Timestamp::now().as_u64()));
+        builder.clock = {
+            let now = now.clone();
+            RelayClock::from_fn(move || Timestamp::from(now.load(Ordering::SeqCst)))
+        };
+
+        let relay = InnerLocalRelay::new(builder).await.unwrap();
+        (relay, now)
+    }
+
+    /// Text note, with the expiration relative to the current time
+    fn note(keys: &Keys, created_at: u64, expires_in: Option<u64>) -> Event {
+        let mut builder =
+            EventBuilder::text_note("").custom_created_at(Timestamp::from(created_at));
+        if let Some(expires_in) = expires_in {
+            let expiration: Timestamp = Timestamp::now() + expires_in;
+            builder = builder.tag(Tag::expiration(expiration));
+        }
+        builder.sign_with_keys(keys).unwrap()
+    }
+
+    fn move_forward(now: &AtomicU64, secs: u64) {
+        now.fetch_add(secs, Ordering::SeqCst);
+    }
+
+    fn received_ids(msgs: &[RelayMessage<'_>]) -> Vec<EventId> {
+        msgs.iter()
+            .filter_map(|msg| match msg {
+                RelayMessage::Event { event, .. } => Some(event.id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn count_eose(msgs: &[RelayMessage<'_>]) -> usize {
+        msgs.iter()
+            .filter(|msg| matches!(msg, RelayMessage::EndOfStoredEvents(..)))
+            .count()
+    }
+
+    async fn save(relay: &InnerLocalRelay, keys: &Keys, kind: Kind, created_at: u64) -> Event {
+        let event = EventBuilder::new(kind, "")
+            .custom_created_at(Timestamp::from(created_at))
+            .sign_with_keys(keys)
+            .unwrap();
+        relay.database.save_event(&event).await.unwrap();
+        event
+    }
+
+    #[tokio::test]
+    async fn test_req_multi_filter_union() {
+        let relay = InnerLocalRelay::new(RelayBuilder::default()).await.unwrap();
+        let keys = Keys::generate();
+
+        let note = save(&relay, &keys, Kind::TextNote, 1).await;
+        let reaction = save(&relay, &keys, Kind::Reaction, 2).await;
+        save(&relay, &keys, Kind::Metadata, 3).await;
+
+        let msgs = req(
+            &relay,
+            vec![
+                Filter::new().kind(Kind::TextNote),
+                Filter::new().kind(Kind::Reaction),
+            ],
+        )
+        .await;
+
+        let ids: HashSet<EventId> = received_ids(&msgs).into_iter().collect();
+        assert_eq!(ids, HashSet::from([note.id, reaction.id]));
+
+        // A single EOSE, after the stored events
+        assert_eq!(count_eose(&msgs), 1);
+        assert!(matches!(
+            msgs.last(),
+            Some(RelayMessage::EndOfStoredEvents(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_req_multi_filter_dedup() {
+        let relay = InnerLocalRelay::new(RelayBuilder::default()).await.unwrap();
+        let keys = Keys::generate();
+
+        let note = save(&relay, &keys, Kind::TextNote, 1).await;
+
+        // The event matches both filters
+        let msgs = req(
+            &relay,
+            vec![
+                Filter::new().kind(Kind::TextNote),
+                Filter::new().author(keys.public_key()),
+            ],
+        )
+        .await;
+
+        assert_eq!(received_ids(&msgs), vec![note.id]);
+        assert_eq!(count_eose(&msgs), 1);
+    }
+
+    #[tokio::test]
+    async fn test_req_multi_filter_limit() {
+        let relay = InnerLocalRelay::new(RelayBuilder::default()).await.unwrap();
+        let keys = Keys::generate();
+
+        save(&relay, &keys, Kind::TextNote, 1).await;
+        save(&relay, &keys, Kind::TextNote, 2).await;
+        let newest_note = save(&relay, &keys, Kind::TextNote, 3).await;
+        save(&relay, &keys, Kind::Reaction, 4).await;
+        let newest_reaction = save(&relay, &keys, Kind::Reaction, 5).await;
+
+        // Each filter has its own limit
+        let msgs = req(
+            &relay,
+            vec![
+                Filter::new().kind(Kind::TextNote).limit(1),
+                Filter::new().kind(Kind::Reaction).limit(1),
+            ],
+        )
+        .await;
+
+        let ids: HashSet<EventId> = received_ids(&msgs).into_iter().collect();
+        assert_eq!(ids, HashSet::from([newest_note.id, newest_reaction.id]));
+        assert_eq!(count_eose(&msgs), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_events_are_excluded_before_the_limit() {
+        let (relay, now) = relay_with_clock(RelayBuilder::default()).await;
+        let keys = Keys::generate();
+
+        let old = note(&keys, 1, None);
+        let expiring = note(&keys, 2, Some(1000));
+        assert!(publish(&relay, old.clone()).await);
+        assert!(publish(&relay, expiring.clone()).await);
+
+        let filter = Filter::new().kind(Kind::TextNote);
+        let latest = filter.clone().limit(1);
+        assert_eq!(
+            received_ids(&req(&relay, vec![latest.clone()]).await),
+            vec![expiring.id]
+        );
+        assert_eq!(count(&relay, filter.clone()).await, 2);
+
+        // Expire the newest event
+        move_forward(&now, 2000);
+
+        assert_eq!(received_ids(&req(&relay, vec![latest]).await), vec![old.id]);
+        assert_eq!(count(&relay, filter).await, 1);
+
+        // Already expired
+        assert!(!publish(&relay, note(&keys, 3, Some(1000))).await);
+    }
+
+    #[tokio::test]
+    async fn test_delete_expired_events() {
+        let (relay, now) = relay_with_clock(RelayBuilder::default()).await;
+        let keys = Keys::generate();
+
+        assert!(publish(&relay, note(&keys, 1, Some(1000))).await);
+        assert!(publish(&relay, note(&keys, 2, Some(3000))).await);
+        assert!(publish(&relay, note(&keys, 3, None)).await);
+
+        assert_eq!(relay.delete_expired_events().await.unwrap(), 0);
+
+        move_forward(&now, 2000);
+        assert_eq!(relay.delete_expired_events().await.unwrap(), 1);
+        assert_eq!(relay.delete_expired_events().await.unwrap(), 0);
+        assert_eq!(relay.database.count(Filter::new()).await.unwrap(), 2);
+
+        move_forward(&now, 2000);
+        assert_eq!(relay.delete_expired_events().await.unwrap(), 1);
+        assert_eq!(relay.database.count(Filter::new()).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stored_expirations_are_indexed_at_startup() {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let keys = Keys::generate();
+        database
+            .save_event(&note(&keys, 1, Some(1000)))
+            .await
+            .unwrap();
+
+        let (relay, now) = relay_with_clock(RelayBuilder::default().database(database)).await;
+
+        move_forward(&now, 2000);
+        assert_eq!(relay.delete_expired_events().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_events_not_indexed_are_excluded() {
+        let (relay, now) = relay_with_clock(RelayBuilder::default()).await;
+        let keys = Keys::generate();
+
+        // Saved into the database without passing through the relay
+        let event = note(&keys, 1, Some(1000));
+        relay.database.save_event(&event).await.unwrap();
+
+        let filter = Filter::new().kind(Kind::TextNote);
+        assert_eq!(
+            received_ids(&req(&relay, vec![filter.clone()]).await),
+            vec![event.id]
+        );
+
+        move_forward(&now, 2000);
+        assert!(received_ids(&req(&relay, vec![filter.clone()]).await).is_empty());
+        assert_eq!(count(&relay, filter).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_deletion_request() {
+        let relay = InnerLocalRelay::new(RelayBuilder::default()).await.unwrap();
+        let keys = Keys::generate();
+
+        let first = note(&keys, 1, None);
+        let second = note(&keys, 2, None);
+        assert!(publish(&relay, first.clone()).await);
+        assert!(publish(&relay, second.clone()).await);
+
+        let deletion = EventBuilder::delete(EventDeletionRequest::new().id(first.id))
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert!(publish(&relay, deletion.clone()).await);
+
+        let ids: HashSet<EventId> = received_ids(&req(&relay, vec![Filter::new()]).await)
+            .into_iter()
+            .collect();
+        assert_eq!(ids, HashSet::from([second.id, deletion.id]));
+
+        // Can't be published again
+        assert!(!publish(&relay, first).await);
+    }
+
+    fn gift_wrap(receiver: &PublicKey, created_at: u64) -> Event {
+        EventBuilder::new(Kind::GiftWrap, "")
+            .tag(Tag::public_key(*receiver))
+            .custom_created_at(Timestamp::from(created_at))
+            .sign_with_keys(&Keys::generate())
+            .unwrap()
+    }
+
+    fn request_vanish(keys: &Keys, target: VanishTarget, created_at: u64) -> Event {
+        EventBuilder::request_vanish(target)
+            .unwrap()
+            .custom_created_at(Timestamp::from(created_at))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_to_vanish() {
+        let url = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let relay = InnerLocalRelay::new(RelayBuilder::default().public_url(url.clone()))
+            .await
+            .unwrap();
+        let keys = Keys::generate();
+
+        let note = note(&keys, 1, None);
+        let wrap = gift_wrap(&keys.public_key(), 2);
+        let other_wrap = gift_wrap(&Keys::generate().public_key(), 3);
+        assert!(publish(&relay, note.clone()).await);
+        assert!(publish(&relay, wrap.clone()).await);
+        assert!(publish(&relay, other_wrap.clone()).await);
+
+        // Request for another relay
+        let other_url = RelayUrl::parse("wss://other.example.com").unwrap();
+        let request = request_vanish(&keys, VanishTarget::relay(other_url), 10);
+        assert!(publish(&relay, request).await);
+        assert_eq!(relay.database.count(Filter::new()).await.unwrap(), 4);
+
+        // Request for this relay
+        let request = request_vanish(&keys, VanishTarget::relay(url), 20);
+        assert!(publish(&relay, request.clone()).await);
+
+        let ids: HashSet<EventId> = received_ids(&req(&relay, vec![Filter::new()]).await)
+            .into_iter()
+            .collect();
+        assert_eq!(ids, HashSet::from([other_wrap.id, request.id]));
+
+        // The deleted events can't be re-broadcast
+        assert!(!publish(&relay, note).await);
+        assert!(!publish(&relay, wrap).await);
+        assert!(!publish(&relay, gift_wrap(&keys.public_key(), 15)).await);
+
+        // Newer events are accepted
+        assert!(publish(&relay, gift_wrap(&keys.public_key(), 30)).await);
+    }
+
+    #[tokio::test]
+    async fn test_vanished_public_keys_are_loaded_at_startup() {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let keys = Keys::generate();
+        let request = request_vanish(&keys, VanishTarget::AllRelays, 10);
+        database.save_event(&request).await.unwrap();
+
+        let relay = InnerLocalRelay::new(RelayBuilder::default().database(database))
+            .await
+            .unwrap();
+
+        assert!(!publish(&relay, note(&keys, 5, None)).await);
+        assert!(publish(&relay, note(&keys, 15, None)).await);
+    }
+
+    #[test]
+    fn test_nip11_document_limitation() {
+        let builder = RelayBuilder::default().min_pow(10);
+        let document = build_nip11_document(&builder);
+        let limitation = document.limitation.unwrap();
+        assert_eq!(limitation.max_subscriptions, Some(500));
+        assert_eq!(limitation.min_pow_difficulty, Some(10));
+        assert_eq!(limitation.auth_required, Some(false));
+        assert!(document.supported_nips.unwrap().contains(&13));
+    }
+
+    #[test]
+    fn test_nip11_document_keeps_user_limitation() {
+        let document = RelayInformationDocument {
+            limitation: Some(Limitation {
+                max_subscriptions: Some(20),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let builder = RelayBuilder::default().nip11(document);
+        let document = build_nip11_document(&builder);
+        assert_eq!(document.limitation.unwrap().max_subscriptions, Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_idle_connections_count_toward_the_limit() {
+        let relay = InnerLocalRelay::run(RelayBuilder::default().max_connections(1))
+            .await
+            .unwrap();
+
+        // Open a connection without sending the HTTP request
+        let idle = TcpStream::connect(relay.addr).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // The idle connection holds the only permit
+        let response = get_nip11(relay.addr).await;
+        assert!(response.is_empty());
+
+        // Release the permit
+        drop(idle);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let response = get_nip11(relay.addr).await;
+        assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+
+        relay.shutdown();
+    }
+
+    const NIP86_URL: &str = "https://relay.example.com/";
+
+    async fn nip86_relay(admin: &Keys) -> InnerLocalRelay {
+        let opts = RelayBuilderNip86::new(Url::parse(NIP86_URL).unwrap(), [admin.public_key()]);
+        InnerLocalRelay::new(RelayBuilder::default().nip86(opts))
+            .await
+            .unwrap()
+    }
+
+    async fn nip86_auth(keys: &Keys, url: &str, method: HttpMethod, payload: &[u8]) -> String {
+        HttpData::new(Url::parse(url).unwrap(), method)
+            .payload(Sha256Hash::hash(payload))
+            .to_authorization(keys)
+            .await
+            .unwrap()
+    }
+
+    /// Send a NIP86 request and return the status line and the response
+    async fn nip86_request(
+        relay: &InnerLocalRelay,
+        auth: &str,
+        body: &[u8],
+    ) -> (String, nip86::Response) {
+        let mut raw: Vec<u8> = format!(
+            "POST / HTTP/1.1\r\n\
+            Host: relay.example.com\r\n\
+            Content-Type: {}\r\n\
+            Authorization: {auth}\r\n\
+            Content-Length: {}\r\n\
+            \r\n",
+            nip86::CONTENT_TYPE,
+            body.len()
+        )
+        .into_bytes();
+        raw.extend_from_slice(body);
+
+        let mut stream: &[u8] = &raw;
+        let head = RequestHead::read(&mut stream).await.unwrap();
+
+        let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+        let opts = relay.nip86.as_ref().unwrap();
+        relay.handle_nip86(&mut server, &head, opts).await.unwrap();
+
+        let mut response: String = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+
+        let (head, json) = response.split_once("\r\n\r\n").unwrap();
+        let status: String = head.lines().next().unwrap().to_string();
+        (status, nip86::Response::from_json(json).unwrap())
+    }
+
+    fn ban_pubkey_body(public_key: PublicKey) -> Vec<u8> {
+        let request = nip86::Request::BanPubkey {
+            public_key,
+            reason: None,
+        };
+        request.as_json().into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_nip86_admin_request() {
+        let admin = Keys::generate();
+        let relay = nip86_relay(&admin).await;
+
+        let keys = Keys::generate();
+        let body = ban_pubkey_body(keys.public_key());
+        let auth = nip86_auth(&admin, NIP86_URL, HttpMethod::POST, &body).await;
+
+        let (status, response) = nip86_request(&relay, &auth, &body).await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(response.error, None);
+
+        let event = note(&keys, 1, None);
+        assert!(!publish(&relay, event).await);
+    }
+
+    #[tokio::test]
+    async fn test_nip86_wrong_url() {
+        let admin = Keys::generate();
+        let relay = nip86_relay(&admin).await;
+
+        let body = ban_pubkey_body(Keys::generate().public_key());
+        let auth = nip86_auth(
+            &admin,
+            "https://other.example.com/",
+            HttpMethod::POST,
+            &body,
+        )
+        .await;
+
+        let (status, response) = nip86_request(&relay, &auth, &body).await;
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_nip86_wrong_method() {
+        let admin = Keys::generate();
+        let relay = nip86_relay(&admin).await;
+
+        let body = ban_pubkey_body(Keys::generate().public_key());
+        let auth = nip86_auth(&admin, NIP86_URL, HttpMethod::GET, &body).await;
+
+        let (status, response) = nip86_request(&relay, &auth, &body).await;
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_nip86_payload_mismatch() {
+        let admin = Keys::generate();
+        let relay = nip86_relay(&admin).await;
+
+        let body = ban_pubkey_body(Keys::generate().public_key());
+        let other = ban_pubkey_body(Keys::generate().public_key());
+        let auth = nip86_auth(&admin, NIP86_URL, HttpMethod::POST, &other).await;
+
+        let (status, response) = nip86_request(&relay, &auth, &body).await;
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_nip86_not_an_admin() {
+        let admin = Keys::generate();
+        let relay = nip86_relay(&admin).await;
+
+        let keys = Keys::generate();
+        let body = ban_pubkey_body(keys.public_key());
+        let auth = nip86_auth(&keys, NIP86_URL, HttpMethod::POST, &body).await;
+
+        let (status, response) = nip86_request(&relay, &auth, &body).await;
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+        assert_eq!(
+            response.error.as_deref(),
+            Some("unauthorized: not an admin")
+        );
+
+        // Not banned
+        let event = note(&keys, 1, None);
+        assert!(publish(&relay, event).await);
+    }
+}
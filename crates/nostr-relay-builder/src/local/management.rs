@@ -0,0 +1,211 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP86 relay management
+
+use std::collections::{BTreeSet, HashMap};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use nostr::nips::nip86::{
+    self, EventIdWithReason, IpAddrWithReason, Method, PublicKeyWithReason, Request, Response,
+};
+use nostr_database::prelude::*;
+use tokio::sync::RwLock;
+
+/// Moderation state, editable at runtime through the NIP86 API
+#[derive(Debug, Default)]
+pub(super) struct Moderation {
+    banned_pubkeys: HashMap<PublicKey, Option<String>>,
+    /// If not empty, only these public keys are allowed to publish
+    allowed_pubkeys: HashMap<PublicKey, Option<String>>,
+    banned_events: HashMap<EventId, Option<String>>,
+    /// If not empty, only these kinds are accepted
+    allowed_kinds: BTreeSet<Kind>,
+    disallowed_kinds: BTreeSet<Kind>,
+    blocked_ips: HashMap<IpAddr, Option<String>>,
+}
+
+impl Moderation {
+    /// Check if the event can be published
+    ///
+    /// Returns the rejection message if not.
+    pub fn check_event(&self, event: &Event) -> Option<String> {
+        if let Some(reason) = self.banned_pubkeys.get(&event.pubkey) {
+            return Some(with_reason("public key banned", reason));
+        }
+
+        if !self.allowed_pubkeys.is_empty() && !self.allowed_pubkeys.contains_key(&event.pubkey) {
+            return Some(String::from("public key not allowed"));
+        }
+
+        if let Some(reason) = self.banned_events.get(&event.id) {
+            return Some(with_reason("event banned", reason));
+        }
+
+        if self.disallowed_kinds.contains(&event.kind)
+            || (!self.allowed_kinds.is_empty() && !self.allowed_kinds.contains(&event.kind))
+        {
+            return Some(format!("kind {} not allowed", event.kind));
+        }
+
+        None
+    }
+
+    /// Check if the event can be served
+    #[inline]
+    pub fn is_visible(&self, event: &Event) -> bool {
+        !self.banned_pubkeys.contains_key(&event.pubkey)
+            && !self.banned_events.contains_key(&event.id)
+    }
+
+    /// Check if the IP is blocked
+    #[inline]
+    pub fn is_ip_blocked(&self, ip: &IpAddr) -> bool {
+        self.blocked_ips.contains_key(ip)
+    }
+}
+
+fn with_reason(msg: &str, reason: &Option<String>) -> String {
+    match reason {
+        Some(reason) => format!("{msg}: {reason}"),
+        None => msg.to_string(),
+    }
+}
+
+/// Execute a NIP86 request
+///
+/// The changes to the NIP11 document and to the moderation state are applied immediately.
+pub(super) async fn handle_request(
+    request: Request,
+    moderation: &RwLock<Moderation>,
+    nip11: &RwLock<RelayInformationDocument>,
+    database: &Arc<dyn NostrEventsDatabase>,
+) -> Result<Response, nip86::Error> {
+    match request {
+        Request::SupportedMethods => {
+            let methods: Vec<&str> = Method::ALL.iter().map(|m| m.as_str()).collect();
+            Response::ok(methods)
+        }
+        Request::BanPubkey { public_key, reason } => {
+            let mut moderation = moderation.write().await;
+            moderation.allowed_pubkeys.remove(&public_key);
+            moderation.banned_pubkeys.insert(public_key, reason);
+            Response::ok(true)
+        }
+        Request::ListBannedPubkeys => {
+            let moderation = moderation.read().await;
+            let list: Vec<PublicKeyWithReason> = moderation
+                .banned_pubkeys
+                .iter()
+                .map(|(pubkey, reason)| PublicKeyWithReason {
+                    pubkey: *pubkey,
+                    reason: reason.clone(),
+                })
+                .collect();
+            Response::ok(list)
+        }
+        Request::AllowPubkey { public_key, reason } => {
+            let mut moderation = moderation.write().await;
+            moderation.banned_pubkeys.remove(&public_key);
+            moderation.allowed_pubkeys.insert(public_key, reason);
+            Response::ok(true)
+        }
+        Request::ListAllowedPubkeys => {
+            let moderation = moderation.read().await;
+            let list: Vec<PublicKeyWithReason> = moderation
+                .allowed_pubkeys
+                .iter()
+                .map(|(pubkey, reason)| PublicKeyWithReason {
+                    pubkey: *pubkey,
+                    reason: reason.clone(),
+                })
+                .collect();
+            Response::ok(list)
+        }
+        // Events are never queued for moderation
+        Request::ListEventsNeedingModeration => Response::ok(Vec::<EventIdWithReason>::new()),
+        Request::AllowEvent { id, .. } => {
+            let mut moderation = moderation.write().await;
+            moderation.banned_events.remove(&id);
+            Response::ok(true)
+        }
+        Request::BanEvent { id, reason } => {
+            moderation.write().await.banned_events.insert(id, reason);
+
+            // Remove the event from the database
+            if let Err(e) = database.delete(Filter::new().id(id)).await {
+                tracing::error!(id = %id, "Can't delete banned event: {e}");
+                return Ok(Response::error("database error"));
+            }
+
+            Response::ok(true)
+        }
+        Request::ListBannedEvents => {
+            let moderation = moderation.read().await;
+            let list: Vec<EventIdWithReason> = moderation
+                .banned_events
+                .iter()
+                .map(|(id, reason)| EventIdWithReason {
+                    id: *id,
+                    reason: reason.clone(),
+                })
+                .collect();
+            Response::ok(list)
+        }
+        Request::ChangeRelayName(name) => {
+            nip11.write().await.name = Some(name);
+            Response::ok(true)
+        }
+        Request::ChangeRelayDescription(description) => {
+            nip11.write().await.description = Some(description);
+            Response::ok(true)
+        }
+        Request::ChangeRelayIcon(icon) => {
+            nip11.write().await.icon = Some(icon.to_string());
+            Response::ok(true)
+        }
+        Request::AllowKind(kind) => {
+            let mut moderation = moderation.write().await;
+            moderation.disallowed_kinds.remove(&kind);
+            moderation.allowed_kinds.insert(kind);
+            Response::ok(true)
+        }
+        Request::DisallowKind(kind) => {
+            let mut moderation = moderation.write().await;
+            moderation.allowed_kinds.remove(&kind);
+            moderation.disallowed_kinds.insert(kind);
+            Response::ok(true)
+        }
+        Request::ListAllowedKinds => {
+            let moderation = moderation.read().await;
+            let list: Vec<u16> = moderation
+                .allowed_kinds
+                .iter()
+                .map(|kind| kind.as_u16())
+                .collect();
+            Response::ok(list)
+        }
+        Request::BlockIp { ip, reason } => {
+            moderation.write().await.blocked_ips.insert(ip, reason);
+            Response::ok(true)
+        }
+        Request::UnblockIp(ip) => {
+            moderation.write().await.blocked_ips.remove(&ip);
+            Response::ok(true)
+        }
+        Request::ListBlockedIps => {
+            let moderation = moderation.read().await;
+            let list: Vec<IpAddrWithReason> = moderation
+                .blocked_ips
+                .iter()
+                .map(|(ip, reason)| IpAddrWithReason {
+                    ip: *ip,
+                    reason: reason.clone(),
+                })
+                .collect();
+            Response::ok(list)
+        }
+    }
+}
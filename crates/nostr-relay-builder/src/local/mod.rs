@@ -6,11 +6,15 @@
 
 use std::net::SocketAddr;
 
+use async_utility::futures_util::{Sink, Stream};
 use atomic_destructor::AtomicDestructor;
 use nostr_database::prelude::*;
 use tokio::io::{AsyncRead, AsyncWrite};
 
+mod expiration;
+mod http;
 mod inner;
+mod management;
 mod session;
 mod util;
 
@@ -56,6 +60,15 @@ impl LocalRelay {
         self.inner.hidden_service()
     }
 
+    /// Get NIP11 relay information document
+    ///
+    /// Useful when the HTTP requests are handled externally (see [`LocalRelay::take_connection`]).
+    /// The document may be changed at runtime through the NIP86 API.
+    #[inline]
+    pub async fn nip11(&self) -> RelayInformationDocument {
+        self.inner.nip11().await
+    }
+
     /// Send event to subscribers
     ///
     /// Return `true` if the event is successfully sent.
@@ -79,4 +92,15 @@ impl LocalRelay {
     {
         self.inner.handle_upgraded_connection(stream, addr).await
     }
+
+    /// Pass a connection that exchanges the JSON messages directly, without the WebSocket framing
+    ///
+    /// Useful to connect the relay to in-memory transports (i.e. the `nostr-relay-pool` loopback transport).
+    pub async fn take_message_connection<T, E>(&self, connection: T, addr: SocketAddr) -> Result<()>
+    where
+        T: Stream<Item = String> + Sink<String, Error = E> + Unpin,
+        E: std::error::Error + 'static,
+    {
+        self.inner.handle_message_connection(connection, addr).await
+    }
 }
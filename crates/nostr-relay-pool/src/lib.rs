@@ -18,6 +18,7 @@ extern crate test;
 
 pub use async_wsocket::ConnectionMode;
 
+pub mod metrics;
 pub mod monitor;
 pub mod policy;
 pub mod pool;
@@ -28,15 +29,20 @@ mod shared;
 pub mod stream;
 pub mod transport;
 
+pub use self::metrics::RelayPoolMetrics;
+pub use self::pool::health::{RelayHealthAction, RelayHealthPolicy, RelayHealthReason};
 pub use self::pool::options::RelayPoolOptions;
+pub use self::pool::scheduler::{SyncScheduler, SyncSchedulerNotification};
 pub use self::pool::{Output, RelayPool, RelayPoolNotification};
 pub use self::relay::flags::{AtomicRelayServiceFlags, RelayServiceFlags};
 pub use self::relay::limits::RelayLimits;
 pub use self::relay::options::{
-    RelayOptions, SubscribeAutoCloseOptions, SubscribeOptions, SyncDirection, SyncOptions,
+    PaginationOptions, RelayOptions, SubscribeAutoCloseOptions, SubscribeOptions, SyncDirection,
+    SyncOptions,
 };
 pub use self::relay::stats::RelayConnectionStats;
-pub use self::relay::{Reconciliation, Relay, RelayNotification, RelayStatus};
+pub use self::relay::RelayLimitation;
+pub use self::relay::{OutboxStatus, Reconciliation, Relay, RelayNotification, RelayStatus};
 
 // Not public API.
 #[doc(hidden)]
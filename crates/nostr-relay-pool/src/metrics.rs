@@ -0,0 +1,502 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Metrics
+//!
+//! Export the relay pool statistics in the [OpenMetrics](https://github.com/prometheus/OpenMetrics/blob/main/specification/OpenMetrics.md) text format,
+//! ready to be scraped by Prometheus.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+#[cfg(all(feature = "metrics-http", not(target_arch = "wasm32")))]
+use std::io;
+#[cfg(all(feature = "metrics-http", not(target_arch = "wasm32")))]
+use std::net::SocketAddr;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+use async_utility::task;
+use atomic_destructor::StealthClone;
+use nostr::{Kind, RelayMessage, RelayUrl};
+#[cfg(all(feature = "metrics-http", not(target_arch = "wasm32")))]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+#[cfg(all(feature = "metrics-http", not(target_arch = "wasm32")))]
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::pool::{RelayPool, RelayPoolNotification};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::relay::LatencyHistogram;
+use crate::relay::{Relay, RelayStatus};
+
+/// OpenMetrics text format content type
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+#[derive(Debug, Default)]
+struct RelayCounters {
+    /// Received events per kind
+    events: BTreeMap<Kind, u64>,
+    /// Events accepted by the relay
+    accepted: u64,
+    /// Events rejected by the relay
+    rejected: u64,
+}
+
+/// Relay pool metrics
+///
+/// Keep track of the events received and published through the relays of a [`RelayPool`]
+/// and render them, together with the [`RelayConnectionStats`](crate::RelayConnectionStats),
+/// in the OpenMetrics text format.
+///
+/// Only the events received and published after the construction of the [`RelayPoolMetrics`] are counted.
+/// The metrics don't keep the pool alive: once all the [`RelayPool`] instances are dropped, the collection stops.
+#[derive(Debug, Clone)]
+pub struct RelayPoolMetrics {
+    pool: RelayPool,
+    counters: Arc<RwLock<HashMap<RelayUrl, RelayCounters>>>,
+}
+
+impl RelayPoolMetrics {
+    /// Start collecting the metrics of a [`RelayPool`]
+    pub fn new(pool: &RelayPool) -> Self {
+        let counters: Arc<RwLock<HashMap<RelayUrl, RelayCounters>>> = Arc::default();
+
+        task::spawn(collect(pool.notifications(), counters.clone()));
+
+        Self {
+            pool: pool.stealth_clone(),
+            counters,
+        }
+    }
+
+    /// Render the metrics in the OpenMetrics text format
+    pub async fn render(&self) -> String {
+        let relays: BTreeMap<String, Relay> = self
+            .pool
+            .all_relays()
+            .await
+            .into_iter()
+            .map(|(url, relay)| (url.to_string(), relay))
+            .collect();
+
+        let mut subscriptions: BTreeMap<&str, usize> = BTreeMap::new();
+        for (url, relay) in relays.iter() {
+            subscriptions.insert(url, relay.subscriptions().await.len());
+        }
+
+        let counters = self.counters.read().await;
+
+        let mut out: String = String::new();
+
+        write_family(
+            &mut out,
+            "nostr_relay_connected",
+            "gauge",
+            "Whether the relay is connected",
+        );
+        for (url, relay) in relays.iter() {
+            let connected: u8 = u8::from(relay.status() == RelayStatus::Connected);
+            write_sample(
+                &mut out,
+                "nostr_relay_connected",
+                &[("relay", url)],
+                connected,
+            );
+        }
+
+        write_family(
+            &mut out,
+            "nostr_relay_connection_attempts",
+            "counter",
+            "Connection attempts",
+        );
+        for (url, relay) in relays.iter() {
+            write_sample(
+                &mut out,
+                "nostr_relay_connection_attempts_total",
+                &[("relay", url)],
+                relay.stats().attempts(),
+            );
+        }
+
+        write_family(
+            &mut out,
+            "nostr_relay_connection_success",
+            "counter",
+            "Successful connections",
+        );
+        for (url, relay) in relays.iter() {
+            write_sample(
+                &mut out,
+                "nostr_relay_connection_success_total",
+                &[("relay", url)],
+                relay.stats().success(),
+            );
+        }
+
+        write_family(&mut out, "nostr_relay_sent_bytes", "counter", "Bytes sent");
+        for (url, relay) in relays.iter() {
+            write_sample(
+                &mut out,
+                "nostr_relay_sent_bytes_total",
+                &[("relay", url)],
+                relay.stats().bytes_sent(),
+            );
+        }
+
+        write_family(
+            &mut out,
+            "nostr_relay_received_bytes",
+            "counter",
+            "Bytes received",
+        );
+        for (url, relay) in relays.iter() {
+            write_sample(
+                &mut out,
+                "nostr_relay_received_bytes_total",
+                &[("relay", url)],
+                relay.stats().bytes_received(),
+            );
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            write_family(
+                &mut out,
+                "nostr_relay_latency_seconds",
+                "histogram",
+                "Relay round-trip latency",
+            );
+            for (url, relay) in relays.iter() {
+                let histogram: LatencyHistogram = relay.stats().latency_histogram();
+
+                for (bound, count) in histogram.buckets.iter() {
+                    write_sample(
+                        &mut out,
+                        "nostr_relay_latency_seconds_bucket",
+                        &[("relay", url), ("le", &format_seconds(*bound))],
+                        count,
+                    );
+                }
+
+                write_sample(
+                    &mut out,
+                    "nostr_relay_latency_seconds_bucket",
+                    &[("relay", url), ("le", "+Inf")],
+                    histogram.count,
+                );
+                write_sample(
+                    &mut out,
+                    "nostr_relay_latency_seconds_count",
+                    &[("relay", url)],
+                    histogram.count,
+                );
+                write_sample(
+                    &mut out,
+                    "nostr_relay_latency_seconds_sum",
+                    &[("relay", url)],
+                    format_seconds(histogram.sum),
+                );
+            }
+        }
+
+        write_family(
+            &mut out,
+            "nostr_relay_subscriptions",
+            "gauge",
+            "Active subscriptions",
+        );
+        for (url, count) in subscriptions.iter() {
+            write_sample(
+                &mut out,
+                "nostr_relay_subscriptions",
+                &[("relay", url)],
+                count,
+            );
+        }
+
+        write_family(
+            &mut out,
+            "nostr_relay_received_events",
+            "counter",
+            "Events received, by kind",
+        );
+        for (url, relay) in relays.iter() {
+            if let Some(counters) = counters.get(relay.url()) {
+                for (kind, count) in counters.events.iter() {
+                    write_sample(
+                        &mut out,
+                        "nostr_relay_received_events_total",
+                        &[("relay", url), ("kind", &kind.as_u16().to_string())],
+                        count,
+                    );
+                }
+            }
+        }
+
+        write_family(
+            &mut out,
+            "nostr_relay_published_events",
+            "counter",
+            "Events published, by relay response",
+        );
+        for (url, relay) in relays.iter() {
+            let (accepted, rejected) = match counters.get(relay.url()) {
+                Some(counters) => (counters.accepted, counters.rejected),
+                None => (0, 0),
+            };
+
+            write_sample(
+                &mut out,
+                "nostr_relay_published_events_total",
+                &[("relay", url), ("status", "accepted")],
+                accepted,
+            );
+            write_sample(
+                &mut out,
+                "nostr_relay_published_events_total",
+                &[("relay", url), ("status", "rejected")],
+                rejected,
+            );
+        }
+
+        out.push_str("# EOF\n");
+        out
+    }
+
+    /// Serve the metrics over HTTP
+    ///
+    /// Every request, regardless of the method and path, gets the rendered metrics.
+    /// This method runs until an error occurs while accepting a connection.
+    #[cfg(all(feature = "metrics-http", not(target_arch = "wasm32")))]
+    pub async fn serve(&self, addr: SocketAddr) -> Result<(), io::Error> {
+        let listener: TcpListener = TcpListener::bind(addr).await?;
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let metrics: Self = self.clone();
+
+            task::spawn(async move {
+                if let Err(e) = metrics.respond(stream).await {
+                    tracing::debug!(error = %e, "Can't serve metrics.");
+                }
+            });
+        }
+    }
+
+    #[cfg(all(feature = "metrics-http", not(target_arch = "wasm32")))]
+    async fn respond(&self, mut stream: TcpStream) -> Result<(), io::Error> {
+        // Read the request head: its content isn't relevant
+        let mut buf: [u8; 1024] = [0u8; 1024];
+        let _ = stream.read(&mut buf).await?;
+
+        let body: String = self.render().await;
+        let head: String = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {OPENMETRICS_CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(body.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+async fn collect(
+    mut notifications: broadcast::Receiver<RelayPoolNotification>,
+    counters: Arc<RwLock<HashMap<RelayUrl, RelayCounters>>>,
+) {
+    loop {
+        match notifications.recv().await {
+            Ok(RelayPoolNotification::Message { relay_url, message }) => match message {
+                RelayMessage::Event { event, .. } => {
+                    let mut counters = counters.write().await;
+                    let relay: &mut RelayCounters = counters.entry(relay_url).or_default();
+                    *relay.events.entry(event.kind).or_default() += 1;
+                }
+                RelayMessage::Ok { status, .. } => {
+                    let mut counters = counters.write().await;
+                    let relay: &mut RelayCounters = counters.entry(relay_url).or_default();
+                    match status {
+                        true => relay.accepted += 1,
+                        false => relay.rejected += 1,
+                    }
+                }
+                _ => {}
+            },
+            Ok(RelayPoolNotification::Shutdown) | Err(RecvError::Closed) => break,
+            Ok(..) => {}
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    skipped,
+                    "Metrics collector lagged behind the notifications."
+                );
+            }
+        }
+    }
+
+    tracing::debug!("Exited from metrics collector.");
+}
+
+fn write_family(out: &mut String, name: &str, r#type: &str, help: &str) {
+    let _ = writeln!(out, "# TYPE {name} {type}");
+    let _ = writeln!(out, "# HELP {name} {help}");
+}
+
+fn write_sample<V>(out: &mut String, name: &str, labels: &[(&str, &str)], value: V)
+where
+    V: fmt::Display,
+{
+    out.push_str(name);
+
+    if !labels.is_empty() {
+        out.push('{');
+        for (index, (key, value)) in labels.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{key}=\"{}\"", escape_label_value(value));
+        }
+        out.push('}');
+    }
+
+    let _ = writeln!(out, " {value}");
+}
+
+/// Escape backslashes, double quotes and line feeds
+fn escape_label_value(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\\', '"', '\n']) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut escaped: String = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Format a duration as seconds, always with the decimal part (i.e. `1.0`)
+#[cfg(not(target_arch = "wasm32"))]
+fn format_seconds(duration: Duration) -> String {
+    let secs: f64 = duration.as_secs_f64();
+    match secs.fract() == 0.0 {
+        true => format!("{secs:.1}"),
+        false => format!("{secs}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use async_utility::time;
+    use nostr::{EventBuilder, Filter, Keys};
+    use nostr_relay_builder::{LocalRelay, RelayBuilder};
+
+    use super::*;
+    use crate::relay::options::ReqExitPolicy;
+    use crate::transport::loopback::LoopbackTransport;
+    use crate::RelayOptions;
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(
+            escape_label_value("wss://relay.damus.io"),
+            "wss://relay.damus.io"
+        );
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_write_sample() {
+        let mut out: String = String::new();
+        write_family(&mut out, "test_total", "counter", "Test");
+        write_sample(
+            &mut out,
+            "test_total",
+            &[("relay", "wss://a"), ("kind", "1")],
+            3,
+        );
+        write_sample(&mut out, "test_total", &[], 0);
+        assert_eq!(
+            out,
+            "# TYPE test_total counter\n# HELP test_total Test\ntest_total{relay=\"wss://a\",kind=\"1\"} 3\ntest_total 0\n"
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_format_seconds() {
+        assert_eq!(format_seconds(Duration::from_millis(50)), "0.05");
+        assert_eq!(format_seconds(Duration::from_secs(1)), "1.0");
+        assert_eq!(format_seconds(Duration::from_millis(2500)), "2.5");
+    }
+
+    #[tokio::test]
+    async fn test_render() {
+        let url = RelayUrl::parse("ws://metrics.relay").unwrap();
+
+        let local = LocalRelay::new(RelayBuilder::default()).await.unwrap();
+        let transport = LoopbackTransport::new();
+        transport.add_relay(&url, move |conn| {
+            let local = local.clone();
+            async move {
+                let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+                local.take_message_connection(conn, addr).await.unwrap();
+            }
+        });
+
+        let pool = RelayPool::builder().websocket_transport(transport).build();
+        let metrics = RelayPoolMetrics::new(&pool);
+
+        pool.add_relay(&url, RelayOptions::default()).await.unwrap();
+        pool.connect().await;
+        pool.wait_for_connection(Duration::from_secs(2)).await;
+
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("metrics")
+            .sign_with_keys(&keys)
+            .unwrap();
+        pool.send_event(&event).await.unwrap();
+
+        let filter = Filter::new().kind(Kind::TextNote);
+        pool.fetch_events(filter, Duration::from_secs(2), ReqExitPolicy::ExitOnEOSE)
+            .await
+            .unwrap();
+
+        // Let the collector process the notifications
+        time::sleep(Duration::from_millis(100)).await;
+
+        let text: String = metrics.render().await;
+        let relay: String = format!("relay=\"{url}\"");
+
+        assert!(text.ends_with("# EOF\n"));
+        assert!(text.contains(&format!("nostr_relay_connected{{{relay}}} 1\n")));
+        assert!(text.contains(&format!(
+            "nostr_relay_connection_success_total{{{relay}}} 1\n"
+        )));
+        assert!(text.contains(&format!(
+            "nostr_relay_received_events_total{{{relay},kind=\"1\"}} 1\n"
+        )));
+        assert!(text.contains(&format!(
+            "nostr_relay_published_events_total{{{relay},status=\"accepted\"}} 1\n"
+        )));
+        assert!(text.contains(&format!(
+            "nostr_relay_published_events_total{{{relay},status=\"rejected\"}} 0\n"
+        )));
+        assert!(text.contains(&format!(
+            "nostr_relay_latency_seconds_bucket{{{relay},le=\"+Inf\"}}"
+        )));
+    }
+}
@@ -4,13 +4,16 @@
 
 //! Monitor
 
-use nostr::RelayUrl;
+use nostr::message::MachineReadablePrefix;
+use nostr::{EventId, RelayUrl, SubscriptionId};
 use tokio::sync::broadcast::{self, Receiver, Sender};
 
+use crate::pool::health::{RelayHealthAction, RelayHealthReason};
 use crate::relay::RelayStatus;
 
 /// Relay monitor notification
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
 pub enum MonitorNotification {
     /// Relay status changed
     StatusChanged {
@@ -19,6 +22,77 @@ pub enum MonitorNotification {
         /// Status
         status: RelayStatus,
     },
+    /// Relay health score dropped below the threshold of the health policy
+    ///
+    /// Check [`RelayHealthPolicy`](crate::pool::health::RelayHealthPolicy).
+    HealthDegraded {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Action applied to the relay
+        action: RelayHealthAction,
+        /// Reason
+        reason: RelayHealthReason,
+    },
+    /// Relay health score is back above the threshold of the health policy
+    HealthRestored {
+        /// Relay URL
+        relay_url: RelayUrl,
+    },
+    /// Subscription opened (`REQ` sent to the relay)
+    SubscriptionOpened {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+    },
+    /// Received `EOSE` from the relay
+    EndOfStoredEvents {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+    },
+    /// Subscription closed by the relay (received `CLOSED`)
+    SubscriptionClosed {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+        /// Machine-readable prefix of the message, if any
+        prefix: Option<MachineReadablePrefix>,
+        /// Message
+        message: String,
+    },
+    /// Authenticated to the relay (NIP-42)
+    Authenticated {
+        /// Relay URL
+        relay_url: RelayUrl,
+    },
+    /// Authentication to the relay failed (NIP-42)
+    AuthenticationFailed {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Reason
+        reason: String,
+    },
+    /// The relay replied with a `rate-limited` message to an `EVENT` or `REQ`
+    RateLimited {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Message
+        message: String,
+    },
+    /// Event rejected by the relay (received `OK` with `false` status)
+    EventRejected {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Event ID
+        event_id: EventId,
+        /// Machine-readable prefix of the message, if any
+        prefix: Option<MachineReadablePrefix>,
+        /// Message
+        message: String,
+    },
 }
 
 /// Relay monitor
@@ -48,7 +122,7 @@ impl Monitor {
     }
 
     #[inline]
-    fn notify(&self, notification: MonitorNotification) {
+    pub(crate) fn notify(&self, notification: MonitorNotification) {
         let _ = self.channel.send(notification);
     }
 
@@ -56,4 +130,23 @@ impl Monitor {
     pub(crate) fn notify_status_change(&self, relay_url: RelayUrl, status: RelayStatus) {
         self.notify(MonitorNotification::StatusChanged { relay_url, status });
     }
+
+    #[inline]
+    pub(crate) fn notify_health_degraded(
+        &self,
+        relay_url: RelayUrl,
+        action: RelayHealthAction,
+        reason: RelayHealthReason,
+    ) {
+        self.notify(MonitorNotification::HealthDegraded {
+            relay_url,
+            action,
+            reason,
+        });
+    }
+
+    #[inline]
+    pub(crate) fn notify_health_restored(&self, relay_url: RelayUrl) {
+        self.notify(MonitorNotification::HealthRestored { relay_url });
+    }
 }
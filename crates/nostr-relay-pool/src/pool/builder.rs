@@ -7,8 +7,9 @@
 use std::sync::Arc;
 
 use nostr::NostrSigner;
-use nostr_database::{MemoryDatabase, NostrDatabase};
+use nostr_database::{MemoryDatabase, NostrDatabase, NostrDatabaseHealth, NostrDatabaseOutbox};
 
+use super::health::RelayHealthPolicy;
 use super::options::RelayPoolOptions;
 use super::RelayPool;
 use crate::monitor::Monitor;
@@ -24,6 +25,12 @@ pub struct RelayPoolBuilder {
     pub admit_policy: Option<Arc<dyn AdmitPolicy>>,
     /// Relay monitor
     pub monitor: Option<Monitor>,
+    /// Relay health policy
+    pub health_policy: Option<RelayHealthPolicy>,
+    /// Relay health scores store
+    pub health_scores: Option<Arc<dyn NostrDatabaseHealth>>,
+    /// Outbox store
+    pub outbox: Option<Arc<dyn NostrDatabaseOutbox>>,
     /// Relay pool options
     pub opts: RelayPoolOptions,
     // Private stuff
@@ -39,6 +46,9 @@ impl Default for RelayPoolBuilder {
             websocket_transport: Arc::new(DefaultWebsocketTransport),
             admit_policy: None,
             monitor: None,
+            health_policy: None,
+            health_scores: None,
+            outbox: None,
             opts: RelayPoolOptions::default(),
             __database: Arc::new(MemoryDatabase::default()),
             __signer: None,
@@ -80,6 +90,33 @@ impl RelayPoolBuilder {
         self
     }
 
+    /// Set relay health policy
+    ///
+    /// The relays are checked in background, after the first connection request.
+    #[inline]
+    pub fn health_policy(mut self, policy: RelayHealthPolicy) -> Self {
+        self.health_policy = Some(policy);
+        self
+    }
+
+    /// Set relay health scores store
+    ///
+    /// Persists the scores computed with the health policy, to restore them after a restart.
+    #[inline]
+    pub fn health_scores(mut self, store: Arc<dyn NostrDatabaseHealth>) -> Self {
+        self.health_scores = Some(store);
+        self
+    }
+
+    /// Set outbox store
+    ///
+    /// Used by the relays with the offline outbox enabled (see [`RelayOptions::outbox`](crate::relay::RelayOptions::outbox)).
+    #[inline]
+    pub fn outbox(mut self, store: Arc<dyn NostrDatabaseOutbox>) -> Self {
+        self.outbox = Some(store);
+        self
+    }
+
     /// Set options
     #[inline]
     pub fn opts(mut self, opts: RelayPoolOptions) -> Self {
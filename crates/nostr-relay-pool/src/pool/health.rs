@@ -0,0 +1,332 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay health policy
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::relay::flags::RelayServiceFlags;
+use crate::relay::stats::RelayConnectionStats;
+
+const DEFAULT_THRESHOLD: f64 = 0.5;
+const DEFAULT_WINDOW: usize = 10;
+const DEFAULT_MIN_SAMPLES: usize = 3;
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Action taken when the health score of a relay drops below the threshold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RelayHealthAction {
+    /// Remove the [`RelayServiceFlags::READ`], [`RelayServiceFlags::WRITE`] and [`RelayServiceFlags::DISCOVERY`] flags
+    ///
+    /// The relay stays in the pool and the removed flags are restored when the score recovers.
+    ///
+    /// [`RelayServiceFlags::READ`]: crate::RelayServiceFlags::READ
+    /// [`RelayServiceFlags::WRITE`]: crate::RelayServiceFlags::WRITE
+    /// [`RelayServiceFlags::DISCOVERY`]: crate::RelayServiceFlags::DISCOVERY
+    #[default]
+    Demote,
+    /// Disconnect and remove the relay from the pool
+    ///
+    /// Same rules of [`RelayPool::remove_relay`](crate::RelayPool::remove_relay) apply.
+    Evict,
+}
+
+/// Why the health score of a relay dropped below the threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RelayHealthReason {
+    /// Too many failed connection attempts in the window
+    ConnectionFailures {
+        /// Connection attempts in the window
+        attempts: usize,
+        /// Successful connections in the window
+        success: usize,
+    },
+    /// Average latency above [`RelayHealthPolicy::max_latency`]
+    HighLatency {
+        /// Average latency
+        latency: Duration,
+    },
+}
+
+/// Relay health policy
+///
+/// Every [`RelayHealthPolicy::interval`] the pool takes a sample of the connection stats of each relay
+/// and computes a score between `0.0` and `1.0` over the last [`RelayHealthPolicy::window`] samples.
+/// When the score drops below the [`RelayHealthPolicy::threshold`], the [`RelayHealthAction`] is applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelayHealthPolicy {
+    pub(super) threshold: f64,
+    pub(super) window: usize,
+    pub(super) min_samples: usize,
+    pub(super) interval: Duration,
+    pub(super) max_latency: Option<Duration>,
+    pub(super) action: RelayHealthAction,
+}
+
+impl Default for RelayHealthPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            window: DEFAULT_WINDOW,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            interval: DEFAULT_INTERVAL,
+            max_latency: None,
+            action: RelayHealthAction::default(),
+        }
+    }
+}
+
+impl RelayHealthPolicy {
+    /// New default policy
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Score threshold, between `0.0` and `1.0` (default: 0.5)
+    #[inline]
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Number of samples used to compute the score (default: 10)
+    #[inline]
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Minimum number of samples required before acting on a relay (default: 3)
+    #[inline]
+    pub fn min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    /// Sampling interval (default: 60 secs)
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Penalize relays with an average latency above this value (default: None)
+    ///
+    /// The score of a sample is scaled by `max_latency / latency`.
+    #[inline]
+    pub fn max_latency(mut self, max_latency: Option<Duration>) -> Self {
+        self.max_latency = max_latency;
+        self
+    }
+
+    /// Action to take for relays below the threshold (default: [`RelayHealthAction::Demote`])
+    #[inline]
+    pub fn action(mut self, action: RelayHealthAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HealthSample {
+    attempts: usize,
+    success: usize,
+    /// Connection success ratio
+    reliability: f64,
+    latency: Option<Duration>,
+}
+
+impl HealthSample {
+    fn score(&self, policy: &RelayHealthPolicy) -> f64 {
+        match (self.latency, policy.max_latency) {
+            (Some(latency), Some(max)) if latency > max => {
+                self.reliability * (max.as_secs_f64() / latency.as_secs_f64())
+            }
+            _ => self.reliability,
+        }
+    }
+}
+
+/// Relay health state tracked by the pool
+#[derive(Debug, Clone)]
+pub(super) struct RelayHealthTracker {
+    samples: VecDeque<HealthSample>,
+    /// Connection attempts at the last sample
+    attempts: usize,
+    /// Successful connections at the last sample
+    success: usize,
+    /// Whether the action has been applied
+    pub(super) degraded: bool,
+    /// Flags removed by [`RelayHealthAction::Demote`]
+    pub(super) demoted: RelayServiceFlags,
+}
+
+impl RelayHealthTracker {
+    /// New tracker
+    ///
+    /// The persisted `score`, if any, is used as the first sample.
+    pub(super) fn new(score: Option<f64>) -> Self {
+        let mut samples: VecDeque<HealthSample> = VecDeque::new();
+
+        if let Some(score) = score {
+            samples.push_back(HealthSample {
+                attempts: 0,
+                success: 0,
+                reliability: score.clamp(0.0, 1.0),
+                latency: None,
+            });
+        }
+
+        Self {
+            samples,
+            attempts: 0,
+            success: 0,
+            degraded: false,
+            demoted: RelayServiceFlags::NONE,
+        }
+    }
+
+    /// Take a new sample from the relay stats
+    ///
+    /// If no connection has been attempted since the last sample, the relay is considered healthy only if connected.
+    /// Returns `false` if there wasn't enough information to take a sample.
+    pub(super) fn sample(
+        &mut self,
+        policy: &RelayHealthPolicy,
+        stats: &RelayConnectionStats,
+        connected: bool,
+    ) -> bool {
+        let attempts: usize = stats.attempts();
+        let success: usize = stats.success();
+
+        let diff_attempts: usize = attempts.saturating_sub(self.attempts);
+        let diff_success: usize = success.saturating_sub(self.success);
+
+        self.attempts = attempts;
+        self.success = success;
+
+        let reliability: f64 = if diff_attempts > 0 {
+            (diff_success as f64 / diff_attempts as f64).min(1.0)
+        } else if connected {
+            1.0
+        } else {
+            return false;
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let latency: Option<Duration> = stats.latency();
+        #[cfg(target_arch = "wasm32")]
+        let latency: Option<Duration> = None;
+
+        self.samples.push_back(HealthSample {
+            attempts: diff_attempts,
+            success: diff_success,
+            reliability,
+            latency,
+        });
+
+        while self.samples.len() > policy.window {
+            self.samples.pop_front();
+        }
+
+        true
+    }
+
+    /// Check if there are enough samples to act on the relay
+    #[inline]
+    pub(super) fn is_ready(&self, policy: &RelayHealthPolicy) -> bool {
+        self.samples.len() >= policy.min_samples
+    }
+
+    /// Health score: the average score of the samples in the window
+    ///
+    /// Returns `None` if there are no samples.
+    pub(super) fn score(&self, policy: &RelayHealthPolicy) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let sum: f64 = self.samples.iter().map(|s| s.score(policy)).sum();
+        Some(sum / self.samples.len() as f64)
+    }
+
+    /// Main reason of a low score
+    pub(super) fn reason(&self, policy: &RelayHealthPolicy) -> RelayHealthReason {
+        let len: f64 = self.samples.len().max(1) as f64;
+        let reliability: f64 = self.samples.iter().map(|s| s.reliability).sum::<f64>() / len;
+
+        let latency: Option<Duration> = self.samples.back().and_then(|s| s.latency);
+
+        match latency {
+            Some(latency) if reliability >= policy.threshold => {
+                RelayHealthReason::HighLatency { latency }
+            }
+            _ => RelayHealthReason::ConnectionFailures {
+                attempts: self.samples.iter().map(|s| s.attempts).sum(),
+                success: self.samples.iter().map(|s| s.success).sum(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_score() {
+        let policy = RelayHealthPolicy::new().window(3).min_samples(2);
+        let stats = RelayConnectionStats::default();
+
+        let mut tracker = RelayHealthTracker::new(None);
+        assert_eq!(tracker.score(&policy), None);
+
+        // Not connected and no attempts: nothing to sample
+        assert!(!tracker.sample(&policy, &stats, false));
+
+        // Connected
+        assert!(tracker.sample(&policy, &stats, true));
+        assert!(!tracker.is_ready(&policy));
+        assert!(tracker.sample(&policy, &stats, true));
+        assert!(tracker.is_ready(&policy));
+        assert_eq!(tracker.score(&policy), Some(1.0));
+
+        // Two failed attempts
+        stats.new_attempt();
+        stats.new_attempt();
+        assert!(tracker.sample(&policy, &stats, false));
+        assert_eq!(tracker.score(&policy), Some(2.0 / 3.0));
+
+        // Sliding window: drop the oldest sample
+        stats.new_attempt();
+        stats.new_success();
+        stats.new_attempt();
+        assert!(tracker.sample(&policy, &stats, false));
+        assert_eq!(tracker.score(&policy), Some((1.0 + 0.0 + 0.5) / 3.0));
+
+        assert_eq!(
+            tracker.reason(&policy),
+            RelayHealthReason::ConnectionFailures {
+                attempts: 4,
+                success: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_health_score_persisted() {
+        let policy = RelayHealthPolicy::new().min_samples(2);
+        let stats = RelayConnectionStats::default();
+
+        let mut tracker = RelayHealthTracker::new(Some(0.2));
+        assert!(!tracker.is_ready(&policy));
+        assert_eq!(tracker.score(&policy), Some(0.2));
+
+        assert!(tracker.sample(&policy, &stats, true));
+        assert!(tracker.is_ready(&policy));
+        assert_eq!(tracker.score(&policy), Some(0.6));
+    }
+}
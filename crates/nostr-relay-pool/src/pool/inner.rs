@@ -13,6 +13,7 @@ use atomic_destructor::AtomicDestroyer;
 use nostr_database::prelude::*;
 use tokio::sync::{broadcast, RwLock};
 
+use super::health::{RelayHealthPolicy, RelayHealthTracker};
 use super::options::RelayPoolOptions;
 use super::{RelayPoolBuilder, RelayPoolNotification};
 use crate::relay::Relay;
@@ -26,6 +27,8 @@ pub(super) type Relays = HashMap<RelayUrl, Relay>;
 pub(super) struct AtomicPrivateData {
     pub(super) relays: RwLock<Relays>,
     pub(super) subscriptions: RwLock<HashMap<SubscriptionId, Filter>>,
+    pub(super) health: RwLock<HashMap<RelayUrl, RelayHealthTracker>>,
+    pub(super) health_checker: AtomicBool,
     pub(super) shutdown: AtomicBool,
 }
 
@@ -34,6 +37,8 @@ pub struct InnerRelayPool {
     pub(super) state: SharedState,
     pub(super) atomic: Arc<AtomicPrivateData>,
     pub(super) notification_sender: broadcast::Sender<RelayPoolNotification>, // TODO: move to shared state?
+    pub(super) health_policy: Option<RelayHealthPolicy>,
+    pub(super) health_scores: Option<Arc<dyn NostrDatabaseHealth>>,
     pub(super) opts: RelayPoolOptions,
 }
 
@@ -56,13 +61,18 @@ impl InnerRelayPool {
                 builder.admit_policy,
                 builder.opts.nip42_auto_authentication,
                 builder.monitor,
+                builder.outbox,
             ),
             atomic: Arc::new(AtomicPrivateData {
                 relays: RwLock::new(HashMap::new()),
                 subscriptions: RwLock::new(HashMap::new()),
+                health: RwLock::new(HashMap::new()),
+                health_checker: AtomicBool::new(false),
                 shutdown: AtomicBool::new(false),
             }),
             notification_sender,
+            health_policy: builder.health_policy,
+            health_scores: builder.health_scores,
             opts: builder.opts,
         }
     }
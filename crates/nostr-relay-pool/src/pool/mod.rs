@@ -11,7 +11,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_utility::futures_util::{future, StreamExt};
-use async_utility::task;
+use async_utility::{task, time};
 use atomic_destructor::{AtomicDestructor, StealthClone};
 use nostr_database::prelude::*;
 use tokio::sync::{broadcast, mpsc, RwLockReadGuard};
@@ -19,22 +19,25 @@ use tokio::sync::{broadcast, mpsc, RwLockReadGuard};
 pub mod builder;
 pub mod constants;
 mod error;
+pub mod health;
 mod inner;
 pub mod options;
 mod output;
+pub mod scheduler;
 
 pub use self::builder::RelayPoolBuilder;
 pub use self::error::Error;
+use self::health::{RelayHealthAction, RelayHealthPolicy, RelayHealthReason, RelayHealthTracker};
 use self::inner::{InnerRelayPool, Relays};
 pub use self::options::RelayPoolOptions;
 pub use self::output::Output;
 use crate::monitor::Monitor;
 use crate::relay::flags::FlagCheck;
-use crate::relay::options::{RelayOptions, ReqExitPolicy, SyncOptions};
-use crate::relay::Relay;
+use crate::relay::options::{PaginationOptions, RelayOptions, ReqExitPolicy, SyncOptions};
+use crate::relay::{Paginator, Relay};
 use crate::shared::SharedState;
 use crate::stream::ReceiverStream;
-use crate::{Reconciliation, RelayServiceFlags, SubscribeOptions};
+use crate::{OutboxStatus, Reconciliation, RelayServiceFlags, RelayStatus, SubscribeOptions};
 
 /// Relay Pool Notification
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -68,6 +71,17 @@ pub enum RelayPoolNotification {
         /// The received relay message.
         message: RelayMessage<'static>,
     },
+    /// Status of an event in the offline outbox of a relay changed
+    ///
+    /// Sent only for the relays with the outbox enabled (see [`RelayOptions::outbox`](crate::RelayOptions::outbox)).
+    Outbox {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Event ID
+        event_id: EventId,
+        /// Outbox status
+        status: OutboxStatus,
+    },
     /// Shutdown
     ///
     /// This notification variant is sent after [`RelayPool::shutdown`] method is called and all connections have been closed.
@@ -254,6 +268,26 @@ impl RelayPool {
         self.internal_relay(&relays, &url).cloned()
     }
 
+    /// Get the health score of a relay
+    ///
+    /// Returns `None` if the relay hasn't been sampled yet or
+    /// if the health policy is not set (see [`RelayPoolBuilder::health_policy`]).
+    pub async fn relay_health_score<U>(&self, url: U) -> Result<Option<f64>, Error>
+    where
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let url: RelayUrl = url.try_into_url()?;
+
+        let policy: &RelayHealthPolicy = match &self.inner.health_policy {
+            Some(policy) => policy,
+            None => return Ok(None),
+        };
+
+        let health = self.inner.atomic.health.read().await;
+        Ok(health.get(&url).and_then(|tracker| tracker.score(policy)))
+    }
+
     /// Add new relay
     ///
     /// If the [`RelayServiceFlags::READ`] flag is set in [`RelayOptions`]
@@ -422,6 +456,9 @@ impl RelayPool {
     /// [`RelayStatus::Initialized`]: crate::relay::RelayStatus::Initialized
     /// [`RelayStatus::Terminated`]: crate::relay::RelayStatus::Terminated
     pub async fn connect(&self) {
+        // Start health checker, if the policy is set
+        self.spawn_health_checker();
+
         // Lock with read shared access
         let relays = self.inner.atomic.relays.read().await;
 
@@ -460,6 +497,9 @@ impl RelayPool {
     ///
     /// For further details, see the documentation of [`Relay::try_connect`].
     pub async fn try_connect(&self, timeout: Duration) -> Output<()> {
+        // Start health checker, if the policy is set
+        self.spawn_health_checker();
+
         // Lock with read shared access
         let relays = self.inner.atomic.relays.read().await;
 
@@ -517,6 +557,9 @@ impl RelayPool {
         // Convert url
         let url: RelayUrl = url.try_into_url()?;
 
+        // Start health checker, if the policy is set
+        self.spawn_health_checker();
+
         // Lock with read shared access
         let relays = self.inner.atomic.relays.read().await;
 
@@ -540,6 +583,9 @@ impl RelayPool {
         // Convert url
         let url: RelayUrl = url.try_into_url()?;
 
+        // Start health checker, if the policy is set
+        self.spawn_health_checker();
+
         // Lock with read shared access
         let relays = self.inner.atomic.relays.read().await;
 
@@ -1236,6 +1282,163 @@ impl RelayPool {
         Ok(ReceiverStream::new(rx))
     }
 
+    /// Fetch events from relays with [`RelayServiceFlags::READ`] flag, walking the history past the max limit of the relays
+    ///
+    /// Check [`Relay::fetch_paginated_events`] to learn more.
+    pub async fn fetch_paginated_events(
+        &self,
+        filter: Filter,
+        opts: PaginationOptions,
+    ) -> Result<Events, Error> {
+        let urls: Vec<RelayUrl> = self.__read_relay_urls().await;
+        self.fetch_paginated_events_from(urls, filter, opts).await
+    }
+
+    /// Fetch events from specific relays, walking the history past the max limit of the relays
+    ///
+    /// Check [`Relay::fetch_paginated_events`] to learn more.
+    pub async fn fetch_paginated_events_from<I, U>(
+        &self,
+        urls: I,
+        filter: Filter,
+        opts: PaginationOptions,
+    ) -> Result<Events, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let mut events: Events = Events::new(&filter);
+
+        // Stream events
+        let mut stream = self
+            .stream_paginated_events_from(urls, filter, opts)
+            .await?;
+        while let Some(event) = stream.next().await {
+            // LOOKUP_ID: EVENTS_FORCE_INSERT
+            events.force_insert(event);
+        }
+
+        Ok(events)
+    }
+
+    /// Stream events from relays with [`RelayServiceFlags::READ`] flag, walking the history past the max limit of the relays
+    ///
+    /// Check [`Relay::fetch_paginated_events`] to learn more.
+    pub async fn stream_paginated_events(
+        &self,
+        filter: Filter,
+        opts: PaginationOptions,
+    ) -> Result<ReceiverStream<Event>, Error> {
+        let urls: Vec<RelayUrl> = self.__read_relay_urls().await;
+        self.stream_paginated_events_from(urls, filter, opts).await
+    }
+
+    /// Stream events from specific relays, walking the history past the max limit of the relays
+    ///
+    /// Each relay is paginated independently and the events are deduplicated across relays.
+    /// Check [`Relay::fetch_paginated_events`] to learn more.
+    pub async fn stream_paginated_events_from<I, U>(
+        &self,
+        urls: I,
+        filter: Filter,
+        opts: PaginationOptions,
+    ) -> Result<ReceiverStream<Event>, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let urls: HashSet<RelayUrl> = urls
+            .into_iter()
+            .map(|u| u.try_into_url())
+            .collect::<Result<_, _>>()?;
+
+        // Check if `urls` set is empty
+        if urls.is_empty() {
+            return Err(Error::NoRelaysSpecified);
+        }
+
+        let relays: Vec<Relay> = {
+            // Lock with read shared access
+            let relays = self.inner.atomic.relays.read().await;
+
+            // Check if empty
+            if relays.is_empty() {
+                return Err(Error::NoRelays);
+            }
+
+            // Get the relays.
+            // Return an error if the relay doesn't exists.
+            urls.iter()
+                .map(|url| self.internal_relay(&relays, url).cloned())
+                .collect::<Result<_, _>>()?
+        };
+
+        // Create channel
+        let (tx, rx) = mpsc::channel::<Event>(512);
+
+        // Spawn stream task
+        task::spawn(async move {
+            // IDs collection, needed to check if an event was already sent to the stream
+            let ids: Mutex<HashSet<EventId>> = Mutex::new(HashSet::new());
+
+            let futures = relays.iter().map(|relay| {
+                let filter: Filter = filter.clone();
+                let ids = &ids;
+                let tx = &tx;
+                let opts = &opts;
+
+                async move {
+                    let mut paginator: Paginator = Paginator::new(filter, relay.page_size(opts));
+
+                    while let Some(page) = paginator.next_page() {
+                        let events: Events = relay
+                            .fetch_events(page, opts.timeout, ReqExitPolicy::ExitOnEOSE)
+                            .await?;
+
+                        for event in paginator.process(events) {
+                            {
+                                // SAFETY: panics only if another user of this mutex panicked while holding the mutex.
+                                let mut ids = ids.lock().unwrap();
+
+                                // Total number of events reached
+                                if matches!(opts.max_events, Some(max) if ids.len() >= max) {
+                                    return Ok(());
+                                }
+
+                                // Already sent by another relay
+                                if !ids.insert(event.id) {
+                                    continue;
+                                }
+                            }
+
+                            // Stream dropped
+                            if tx.send(event).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+
+                    Ok::<(), crate::relay::Error>(())
+                }
+            });
+
+            // Join all futures
+            let list = future::join_all(futures).await;
+
+            // Iter results
+            for (relay, result) in relays.iter().zip(list.into_iter()) {
+                if let Err(e) = result {
+                    tracing::error!(url = %relay.url(), error = %e, "Failed to paginate events.");
+                }
+            }
+        });
+
+        // Return stream
+        Ok(ReceiverStream::new(rx))
+    }
+
     /// Handle notifications
     pub async fn handle_notifications<F, Fut>(&self, func: F) -> Result<(), Error>
     where
@@ -1254,6 +1457,200 @@ impl RelayPool {
         }
         Ok(())
     }
+
+    fn spawn_health_checker(&self) {
+        let policy: RelayHealthPolicy = match self.inner.health_policy {
+            Some(policy) => policy,
+            None => return,
+        };
+
+        // Check if already running
+        if self
+            .inner
+            .atomic
+            .health_checker
+            .swap(true, Ordering::SeqCst)
+        {
+            return;
+        }
+
+        // Stealth clone, to not prevent the pool from being dropped
+        let pool: Self = self.stealth_clone();
+
+        task::spawn(async move {
+            tracing::debug!("Relay health checker started.");
+
+            loop {
+                time::sleep(policy.interval).await;
+
+                if pool.is_shutdown() {
+                    break;
+                }
+
+                pool.check_relays_health(&policy).await;
+            }
+
+            tracing::debug!("Relay health checker exited.");
+        });
+    }
+
+    /// Sample the relays, update the health scores and apply the policy action
+    ///
+    /// The health lock is never held across the database operations or the relay removals.
+    async fn check_relays_health(&self, policy: &RelayHealthPolicy) {
+        let all_relays: HashMap<RelayUrl, Relay> = self.all_relays().await;
+
+        // Skip the relays that aren't in use
+        let relays: Vec<(&RelayUrl, &Relay, RelayStatus)> = all_relays
+            .iter()
+            .filter_map(|(url, relay)| {
+                let status: RelayStatus = relay.status();
+                if status.is_initialized() || status.is_terminated() || status.is_banned() {
+                    None
+                } else {
+                    Some((url, relay, status))
+                }
+            })
+            .collect();
+
+        // Relays not tracked yet
+        let untracked: Vec<RelayUrl> = {
+            let health = self.inner.atomic.health.read().await;
+            relays
+                .iter()
+                .filter(|(url, ..)| !health.contains_key(*url))
+                .map(|(url, ..)| (*url).clone())
+                .collect()
+        };
+
+        // Load the persisted scores of the relays not tracked yet
+        let mut persisted: HashMap<RelayUrl, Option<f64>> = HashMap::with_capacity(untracked.len());
+        if let Some(store) = &self.inner.health_scores {
+            for url in untracked.into_iter() {
+                let score: Option<f64> = match store.relay_health_score(&url).await {
+                    Ok(score) => score,
+                    Err(e) => {
+                        tracing::warn!(url = %url, error = %e, "Can't load relay health score.");
+                        None
+                    }
+                };
+                persisted.insert(url, score);
+            }
+        }
+
+        let mut scores: Vec<(RelayUrl, f64)> = Vec::new();
+        let mut degraded: Vec<(RelayUrl, RelayHealthReason)> = Vec::new();
+        let mut restored: Vec<RelayUrl> = Vec::new();
+
+        {
+            let mut health = self.inner.atomic.health.write().await;
+
+            // Forget the removed relays
+            health.retain(|url, _| all_relays.contains_key(url));
+
+            for (url, relay, status) in relays.into_iter() {
+                let tracker: &mut RelayHealthTracker =
+                    health.entry(url.clone()).or_insert_with(|| {
+                        RelayHealthTracker::new(persisted.get(url).copied().flatten())
+                    });
+
+                if !tracker.sample(policy, relay.stats(), status.is_connected()) {
+                    continue;
+                }
+
+                let score: f64 = match tracker.score(policy) {
+                    Some(score) => score,
+                    None => continue,
+                };
+
+                scores.push((url.clone(), score));
+
+                if !tracker.is_ready(policy) {
+                    continue;
+                }
+
+                if score < policy.threshold && !tracker.degraded {
+                    tracker.degraded = true;
+
+                    let reason: RelayHealthReason = tracker.reason(policy);
+
+                    tracing::warn!(
+                        url = %url,
+                        score = %score,
+                        reason = ?reason,
+                        action = ?policy.action,
+                        "Relay health score below threshold."
+                    );
+
+                    if let RelayHealthAction::Demote = policy.action {
+                        tracker.demoted = demote_relay(relay);
+                    }
+
+                    degraded.push((url.clone(), reason));
+                } else if score >= policy.threshold && tracker.degraded {
+                    tracker.degraded = false;
+
+                    // Restore the flags removed by the demotion
+                    relay.flags().add(tracker.demoted);
+                    tracker.demoted = RelayServiceFlags::NONE;
+
+                    tracing::info!(url = %url, score = %score, "Relay health score restored.");
+
+                    restored.push(url.clone());
+                }
+            }
+        }
+
+        // Persist scores
+        if let Some(store) = &self.inner.health_scores {
+            for (url, score) in scores.into_iter() {
+                if let Err(e) = store.save_relay_health_score(&url, score).await {
+                    tracing::warn!(url = %url, error = %e, "Can't save relay health score.");
+                }
+            }
+        }
+
+        for (url, reason) in degraded.into_iter() {
+            if let RelayHealthAction::Evict = policy.action {
+                if let Err(e) = self._remove_relay(&url, false).await {
+                    tracing::error!(url = %url, error = %e, "Can't evict relay.");
+                }
+            }
+
+            if let Some(monitor) = self.monitor() {
+                monitor.notify_health_degraded(url, policy.action, reason);
+            }
+        }
+
+        if let Some(monitor) = self.monitor() {
+            for url in restored.into_iter() {
+                monitor.notify_health_restored(url);
+            }
+        }
+    }
+}
+
+/// Remove the `READ`, `WRITE` and `DISCOVERY` flags of a relay
+///
+/// Return the flags that have been removed.
+fn demote_relay(relay: &Relay) -> RelayServiceFlags {
+    let flags = relay.flags();
+
+    let mut removed: RelayServiceFlags = RelayServiceFlags::NONE;
+
+    for flag in [
+        RelayServiceFlags::READ,
+        RelayServiceFlags::WRITE,
+        RelayServiceFlags::DISCOVERY,
+    ] {
+        if flags.has_all(flag) {
+            removed.add(flag);
+        }
+    }
+
+    flags.remove(removed);
+
+    removed
 }
 
 /// Return `true` if the relay can be removed
@@ -1278,9 +1675,14 @@ fn can_remove_relay(relay: &Relay) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use nostr_relay_builder::MockRelay;
+    use std::net::SocketAddr;
+
+    use nostr_relay_builder::{LocalRelay, MockRelay, RelayBuilder};
 
+    use super::health::RelayHealthReason;
     use super::*;
+    use crate::monitor::MonitorNotification;
+    use crate::transport::loopback::LoopbackTransport;
 
     fn relay_gossip_opts() -> RelayOptions {
         let mut flags: RelayServiceFlags = RelayServiceFlags::default();
@@ -1457,4 +1859,100 @@ mod tests {
             Error::RelayNotFound
         ));
     }
+
+    #[tokio::test]
+    async fn test_health_policy() {
+        let url = RelayUrl::parse("ws://health.relay").unwrap();
+
+        let local = LocalRelay::new(RelayBuilder::default()).await.unwrap();
+        let transport = LoopbackTransport::new();
+        transport.add_relay(&url, move |conn| {
+            let local = local.clone();
+            async move {
+                let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+                local.take_message_connection(conn, addr).await.unwrap();
+            }
+        });
+
+        // Relay unreachable
+        transport.refuse_connections(&url, true);
+
+        let monitor = Monitor::new(1024);
+        let mut notifications = monitor.subscribe();
+
+        let policy = RelayHealthPolicy::new()
+            .interval(Duration::from_millis(100))
+            .window(3)
+            .min_samples(2);
+        let store: Arc<MemoryDatabase> = Arc::new(MemoryDatabase::new());
+        let pool = RelayPool::builder()
+            .websocket_transport(transport.clone())
+            .monitor(monitor)
+            .health_policy(policy)
+            .health_scores(store.clone())
+            .build();
+
+        let opts = RelayOptions::default()
+            .retry_interval(Duration::from_millis(50))
+            .adjust_retry_interval(false);
+        pool.add_relay(&url, opts).await.unwrap();
+        pool.connect().await;
+
+        // Wait for the demotion
+        let notification = time::timeout(Some(Duration::from_secs(5)), async {
+            while let Ok(notification) = notifications.recv().await {
+                if let MonitorNotification::HealthDegraded { .. } = notification {
+                    return Some(notification);
+                }
+            }
+            None
+        })
+        .await
+        .flatten();
+        match notification {
+            Some(MonitorNotification::HealthDegraded {
+                relay_url,
+                action,
+                reason,
+            }) => {
+                assert_eq!(relay_url, url);
+                assert_eq!(action, RelayHealthAction::Demote);
+                assert!(matches!(
+                    reason,
+                    RelayHealthReason::ConnectionFailures { success: 0, .. }
+                ));
+            }
+            n => panic!("unexpected notification: {n:?}"),
+        }
+
+        let relay = pool.relay(&url).await.unwrap();
+        assert!(!relay.flags().has_read());
+        assert!(!relay.flags().has_write());
+        assert!(pool.relays().await.is_empty());
+
+        // Score persisted
+        let score: f64 = pool.relay_health_score(&url).await.unwrap().unwrap();
+        assert!(score < 0.5);
+        let persisted: f64 = store.relay_health_score(&url).await.unwrap().unwrap();
+        assert!(persisted < 0.5);
+
+        // Relay reachable again
+        transport.refuse_connections(&url, false);
+
+        // Wait for the restoration
+        let restored = time::timeout(Some(Duration::from_secs(5)), async {
+            while let Ok(notification) = notifications.recv().await {
+                if let MonitorNotification::HealthRestored { relay_url } = notification {
+                    return Some(relay_url);
+                }
+            }
+            None
+        })
+        .await
+        .flatten();
+        assert_eq!(restored, Some(url));
+
+        assert!(relay.flags().has_read());
+        assert!(relay.flags().has_write());
+    }
 }
@@ -0,0 +1,564 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Negentropy sync scheduler
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_utility::futures_util::future;
+use async_utility::{task, time};
+use atomic_destructor::StealthClone;
+use nostr::{Filter, RelayUrl, Timestamp};
+use nostr_database::NostrDatabaseCheckpoint;
+use tokio::sync::{broadcast, mpsc};
+
+use super::{Output, RelayPool, RelayPoolNotification};
+use crate::relay::{Reconciliation, Relay, SyncOptions, SyncProgress};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_MIN_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_LOOKBACK: Duration = Duration::from_secs(10 * 60);
+/// Max time between two checks of the jobs, to pick up the relays added to the pool
+const MAX_WAIT: Duration = Duration::from_secs(60);
+const NOTIFICATION_CHANNEL_SIZE: usize = 1024;
+
+/// Sync scheduler notification
+#[derive(Debug, Clone)]
+pub enum SyncSchedulerNotification {
+    /// Progress of the reconciliation of a filter with a relay
+    Progress {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Filter (as configured, not narrowed by the checkpoint)
+        filter: Filter,
+        /// Progress
+        progress: SyncProgress,
+    },
+    /// The reconciliation of a filter with a relay failed
+    Failed {
+        /// Relay URL
+        relay_url: RelayUrl,
+        /// Filter (as configured, not narrowed by the checkpoint)
+        filter: Filter,
+        /// Error
+        error: String,
+        /// Time before the next attempt
+        retry_in: Duration,
+    },
+    /// All the reconciliations due in a run have been executed
+    ///
+    /// A relay is in [`Output::success`] only if the reconciliations of all the filters succeeded.
+    Completed(Output<Reconciliation>),
+}
+
+#[derive(Debug)]
+struct Job {
+    /// Time of the last successful reconciliation
+    checkpoint: Option<Timestamp>,
+    /// Consecutive failures
+    failures: u32,
+    next_run: Timestamp,
+}
+
+/// Negentropy sync scheduler
+///
+/// Periodically reconcile a set of filters with a set of relays.
+///
+/// After each successful reconciliation, the time at which it started is kept as checkpoint,
+/// so the next ones are narrowed with `since`.
+/// A reconciliation with send failures doesn't move the checkpoint.
+///
+/// The checkpoints are kept only in memory, unless a store is set with [`SyncScheduler::checkpoints`]:
+/// in that case, the scheduler resumes from them after a restart.
+///
+/// The failed reconciliations are retried with an exponential backoff.
+///
+/// The scheduler doesn't keep the pool alive: once all the [`RelayPool`] instances are dropped, it stops.
+#[derive(Debug, Clone)]
+pub struct SyncScheduler {
+    pool: RelayPool,
+    filters: Vec<Filter>,
+    urls: Vec<RelayUrl>,
+    interval: Duration,
+    min_retry_interval: Duration,
+    max_retry_interval: Duration,
+    lookback: Duration,
+    opts: SyncOptions,
+    checkpoints: Option<Arc<dyn NostrDatabaseCheckpoint>>,
+}
+
+impl SyncScheduler {
+    /// New sync scheduler
+    pub fn new(pool: &RelayPool) -> Self {
+        Self {
+            pool: pool.stealth_clone(),
+            filters: Vec::new(),
+            urls: Vec::new(),
+            interval: DEFAULT_INTERVAL,
+            min_retry_interval: DEFAULT_MIN_RETRY_INTERVAL,
+            max_retry_interval: DEFAULT_MAX_RETRY_INTERVAL,
+            lookback: DEFAULT_LOOKBACK,
+            opts: SyncOptions::default(),
+            checkpoints: None,
+        }
+    }
+
+    /// Add a filter to reconcile
+    #[inline]
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Relays to reconcile with
+    ///
+    /// By default, all the relays of the pool with `READ` or `WRITE` flag, checked at every run.
+    #[inline]
+    pub fn relays<I>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = RelayUrl>,
+    {
+        self.urls.extend(urls);
+        self
+    }
+
+    /// Interval between the successful reconciliations of a filter with a relay (default: 1 hour)
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Min and max interval before retrying a failed reconciliation (default: 30 secs and 1 hour)
+    ///
+    /// The interval doubles at every consecutive failure, starting from `min`, up to `max`.
+    #[inline]
+    pub fn retry_interval(mut self, min: Duration, max: Duration) -> Self {
+        self.min_retry_interval = min;
+        self.max_retry_interval = max.max(min);
+        self
+    }
+
+    /// Time subtracted from the checkpoint when narrowing the filters (default: 10 min)
+    ///
+    /// Covers the events with a `created_at` slightly in the past, received by the relays after the last reconciliation.
+    #[inline]
+    pub fn lookback(mut self, lookback: Duration) -> Self {
+        self.lookback = lookback;
+        self
+    }
+
+    /// Reconciliation options
+    ///
+    /// The [`SyncOptions::progress`] is replaced, to report the progress through the scheduler notifications.
+    #[inline]
+    pub fn sync_options(mut self, opts: SyncOptions) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    /// Store of the checkpoints, to resume from them after a restart
+    ///
+    /// Usually the same database of the pool, if it implements [`NostrDatabaseCheckpoint`].
+    #[inline]
+    pub fn checkpoints(mut self, store: Arc<dyn NostrDatabaseCheckpoint>) -> Self {
+        self.checkpoints = Some(store);
+        self
+    }
+
+    /// Spawn the scheduler
+    ///
+    /// The scheduler stops when the returned receiver is dropped or the pool is shut down.
+    pub fn spawn(self) -> mpsc::Receiver<SyncSchedulerNotification> {
+        let (tx, rx) = mpsc::channel(NOTIFICATION_CHANNEL_SIZE);
+        task::spawn(async move {
+            tracing::debug!("Sync scheduler started.");
+            self.run(tx).await;
+            tracing::debug!("Sync scheduler stopped.");
+        });
+        rx
+    }
+
+    async fn run(self, tx: mpsc::Sender<SyncSchedulerNotification>) {
+        let mut notifications = self.pool.notifications();
+        let mut jobs: HashMap<(RelayUrl, Filter), Job> = HashMap::new();
+
+        loop {
+            if self.pool.is_shutdown() {
+                break;
+            }
+
+            self.update_jobs(&mut jobs).await;
+
+            // Reconcile the due jobs
+            let now: Timestamp = Timestamp::now();
+            let due: Vec<(&(RelayUrl, Filter), &mut Job)> = jobs
+                .iter_mut()
+                .filter(|(_, job)| job.next_run <= now)
+                .collect();
+
+            if !due.is_empty() {
+                let this: &Self = &self;
+                let tx = &tx;
+                let futures = due.into_iter().map(|((url, filter), job)| async move {
+                    let result = this.reconcile(url, filter, job, tx).await;
+                    (url.clone(), result)
+                });
+                let results: Vec<(RelayUrl, Result<Reconciliation, String>)> =
+                    future::join_all(futures).await;
+
+                let mut output: Output<Reconciliation> = Output::default();
+                for (url, result) in results.into_iter() {
+                    match result {
+                        Ok(reconciliation) => {
+                            output.success.insert(url);
+                            output.val.merge(reconciliation);
+                        }
+                        Err(e) => {
+                            output.failed.insert(url, e);
+                        }
+                    }
+                }
+
+                // Success only if all the filters succeeded
+                output
+                    .success
+                    .retain(|url| !output.failed.contains_key(url));
+
+                if tx
+                    .send(SyncSchedulerNotification::Completed(output))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+
+            // Wait for the next due job
+            let next_run: Timestamp = jobs
+                .values()
+                .map(|job| job.next_run)
+                .min()
+                .unwrap_or_else(|| Timestamp::now() + self.interval);
+            let wait: Duration = Duration::from_secs(
+                next_run
+                    .as_u64()
+                    .saturating_sub(Timestamp::now().as_u64())
+                    .max(1),
+            )
+            .min(MAX_WAIT);
+
+            tokio::select! {
+                _ = time::sleep(wait) => {}
+                _ = tx.closed() => break,
+                _ = wait_for_shutdown(&mut notifications) => break,
+            }
+        }
+    }
+
+    /// Add the jobs of the new relays and remove the ones of the removed relays
+    async fn update_jobs(&self, jobs: &mut HashMap<(RelayUrl, Filter), Job>) {
+        let urls: Vec<RelayUrl> = if self.urls.is_empty() {
+            self.pool.__relay_urls().await
+        } else {
+            self.urls.clone()
+        };
+
+        jobs.retain(|(url, ..), _| urls.contains(url));
+
+        for url in urls.into_iter() {
+            for filter in self.filters.iter() {
+                let key: (RelayUrl, Filter) = (url.clone(), filter.clone());
+                if jobs.contains_key(&key) {
+                    continue;
+                }
+
+                // Resume from the saved checkpoint
+                let checkpoint: Option<Timestamp> = match &self.checkpoints {
+                    Some(store) => match store.sync_checkpoint(&url, filter).await {
+                        Ok(checkpoint) => checkpoint,
+                        Err(e) => {
+                            tracing::warn!(url = %url, error = %e, "Can't get sync checkpoint.");
+                            None
+                        }
+                    },
+                    None => None,
+                };
+
+                jobs.insert(
+                    key,
+                    Job {
+                        checkpoint,
+                        failures: 0,
+                        next_run: Timestamp::zero(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Reconcile a filter with a relay and schedule the next run
+    async fn reconcile(
+        &self,
+        url: &RelayUrl,
+        filter: &Filter,
+        job: &mut Job,
+        tx: &mpsc::Sender<SyncSchedulerNotification>,
+    ) -> Result<Reconciliation, String> {
+        let started_at: Timestamp = Timestamp::now();
+
+        match self.try_reconcile(url, filter, job.checkpoint, tx).await {
+            Ok(reconciliation) => {
+                job.failures = 0;
+                job.next_run = started_at + self.interval;
+
+                // Some events haven't been sent: don't skip them in the next reconciliations
+                if reconciliation
+                    .send_failures
+                    .values()
+                    .any(|failures| !failures.is_empty())
+                {
+                    tracing::debug!(url = %url, "Sync completed with failures, keeping the checkpoint.");
+                    return Ok(reconciliation);
+                }
+
+                job.checkpoint = Some(started_at);
+
+                if let Some(store) = &self.checkpoints {
+                    if let Err(e) = store.save_sync_checkpoint(url, filter, started_at).await {
+                        tracing::warn!(url = %url, error = %e, "Can't save sync checkpoint.");
+                    }
+                }
+
+                Ok(reconciliation)
+            }
+            Err(e) => {
+                job.failures = job.failures.saturating_add(1);
+
+                let retry_in: Duration = self.retry_in(job.failures);
+                job.next_run = Timestamp::now() + retry_in;
+
+                tracing::warn!(url = %url, error = %e, "Sync failed, retrying in {retry_in:?}.");
+
+                let _ = tx
+                    .send(SyncSchedulerNotification::Failed {
+                        relay_url: url.clone(),
+                        filter: filter.clone(),
+                        error: e.clone(),
+                        retry_in,
+                    })
+                    .await;
+
+                Err(e)
+            }
+        }
+    }
+
+    async fn try_reconcile(
+        &self,
+        url: &RelayUrl,
+        filter: &Filter,
+        checkpoint: Option<Timestamp>,
+        tx: &mpsc::Sender<SyncSchedulerNotification>,
+    ) -> Result<Reconciliation, String> {
+        let relay: Relay = self.pool.relay(url).await.map_err(|e| e.to_string())?;
+
+        let (progress_tx, mut progress_rx) = SyncProgress::channel();
+        let opts: SyncOptions = self.opts.clone().progress(progress_tx);
+        let narrowed: Filter = narrow(filter, checkpoint, self.lookback);
+
+        // The progress sender is dropped at the end of the reconciliation, stopping the forwarding
+        let reconcile = async move { relay.sync(narrowed, &opts).await };
+        let forward = async {
+            while progress_rx.changed().await.is_ok() {
+                let progress: SyncProgress = *progress_rx.borrow_and_update();
+                let _ = tx
+                    .send(SyncSchedulerNotification::Progress {
+                        relay_url: url.clone(),
+                        filter: filter.clone(),
+                        progress,
+                    })
+                    .await;
+            }
+        };
+
+        let (result, ..) = future::join(reconcile, forward).await;
+        result.map_err(|e| e.to_string())
+    }
+
+    /// Interval before the next attempt, after `failures` consecutive failures
+    fn retry_in(&self, failures: u32) -> Duration {
+        let factor: u32 = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.min_retry_interval
+            .checked_mul(factor)
+            .unwrap_or(self.max_retry_interval)
+            .min(self.max_retry_interval)
+    }
+}
+
+/// Narrow the filter with the checkpoint
+fn narrow(filter: &Filter, checkpoint: Option<Timestamp>, lookback: Duration) -> Filter {
+    let since: Timestamp = match checkpoint {
+        Some(checkpoint) => checkpoint - lookback,
+        None => return filter.clone(),
+    };
+
+    match filter.since {
+        Some(current) if current >= since => filter.clone(),
+        _ => filter.clone().since(since),
+    }
+}
+
+async fn wait_for_shutdown(notifications: &mut broadcast::Receiver<RelayPoolNotification>) {
+    loop {
+        match notifications.recv().await {
+            Ok(RelayPoolNotification::Shutdown) | Err(broadcast::error::RecvError::Closed) => {
+                return
+            }
+            _ => continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{Event, EventBuilder, Keys, Kind};
+    use nostr_database::{MemoryDatabase, MemoryDatabaseOptions, NostrEventsDatabase};
+    use nostr_relay_builder::{LocalRelay, RelayBuilder};
+
+    use super::*;
+    use crate::relay::RelayOptions;
+
+    /// Run a local relay with an old event, not known by the pool
+    async fn setup_relay() -> (LocalRelay, RelayPool, Event) {
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("old")
+            .custom_created_at(Timestamp::now() - Duration::from_secs(60 * 60 * 24))
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        database.save_event(&event).await.unwrap();
+
+        let mock = LocalRelay::run(RelayBuilder::default().database(database))
+            .await
+            .unwrap();
+
+        let pool = RelayPool::default();
+        pool.add_relay(mock.url(), RelayOptions::default())
+            .await
+            .unwrap();
+        pool.connect().await;
+        pool.wait_for_connection(Duration::from_secs(5)).await;
+
+        (mock, pool, event)
+    }
+
+    async fn wait_for_completion(
+        rx: &mut mpsc::Receiver<SyncSchedulerNotification>,
+    ) -> Output<Reconciliation> {
+        time::timeout(Some(Duration::from_secs(10)), async {
+            while let Some(notification) = rx.recv().await {
+                if let SyncSchedulerNotification::Completed(output) = notification {
+                    return Some(output);
+                }
+            }
+            None
+        })
+        .await
+        .flatten()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_narrow() {
+        let filter = Filter::new().kind(Kind::TextNote);
+        let lookback = Duration::from_secs(60);
+
+        // Never synced
+        assert_eq!(narrow(&filter, None, lookback), filter);
+
+        // Synced
+        let checkpoint = Timestamp::from_secs(1_000);
+        assert_eq!(
+            narrow(&filter, Some(checkpoint), lookback),
+            filter.clone().since(Timestamp::from_secs(940))
+        );
+
+        // The filter is already narrower
+        let filter = filter.since(Timestamp::from_secs(990));
+        assert_eq!(narrow(&filter, Some(checkpoint), lookback), filter);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_run() {
+        let (mock, pool, event) = setup_relay().await;
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+        let filter = Filter::new().kind(Kind::TextNote);
+
+        let store = Arc::new(MemoryDatabase::new());
+        let mut rx = SyncScheduler::new(&pool)
+            .filter(filter.clone())
+            .checkpoints(store.clone())
+            .spawn();
+
+        // Never synced: the whole history is reconciled
+        let started_at = Timestamp::now();
+        let output = wait_for_completion(&mut rx).await;
+        assert!(output.success.contains(&url));
+        assert!(output.val.received.contains(&event.id));
+
+        // The checkpoint is saved
+        let checkpoint = store.sync_checkpoint(&url, &filter).await.unwrap();
+        assert!(matches!(checkpoint, Some(checkpoint) if checkpoint >= started_at));
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_resume_from_checkpoint() {
+        let (mock, pool, event) = setup_relay().await;
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+        let filter = Filter::new().kind(Kind::TextNote);
+
+        // Synced before the restart
+        let store = Arc::new(MemoryDatabase::new());
+        let checkpoint = Timestamp::now() - Duration::from_secs(60);
+        store
+            .save_sync_checkpoint(&url, &filter, checkpoint)
+            .await
+            .unwrap();
+
+        let mut rx = SyncScheduler::new(&pool)
+            .filter(filter.clone())
+            .checkpoints(store.clone())
+            .spawn();
+
+        // Narrowed by the checkpoint: the old event is skipped
+        let output = wait_for_completion(&mut rx).await;
+        assert!(output.success.contains(&url));
+        assert!(!output.val.received.contains(&event.id));
+
+        let saved = store.sync_checkpoint(&url, &filter).await.unwrap();
+        assert!(matches!(saved, Some(saved) if saved > checkpoint));
+    }
+
+    #[tokio::test]
+    async fn test_retry_in() {
+        let pool = RelayPool::default();
+        let scheduler = SyncScheduler::new(&pool)
+            .retry_interval(Duration::from_secs(10), Duration::from_secs(100));
+
+        assert_eq!(scheduler.retry_in(1), Duration::from_secs(10));
+        assert_eq!(scheduler.retry_in(2), Duration::from_secs(20));
+        assert_eq!(scheduler.retry_in(3), Duration::from_secs(40));
+        assert_eq!(scheduler.retry_in(4), Duration::from_secs(80));
+        assert_eq!(scheduler.retry_in(5), Duration::from_secs(100));
+        assert_eq!(scheduler.retry_in(u32::MAX), Duration::from_secs(100));
+    }
+}
@@ -14,11 +14,14 @@ pub use nostr::prelude::*;
 pub use nostr_database::*;
 
 // Internal modules
+pub use crate::metrics::*;
 pub use crate::monitor::{self, *};
 pub use crate::policy::*;
 pub use crate::pool::builder::*;
 pub use crate::pool::constants::*;
+pub use crate::pool::health::*;
 pub use crate::pool::options::*;
+pub use crate::pool::scheduler::*;
 pub use crate::pool::{self, *};
 pub use crate::relay::{self, *};
 pub use crate::stream::*;
@@ -14,6 +14,17 @@ pub(super) const DEFAULT_CONNECTION_TIMEOUT: Duration = Duration::from_secs(60);
 /// Relay default notification channel size
 pub const DEFAULT_NOTIFICATION_CHANNEL_SIZE: usize = 2048;
 
+/// Default max number of filters per multiplexed REQ
+pub const DEFAULT_MULTIPLEX_MAX_FILTERS: usize = 10;
+
+/// Slack window before the EOSE time, used when a multiplexed REQ is sent again
+///
+/// Events received late (i.e., with a clock skew or broadcasted later) are requested again and then deduplicated.
+pub(super) const MULTIPLEX_EOSE_SLACK: Duration = Duration::from_secs(15 * 60);
+
+/// Default number of events per page of the paginated fetch, if the relay doesn't advertise its max limit
+pub const DEFAULT_PAGE_SIZE: usize = 500;
+
 /// Max relay size
 pub const MAX_MESSAGE_SIZE: u32 = 5 * 1024 * 1024; // 5 MB
 /// Max event size
@@ -41,3 +52,14 @@ pub(super) const WEBSOCKET_TX_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) const LATENCY_MIN_READS: u64 = 3;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) const LATENCY_BUCKETS: [Duration; 8] = [
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
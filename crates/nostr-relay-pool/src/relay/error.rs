@@ -87,6 +87,20 @@ pub enum Error {
         /// Max message size
         max_size: usize,
     },
+    /// Message too large for the relay
+    MessageTooLarge {
+        /// Message size
+        size: usize,
+        /// Max message size accepted by the relay
+        max_size: usize,
+    },
+    /// Too many filters in a REQ for the relay
+    TooManyFilters {
+        /// Number of filters
+        size: usize,
+        /// Max number of filters accepted by the relay
+        max_size: usize,
+    },
     /// Event too large
     EventTooLarge {
         /// Event size
@@ -116,6 +130,8 @@ pub enum Error {
     AuthenticationFailed,
     /// Premature exit
     PrematureExit,
+    /// Event queued in the outbox, to be sent on reconnection
+    EventQueued,
 }
 
 impl std::error::Error for Error {}
@@ -161,6 +177,14 @@ impl fmt::Display for Error {
                 f,
                 "Received message too large: size={size}, max_size={max_size}"
             ),
+            Self::MessageTooLarge { size, max_size } => write!(
+                f,
+                "Message too large for the relay: size={size}, max_size={max_size}"
+            ),
+            Self::TooManyFilters { size, max_size } => write!(
+                f,
+                "Too many filters for the relay: size={size}, max_size={max_size}"
+            ),
             Self::EventTooLarge { size, max_size } => write!(
                 f,
                 "Received event too large: size={size}, max_size={max_size}"
@@ -179,6 +203,7 @@ impl fmt::Display for Error {
             ),
             Self::AuthenticationFailed => write!(f, "authentication failed"),
             Self::PrematureExit => write!(f, "premature exit"),
+            Self::EventQueued => write!(f, "relay not connected, event queued in the outbox"),
         }
     }
 }
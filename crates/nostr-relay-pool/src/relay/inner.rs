@@ -5,9 +5,7 @@
 use std::borrow::Cow;
 use std::cmp;
 use std::collections::{HashMap, HashSet};
-#[cfg(feature = "nip11")]
-use std::sync::atomic::AtomicU64;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -24,16 +22,20 @@ use tokio::sync::{broadcast, Mutex, MutexGuard, Notify, RwLock, RwLockWriteGuard
 use super::constants::{
     DEFAULT_CONNECTION_TIMEOUT, JITTER_RANGE, MAX_RETRY_INTERVAL, MIN_ATTEMPTS, MIN_SUCCESS_RATE,
     NEGENTROPY_BATCH_SIZE_DOWN, NEGENTROPY_FRAME_SIZE_LIMIT, NEGENTROPY_HIGH_WATER_UP,
-    NEGENTROPY_LOW_WATER_UP, PING_INTERVAL, WAIT_FOR_OK_TIMEOUT, WEBSOCKET_TX_TIMEOUT,
+    NEGENTROPY_LOW_WATER_UP, PING_INTERVAL, WAIT_FOR_AUTHENTICATION_TIMEOUT, WAIT_FOR_OK_TIMEOUT,
+    WEBSOCKET_TX_TIMEOUT,
 };
 use super::flags::AtomicRelayServiceFlags;
+use super::limitation::{AtomicRelayLimitation, RelayLimitation};
+use super::multiplexer::{Multiplexer, MultiplexerUpdate};
 use super::options::{RelayOptions, ReqExitPolicy, SubscribeAutoCloseOptions, SyncOptions};
 use super::ping::PingTracker;
 use super::stats::RelayConnectionStats;
 use super::{
-    Error, Reconciliation, RelayNotification, RelayStatus, SubscriptionActivity,
+    Error, OutboxStatus, Reconciliation, RelayNotification, RelayStatus, SubscriptionActivity,
     SubscriptionAutoClosedReason,
 };
+use crate::monitor::MonitorNotification;
 use crate::policy::AdmitStatus;
 use crate::pool::RelayPoolNotification;
 use crate::relay::status::AtomicRelayStatus;
@@ -77,10 +79,7 @@ impl RelayChannels {
         }
     }
 
-    pub fn send_client_msgs(&self, msgs: Vec<ClientMessage>) -> Result<(), Error> {
-        // Serialize messages to JSON
-        let msgs: Vec<ClientMessageJson> = msgs.into_iter().map(|msg| msg.as_json()).collect();
-
+    pub fn send_client_msgs(&self, msgs: Vec<ClientMessageJson>) -> Result<(), Error> {
         // Send
         self.nostr
             .0
@@ -116,6 +115,15 @@ struct SubscriptionData {
     pub subscribed_at: Timestamp,
     /// Subscription closed by relay
     pub closed: bool,
+    /// Position in the queue of the subscriptions waiting for the relay to accept more of them
+    pub queued: Option<u64>,
+}
+
+impl SubscriptionData {
+    /// Check if the REQ has been sent and not closed by the relay
+    fn is_open(&self) -> bool {
+        self.queued.is_none() && !self.closed && !self.subscribed_at.is_zero()
+    }
 }
 
 impl Default for SubscriptionData {
@@ -125,6 +133,7 @@ impl Default for SubscriptionData {
             filter: Filter::new(),
             subscribed_at: Timestamp::zero(),
             closed: false,
+            queued: None,
         }
     }
 }
@@ -140,6 +149,20 @@ pub(super) struct AtomicPrivateData {
     last_document_fetch: AtomicU64,
     channels: RelayChannels,
     subscriptions: RwLock<HashMap<SubscriptionId, SubscriptionData>>,
+    pub(super) multiplexer: RwLock<Multiplexer>,
+    /// Next position in the subscriptions queue
+    queue_sequence: AtomicU64,
+    limitation: AtomicRelayLimitation,
+    /// NIP-42 authenticated in the current session
+    authenticated: AtomicBool,
+    /// Stopped waiting for the NIP-42 authentication in the current session
+    authentication_expired: AtomicBool,
+    /// Websocket session counter
+    session: AtomicU64,
+    /// Auto-closing REQs open
+    auto_closing_reqs: AtomicUsize,
+    /// Notified when the relay may accept more REQs
+    req_slots: Notify,
     running: AtomicBool,
 }
 
@@ -176,6 +199,14 @@ impl InnerRelay {
                 last_document_fetch: AtomicU64::new(0),
                 channels: RelayChannels::new(),
                 subscriptions: RwLock::new(HashMap::new()),
+                multiplexer: RwLock::new(Multiplexer::new(opts.multiplex_max_filters)),
+                queue_sequence: AtomicU64::new(0),
+                limitation: AtomicRelayLimitation::default(),
+                authenticated: AtomicBool::new(false),
+                authentication_expired: AtomicBool::new(false),
+                session: AtomicU64::new(0),
+                auto_closing_reqs: AtomicUsize::new(0),
+                req_slots: Notify::new(),
                 running: AtomicBool::new(false),
             }),
             flags: AtomicRelayServiceFlags::new(opts.flags),
@@ -231,6 +262,19 @@ impl InnerRelay {
         }
     }
 
+    /// Send a notification to the monitor, if enabled
+    ///
+    /// The notification is constructed only if the monitor is enabled.
+    #[inline]
+    fn notify_monitor<F>(&self, notification: F)
+    where
+        F: FnOnce() -> MonitorNotification,
+    {
+        if let Some(monitor) = &self.state.monitor {
+            monitor.notify(notification());
+        }
+    }
+
     /// Perform health checks
     pub(super) fn health_check(&self) -> Result<(), Error> {
         let status: RelayStatus = self.status();
@@ -288,16 +332,25 @@ impl InnerRelay {
                 self.atomic.last_document_fetch.store(now, Ordering::SeqCst);
 
                 // Fetch
-                let url = self.url.clone();
-                let atomic = self.atomic.clone();
+                let relay = self.clone();
                 task::spawn(async move {
-                    match RelayInformationDocument::get(url.clone().into(), proxy).await {
+                    match RelayInformationDocument::get(relay.url.clone().into(), proxy).await {
                         Ok(document) => {
-                            let mut d = atomic.document.write().await;
-                            *d = document
+                            let limitation: RelayLimitation = document
+                                .limitation
+                                .as_ref()
+                                .map(RelayLimitation::from)
+                                .unwrap_or_default();
+
+                            let mut d = relay.atomic.document.write().await;
+                            *d = document;
+                            drop(d);
+
+                            // Adapt to the limitations of the relay
+                            relay.set_limitation(limitation).await;
                         }
                         Err(e) => {
-                            tracing::warn!(url = %url, error = %e, "Can't get information document.")
+                            tracing::warn!(url = %relay.url, error = %e, "Can't get information document.")
                         }
                     };
                 });
@@ -305,6 +358,249 @@ impl InnerRelay {
         }
     }
 
+    #[inline]
+    pub fn limitation(&self) -> RelayLimitation {
+        self.atomic.limitation.load()
+    }
+
+    /// Update the limitations of the relay
+    pub(super) async fn set_limitation(&self, limitation: RelayLimitation) {
+        self.atomic.limitation.store(limitation);
+
+        // Max filters per multiplexed REQ
+        let max_filters: usize = match limitation.max_filters {
+            Some(max) => cmp::min(max, self.opts.multiplex_max_filters),
+            None => self.opts.multiplex_max_filters,
+        };
+
+        let (updates, evicted) = self
+            .atomic
+            .multiplexer
+            .write()
+            .await
+            .set_max_filters(max_filters);
+
+        // Send again the REQs that had too many filters
+        for update in updates.into_iter() {
+            if let Err(e) = self.send_multiplexer_update(update) {
+                tracing::error!(url = %self.url, error = %e, "Impossible to update multiplexed REQ.");
+            }
+        }
+
+        // Move the subscriptions that no longer fit in their REQ
+        for (id, filter) in evicted.into_iter() {
+            if let Err(e) = self.subscribe_long_lived(id, filter).await {
+                tracing::error!(url = %self.url, error = %e, "Impossible to subscribe.");
+            }
+        }
+
+        // The relay may accept more subscriptions
+        self.release_req_slots().await;
+    }
+
+    /// Check if the relay requires NIP-42 authentication before subscribing and it hasn't been done yet
+    ///
+    /// The subscriptions wait for the authentication at most [`WAIT_FOR_AUTHENTICATION_TIMEOUT`] per session.
+    async fn is_waiting_for_authentication(&self) -> bool {
+        self.limitation().auth_required
+            && self.state.is_auto_authentication_enabled()
+            && !self.atomic.authenticated.load(Ordering::SeqCst)
+            && !self.atomic.authentication_expired.load(Ordering::SeqCst)
+            && self.state.has_signer().await
+    }
+
+    /// Stop waiting for the NIP-42 authentication after the timeout and send the subscriptions anyway
+    fn spawn_authentication_timeout(&self) {
+        let session: u64 = self.atomic.session.load(Ordering::SeqCst);
+        let relay: InnerRelay = self.clone();
+        task::spawn(async move {
+            time::sleep(WAIT_FOR_AUTHENTICATION_TIMEOUT).await;
+
+            // Authenticated or new session
+            if relay.atomic.authenticated.load(Ordering::SeqCst)
+                || relay.atomic.session.load(Ordering::SeqCst) != session
+            {
+                return;
+            }
+
+            relay
+                .atomic
+                .authentication_expired
+                .store(true, Ordering::SeqCst);
+
+            tracing::debug!(url = %relay.url, "Authentication not completed, subscribing anyway.");
+
+            if let Err(e) = relay.resubscribe().await {
+                tracing::error!(url = %relay.url, error = %e, "Impossible to subscribe.");
+            }
+        });
+    }
+
+    /// Check if the relay accepts another REQ, according to its `max_subscriptions` limitation
+    ///
+    /// The REQs of the long-lived and of the auto-closing subscriptions are counted.
+    /// To avoid races, the caller must hold the locks until the REQ is registered.
+    fn has_req_slot(
+        &self,
+        subscriptions: &HashMap<SubscriptionId, SubscriptionData>,
+        multiplexer: &Multiplexer,
+    ) -> bool {
+        let max: usize = match self.limitation().max_subscriptions {
+            Some(max) => max,
+            None => return true,
+        };
+
+        // With the multiplexer, all the long-lived subscriptions are in its REQs
+        let long_lived: usize = if self.opts.multiplex {
+            multiplexer.len()
+        } else {
+            subscriptions.values().filter(|data| data.is_open()).count()
+        };
+
+        long_lived + self.atomic.auto_closing_reqs.load(Ordering::SeqCst) < max
+    }
+
+    /// Reserve a REQ for an auto-closing subscription
+    ///
+    /// Waits for the relay to accept another REQ, up to the `timeout`.
+    pub(super) async fn reserve_auto_closing_req(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        time::timeout(timeout, async {
+            loop {
+                // Register before checking, to not miss the release of a REQ
+                let released = self.atomic.req_slots.notified();
+
+                {
+                    let subscriptions = self.atomic.subscriptions.write().await;
+                    let multiplexer = self.atomic.multiplexer.read().await;
+
+                    if self.has_req_slot(&subscriptions, &multiplexer) {
+                        self.atomic.auto_closing_reqs.fetch_add(1, Ordering::SeqCst);
+                        return;
+                    }
+                }
+
+                tracing::debug!(url = %self.url, "Too many subscriptions, waiting.");
+
+                released.await;
+            }
+        })
+        .await
+        .ok_or(Error::Timeout)
+    }
+
+    /// Release the REQ of an auto-closing subscription
+    pub(super) async fn release_auto_closing_req(&self) {
+        self.atomic.auto_closing_reqs.fetch_sub(1, Ordering::SeqCst);
+        self.release_req_slots().await;
+    }
+
+    /// The relay may accept more REQs: wake up the auto-closing subscriptions and send the queued ones
+    async fn release_req_slots(&self) {
+        self.atomic.req_slots.notify_waiters();
+
+        if let Err(e) = self.dequeue_subscriptions().await {
+            tracing::error!(url = %self.url, error = %e, "Impossible to send queued subscriptions.");
+        }
+    }
+
+    /// Queue a long-lived subscription, to send it when the relay accepts more subscriptions
+    fn _queue_subscription(
+        &self,
+        subscriptions: &mut HashMap<SubscriptionId, SubscriptionData>,
+        id: SubscriptionId,
+        filter: Filter,
+    ) {
+        tracing::debug!(url = %self.url, id = %id, "Too many subscriptions, queueing.");
+
+        let data: &mut SubscriptionData = subscriptions.entry(id).or_default();
+        data.filter = filter;
+
+        if data.queued.is_none() {
+            data.queued = Some(self.atomic.queue_sequence.fetch_add(1, Ordering::SeqCst));
+        }
+    }
+
+    /// Check if a subscription is waiting for the relay to accept more subscriptions
+    pub(super) async fn is_queued(&self, id: &SubscriptionId) -> bool {
+        let subscriptions = self.atomic.subscriptions.read().await;
+        matches!(subscriptions.get(id), Some(data) if data.queued.is_some())
+    }
+
+    /// Send the queued subscriptions, while the relay accepts more
+    async fn dequeue_subscriptions(&self) -> Result<(), Error> {
+        loop {
+            // Get the first subscription in the queue
+            let next: Option<(SubscriptionId, Filter)> = {
+                let subscriptions = self.atomic.subscriptions.read().await;
+                subscriptions
+                    .iter()
+                    .filter_map(|(id, data)| data.queued.map(|position| (position, id, data)))
+                    .min_by_key(|(position, ..)| *position)
+                    .map(|(_, id, data)| (id.clone(), data.filter.clone()))
+            };
+
+            let (id, filter) = match next {
+                Some(next) => next,
+                None => return Ok(()),
+            };
+
+            self.subscribe_long_lived(id.clone(), filter).await?;
+
+            // Still queued: the relay doesn't accept more subscriptions
+            if self.is_queued(&id).await {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Subscribe a long-lived subscription, adapting to the limitations of the relay
+    pub(super) async fn subscribe_long_lived(
+        &self,
+        id: SubscriptionId,
+        filter: Filter,
+    ) -> Result<(), Error> {
+        // Perform health checks
+        self.health_check()?;
+
+        if !self.flags.can_read() {
+            return Err(Error::ReadDisabled);
+        }
+
+        // The relay requires authentication: subscribe after it (see `resubscribe`)
+        if self.is_waiting_for_authentication().await {
+            self.update_subscription(id, filter, false).await;
+            return Ok(());
+        }
+
+        // Coalesce with the other long-lived subscriptions
+        if self.opts.multiplex {
+            return self.subscribe_multiplexed(id, filter).await;
+        }
+
+        let mut subscriptions = self.atomic.subscriptions.write().await;
+        let multiplexer = self.atomic.multiplexer.read().await;
+
+        // The relay doesn't accept more subscriptions
+        if !self.has_req_slot(&subscriptions, &multiplexer) {
+            self._queue_subscription(&mut subscriptions, id, filter);
+            return Ok(());
+        }
+
+        // Send REQ message
+        self.send_msg(ClientMessage::Req {
+            subscription_id: Cow::Borrowed(&id),
+            filter: Cow::Borrowed(&filter),
+        })?;
+
+        // No auto-close subscription: update subscription filter
+        Self::_update_subscription(&mut subscriptions, id, filter, true);
+
+        Ok(())
+    }
+
     pub async fn subscriptions(&self) -> HashMap<SubscriptionId, Filter> {
         let subscription = self.atomic.subscriptions.read().await;
         subscription
@@ -325,11 +621,22 @@ impl InnerRelay {
         update_subscribed_at: bool,
     ) {
         let mut subscriptions = self.atomic.subscriptions.write().await;
+        Self::_update_subscription(&mut subscriptions, id, filter, update_subscribed_at);
+    }
+
+    fn _update_subscription(
+        subscriptions: &mut HashMap<SubscriptionId, SubscriptionData>,
+        id: SubscriptionId,
+        filter: Filter,
+        update_subscribed_at: bool,
+    ) {
         let data: &mut SubscriptionData = subscriptions.entry(id).or_default();
         data.filter = filter;
+        data.queued = None;
 
         if update_subscribed_at {
             data.subscribed_at = Timestamp::now();
+            data.closed = false;
         }
     }
 
@@ -401,6 +708,13 @@ impl InnerRelay {
                     RelayNotification::RelayStatus { .. } => None,
                     RelayNotification::Authenticated => None,
                     RelayNotification::AuthenticationFailed => None,
+                    RelayNotification::Outbox { event_id, status } => {
+                        Some(RelayPoolNotification::Outbox {
+                            relay_url: self.url.clone(),
+                            event_id,
+                            status,
+                        })
+                    }
                     RelayNotification::Shutdown => Some(RelayPoolNotification::Shutdown),
                 };
 
@@ -660,6 +974,13 @@ impl InnerRelay {
         ws_rx: BoxStream,
         rx_nostr: &mut MutexGuard<'_, Receiver<Vec<ClientMessageJson>>>,
     ) {
+        // New session: not authenticated yet
+        self.atomic.session.fetch_add(1, Ordering::SeqCst);
+        self.atomic.authenticated.store(false, Ordering::SeqCst);
+        self.atomic
+            .authentication_expired
+            .store(false, Ordering::SeqCst);
+
         // Request information document
         #[cfg(feature = "nip11")]
         self.request_nip11_document();
@@ -669,6 +990,19 @@ impl InnerRelay {
             if let Err(e) = self.resubscribe().await {
                 tracing::error!(url = %self.url, error = %e, "Impossible to subscribe.")
             }
+
+            // Don't wait for the authentication forever
+            self.spawn_authentication_timeout();
+        }
+
+        // Send the events queued in the outbox
+        if self.outbox().is_some() && self.flags.can_write() {
+            let relay: InnerRelay = self.clone();
+            task::spawn(async move {
+                if let Err(e) = relay.replay_outbox().await {
+                    tracing::error!(url = %relay.url, error = %e, "Impossible to replay the outbox.");
+                }
+            });
         }
 
         let ping: PingTracker = PingTracker::default();
@@ -850,7 +1184,12 @@ impl InnerRelay {
                 IngesterCommand::Authenticate { challenge } => {
                     match self.auth(challenge).await {
                         Ok(..) => {
+                            self.atomic.authenticated.store(true, Ordering::SeqCst);
+
                             self.send_notification(RelayNotification::Authenticated, false);
+                            self.notify_monitor(|| MonitorNotification::Authenticated {
+                                relay_url: self.url.clone(),
+                            });
 
                             tracing::info!(url = %self.url, "Authenticated to relay.");
 
@@ -865,6 +1204,10 @@ impl InnerRelay {
                         }
                         Err(e) => {
                             self.send_notification(RelayNotification::AuthenticationFailed, false);
+                            self.notify_monitor(|| MonitorNotification::AuthenticationFailed {
+                                relay_url: self.url.clone(),
+                                reason: e.to_string(),
+                            });
 
                             tracing::error!(
                                 url = %self.url,
@@ -902,104 +1245,149 @@ impl InnerRelay {
     ) {
         match self.handle_raw_relay_message(msg).await {
             Ok(Some(message)) => {
-                match &message {
-                    RelayMessage::Notice(message) => {
-                        tracing::warn!(url = %self.url, msg = %message, "Received NOTICE.")
-                    }
-                    RelayMessage::Ok {
-                        event_id,
-                        status,
-                        message,
-                    } => {
-                        tracing::debug!(
-                            url = %self.url,
-                            id = %event_id,
-                            status = %status,
-                            msg = %message,
-                            "Received OK."
-                        );
-                    }
-                    RelayMessage::EndOfStoredEvents(id) => {
-                        tracing::debug!(
-                            url = %self.url,
-                            id = %id,
-                            "Received EOSE."
-                        );
-                    }
-                    RelayMessage::Closed {
-                        subscription_id,
-                        message,
-                    } => {
-                        tracing::debug!(
-                            url = %self.url,
-                            id = %subscription_id,
-                            msg = %message,
-                            "Subscription closed by relay."
-                        );
-
-                        // Check machine-readable prefix
-                        let res: HandleClosedMsg = match MachineReadablePrefix::parse(message) {
-                            Some(MachineReadablePrefix::Duplicate) => HandleClosedMsg::Remove,
-                            Some(MachineReadablePrefix::Pow) => HandleClosedMsg::Remove,
-                            Some(MachineReadablePrefix::Blocked) => HandleClosedMsg::Remove,
-                            Some(MachineReadablePrefix::RateLimited) => {
-                                // TODO: add something like MarkAsRateLimited?
-                                // TODO: And retry after some time to re-subscribe
-                                HandleClosedMsg::MarkAsClosed
-                            }
-                            Some(MachineReadablePrefix::Invalid) => HandleClosedMsg::Remove,
-                            Some(MachineReadablePrefix::Error) => HandleClosedMsg::Remove,
-                            Some(MachineReadablePrefix::Unsupported) => HandleClosedMsg::Remove,
-                            Some(MachineReadablePrefix::AuthRequired) => {
-                                // Authentication is handled in other parts of code,
-                                // so here just mark as closed.
-                                HandleClosedMsg::MarkAsClosed
-                            }
-                            Some(MachineReadablePrefix::Restricted) => HandleClosedMsg::Remove,
-                            None => {
-                                // Doesn't mach any prefix,
-                                // meaning that it probably closed without errors,
-                                // so remove it.
-                                HandleClosedMsg::Remove
-                            }
-                        };
+                // Route the messages of the multiplexed REQs to each subscription
+                for message in self.demultiplex(message).await.into_iter() {
+                    match &message {
+                        RelayMessage::Notice(message) => {
+                            tracing::warn!(url = %self.url, msg = %message, "Received NOTICE.")
+                        }
+                        RelayMessage::Ok {
+                            event_id,
+                            status,
+                            message,
+                        } => {
+                            tracing::debug!(
+                                url = %self.url,
+                                id = %event_id,
+                                status = %status,
+                                msg = %message,
+                                "Received OK."
+                            );
 
-                        match res {
-                            HandleClosedMsg::MarkAsClosed => {
-                                self.subscription_closed(subscription_id).await;
-                            }
-                            HandleClosedMsg::Remove => {
-                                tracing::debug!(
-                                    url = %self.url,
-                                    id = %subscription_id,
-                                    "Removing subscription."
-                                );
+                            if !status {
+                                let prefix: Option<MachineReadablePrefix> =
+                                    MachineReadablePrefix::parse(message);
+
+                                if let Some(MachineReadablePrefix::RateLimited) = prefix {
+                                    self.notify_monitor(|| MonitorNotification::RateLimited {
+                                        relay_url: self.url.clone(),
+                                        message: message.to_string(),
+                                    });
+                                }
 
-                                let mut subscriptions = self.atomic.subscriptions.write().await;
-                                subscriptions.remove(subscription_id);
+                                self.notify_monitor(|| MonitorNotification::EventRejected {
+                                    relay_url: self.url.clone(),
+                                    event_id: *event_id,
+                                    prefix,
+                                    message: message.to_string(),
+                                });
                             }
                         }
-                    }
-                    RelayMessage::Auth { challenge } => {
-                        tracing::debug!(
-                            url = %self.url,
-                            challenge = %challenge,
-                            "Received auth challenge."
-                        );
-
-                        // Check if NIP42 auto authentication is enabled
-                        if self.state.is_auto_authentication_enabled() {
-                            // Forward action to ingester
-                            let _ = ingester_tx.send(IngesterCommand::Authenticate {
-                                challenge: challenge.to_string(),
+                        RelayMessage::EndOfStoredEvents(id) => {
+                            tracing::debug!(
+                                url = %self.url,
+                                id = %id,
+                                "Received EOSE."
+                            );
+
+                            self.notify_monitor(|| MonitorNotification::EndOfStoredEvents {
+                                relay_url: self.url.clone(),
+                                subscription_id: id.clone().into_owned(),
                             });
                         }
+                        RelayMessage::Closed {
+                            subscription_id,
+                            message,
+                        } => {
+                            tracing::debug!(
+                                url = %self.url,
+                                id = %subscription_id,
+                                msg = %message,
+                                "Subscription closed by relay."
+                            );
+
+                            // Check machine-readable prefix
+                            let prefix: Option<MachineReadablePrefix> =
+                                MachineReadablePrefix::parse(message);
+
+                            self.notify_monitor(|| MonitorNotification::SubscriptionClosed {
+                                relay_url: self.url.clone(),
+                                subscription_id: subscription_id.clone().into_owned(),
+                                prefix,
+                                message: message.to_string(),
+                            });
+
+                            let res: HandleClosedMsg = match prefix {
+                                Some(MachineReadablePrefix::Duplicate) => HandleClosedMsg::Remove,
+                                Some(MachineReadablePrefix::Pow) => HandleClosedMsg::Remove,
+                                Some(MachineReadablePrefix::Blocked) => HandleClosedMsg::Remove,
+                                Some(MachineReadablePrefix::RateLimited) => {
+                                    self.notify_monitor(|| MonitorNotification::RateLimited {
+                                        relay_url: self.url.clone(),
+                                        message: message.to_string(),
+                                    });
+
+                                    // TODO: add something like MarkAsRateLimited?
+                                    // TODO: And retry after some time to re-subscribe
+                                    HandleClosedMsg::MarkAsClosed
+                                }
+                                Some(MachineReadablePrefix::Invalid) => HandleClosedMsg::Remove,
+                                Some(MachineReadablePrefix::Error) => HandleClosedMsg::Remove,
+                                Some(MachineReadablePrefix::Unsupported) => HandleClosedMsg::Remove,
+                                Some(MachineReadablePrefix::AuthRequired) => {
+                                    // Authentication is handled in other parts of code,
+                                    // so here just mark as closed.
+                                    HandleClosedMsg::MarkAsClosed
+                                }
+                                Some(MachineReadablePrefix::Restricted) => HandleClosedMsg::Remove,
+                                None => {
+                                    // Doesn't mach any prefix,
+                                    // meaning that it probably closed without errors,
+                                    // so remove it.
+                                    HandleClosedMsg::Remove
+                                }
+                            };
+
+                            match res {
+                                HandleClosedMsg::MarkAsClosed => {
+                                    self.subscription_closed(subscription_id).await;
+                                }
+                                HandleClosedMsg::Remove => {
+                                    tracing::debug!(
+                                        url = %self.url,
+                                        id = %subscription_id,
+                                        "Removing subscription."
+                                    );
+
+                                    let mut subscriptions = self.atomic.subscriptions.write().await;
+                                    subscriptions.remove(subscription_id);
+                                }
+                            }
+
+                            // A REQ has been closed: the relay may accept more subscriptions
+                            self.release_req_slots().await;
+                        }
+                        RelayMessage::Auth { challenge } => {
+                            tracing::debug!(
+                                url = %self.url,
+                                challenge = %challenge,
+                                "Received auth challenge."
+                            );
+
+                            // Check if NIP42 auto authentication is enabled
+                            if self.state.is_auto_authentication_enabled() {
+                                // Forward action to ingester
+                                let _ = ingester_tx.send(IngesterCommand::Authenticate {
+                                    challenge: challenge.to_string(),
+                                });
+                            }
+                        }
+                        _ => (),
                     }
-                    _ => (),
-                }
 
-                // Send notification
-                self.send_notification(RelayNotification::Message { message }, true);
+                    // Send notification
+                    self.send_notification(RelayNotification::Message { message }, true);
+                }
             }
             Ok(None) | Err(Error::MessageHandle(MessageHandleError::EmptyMsg)) => (),
             Err(e) => tracing::error!(
@@ -1011,6 +1399,83 @@ impl InnerRelay {
         }
     }
 
+    /// Get the subscriptions matching an event received for a multiplexed REQ
+    ///
+    /// Returns `None` if the REQ isn't multiplexed.
+    async fn route_event(
+        &self,
+        subscription_id: &SubscriptionId,
+        event: &Event,
+    ) -> Option<Vec<SubscriptionId>> {
+        if !self.opts.multiplex {
+            return None;
+        }
+
+        let multiplexer = self.atomic.multiplexer.read().await;
+        multiplexer.route_event(subscription_id, event)
+    }
+
+    /// Route the messages of the multiplexed REQs to the subscriptions in them
+    ///
+    /// Other messages are returned as they are.
+    async fn demultiplex(&self, message: RelayMessage<'static>) -> Vec<RelayMessage<'static>> {
+        if !self.opts.multiplex {
+            return vec![message];
+        }
+
+        match message {
+            RelayMessage::Event {
+                subscription_id,
+                event,
+            } => {
+                let multiplexer = self.atomic.multiplexer.read().await;
+                match multiplexer.route_event(&subscription_id, &event) {
+                    Some(ids) => ids
+                        .into_iter()
+                        .map(|id| RelayMessage::Event {
+                            subscription_id: Cow::Owned(id),
+                            event: event.clone(),
+                        })
+                        .collect(),
+                    None => vec![RelayMessage::Event {
+                        subscription_id,
+                        event,
+                    }],
+                }
+            }
+            RelayMessage::EndOfStoredEvents(subscription_id) => {
+                let mut multiplexer = self.atomic.multiplexer.write().await;
+                match multiplexer.route_eose(&subscription_id) {
+                    Some(ids) => ids
+                        .into_iter()
+                        .map(|id| RelayMessage::EndOfStoredEvents(Cow::Owned(id)))
+                        .collect(),
+                    None => vec![RelayMessage::EndOfStoredEvents(subscription_id)],
+                }
+            }
+            RelayMessage::Closed {
+                subscription_id,
+                message,
+            } => {
+                let mut multiplexer = self.atomic.multiplexer.write().await;
+                match multiplexer.route_closed(&subscription_id) {
+                    Some(ids) => ids
+                        .into_iter()
+                        .map(|id| RelayMessage::Closed {
+                            subscription_id: Cow::Owned(id),
+                            message: message.clone(),
+                        })
+                        .collect(),
+                    None => vec![RelayMessage::Closed {
+                        subscription_id,
+                        message,
+                    }],
+                }
+            }
+            message => vec![message],
+        }
+    }
+
     async fn handle_raw_relay_message(
         &self,
         msg: &str,
@@ -1074,14 +1539,29 @@ impl InnerRelay {
             return Err(Error::EventExpired);
         }
 
-        // Check event admission policy
-        if let Some(policy) = &self.state.admit_policy {
-            if let AdmitStatus::Rejected { .. } = policy
-                .admit_event(&self.url, &subscription_id, &event)
-                .await?
-            {
-                return Ok(None);
+        // Multiplexed REQ: get the matching subscriptions.
+        // Events that don't match any subscription have been received only because of the filters coalescing.
+        let ids: Vec<SubscriptionId> = match self.route_event(&subscription_id, &event).await {
+            Some(ids) => ids,
+            None => vec![subscription_id.clone()],
+        };
+
+        // Check event admission policy, for every subscription
+        let mut admitted: Vec<SubscriptionId> = Vec::with_capacity(ids.len());
+        for id in ids.into_iter() {
+            if let Some(policy) = &self.state.admit_policy {
+                if let AdmitStatus::Rejected { .. } =
+                    policy.admit_event(&self.url, &id, &event).await?
+                {
+                    continue;
+                }
             }
+
+            admitted.push(id);
+        }
+
+        if admitted.is_empty() {
+            return Ok(None);
         }
 
         // Check if event status
@@ -1123,14 +1603,16 @@ impl InnerRelay {
             // Save into the database
             self.state.database().save_event(&event).await?;
 
-            // Send notification
-            self.send_notification(
-                RelayNotification::Event {
-                    subscription_id: subscription_id.clone(),
-                    event: Box::new(event.clone()),
-                },
-                true,
-            );
+            // Send a notification for every admitted subscription
+            for id in admitted.into_iter() {
+                self.send_notification(
+                    RelayNotification::Event {
+                        subscription_id: id,
+                        event: Box::new(event.clone()),
+                    },
+                    true,
+                );
+            }
         }
 
         Ok(Some(RelayMessage::Event {
@@ -1180,7 +1662,64 @@ impl InnerRelay {
         self.batch_msg(vec![msg])
     }
 
-    pub fn batch_msg(&self, msgs: Vec<ClientMessage<'_>>) -> Result<(), Error> {
+    /// Adapt the messages to the limitations of the relay and send them
+    fn send_client_msgs(&self, msgs: Vec<ClientMessage<'_>>) -> Result<(), Error> {
+        let limitation: RelayLimitation = self.limitation();
+
+        // Check the number of filters of the REQs
+        if let Some(max_size) = limitation.max_filters {
+            for msg in msgs.iter() {
+                if let ClientMessage::ReqMultiFilter { filters, .. } = msg {
+                    if filters.len() > max_size {
+                        return Err(Error::TooManyFilters {
+                            size: filters.len(),
+                            max_size,
+                        });
+                    }
+                }
+            }
+        }
+
+        let msgs: Vec<ClientMessageJson> = msgs
+            .into_iter()
+            .map(|mut msg| {
+                // Clamp the limit of the filters
+                if let Some(max) = limitation.max_limit {
+                    match &mut msg {
+                        ClientMessage::Req { filter, .. } => {
+                            if matches!(filter.limit, Some(limit) if limit > max) {
+                                filter.to_mut().limit = Some(max);
+                            }
+                        }
+                        ClientMessage::ReqMultiFilter { filters, .. } => {
+                            for filter in filters.iter_mut() {
+                                if matches!(filter.limit, Some(limit) if limit > max) {
+                                    filter.limit = Some(max);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                msg.as_json()
+            })
+            .collect();
+
+        // Check the size of the messages
+        if let Some(max_size) = limitation.max_message_length {
+            if let Some(msg) = msgs.iter().find(|msg| msg.len() > max_size) {
+                return Err(Error::MessageTooLarge {
+                    size: msg.len(),
+                    max_size,
+                });
+            }
+        }
+
+        self.atomic.channels.send_client_msgs(msgs)
+    }
+
+    fn check_msgs(&self, msgs: &[ClientMessage<'_>]) -> Result<(), Error> {
         // Perform health checks
         self.health_check()?;
 
@@ -1199,8 +1738,112 @@ impl InnerRelay {
             return Err(Error::ReadDisabled);
         }
 
+        Ok(())
+    }
+
+    pub fn batch_msg(&self, msgs: Vec<ClientMessage<'_>>) -> Result<(), Error> {
+        self.check_msgs(&msgs)?;
+
+        // Collect the IDs of the subscriptions to open, only if the monitor is enabled
+        let opened: Vec<SubscriptionId> = match &self.state.monitor {
+            Some(..) => msgs
+                .iter()
+                .filter_map(|msg| match msg {
+                    ClientMessage::Req {
+                        subscription_id, ..
+                    }
+                    | ClientMessage::ReqMultiFilter {
+                        subscription_id, ..
+                    } => Some(subscription_id.clone().into_owned()),
+                    _ => None,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
         // Send messages
-        self.atomic.channels.send_client_msgs(msgs)
+        self.send_client_msgs(msgs)?;
+
+        for subscription_id in opened.into_iter() {
+            self.notify_monitor(|| MonitorNotification::SubscriptionOpened {
+                relay_url: self.url.clone(),
+                subscription_id,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Send a multiplexed REQ
+    ///
+    /// The monitor is notified only about the `opened` subscription, if any, and not about the REQ itself.
+    fn send_multiplexed_req(
+        &self,
+        subscription_id: SubscriptionId,
+        filters: Vec<Filter>,
+        opened: Option<&SubscriptionId>,
+    ) -> Result<(), Error> {
+        let msgs: Vec<ClientMessage> = vec![ClientMessage::ReqMultiFilter {
+            subscription_id: Cow::Owned(subscription_id),
+            filters,
+        }];
+
+        self.check_msgs(&msgs)?;
+        self.send_client_msgs(msgs)?;
+
+        if let Some(id) = opened {
+            self.notify_monitor(|| MonitorNotification::SubscriptionOpened {
+                relay_url: self.url.clone(),
+                subscription_id: id.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn send_multiplexer_update(&self, update: MultiplexerUpdate) -> Result<(), Error> {
+        match update {
+            MultiplexerUpdate::Req {
+                subscription_id,
+                filters,
+            } => self.send_multiplexed_req(subscription_id, filters, None),
+            MultiplexerUpdate::Close(subscription_id) => {
+                self.send_msg(ClientMessage::Close(Cow::Owned(subscription_id)))
+            }
+            MultiplexerUpdate::Unchanged => Ok(()),
+        }
+    }
+
+    /// Subscribe through the multiplexer
+    pub(super) async fn subscribe_multiplexed(
+        &self,
+        id: SubscriptionId,
+        filter: Filter,
+    ) -> Result<(), Error> {
+        let mut subscriptions = self.atomic.subscriptions.write().await;
+        let mut multiplexer = self.atomic.multiplexer.write().await;
+
+        // A new REQ is composed only if the relay accepts it
+        let new_req: bool = self.has_req_slot(&subscriptions, &multiplexer);
+
+        let req_id: SubscriptionId = match multiplexer.insert(id.clone(), filter.clone(), new_req) {
+            Some(req_id) => req_id,
+            None => {
+                self._queue_subscription(&mut subscriptions, id, filter);
+                return Ok(());
+            }
+        };
+        let filters: Vec<Filter> = multiplexer.request(&req_id).unwrap_or_default();
+
+        if let Err(e) = self.send_multiplexed_req(req_id, filters, Some(&id)) {
+            multiplexer.remove(&id);
+            return Err(e);
+        }
+
+        // Update subscription filter
+        Self::_update_subscription(&mut subscriptions, id, filter, true);
+
+        Ok(())
     }
 
     fn send_neg_msg(&self, id: &SubscriptionId, message: &str) -> Result<(), Error> {
@@ -1245,6 +1888,122 @@ impl InnerRelay {
         }
     }
 
+    /// Get the outbox store, if the outbox is enabled
+    pub(super) fn outbox(&self) -> Option<&Arc<dyn NostrDatabaseOutbox>> {
+        if self.opts.outbox {
+            self.state.outbox.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Keep the event in the outbox, to send it when the connection is established again
+    pub(super) async fn queue_outbox_event(
+        &self,
+        outbox: &Arc<dyn NostrDatabaseOutbox>,
+        event: &Event,
+    ) -> Result<(), Error> {
+        outbox.save_outbox_event(&self.url, event).await?;
+
+        tracing::debug!(url = %self.url, id = %event.id, "Event queued in the outbox.");
+
+        self.send_notification(
+            RelayNotification::Outbox {
+                event_id: event.id,
+                status: OutboxStatus::Pending,
+            },
+            true,
+        );
+
+        Ok(())
+    }
+
+    /// Send the events queued in the outbox
+    ///
+    /// The events are removed from the outbox once the relay replies with an `OK`.
+    /// If the connection is lost, the remaining events are kept for the next connection.
+    async fn replay_outbox(&self) -> Result<(), Error> {
+        let outbox: &Arc<dyn NostrDatabaseOutbox> = match self.outbox() {
+            Some(outbox) => outbox,
+            None => return Ok(()),
+        };
+
+        let events: Vec<Event> = outbox.outbox_events(&self.url).await?;
+
+        if !events.is_empty() {
+            tracing::debug!(url = %self.url, "Sending {} events from the outbox.", events.len());
+        }
+
+        for event in events.into_iter() {
+            let status: OutboxStatus = match self.send_outbox_event(&event).await {
+                Ok((true, ..)) => OutboxStatus::Sent,
+                Ok((false, message)) => match MachineReadablePrefix::parse(&message) {
+                    // Can't authenticate: keep it, will be sent again on the next connection
+                    Some(MachineReadablePrefix::AuthRequired) => continue,
+                    _ => OutboxStatus::Rejected { message },
+                },
+                Err(e) => {
+                    tracing::warn!(url = %self.url, error = %e, "Outbox replay interrupted.");
+                    break;
+                }
+            };
+
+            outbox.remove_outbox_event(&self.url, &event.id).await?;
+
+            self.send_notification(
+                RelayNotification::Outbox {
+                    event_id: event.id,
+                    status,
+                },
+                true,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Send an event of the outbox and wait for the `OK`
+    ///
+    /// If the relay requires authentication, the event is sent again on the same connection
+    /// after a successful authentication.
+    async fn send_outbox_event(&self, event: &Event) -> Result<(bool, String), Error> {
+        // Subscribe to notifications
+        let mut notifications = self.internal_notification_sender.subscribe();
+
+        // Send the EVENT message
+        self.send_msg(ClientMessage::Event(Cow::Borrowed(event)))?;
+
+        let (status, message) = self
+            .wait_for_ok(&mut notifications, &event.id, WAIT_FOR_OK_TIMEOUT)
+            .await?;
+
+        if !status {
+            if let Some(MachineReadablePrefix::AuthRequired) =
+                MachineReadablePrefix::parse(&message)
+            {
+                if self.state.is_auto_authentication_enabled() && self.state.has_signer().await {
+                    // Wait for the authentication, if not already completed
+                    if !self.atomic.authenticated.load(Ordering::SeqCst) {
+                        self.wait_for_authentication(
+                            &mut notifications,
+                            WAIT_FOR_AUTHENTICATION_TIMEOUT,
+                        )
+                        .await?;
+                    }
+
+                    // Send the event again
+                    self.send_msg(ClientMessage::Event(Cow::Borrowed(event)))?;
+
+                    return self
+                        .wait_for_ok(&mut notifications, &event.id, WAIT_FOR_OK_TIMEOUT)
+                        .await;
+                }
+            }
+        }
+
+        Ok((status, message))
+    }
+
     pub(super) async fn wait_for_ok(
         &self,
         notifications: &mut broadcast::Receiver<RelayNotification>,
@@ -1283,21 +2042,108 @@ impl InnerRelay {
         .ok_or(Error::Timeout)?
     }
 
+    pub(super) async fn wait_for_authentication(
+        &self,
+        notifications: &mut broadcast::Receiver<RelayNotification>,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        time::timeout(Some(timeout), async {
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayNotification::Authenticated => {
+                        return Ok(());
+                    }
+                    RelayNotification::AuthenticationFailed => {
+                        return Err(Error::AuthenticationFailed);
+                    }
+                    RelayNotification::RelayStatus { status } => {
+                        if status.is_disconnected() {
+                            return Err(Error::NotConnected);
+                        }
+                    }
+                    RelayNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+
+            Err(Error::PrematureExit)
+        })
+        .await
+        .ok_or(Error::Timeout)?
+    }
+
     pub async fn resubscribe(&self) -> Result<(), Error> {
+        // The relay requires authentication: wait for it
+        if self.is_waiting_for_authentication().await {
+            tracing::debug!(url = %self.url, "Waiting for authentication before re-subscribing.");
+            return Ok(());
+        }
+
         // TODO: avoid subscriptions clone
         let subscriptions = self.subscriptions().await;
+
+        // Multiplexed REQs to send again
+        let mut requests: HashSet<SubscriptionId> = HashSet::new();
+
         for (id, filter) in subscriptions.into_iter() {
             if !filter.is_empty() && self.should_resubscribe(&id).await {
+                // Sent when the relay accepts more subscriptions
+                if self.is_queued(&id).await {
+                    continue;
+                }
+
+                let mut subscriptions = self.atomic.subscriptions.write().await;
+                let mut multiplexer = self.atomic.multiplexer.write().await;
+
+                if self.opts.multiplex {
+                    let req_id: Option<SubscriptionId> = match multiplexer.route(&id) {
+                        Some(req_id) => Some(req_id.clone()),
+                        None => {
+                            let new_req: bool = self.has_req_slot(&subscriptions, &multiplexer);
+                            multiplexer.insert(id.clone(), filter.clone(), new_req)
+                        }
+                    };
+
+                    match req_id {
+                        Some(req_id) => {
+                            requests.insert(req_id);
+                            Self::_update_subscription(&mut subscriptions, id, filter, true);
+                        }
+                        None => self._queue_subscription(&mut subscriptions, id, filter),
+                    }
+
+                    continue;
+                }
+
+                // Not opened before (i.e., waiting for authentication): check the relay capacity
+                let open: bool = matches!(subscriptions.get(&id), Some(data) if data.is_open());
+                if !open && !self.has_req_slot(&subscriptions, &multiplexer) {
+                    self._queue_subscription(&mut subscriptions, id, filter);
+                    continue;
+                }
+
                 self.send_msg(ClientMessage::Req {
-                    subscription_id: Cow::Owned(id),
-                    filter: Cow::Owned(filter),
+                    subscription_id: Cow::Borrowed(&id),
+                    filter: Cow::Borrowed(&filter),
                 })?;
+
+                Self::_update_subscription(&mut subscriptions, id, filter, true);
             } else {
                 tracing::debug!("Skip re-subscription of '{id}'");
             }
         }
 
-        Ok(())
+        if !requests.is_empty() {
+            let mut multiplexer = self.atomic.multiplexer.write().await;
+            for req_id in requests.into_iter() {
+                if let Some(filters) = multiplexer.request(&req_id) {
+                    self.send_multiplexed_req(req_id, filters, None)?;
+                }
+            }
+        }
+
+        // Send the queued subscriptions, if the relay accepts them
+        self.dequeue_subscriptions().await
     }
 
     pub(super) fn spawn_auto_closing_handler(
@@ -1339,10 +2185,13 @@ impl InnerRelay {
             // Close subscription
             if to_close {
                 tracing::debug!(id = %id, "Auto-closing subscription.");
-                relay.send_msg(ClientMessage::Close(Cow::Owned(id)))?;
+                if let Err(e) = relay.send_msg(ClientMessage::Close(Cow::Owned(id))) {
+                    tracing::error!(url = %relay.url, error = %e, "Impossible to close subscription.");
+                }
             }
 
-            Ok::<(), Error>(())
+            // The REQ is closed
+            relay.release_auto_closing_req().await;
         });
     }
 
@@ -1579,20 +2428,62 @@ impl InnerRelay {
 
     pub async fn unsubscribe(&self, id: &SubscriptionId) -> Result<(), Error> {
         let mut subscriptions = self.atomic.subscriptions.write().await;
-        self._unsubscribe(&mut subscriptions, id)
+        let mut multiplexer = self.atomic.multiplexer.write().await;
+
+        match multiplexer.remove(id) {
+            Some(update) => {
+                subscriptions.remove(id);
+                self.send_multiplexer_update(update)?;
+            }
+            None => match subscriptions.get(id) {
+                // Never sent to the relay: just remove it from the queue
+                Some(data) if data.queued.is_some() => {
+                    subscriptions.remove(id);
+                }
+                _ => self._unsubscribe(&mut subscriptions, id)?,
+            },
+        }
+
+        drop(multiplexer);
+        drop(subscriptions);
+
+        // The relay may accept more subscriptions
+        self.release_req_slots().await;
+
+        Ok(())
     }
 
     pub async fn unsubscribe_all(&self) -> Result<(), Error> {
         let mut subscriptions = self.atomic.subscriptions.write().await;
+        let mut multiplexer = self.atomic.multiplexer.write().await;
 
         // All IDs
         let ids: Vec<SubscriptionId> = subscriptions.keys().cloned().collect();
 
         // Unsubscribe
         for id in ids.into_iter() {
-            self._unsubscribe(&mut subscriptions, &id)?;
+            let queued: bool =
+                matches!(subscriptions.get(&id), Some(data) if data.queued.is_some());
+
+            match multiplexer.route(&id) {
+                Some(..) => {
+                    subscriptions.remove(&id);
+                }
+                None if queued => {
+                    subscriptions.remove(&id);
+                }
+                None => self._unsubscribe(&mut subscriptions, &id)?,
+            }
         }
 
+        // Close the multiplexed REQs
+        for req_id in multiplexer.clear().into_iter() {
+            self.send_msg(ClientMessage::Close(Cow::Owned(req_id)))?;
+        }
+
+        // The relay may accept more auto-closing subscriptions
+        self.atomic.req_slots.notify_waiters();
+
         Ok(())
     }
 
@@ -0,0 +1,135 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay limitation
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+#[cfg(feature = "nip11")]
+use nostr::nips::nip11::Limitation;
+
+/// Limitations advertised by the relay in its NIP-11 information document
+///
+/// Populated only if the `nip11` feature is enabled: otherwise, the relay is considered without limitations.
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/11.md>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RelayLimitation {
+    /// Max size of the messages sent to the relay, in bytes
+    pub max_message_length: Option<usize>,
+    /// Max number of REQs open at the same time
+    pub max_subscriptions: Option<usize>,
+    /// Max number of filters per REQ
+    pub max_filters: Option<usize>,
+    /// Max `limit` of the filters
+    pub max_limit: Option<usize>,
+    /// NIP-42 authentication required before any other action
+    pub auth_required: bool,
+}
+
+#[cfg(feature = "nip11")]
+impl From<&Limitation> for RelayLimitation {
+    fn from(limitation: &Limitation) -> Self {
+        // Zero or negative values are treated as not set
+        let positive = |value: Option<i32>| -> Option<usize> {
+            value.and_then(|value| usize::try_from(value).ok().filter(|value| *value > 0))
+        };
+
+        Self {
+            max_message_length: positive(limitation.max_message_length),
+            max_subscriptions: positive(limitation.max_subscriptions),
+            max_filters: positive(limitation.max_filters),
+            max_limit: positive(limitation.max_limit),
+            auth_required: limitation.auth_required.unwrap_or_default(),
+        }
+    }
+}
+
+/// Atomic relay limitation
+#[derive(Debug, Default)]
+pub(super) struct AtomicRelayLimitation {
+    /// Zero if not set
+    max_message_length: AtomicUsize,
+    /// Zero if not set
+    max_subscriptions: AtomicUsize,
+    /// Zero if not set
+    max_filters: AtomicUsize,
+    /// Zero if not set
+    max_limit: AtomicUsize,
+    auth_required: AtomicBool,
+}
+
+impl AtomicRelayLimitation {
+    pub(super) fn load(&self) -> RelayLimitation {
+        let get = |value: &AtomicUsize| -> Option<usize> {
+            match value.load(Ordering::SeqCst) {
+                0 => None,
+                value => Some(value),
+            }
+        };
+
+        RelayLimitation {
+            max_message_length: get(&self.max_message_length),
+            max_subscriptions: get(&self.max_subscriptions),
+            max_filters: get(&self.max_filters),
+            max_limit: get(&self.max_limit),
+            auth_required: self.auth_required.load(Ordering::SeqCst),
+        }
+    }
+
+    pub(super) fn store(&self, limitation: RelayLimitation) {
+        let set = |atomic: &AtomicUsize, value: Option<usize>| {
+            atomic.store(value.unwrap_or_default(), Ordering::SeqCst);
+        };
+
+        set(&self.max_message_length, limitation.max_message_length);
+        set(&self.max_subscriptions, limitation.max_subscriptions);
+        set(&self.max_filters, limitation.max_filters);
+        set(&self.max_limit, limitation.max_limit);
+        self.auth_required
+            .store(limitation.auth_required, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_relay_limitation() {
+        let atomic = AtomicRelayLimitation::default();
+        assert_eq!(atomic.load(), RelayLimitation::default());
+
+        let limitation = RelayLimitation {
+            max_message_length: Some(16_384),
+            max_subscriptions: Some(20),
+            max_filters: None,
+            max_limit: Some(500),
+            auth_required: true,
+        };
+        atomic.store(limitation);
+        assert_eq!(atomic.load(), limitation);
+    }
+
+    #[cfg(feature = "nip11")]
+    #[test]
+    fn test_relay_limitation_from_nip11() {
+        let limitation = Limitation {
+            max_subscriptions: Some(10),
+            max_filters: Some(0),
+            max_limit: Some(-1),
+            auth_required: Some(true),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            RelayLimitation::from(&limitation),
+            RelayLimitation {
+                max_subscriptions: Some(10),
+                auth_required: true,
+                ..Default::default()
+            }
+        );
+    }
+}
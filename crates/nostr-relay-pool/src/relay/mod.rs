@@ -7,6 +7,7 @@
 use std::borrow::Cow;
 use std::cmp;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_utility::time;
@@ -20,21 +21,28 @@ pub mod constants;
 mod error;
 pub mod flags;
 mod inner;
+mod limitation;
 pub mod limits;
+mod multiplexer;
 pub mod options;
+mod pagination;
 mod ping;
 pub mod stats;
 mod status;
 
-use self::constants::{WAIT_FOR_AUTHENTICATION_TIMEOUT, WAIT_FOR_OK_TIMEOUT};
+use self::constants::{DEFAULT_PAGE_SIZE, WAIT_FOR_AUTHENTICATION_TIMEOUT, WAIT_FOR_OK_TIMEOUT};
 pub use self::error::Error;
 pub use self::flags::{AtomicRelayServiceFlags, FlagCheck, RelayServiceFlags};
 use self::inner::InnerRelay;
+pub use self::limitation::RelayLimitation;
 pub use self::limits::RelayLimits;
 pub use self::options::{
-    RelayOptions, ReqExitPolicy, SubscribeAutoCloseOptions, SubscribeOptions, SyncDirection,
-    SyncOptions, SyncProgress,
+    PaginationOptions, RelayOptions, ReqExitPolicy, SubscribeAutoCloseOptions, SubscribeOptions,
+    SyncDirection, SyncOptions, SyncProgress,
 };
+pub(crate) use self::pagination::Paginator;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::stats::LatencyHistogram;
 pub use self::stats::RelayConnectionStats;
 pub use self::status::RelayStatus;
 use crate::policy::AdmitStatus;
@@ -60,6 +68,22 @@ enum SubscriptionActivity {
     Closed(SubscriptionAutoClosedReason),
 }
 
+/// Status of an event in the offline outbox
+///
+/// See [`RelayOptions::outbox`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OutboxStatus {
+    /// Queued, waiting for the relay to be connected
+    Pending,
+    /// Sent and accepted by the relay
+    Sent,
+    /// Sent but rejected by the relay
+    Rejected {
+        /// Message of the relay
+        message: String,
+    },
+}
+
 /// Relay Notification
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RelayNotification {
@@ -86,6 +110,13 @@ pub enum RelayNotification {
     Authenticated,
     /// Authentication failed
     AuthenticationFailed,
+    /// Status of an event in the offline outbox changed
+    Outbox {
+        /// Event ID
+        event_id: EventId,
+        /// Outbox status
+        status: OutboxStatus,
+    },
     /// Shutdown
     Shutdown,
 }
@@ -212,6 +243,15 @@ impl Relay {
         &self.inner.opts
     }
 
+    /// Get the [`RelayLimitation`] advertised by the relay
+    ///
+    /// Updated every time the NIP-11 document is fetched. Empty if the document isn't available
+    /// or if the `nip11` feature is disabled.
+    #[inline]
+    pub fn limitation(&self) -> RelayLimitation {
+        self.inner.limitation()
+    }
+
     /// Get [`RelayConnectionStats`]
     #[inline]
     pub fn stats(&self) -> &RelayConnectionStats {
@@ -391,7 +431,42 @@ impl Relay {
     }
 
     /// Send event and wait for `OK` relay msg
+    ///
+    /// If the outbox is enabled (see [`RelayOptions::outbox`]) and the relay is not connected,
+    /// the event is queued and [`Error::EventQueued`] is returned.
     pub async fn send_event(&self, event: &Event) -> Result<EventId, Error> {
+        let outbox: &Arc<dyn NostrDatabaseOutbox> = match self.inner.outbox() {
+            Some(outbox) => outbox,
+            None => return self.try_send_event(event).await,
+        };
+
+        let status: RelayStatus = self.status();
+
+        if status.is_banned() {
+            return Err(Error::Banned);
+        }
+
+        if !self.inner.flags.can_write() {
+            return Err(Error::WriteDisabled);
+        }
+
+        // Not connected, queue the event
+        if !status.is_connected() {
+            self.inner.queue_outbox_event(outbox, event).await?;
+            return Err(Error::EventQueued);
+        }
+
+        match self.try_send_event(event).await {
+            // Connection lost while waiting for the `OK`
+            Err(Error::NotConnected | Error::Timeout) if !self.is_connected() => {
+                self.inner.queue_outbox_event(outbox, event).await?;
+                Err(Error::EventQueued)
+            }
+            res => res,
+        }
+    }
+
+    async fn try_send_event(&self, event: &Event) -> Result<EventId, Error> {
         // Health, write permission and number of messages checks are executed in `batch_msg` method.
 
         // Subscribe to notifications
@@ -411,7 +486,8 @@ impl Relay {
             let has_signer: bool = self.inner.state.has_signer().await;
             if self.inner.state.is_auto_authentication_enabled() && has_signer {
                 // Wait that relay authenticate
-                self.wait_for_authentication(&mut notifications, WAIT_FOR_AUTHENTICATION_TIMEOUT)
+                self.inner
+                    .wait_for_authentication(&mut notifications, WAIT_FOR_AUTHENTICATION_TIMEOUT)
                     .await?;
 
                 // Try to resend event
@@ -429,36 +505,6 @@ impl Relay {
         Err(Error::RelayMessage(message))
     }
 
-    async fn wait_for_authentication(
-        &self,
-        notifications: &mut broadcast::Receiver<RelayNotification>,
-        timeout: Duration,
-    ) -> Result<(), Error> {
-        time::timeout(Some(timeout), async {
-            while let Ok(notification) = notifications.recv().await {
-                match notification {
-                    RelayNotification::Authenticated => {
-                        return Ok(());
-                    }
-                    RelayNotification::AuthenticationFailed => {
-                        return Err(Error::AuthenticationFailed);
-                    }
-                    RelayNotification::RelayStatus { status } => {
-                        if status.is_disconnected() {
-                            return Err(Error::NotConnected);
-                        }
-                    }
-                    RelayNotification::Shutdown => break,
-                    _ => (),
-                }
-            }
-
-            Err(Error::PrematureExit)
-        })
-        .await
-        .ok_or(Error::Timeout)?
-    }
-
     /// Resubscribe to all **closed** or not yet initiated subscriptions
     #[inline]
     pub async fn resubscribe(&self) -> Result<(), Error> {
@@ -499,18 +545,21 @@ impl Relay {
     ) -> Result<(), Error> {
         // Check if auto-close condition is set
         match opts.auto_close {
-            Some(opts) => self.subscribe_auto_closing(id, filter, opts, None),
+            Some(opts) => self.subscribe_auto_closing(id, filter, opts, None).await,
             None => self.subscribe_long_lived(id, filter).await,
         }
     }
 
-    fn subscribe_auto_closing(
+    async fn subscribe_auto_closing(
         &self,
         id: SubscriptionId,
         filter: Filter,
         opts: SubscribeAutoCloseOptions,
         activity: Option<mpsc::Sender<SubscriptionActivity>>,
     ) -> Result<(), Error> {
+        // Wait for the relay to accept another REQ
+        self.inner.reserve_auto_closing_req(opts.timeout).await?;
+
         // Compose REQ message
         let msg: ClientMessage = ClientMessage::Req {
             subscription_id: Cow::Borrowed(&id),
@@ -521,7 +570,10 @@ impl Relay {
         let notifications = self.inner.internal_notification_sender.subscribe();
 
         // Send REQ message
-        self.inner.send_msg(msg)?;
+        if let Err(e) = self.inner.send_msg(msg) {
+            self.inner.release_auto_closing_req().await;
+            return Err(e);
+        }
 
         // Spawn auto-closing handler
         self.inner
@@ -531,21 +583,9 @@ impl Relay {
         Ok(())
     }
 
+    #[inline]
     async fn subscribe_long_lived(&self, id: SubscriptionId, filter: Filter) -> Result<(), Error> {
-        // Compose REQ message
-        let msg: ClientMessage = ClientMessage::Req {
-            subscription_id: Cow::Borrowed(&id),
-            filter: Cow::Borrowed(&filter),
-        };
-
-        // Send REQ message
-        self.inner.send_msg(msg)?;
-
-        // No auto-close subscription: update subscription filter
-        self.inner.update_subscription(id, filter, true).await;
-
-        // Return
-        Ok(())
+        self.inner.subscribe_long_lived(id, filter).await
     }
 
     /// Unsubscribe
@@ -581,7 +621,8 @@ impl Relay {
 
         // Subscribe
         let id: SubscriptionId = SubscriptionId::generate();
-        self.subscribe_auto_closing(id, filter, opts, Some(tx))?;
+        self.subscribe_auto_closing(id, filter, opts, Some(tx))
+            .await?;
 
         // Handle subscription activity
         while let Some(activity) = rx.recv().await {
@@ -647,6 +688,47 @@ impl Relay {
         Ok(events)
     }
 
+    /// Number of events requested with each REQ of the paginated fetch
+    pub(crate) fn page_size(&self, opts: &PaginationOptions) -> usize {
+        opts.page_size
+            .or(self.limitation().max_limit)
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+    }
+
+    /// Fetch events, walking the history past the max limit of the relay
+    ///
+    /// The events are requested in pages, moving the `until` back to the oldest received event,
+    /// until the relay has no older events, the `since` of the filter is reached
+    /// or [`PaginationOptions::max_events`] events have been received.
+    ///
+    /// The `limit` of the filter is replaced by the page size (see [`PaginationOptions::page_size`]).
+    /// A second with more events than the page size is requested entirely, without `limit`.
+    pub async fn fetch_paginated_events(
+        &self,
+        filter: Filter,
+        opts: &PaginationOptions,
+    ) -> Result<Events, Error> {
+        let mut events: Events = Events::new(&filter);
+        let mut paginator: Paginator = Paginator::new(filter, self.page_size(opts));
+
+        while let Some(page) = paginator.next_page() {
+            let fetched: Events = self
+                .fetch_events(page, opts.timeout, ReqExitPolicy::ExitOnEOSE)
+                .await?;
+
+            for event in paginator.process(fetched) {
+                if matches!(opts.max_events, Some(max) if events.len() >= max) {
+                    return Ok(events);
+                }
+
+                // LOOKUP_ID: EVENTS_FORCE_INSERT
+                events.force_insert(event);
+            }
+        }
+
+        Ok(events)
+    }
+
     /// Count events
     pub async fn count_events(&self, filter: Filter, timeout: Duration) -> Result<usize, Error> {
         let id = SubscriptionId::generate();
@@ -742,13 +824,18 @@ impl Relay {
 
 #[cfg(test)]
 mod tests {
+    use std::net::SocketAddr;
     use std::sync::Arc;
+    use std::time::Instant;
 
     use async_utility::time;
     use nostr_relay_builder::prelude::*;
 
     use super::{Error, *};
+    use crate::monitor::{Monitor, MonitorNotification};
     use crate::policy::{AdmitPolicy, PolicyError};
+    use crate::transport::loopback::LoopbackTransport;
+    use crate::transport::websocket::DefaultWebsocketTransport;
 
     #[derive(Debug)]
     struct CustomTestPolicy {
@@ -774,6 +861,19 @@ mod tests {
         Relay::new(url, SharedState::default(), opts)
     }
 
+    fn new_relay_with_monitor(url: RelayUrl, monitor: Monitor) -> Relay {
+        let state = SharedState::new(
+            MemoryDatabase::new().into_nostr_database(),
+            Arc::new(DefaultWebsocketTransport),
+            None,
+            None,
+            true,
+            Some(monitor),
+            None,
+        );
+        Relay::new(url, state, RelayOptions::default())
+    }
+
     /// Setup public (without NIP42 auth) relay with N events to test event fetching
     ///
     /// **Adds ONLY text notes**
@@ -1522,5 +1622,616 @@ mod tests {
         assert!(!relay.inner.is_running());
     }
 
+    /// Setup a local relay (without listening) reachable through the loopback transport
+    async fn setup_loopback_relay(url: &RelayUrl) -> LoopbackTransport {
+        setup_loopback_relay_with_builder(url, RelayBuilder::default()).await
+    }
+
+    async fn setup_loopback_relay_with_builder(
+        url: &RelayUrl,
+        builder: RelayBuilder,
+    ) -> LoopbackTransport {
+        let local = LocalRelay::new(builder).await.unwrap();
+
+        let transport = LoopbackTransport::new();
+        transport.add_relay(url, move |conn| {
+            let local = local.clone();
+            async move {
+                let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+                local.take_message_connection(conn, addr).await.unwrap();
+            }
+        });
+
+        transport
+    }
+
+    #[tokio::test]
+    async fn test_loopback_transport() {
+        let url = RelayUrl::parse("ws://loopback.relay").unwrap();
+        let transport = setup_loopback_relay(&url).await;
+
+        let mut relay = new_relay(url.clone(), RelayOptions::default().reconnect(false));
+        relay.inner.state.transport = Arc::new(transport.clone());
+
+        relay.try_connect(Duration::from_secs(1)).await.unwrap();
+
+        assert_eq!(relay.status(), RelayStatus::Connected);
+        assert_eq!(transport.connections(&url), 1);
+
+        // Send and fetch
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("loopback")
+            .sign_with_keys(&keys)
+            .unwrap();
+        relay.send_event(&event).await.unwrap();
+
+        let events = relay
+            .fetch_events(
+                Filter::new().kind(Kind::TextNote),
+                Duration::from_secs(1),
+                ReqExitPolicy::ExitOnEOSE,
+            )
+            .await
+            .unwrap();
+        assert_eq!(events.to_vec(), vec![event]);
+
+        // Wait for the CLOSE message
+        time::sleep(Duration::from_millis(100)).await;
+
+        // Drop the next message: the relay never receives the REQ
+        transport.drop_next(1);
+        let res = relay
+            .fetch_events(
+                Filter::new().kind(Kind::TextNote),
+                Duration::from_millis(300),
+                ReqExitPolicy::ExitOnEOSE,
+            )
+            .await;
+        assert!(res.unwrap().is_empty());
+
+        // Simulate a network failure
+        transport.disconnect(&url);
+
+        time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(relay.status(), RelayStatus::Terminated);
+        assert_eq!(transport.connections(&url), 0);
+
+        // New connections refused
+        transport.refuse_connections(&url, true);
+        let res = relay.try_connect(Duration::from_secs(1)).await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_outbox() {
+        let url = RelayUrl::parse("ws://outbox.relay").unwrap();
+        let transport = setup_loopback_relay(&url).await;
+
+        // Relay unreachable
+        transport.refuse_connections(&url, true);
+
+        let opts = RelayOptions::default()
+            .outbox(true)
+            .retry_interval(Duration::from_millis(100))
+            .adjust_retry_interval(false);
+        let mut relay = new_relay(url.clone(), opts);
+        relay.inner.state.transport = Arc::new(transport.clone());
+
+        let outbox: Arc<MemoryDatabase> = Arc::new(MemoryDatabase::new());
+        relay.inner.state.outbox = Some(outbox.clone());
+
+        let mut notifications = relay.notifications();
+
+        relay.connect();
+
+        // Send event while offline
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("offline")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let res = relay.send_event(&event).await;
+        assert!(matches!(res.unwrap_err(), Error::EventQueued));
+
+        assert_eq!(
+            outbox.outbox_events(&url).await.unwrap(),
+            vec![event.clone()]
+        );
+
+        // Relay reachable again
+        transport.refuse_connections(&url, false);
+
+        // Wait for the replay
+        let status = time::timeout(Some(Duration::from_secs(5)), async {
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayNotification::Outbox { event_id, status } = notification {
+                    if event_id == event.id && status != OutboxStatus::Pending {
+                        return Some(status);
+                    }
+                }
+            }
+            None
+        })
+        .await
+        .flatten();
+        assert_eq!(status, Some(OutboxStatus::Sent));
+        assert!(outbox.outbox_events(&url).await.unwrap().is_empty());
+
+        // The relay received the event
+        let events = relay
+            .fetch_events(
+                Filter::new().id(event.id),
+                Duration::from_secs(1),
+                ReqExitPolicy::ExitOnEOSE,
+            )
+            .await
+            .unwrap();
+        assert_eq!(events.to_vec(), vec![event]);
+    }
+
+    #[tokio::test]
+    async fn test_outbox_auth_required() {
+        let url = RelayUrl::parse("ws://outbox-auth.relay").unwrap();
+        let builder = RelayBuilder::default().nip42(RelayBuilderNip42 {
+            mode: RelayBuilderNip42Mode::Write,
+        });
+        let transport = setup_loopback_relay_with_builder(&url, builder).await;
+
+        // Relay unreachable
+        transport.refuse_connections(&url, true);
+
+        let opts = RelayOptions::default()
+            .outbox(true)
+            .retry_interval(Duration::from_millis(100))
+            .adjust_retry_interval(false);
+        let mut relay = new_relay(url.clone(), opts);
+        relay.inner.state.transport = Arc::new(transport.clone());
+
+        let outbox: Arc<MemoryDatabase> = Arc::new(MemoryDatabase::new());
+        relay.inner.state.outbox = Some(outbox.clone());
+
+        let keys = Keys::generate();
+        relay.inner.state.set_signer(keys.clone()).await;
+
+        let mut notifications = relay.notifications();
+
+        relay.connect();
+
+        // Send event while offline
+        let event = EventBuilder::text_note("offline")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let res = relay.send_event(&event).await;
+        assert!(matches!(res.unwrap_err(), Error::EventQueued));
+
+        // Relay reachable again
+        transport.refuse_connections(&url, false);
+
+        // The event is sent after the authentication, on the same connection
+        let status = time::timeout(Some(Duration::from_secs(5)), async {
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayNotification::Outbox { event_id, status } = notification {
+                    if event_id == event.id && status != OutboxStatus::Pending {
+                        return Some(status);
+                    }
+                }
+            }
+            None
+        })
+        .await
+        .flatten();
+        assert_eq!(status, Some(OutboxStatus::Sent));
+        assert!(outbox.outbox_events(&url).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_monitor_notifications() {
+        // Mock relay that accepts only 1 event per minute
+        let builder = RelayBuilder::default().rate_limit(RateLimit {
+            max_reqs: 10,
+            notes_per_minute: 1,
+        });
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let monitor = Monitor::new(1024);
+        let mut notifications = monitor.subscribe();
+
+        let relay = new_relay_with_monitor(url.clone(), monitor);
+
+        relay.try_connect(Duration::from_secs(3)).await.unwrap();
+
+        // Subscription lifecycle
+        let id = SubscriptionId::new("monitor");
+        relay
+            .subscribe_with_id(
+                id.clone(),
+                Filter::new().kind(Kind::TextNote),
+                SubscribeOptions::default(),
+            )
+            .await
+            .unwrap();
+        time::sleep(Duration::from_millis(500)).await;
+
+        // The first event is accepted, the second is rate limited
+        let keys = Keys::generate();
+        let first = EventBuilder::text_note("first")
+            .sign_with_keys(&keys)
+            .unwrap();
+        relay.send_event(&first).await.unwrap();
+        let second = EventBuilder::text_note("second")
+            .sign_with_keys(&keys)
+            .unwrap();
+        relay.send_event(&second).await.unwrap_err();
+
+        let mut received: Vec<MonitorNotification> = Vec::new();
+        while let Ok(notification) = notifications.try_recv() {
+            if !matches!(notification, MonitorNotification::StatusChanged { .. }) {
+                received.push(notification);
+            }
+        }
+
+        let message = String::from("rate-limited: slow down");
+        assert_eq!(
+            received,
+            vec![
+                MonitorNotification::SubscriptionOpened {
+                    relay_url: url.clone(),
+                    subscription_id: id.clone(),
+                },
+                MonitorNotification::EndOfStoredEvents {
+                    relay_url: url.clone(),
+                    subscription_id: id,
+                },
+                MonitorNotification::RateLimited {
+                    relay_url: url.clone(),
+                    message: message.clone(),
+                },
+                MonitorNotification::EventRejected {
+                    relay_url: url,
+                    event_id: second.id,
+                    prefix: Some(MachineReadablePrefix::RateLimited),
+                    message,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multiplex_subscriptions() {
+        let url = RelayUrl::parse("ws://multiplex.relay").unwrap();
+        let transport = setup_loopback_relay(&url).await;
+
+        let opts = RelayOptions::default().reconnect(false).multiplex(true);
+        let mut relay = new_relay(url.clone(), opts);
+        relay.inner.state.transport = Arc::new(transport);
+
+        relay.try_connect(Duration::from_secs(1)).await.unwrap();
+
+        let keys1 = Keys::generate();
+        let keys2 = Keys::generate();
+
+        // Two subscriptions, one REQ
+        let id1 = relay
+            .subscribe(
+                Filter::new()
+                    .author(keys1.public_key())
+                    .kind(Kind::TextNote),
+                SubscribeOptions::default(),
+            )
+            .await
+            .unwrap();
+        let id2 = relay
+            .subscribe(
+                Filter::new()
+                    .author(keys2.public_key())
+                    .kind(Kind::TextNote),
+                SubscribeOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(relay.subscriptions().await.len(), 2);
+
+        let req_id: SubscriptionId = {
+            let mut multiplexer = relay.inner.atomic.multiplexer.write().await;
+            let req_id = multiplexer.route(&id1).cloned().unwrap();
+            assert_eq!(multiplexer.route(&id2), Some(&req_id));
+            assert_eq!(multiplexer.request(&req_id).unwrap().len(), 1);
+            req_id
+        };
+        assert_ne!(req_id, id1);
+        assert_ne!(req_id, id2);
+
+        let mut notifications = relay.notifications();
+
+        // Events are routed to the matching subscription
+        let event1 = EventBuilder::text_note("1").sign_with_keys(&keys1).unwrap();
+        let event2 = EventBuilder::text_note("2").sign_with_keys(&keys2).unwrap();
+        relay.send_event(&event1).await.unwrap();
+        relay.send_event(&event2).await.unwrap();
+
+        let mut received: Vec<(SubscriptionId, EventId)> = Vec::new();
+        time::timeout(Some(Duration::from_secs(2)), async {
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayNotification::Message {
+                    message:
+                        RelayMessage::Event {
+                            subscription_id,
+                            event,
+                        },
+                } = notification
+                {
+                    received.push((subscription_id.into_owned(), event.id));
+                    if received.len() == 2 {
+                        break;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert!(received.contains(&(id1.clone(), event1.id)));
+        assert!(received.contains(&(id2.clone(), event2.id)));
+
+        // Split on close
+        relay.unsubscribe(&id1).await.unwrap();
+        assert!(relay.subscription(&id1).await.is_none());
+        {
+            let multiplexer = relay.inner.atomic.multiplexer.read().await;
+            assert_eq!(multiplexer.route(&id1), None);
+            assert_eq!(multiplexer.route(&id2), Some(&req_id));
+        }
+
+        relay.unsubscribe_all().await.unwrap();
+        assert!(relay.subscriptions().await.is_empty());
+        assert_eq!(
+            relay.inner.atomic.multiplexer.read().await.route(&id2),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multiplex_event_notifications() {
+        let url = RelayUrl::parse("ws://multiplex-notifications.relay").unwrap();
+        let transport = setup_loopback_relay(&url).await;
+
+        let opts = RelayOptions::default().reconnect(false).multiplex(true);
+        let mut relay = new_relay(url.clone(), opts);
+        relay.inner.state.transport = Arc::new(transport);
+
+        relay.try_connect(Duration::from_secs(1)).await.unwrap();
+
+        let keys = Keys::generate();
+
+        // Two subscriptions matching the same event
+        let filter = Filter::new().author(keys.public_key()).kind(Kind::TextNote);
+        let id1 = relay
+            .subscribe(filter.clone(), SubscribeOptions::default())
+            .await
+            .unwrap();
+        let id2 = relay
+            .subscribe(filter, SubscribeOptions::default())
+            .await
+            .unwrap();
+
+        let mut notifications = relay.notifications();
+
+        let event = EventBuilder::text_note("1").sign_with_keys(&keys).unwrap();
+        relay.send_event(&event).await.unwrap();
+
+        // The event is notified to both the subscriptions
+        let mut received: HashSet<SubscriptionId> = HashSet::new();
+        time::timeout(Some(Duration::from_secs(2)), async {
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayNotification::Event {
+                    subscription_id,
+                    event: e,
+                } = notification
+                {
+                    assert_eq!(e.id, event.id);
+                    received.insert(subscription_id);
+                    if received.len() == 2 {
+                        break;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(received, HashSet::from([id1, id2]));
+    }
+
+    #[tokio::test]
+    async fn test_relay_limitation() {
+        let url = RelayUrl::parse("ws://limitation.relay").unwrap();
+        let transport = setup_loopback_relay(&url).await;
+
+        let opts = RelayOptions::default().reconnect(false);
+        let mut relay = new_relay(url.clone(), opts);
+        relay.inner.state.transport = Arc::new(transport);
+
+        relay.try_connect(Duration::from_secs(1)).await.unwrap();
+
+        relay
+            .inner
+            .set_limitation(RelayLimitation {
+                max_message_length: Some(512),
+                max_subscriptions: Some(1),
+                max_limit: Some(2),
+                ..Default::default()
+            })
+            .await;
+
+        // Only one REQ at a time: the second subscription is queued
+        let id1 = relay
+            .subscribe(
+                Filter::new().kind(Kind::TextNote),
+                SubscribeOptions::default(),
+            )
+            .await
+            .unwrap();
+        let id2 = relay
+            .subscribe(
+                Filter::new().kind(Kind::Metadata),
+                SubscribeOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert!(!relay.inner.is_queued(&id1).await);
+        assert!(relay.inner.is_queued(&id2).await);
+        assert_eq!(relay.subscriptions().await.len(), 2);
+
+        // Closing the first, the second is sent
+        relay.unsubscribe(&id1).await.unwrap();
+        assert!(!relay.inner.is_queued(&id2).await);
+        assert!(relay.subscription(&id2).await.is_some());
+
+        // Message too large
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("a".repeat(1024))
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert!(matches!(
+            relay.send_event(&event).await.unwrap_err(),
+            Error::MessageTooLarge { max_size: 512, .. }
+        ));
+
+        // Limit clamped
+        for i in 0..3 {
+            let event = EventBuilder::text_note(i.to_string())
+                .sign_with_keys(&keys)
+                .unwrap();
+            relay.send_event(&event).await.unwrap();
+        }
+
+        let filter = Filter::new().author(keys.public_key()).limit(10);
+
+        // The REQ of the second subscription is open: the fetch waits for it
+        let err = relay
+            .fetch_events(
+                filter.clone(),
+                Duration::from_millis(500),
+                ReqExitPolicy::ExitOnEOSE,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout));
+
+        relay.unsubscribe(&id2).await.unwrap();
+
+        let events = relay
+            .fetch_events(filter, Duration::from_secs(5), ReqExitPolicy::ExitOnEOSE)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_relay_limitation_auth_timeout() {
+        let url = RelayUrl::parse("ws://auth-timeout.relay").unwrap();
+        let transport = setup_loopback_relay(&url).await;
+
+        let opts = RelayOptions::default().reconnect(false);
+        let mut relay = new_relay(url.clone(), opts);
+        relay.inner.state.transport = Arc::new(transport);
+
+        let keys = Keys::generate();
+        relay.inner.state.set_signer(keys.clone()).await;
+
+        relay.try_connect(Duration::from_secs(1)).await.unwrap();
+
+        // Authentication required, but the relay never sends the challenge
+        relay
+            .inner
+            .set_limitation(RelayLimitation {
+                auth_required: true,
+                ..Default::default()
+            })
+            .await;
+
+        let event = EventBuilder::text_note("auth timeout")
+            .sign_with_keys(&keys)
+            .unwrap();
+        relay.send_event(&event).await.unwrap();
+
+        let mut notifications = relay.notifications();
+
+        let id = relay
+            .subscribe(
+                Filter::new().author(keys.public_key()),
+                SubscribeOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let received = async {
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayNotification::Message {
+                    message:
+                        RelayMessage::Event {
+                            subscription_id,
+                            event: e,
+                        },
+                } = notification
+                {
+                    if subscription_id.as_ref() == &id && e.id == event.id {
+                        return true;
+                    }
+                }
+            }
+            false
+        };
+
+        // Waiting for the authentication, then subscribed anyway
+        let start = Instant::now();
+        let received = time::timeout(
+            Some(WAIT_FOR_AUTHENTICATION_TIMEOUT + Duration::from_secs(5)),
+            received,
+        )
+        .await;
+        assert_eq!(received, Some(true));
+        assert!(start.elapsed() >= WAIT_FOR_AUTHENTICATION_TIMEOUT - Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_paginated_events() {
+        let url = RelayUrl::parse("ws://pagination.relay").unwrap();
+        let transport = setup_loopback_relay(&url).await;
+
+        let opts = RelayOptions::default().reconnect(false);
+        let mut relay = new_relay(url.clone(), opts);
+        relay.inner.state.transport = Arc::new(transport);
+
+        relay.try_connect(Duration::from_secs(1)).await.unwrap();
+
+        let keys = Keys::generate();
+        let now = Timestamp::now();
+        for i in 0..7 {
+            let event = EventBuilder::text_note(i.to_string())
+                .custom_created_at(now - i)
+                .sign_with_keys(&keys)
+                .unwrap();
+            relay.send_event(&event).await.unwrap();
+        }
+
+        let filter = Filter::new().author(keys.public_key()).kind(Kind::TextNote);
+
+        // Walk the whole history
+        let opts = PaginationOptions::new().page_size(3);
+        let events = relay
+            .fetch_paginated_events(filter.clone(), &opts)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 7);
+
+        // Stop at the max number of events
+        let opts = opts.max_events(5);
+        let events = relay.fetch_paginated_events(filter, &opts).await.unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events.first().unwrap().created_at, now);
+    }
+
     // TODO: add negentropy reconciliation test
 }
@@ -0,0 +1,615 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Subscription multiplexer
+//!
+//! Coalesce the filters of many long-lived subscriptions into fewer REQs
+//! and route the relay messages back to each subscription.
+
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::iter;
+
+use nostr::{Event, Filter, SubscriptionId, Timestamp};
+
+use super::constants::MULTIPLEX_EOSE_SLACK;
+
+/// Subscription multiplexed in a REQ
+#[derive(Debug, Clone)]
+struct Member {
+    filter: Filter,
+    /// When the EOSE has been received
+    eose: Option<Timestamp>,
+}
+
+impl Member {
+    #[inline]
+    fn new(filter: Filter) -> Self {
+        Self { filter, eose: None }
+    }
+
+    /// Filter to send to the relay
+    ///
+    /// After the EOSE, the stored events have already been received: request only the newer ones.
+    /// The events created slightly before the EOSE are requested again, to not miss the ones received late by the relay
+    /// (the duplicates are discarded).
+    fn req_filter(&self) -> Filter {
+        match self.eose {
+            Some(eose) => {
+                let since: Timestamp = eose - MULTIPLEX_EOSE_SLACK;
+                let mut filter: Filter = self.filter.clone();
+                filter.since = Some(match filter.since {
+                    Some(s) => cmp::max(s, since),
+                    None => since,
+                });
+                filter.limit = None;
+                filter
+            }
+            None => self.filter.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    members: BTreeMap<SubscriptionId, Member>,
+    /// Filters of the last REQ sent
+    sent: Vec<Filter>,
+}
+
+impl Group {
+    fn filters(&self) -> Vec<Filter> {
+        coalesce(self.members.values().map(Member::req_filter))
+    }
+}
+
+/// Update to send to the relay after the removal of a subscription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum MultiplexerUpdate {
+    /// Replace the REQ with new filters
+    Req {
+        subscription_id: SubscriptionId,
+        filters: Vec<Filter>,
+    },
+    /// Close the REQ: no more subscriptions in it
+    Close(SubscriptionId),
+    /// The REQ is unchanged
+    Unchanged,
+}
+
+/// Subscription multiplexer
+///
+/// The subscriptions are grouped in REQs of at most `max_filters` filters.
+/// Each REQ has its own ID, different from the IDs of the subscriptions in it.
+#[derive(Debug)]
+pub(super) struct Multiplexer {
+    groups: HashMap<SubscriptionId, Group>,
+    /// Subscription ID -> REQ ID
+    routes: HashMap<SubscriptionId, SubscriptionId>,
+    max_filters: usize,
+}
+
+impl Multiplexer {
+    pub(super) fn new(max_filters: usize) -> Self {
+        Self {
+            groups: HashMap::new(),
+            routes: HashMap::new(),
+            max_filters: max_filters.max(1),
+        }
+    }
+
+    /// Update the max number of filters per REQ
+    ///
+    /// The subscriptions that no longer fit in their REQ are removed from it.
+    ///
+    /// Returns the updates of the REQs that had too many filters and the removed subscriptions, to add again.
+    pub(super) fn set_max_filters(
+        &mut self,
+        max_filters: usize,
+    ) -> (Vec<MultiplexerUpdate>, Vec<(SubscriptionId, Filter)>) {
+        self.max_filters = max_filters.max(1);
+
+        let mut updates: Vec<MultiplexerUpdate> = Vec::new();
+        let mut evicted: Vec<(SubscriptionId, Filter)> = Vec::new();
+
+        for (req_id, group) in self.groups.iter_mut() {
+            if group.filters().len() <= self.max_filters {
+                continue;
+            }
+
+            // A single subscription always fits in a REQ
+            while group.members.len() > 1 && group.filters().len() > self.max_filters {
+                if let Some((id, member)) = group.members.pop_last() {
+                    self.routes.remove(&id);
+                    evicted.push((id, member.filter));
+                }
+            }
+
+            group.sent = group.filters();
+
+            updates.push(MultiplexerUpdate::Req {
+                subscription_id: req_id.clone(),
+                filters: group.sent.clone(),
+            });
+        }
+
+        (updates, evicted)
+    }
+
+    /// Number of REQs
+    #[inline]
+    pub(super) fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Get the ID of the REQ of a subscription
+    #[inline]
+    pub(super) fn route(&self, id: &SubscriptionId) -> Option<&SubscriptionId> {
+        self.routes.get(id)
+    }
+
+    /// Add a subscription, or replace the filter of an existing one
+    ///
+    /// If `new_req` is `false`, the subscription is added only if it fits in one of the existing REQs.
+    ///
+    /// Returns the ID of the REQ that must be (re)sent with [`Multiplexer::request`]
+    /// or `None` if the subscription hasn't been added.
+    pub(super) fn insert(
+        &mut self,
+        id: SubscriptionId,
+        filter: Filter,
+        new_req: bool,
+    ) -> Option<SubscriptionId> {
+        // Already multiplexed: replace the filter
+        if let Some(req_id) = self.routes.get(&id) {
+            if let Some(group) = self.groups.get_mut(req_id) {
+                group.members.insert(id, Member::new(filter));
+                return Some(req_id.clone());
+            }
+        }
+
+        let member: Member = Member::new(filter);
+
+        // Find the REQ that needs the fewest filters after adding the new one
+        let mut best: Option<(&SubscriptionId, usize)> = None;
+        for (req_id, group) in self.groups.iter() {
+            let filters = group
+                .members
+                .values()
+                .map(Member::req_filter)
+                .chain(iter::once(member.req_filter()));
+            let len: usize = coalesce(filters).len();
+
+            if len <= self.max_filters && best.map_or(true, |(_, best_len)| len < best_len) {
+                best = Some((req_id, len));
+            }
+        }
+
+        let req_id: SubscriptionId = match best {
+            Some((req_id, ..)) => req_id.clone(),
+            None if new_req => SubscriptionId::generate(),
+            None => return None,
+        };
+
+        let group: &mut Group = self.groups.entry(req_id.clone()).or_default();
+        group.members.insert(id.clone(), member);
+        self.routes.insert(id, req_id.clone());
+
+        Some(req_id)
+    }
+
+    /// Get the filters of a REQ, marking them as sent
+    pub(super) fn request(&mut self, req_id: &SubscriptionId) -> Option<Vec<Filter>> {
+        let group: &mut Group = self.groups.get_mut(req_id)?;
+        group.sent = group.filters();
+        Some(group.sent.clone())
+    }
+
+    /// Remove a subscription
+    ///
+    /// Returns `None` if the subscription isn't multiplexed.
+    pub(super) fn remove(&mut self, id: &SubscriptionId) -> Option<MultiplexerUpdate> {
+        let req_id: SubscriptionId = self.routes.remove(id)?;
+        let group: &mut Group = self.groups.get_mut(&req_id)?;
+        group.members.remove(id);
+
+        if group.members.is_empty() {
+            self.groups.remove(&req_id);
+            return Some(MultiplexerUpdate::Close(req_id));
+        }
+
+        let filters: Vec<Filter> = group.filters();
+
+        if filters == group.sent {
+            return Some(MultiplexerUpdate::Unchanged);
+        }
+
+        group.sent = filters.clone();
+
+        Some(MultiplexerUpdate::Req {
+            subscription_id: req_id,
+            filters,
+        })
+    }
+
+    /// Remove all the subscriptions
+    ///
+    /// Returns the IDs of the REQs to close.
+    pub(super) fn clear(&mut self) -> Vec<SubscriptionId> {
+        self.routes.clear();
+        self.groups.drain().map(|(req_id, ..)| req_id).collect()
+    }
+
+    /// Get the subscriptions matching an event received for a REQ
+    ///
+    /// Returns `None` if the REQ isn't multiplexed.
+    pub(super) fn route_event(
+        &self,
+        req_id: &SubscriptionId,
+        event: &Event,
+    ) -> Option<Vec<SubscriptionId>> {
+        let group: &Group = self.groups.get(req_id)?;
+        Some(
+            group
+                .members
+                .iter()
+                .filter(|(_, member)| member.filter.match_event(event))
+                .map(|(id, ..)| id.clone())
+                .collect(),
+        )
+    }
+
+    /// Get the subscriptions that are still waiting for the EOSE of a REQ
+    ///
+    /// Returns `None` if the REQ isn't multiplexed.
+    pub(super) fn route_eose(&mut self, req_id: &SubscriptionId) -> Option<Vec<SubscriptionId>> {
+        let group: &mut Group = self.groups.get_mut(req_id)?;
+        let now: Timestamp = Timestamp::now();
+        Some(
+            group
+                .members
+                .iter_mut()
+                .filter(|(_, member)| member.eose.is_none())
+                .map(|(id, member)| {
+                    member.eose = Some(now);
+                    id.clone()
+                })
+                .collect(),
+        )
+    }
+
+    /// Remove a REQ closed by the relay
+    ///
+    /// Returns the subscriptions that were in it or `None` if the REQ isn't multiplexed.
+    pub(super) fn route_closed(&mut self, req_id: &SubscriptionId) -> Option<Vec<SubscriptionId>> {
+        let group: Group = self.groups.remove(req_id)?;
+        Some(
+            group
+                .members
+                .into_keys()
+                .inspect(|id| {
+                    self.routes.remove(id);
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Coalesce filters, merging the compatible ones
+fn coalesce<I>(filters: I) -> Vec<Filter>
+where
+    I: IntoIterator<Item = Filter>,
+{
+    let mut coalesced: Vec<Filter> = Vec::new();
+
+    for filter in filters.into_iter() {
+        let merged: bool = coalesced
+            .iter_mut()
+            .any(|existing| match merge(existing, &filter) {
+                Some(merged) => {
+                    *existing = merged;
+                    true
+                }
+                None => false,
+            });
+
+        if !merged {
+            coalesced.push(filter);
+        }
+    }
+
+    coalesced
+}
+
+/// Merge two filters into one matching the events of both
+///
+/// Filters with a `limit` or a `search` are merged only if equal.
+/// The others are merged if they differ at most in one of `ids`, `authors`, `kinds` or a generic tag:
+/// the time range of the merged filter covers both.
+fn merge(a: &Filter, b: &Filter) -> Option<Filter> {
+    if a == b {
+        return Some(a.clone());
+    }
+
+    if a.limit.is_some() || b.limit.is_some() || a.search.is_some() || b.search.is_some() {
+        return None;
+    }
+
+    // Generic tags must have the same keys
+    if !a.generic_tags.keys().eq(b.generic_tags.keys()) {
+        return None;
+    }
+
+    let mut merged: Filter = a.clone();
+    let mut differences: usize = 0;
+
+    if a.ids != b.ids {
+        differences += 1;
+        merged.ids = union(&a.ids, &b.ids);
+    }
+
+    if a.authors != b.authors {
+        differences += 1;
+        merged.authors = union(&a.authors, &b.authors);
+    }
+
+    if a.kinds != b.kinds {
+        differences += 1;
+        merged.kinds = union(&a.kinds, &b.kinds);
+    }
+
+    for (tag, values) in b.generic_tags.iter() {
+        if let Some(existing) = merged.generic_tags.get_mut(tag) {
+            if existing != values {
+                differences += 1;
+                existing.extend(values.iter().cloned());
+            }
+        }
+    }
+
+    if differences > 1 {
+        return None;
+    }
+
+    merged.since = match (a.since, b.since) {
+        (Some(a), Some(b)) => Some(cmp::min(a, b)),
+        _ => None,
+    };
+    merged.until = match (a.until, b.until) {
+        (Some(a), Some(b)) => Some(cmp::max(a, b)),
+        _ => None,
+    };
+
+    Some(merged)
+}
+
+/// Union of two sets, where `None` matches everything
+fn union<T>(a: &Option<BTreeSet<T>>, b: &Option<BTreeSet<T>>) -> Option<BTreeSet<T>>
+where
+    T: Ord + Clone,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.union(b).cloned().collect()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{Alphabet, EventBuilder, Keys, Kind, SingleLetterTag};
+
+    use super::*;
+
+    #[test]
+    fn test_merge_filters() {
+        let a = Keys::generate();
+        let b = Keys::generate();
+
+        // Different authors
+        let f1 = Filter::new().author(a.public_key()).kind(Kind::TextNote);
+        let f2 = Filter::new().author(b.public_key()).kind(Kind::TextNote);
+        assert_eq!(
+            merge(&f1, &f2),
+            Some(
+                Filter::new()
+                    .authors([a.public_key(), b.public_key()])
+                    .kind(Kind::TextNote)
+            )
+        );
+
+        // Different authors and kinds
+        let f3 = Filter::new().author(b.public_key()).kind(Kind::Metadata);
+        assert_eq!(merge(&f1, &f3), None);
+
+        // Limit
+        let f4 = f2.clone().limit(10);
+        assert_eq!(merge(&f1, &f4), None);
+        assert_eq!(merge(&f4, &f4), Some(f4.clone()));
+
+        // Time range
+        let f5 = f2
+            .clone()
+            .since(Timestamp::from(100))
+            .until(Timestamp::from(200));
+        let f6 = f2
+            .clone()
+            .since(Timestamp::from(50))
+            .until(Timestamp::from(150));
+        assert_eq!(
+            merge(&f5, &f6),
+            Some(
+                f2.clone()
+                    .since(Timestamp::from(50))
+                    .until(Timestamp::from(200))
+            )
+        );
+
+        // Generic tags
+        let f7 = Filter::new().kind(Kind::TextNote).pubkey(a.public_key());
+        let f8 = Filter::new().kind(Kind::TextNote).pubkey(b.public_key());
+        let merged = merge(&f7, &f8).unwrap();
+        assert_eq!(
+            merged
+                .generic_tags
+                .get(&SingleLetterTag::lowercase(Alphabet::P))
+                .map(|v| v.len()),
+            Some(2)
+        );
+        assert_eq!(merge(&f1, &f7), None);
+    }
+
+    #[test]
+    fn test_multiplexer_insert_and_remove() {
+        let a = Keys::generate();
+        let b = Keys::generate();
+
+        let mut multiplexer = Multiplexer::new(2);
+
+        let id1 = SubscriptionId::new("1");
+        let id2 = SubscriptionId::new("2");
+        let id3 = SubscriptionId::new("3");
+        let id4 = SubscriptionId::new("4");
+
+        let f1 = Filter::new().author(a.public_key()).kind(Kind::TextNote);
+        let f2 = Filter::new().author(b.public_key()).kind(Kind::TextNote);
+        let f3 = Filter::new().kind(Kind::Metadata).limit(1);
+        let f4 = Filter::new().kind(Kind::ContactList).limit(1);
+
+        // Coalesced in the same filter
+        let req1 = multiplexer.insert(id1.clone(), f1.clone(), true).unwrap();
+        let req2 = multiplexer.insert(id2.clone(), f2.clone(), true).unwrap();
+        assert_eq!(req1, req2);
+        assert_eq!(multiplexer.request(&req1).unwrap().len(), 1);
+
+        // Same REQ, another filter
+        let req3 = multiplexer.insert(id3.clone(), f3, true).unwrap();
+        assert_eq!(req1, req3);
+        assert_eq!(multiplexer.request(&req1).unwrap().len(), 2);
+
+        // Max filters reached: new REQ, if allowed
+        assert_eq!(multiplexer.insert(id4.clone(), f4.clone(), false), None);
+        let req4 = multiplexer.insert(id4.clone(), f4, true).unwrap();
+        assert_eq!(multiplexer.len(), 2);
+        assert_ne!(req1, req4);
+        assert_eq!(multiplexer.route(&id4), Some(&req4));
+
+        // Remove: the REQ is sent again without the filter
+        assert_eq!(
+            multiplexer.remove(&id3),
+            Some(MultiplexerUpdate::Req {
+                subscription_id: req1.clone(),
+                filters: vec![Filter::new()
+                    .authors([a.public_key(), b.public_key()])
+                    .kind(Kind::TextNote)],
+            })
+        );
+
+        // Remove the last subscription of a REQ
+        assert_eq!(
+            multiplexer.remove(&id4),
+            Some(MultiplexerUpdate::Close(req4))
+        );
+
+        // Not multiplexed
+        assert_eq!(multiplexer.remove(&id4), None);
+
+        // Same filter of another subscription: unchanged
+        let id5 = SubscriptionId::new("5");
+        multiplexer.insert(id5.clone(), f1.clone(), true).unwrap();
+        multiplexer.request(&req1).unwrap();
+        assert_eq!(multiplexer.remove(&id5), Some(MultiplexerUpdate::Unchanged));
+
+        // Clear
+        assert_eq!(multiplexer.clear(), vec![req1]);
+        assert_eq!(multiplexer.route(&id1), None);
+    }
+
+    #[test]
+    fn test_multiplexer_set_max_filters() {
+        let a = Keys::generate();
+
+        let mut multiplexer = Multiplexer::new(3);
+
+        let id1 = SubscriptionId::new("1");
+        let id2 = SubscriptionId::new("2");
+        let id3 = SubscriptionId::new("3");
+
+        let f1 = Filter::new().author(a.public_key()).kind(Kind::TextNote);
+        let f2 = Filter::new().kind(Kind::Metadata).limit(1);
+        let f3 = Filter::new().kind(Kind::ContactList).limit(1);
+
+        let req_id = multiplexer.insert(id1.clone(), f1.clone(), true).unwrap();
+        multiplexer.insert(id2.clone(), f2.clone(), true).unwrap();
+        multiplexer.insert(id3.clone(), f3.clone(), true).unwrap();
+        assert_eq!(multiplexer.request(&req_id).unwrap().len(), 3);
+
+        // Lower limit: the last subscriptions are removed and the REQ is sent again
+        let (updates, evicted) = multiplexer.set_max_filters(1);
+        assert_eq!(
+            updates,
+            vec![MultiplexerUpdate::Req {
+                subscription_id: req_id.clone(),
+                filters: vec![f1],
+            }]
+        );
+        assert_eq!(evicted, vec![(id3.clone(), f3), (id2.clone(), f2)]);
+        assert_eq!(multiplexer.route(&id1), Some(&req_id));
+        assert_eq!(multiplexer.route(&id2), None);
+        assert_eq!(multiplexer.route(&id3), None);
+
+        // Higher limit: nothing to update
+        let (updates, evicted) = multiplexer.set_max_filters(10);
+        assert!(updates.is_empty());
+        assert!(evicted.is_empty());
+    }
+
+    #[test]
+    fn test_multiplexer_routing() {
+        let a = Keys::generate();
+        let b = Keys::generate();
+
+        let mut multiplexer = Multiplexer::new(10);
+
+        let id1 = SubscriptionId::new("1");
+        let id2 = SubscriptionId::new("2");
+
+        let req = multiplexer
+            .insert(id1.clone(), Filter::new().author(a.public_key()), true)
+            .unwrap();
+        multiplexer
+            .insert(id2.clone(), Filter::new().author(b.public_key()), true)
+            .unwrap();
+
+        let event = EventBuilder::text_note("a").sign_with_keys(&a).unwrap();
+        assert_eq!(
+            multiplexer.route_event(&req, &event),
+            Some(vec![id1.clone()])
+        );
+        assert_eq!(
+            multiplexer.route_event(&SubscriptionId::new("other"), &event),
+            None
+        );
+
+        // EOSE only once
+        let before_eose = Timestamp::now();
+        assert_eq!(
+            multiplexer.route_eose(&req),
+            Some(vec![id1.clone(), id2.clone()])
+        );
+        assert_eq!(multiplexer.route_eose(&req), Some(Vec::new()));
+
+        // After EOSE only the new events are requested, with a slack window
+        let filters = multiplexer.request(&req).unwrap();
+        for filter in filters.iter() {
+            let since = filter.since.unwrap();
+            assert!(since >= before_eose - MULTIPLEX_EOSE_SLACK);
+            assert!(since <= Timestamp::now() - MULTIPLEX_EOSE_SLACK);
+        }
+
+        // Closed by relay
+        assert_eq!(multiplexer.route_closed(&req), Some(vec![id1.clone(), id2]));
+        assert_eq!(multiplexer.route(&id1), None);
+        assert_eq!(multiplexer.route_event(&req, &event), None);
+    }
+}
@@ -9,7 +9,9 @@ use std::time::Duration;
 use async_wsocket::ConnectionMode;
 use tokio::sync::watch::{self, Receiver, Sender};
 
-use super::constants::{DEFAULT_NOTIFICATION_CHANNEL_SIZE, DEFAULT_RETRY_INTERVAL};
+use super::constants::{
+    DEFAULT_MULTIPLEX_MAX_FILTERS, DEFAULT_NOTIFICATION_CHANNEL_SIZE, DEFAULT_RETRY_INTERVAL,
+};
 use super::flags::RelayServiceFlags;
 use crate::RelayLimits;
 
@@ -24,6 +26,9 @@ pub struct RelayOptions {
     pub(super) limits: RelayLimits,
     pub(super) max_avg_latency: Option<Duration>,
     pub(super) notification_channel_size: usize,
+    pub(super) outbox: bool,
+    pub(super) multiplex: bool,
+    pub(super) multiplex_max_filters: usize,
 }
 
 impl Default for RelayOptions {
@@ -37,6 +42,9 @@ impl Default for RelayOptions {
             limits: RelayLimits::default(),
             max_avg_latency: None,
             notification_channel_size: DEFAULT_NOTIFICATION_CHANNEL_SIZE,
+            outbox: false,
+            multiplex: false,
+            multiplex_max_filters: DEFAULT_MULTIPLEX_MAX_FILTERS,
         }
     }
 }
@@ -130,6 +138,44 @@ impl RelayOptions {
         self.notification_channel_size = size;
         self
     }
+
+    /// Enable/disable the offline outbox (default: false)
+    ///
+    /// If enabled, the events that can't be sent because the relay is not connected
+    /// are kept in the database outbox and automatically sent when the connection is established again.
+    ///
+    /// Requires an outbox store (see [`RelayPoolBuilder::outbox`](crate::pool::RelayPoolBuilder::outbox)),
+    /// otherwise the events are sent as if the outbox was disabled.
+    #[inline]
+    pub fn outbox(mut self, enable: bool) -> Self {
+        self.outbox = enable;
+        self
+    }
+
+    /// Enable/disable the subscription multiplexing (default: false)
+    ///
+    /// If enabled, the filters of the long-lived subscriptions are coalesced into fewer REQs,
+    /// to stay below the max number of subscriptions allowed by the relay.
+    /// The events are routed back to each subscription with [`Filter::match_event`](nostr::Filter::match_event),
+    /// so the subscriptions keep working as if they were sent separately.
+    ///
+    /// When a subscription joins or leaves a REQ, the REQ is sent again with the updated filters:
+    /// for the subscriptions that already received the EOSE, only the events newer than it are requested.
+    /// If the relay closes a REQ, all the subscriptions in it are closed.
+    ///
+    /// Auto-closing subscriptions are never multiplexed.
+    #[inline]
+    pub fn multiplex(mut self, enable: bool) -> Self {
+        self.multiplex = enable;
+        self
+    }
+
+    /// Max number of filters per multiplexed REQ (default: [`DEFAULT_MULTIPLEX_MAX_FILTERS`])
+    #[inline]
+    pub fn multiplex_max_filters(mut self, max: usize) -> Self {
+        self.multiplex_max_filters = max;
+        self
+    }
 }
 
 /// Auto-closing subscribe options
@@ -303,3 +349,55 @@ impl SyncOptions {
         !self.dry_run && matches!(self.direction, SyncDirection::Down | SyncDirection::Both)
     }
 }
+
+/// Paginated fetch options
+///
+/// The time window to walk is the one of the filter (`since` and `until`).
+#[derive(Debug, Clone)]
+pub struct PaginationOptions {
+    pub(crate) page_size: Option<usize>,
+    pub(crate) max_events: Option<usize>,
+    pub(crate) timeout: Duration,
+}
+
+impl Default for PaginationOptions {
+    fn default() -> Self {
+        Self {
+            page_size: None,
+            max_events: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl PaginationOptions {
+    /// New default [`PaginationOptions`]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of events requested with each REQ
+    ///
+    /// By default, the max limit advertised by the relay in its NIP-11 document
+    /// or [`DEFAULT_PAGE_SIZE`](super::constants::DEFAULT_PAGE_SIZE).
+    #[inline]
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size.max(1));
+        self
+    }
+
+    /// Stop after receiving this number of events in total (default: unlimited)
+    #[inline]
+    pub fn max_events(mut self, max_events: usize) -> Self {
+        self.max_events = Some(max_events);
+        self
+    }
+
+    /// Timeout of each page (default: 10 secs)
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
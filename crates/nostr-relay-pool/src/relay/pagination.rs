@@ -0,0 +1,232 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Historical events pagination
+
+use std::collections::HashSet;
+
+use nostr::{Event, EventId, Filter, Timestamp};
+
+/// Walk back the history of a filter, one page (REQ) at a time
+///
+/// Each page is requested with the `until` moved to the oldest `created_at` received so far.
+/// Since `until` is inclusive, the events at the boundary second are received again and deduplicated.
+///
+/// If a whole page is in the boundary second, there may be more events in it than the page size:
+/// the second is requested entirely (`since` and `until` set to it, without `limit`) before moving on.
+#[derive(Debug)]
+pub(crate) struct Paginator {
+    filter: Filter,
+    page_size: usize,
+    until: Option<Timestamp>,
+    /// Second to request entirely with the next page
+    drain: Option<Timestamp>,
+    seen: HashSet<EventId>,
+    done: bool,
+}
+
+impl Paginator {
+    pub(crate) fn new(filter: Filter, page_size: usize) -> Self {
+        Self {
+            until: filter.until,
+            filter,
+            page_size,
+            drain: None,
+            seen: HashSet::new(),
+            done: false,
+        }
+    }
+
+    /// Get the filter of the next page or `None` if the history has been walked completely
+    pub(crate) fn next_page(&self) -> Option<Filter> {
+        if self.done {
+            return None;
+        }
+
+        let mut filter: Filter = self.filter.clone();
+
+        match self.drain {
+            Some(second) => {
+                filter.since = Some(second);
+                filter.until = Some(second);
+                filter.limit = None;
+            }
+            None => {
+                filter.until = self.until;
+                filter.limit = Some(self.page_size);
+            }
+        }
+
+        Some(filter)
+    }
+
+    /// Process the events of a page, moving the cursor back
+    ///
+    /// Returns the events not received in the previous pages.
+    pub(crate) fn process<I>(&mut self, events: I) -> Vec<Event>
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        let mut oldest: Option<Timestamp> = None;
+        let mut new: Vec<Event> = Vec::new();
+
+        for event in events.into_iter() {
+            oldest = Some(match oldest {
+                Some(oldest) => oldest.min(event.created_at),
+                None => event.created_at,
+            });
+
+            if self.seen.insert(event.id) {
+                new.push(event);
+            }
+        }
+
+        match self.drain.take() {
+            // The second has been received entirely: continue with the older events
+            Some(second) => {
+                if second.is_zero() {
+                    self.done = true;
+                } else {
+                    self.until = Some(second - 1);
+                }
+            }
+            None => match (oldest, self.until) {
+                // Empty page: nothing older
+                (None, ..) => self.done = true,
+                // The whole page is in the boundary second: request it entirely
+                (Some(oldest), Some(until)) if oldest == until => self.drain = Some(oldest),
+                // The relay doesn't respect `until`: stop, to not walk the same pages forever
+                (Some(oldest), Some(until)) if oldest > until => self.done = true,
+                // Request again the boundary second, there may be other events with the same timestamp
+                (Some(oldest), ..) => self.until = Some(oldest),
+            },
+        }
+
+        // Reached the start of the time window
+        if let (Some(since), Some(until)) = (self.filter.since, self.until) {
+            if until < since {
+                self.done = true;
+            }
+        }
+
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Reverse;
+
+    use nostr::{EventBuilder, Keys, Kind};
+
+    use super::*;
+
+    fn event(keys: &Keys, content: &str, created_at: u64) -> Event {
+        EventBuilder::text_note(content)
+            .custom_created_at(Timestamp::from_secs(created_at))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    /// Simulate a relay that returns at most `limit` events, newest first
+    fn query(events: &[Event], filter: &Filter) -> Vec<Event> {
+        let mut matching: Vec<Event> = events
+            .iter()
+            .filter(|e| filter.match_event(e))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|e| Reverse(e.created_at));
+        matching.truncate(filter.limit.unwrap_or(usize::MAX));
+        matching
+    }
+
+    fn paginate(events: &[Event], filter: Filter, page_size: usize) -> Vec<Event> {
+        let mut paginator = Paginator::new(filter, page_size);
+        let mut received: Vec<Event> = Vec::new();
+        while let Some(page) = paginator.next_page() {
+            received.extend(paginator.process(query(events, &page)));
+        }
+        received
+    }
+
+    #[test]
+    fn test_paginate_history() {
+        let keys = Keys::generate();
+        let events: Vec<Event> = (1..=10)
+            .map(|i| event(&keys, &i.to_string(), i * 10))
+            .collect();
+
+        let filter = Filter::new().author(keys.public_key()).kind(Kind::TextNote);
+        let received = paginate(&events, filter, 3);
+        assert_eq!(received.len(), 10);
+
+        // Newest first
+        assert_eq!(received[0].created_at, Timestamp::from_secs(100));
+        assert_eq!(received[9].created_at, Timestamp::from_secs(10));
+    }
+
+    #[test]
+    fn test_paginate_same_second() {
+        let keys = Keys::generate();
+
+        // 3 events in the same second, across the page boundary
+        let mut events: Vec<Event> = vec![
+            event(&keys, "a", 100),
+            event(&keys, "b", 90),
+            event(&keys, "c", 90),
+            event(&keys, "d", 90),
+            event(&keys, "e", 80),
+        ];
+
+        let filter = Filter::new().author(keys.public_key());
+        assert_eq!(paginate(&events, filter.clone(), 3).len(), 5);
+
+        // More events in the same second than the page size: the second is requested entirely
+        events.push(event(&keys, "f", 90));
+        events.push(event(&keys, "g", 90));
+        events.push(event(&keys, "h", 70));
+        let received = paginate(&events, filter, 3);
+        assert_eq!(received.len(), events.len());
+
+        let ids: HashSet<EventId> = received.iter().map(|e| e.id).collect();
+        assert!(events.iter().all(|e| ids.contains(&e.id)));
+    }
+
+    #[test]
+    fn test_paginate_until_not_respected() {
+        let keys = Keys::generate();
+        let events: Vec<Event> = (1..=10)
+            .map(|i| event(&keys, &i.to_string(), i * 10))
+            .collect();
+
+        // The relay always returns the newest events
+        let mut paginator = Paginator::new(Filter::new().author(keys.public_key()), 3);
+        let mut pages: usize = 0;
+        while let Some(mut page) = paginator.next_page() {
+            page.until = None;
+            paginator.process(query(&events, &page));
+
+            pages += 1;
+            assert!(pages <= 10);
+        }
+    }
+
+    #[test]
+    fn test_paginate_window() {
+        let keys = Keys::generate();
+        let events: Vec<Event> = (1..=10)
+            .map(|i| event(&keys, &i.to_string(), i * 10))
+            .collect();
+
+        let filter = Filter::new()
+            .author(keys.public_key())
+            .since(Timestamp::from_secs(30))
+            .until(Timestamp::from_secs(70));
+        let received = paginate(&events, filter, 2);
+        assert_eq!(received.len(), 5);
+        assert!(received
+            .iter()
+            .all(|e| (30..=70).contains(&e.created_at.as_u64())));
+    }
+}
@@ -12,7 +12,7 @@ use std::time::Duration;
 use nostr::Timestamp;
 
 #[cfg(not(target_arch = "wasm32"))]
-use super::constants::LATENCY_MIN_READS;
+use super::constants::{LATENCY_BUCKETS, LATENCY_MIN_READS};
 
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Default)]
@@ -21,6 +21,20 @@ struct AverageLatency {
     total: AtomicU64,
     /// Count of latencies
     count: AtomicU64,
+    /// Count of latencies for each bucket of [`LATENCY_BUCKETS`] (not cumulative)
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+}
+
+/// Latency histogram
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Upper bounds and cumulative count of latencies less than or equal to them
+    pub buckets: Vec<(Duration, u64)>,
+    /// Count of all latencies
+    pub count: u64,
+    /// Sum of all latencies
+    pub sum: Duration,
 }
 
 #[derive(Debug, Default)]
@@ -104,12 +118,32 @@ impl RelayConnectionStats {
         total.checked_div(count).map(Duration::from_millis)
     }
 
+    /// Latency histogram
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn latency_histogram(&self) -> LatencyHistogram {
+        let mut cumulative: u64 = 0;
+        let buckets: Vec<(Duration, u64)> = LATENCY_BUCKETS
+            .iter()
+            .zip(self.inner.latency.buckets.iter())
+            .map(|(bound, count)| {
+                cumulative += count.load(Ordering::SeqCst);
+                (*bound, cumulative)
+            })
+            .collect();
+
+        LatencyHistogram {
+            buckets,
+            count: self.inner.latency.count.load(Ordering::SeqCst),
+            sum: Duration::from_millis(self.inner.latency.total.load(Ordering::SeqCst)),
+        }
+    }
+
     #[inline]
-    pub(super) fn new_attempt(&self) {
+    pub(crate) fn new_attempt(&self) {
         self.inner.attempts.fetch_add(1, Ordering::SeqCst);
     }
 
-    pub(super) fn new_success(&self) {
+    pub(crate) fn new_success(&self) {
         self.inner.success.fetch_add(1, Ordering::SeqCst);
 
         let now: u64 = Timestamp::now().as_u64();
@@ -144,6 +178,42 @@ impl RelayConnectionStats {
                 .total
                 .fetch_add(ms as u64, Ordering::SeqCst);
             self.inner.latency.count.fetch_add(1, Ordering::SeqCst);
+
+            if let Some(index) = LATENCY_BUCKETS.iter().position(|bound| latency <= *bound) {
+                self.inner.latency.buckets[index].fetch_add(1, Ordering::SeqCst);
+            }
         }
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_histogram() {
+        let stats = RelayConnectionStats::default();
+
+        stats.save_latency(Duration::from_millis(30));
+        stats.save_latency(Duration::from_millis(100));
+        stats.save_latency(Duration::from_millis(700));
+        stats.save_latency(Duration::from_secs(20));
+
+        let histogram: LatencyHistogram = stats.latency_histogram();
+        assert_eq!(histogram.count, 4);
+        assert_eq!(histogram.sum, Duration::from_millis(20_830));
+        assert_eq!(
+            histogram.buckets,
+            vec![
+                (Duration::from_millis(50), 1),
+                (Duration::from_millis(100), 2),
+                (Duration::from_millis(250), 2),
+                (Duration::from_millis(500), 2),
+                (Duration::from_secs(1), 3),
+                (Duration::from_millis(2500), 3),
+                (Duration::from_secs(5), 3),
+                (Duration::from_secs(10), 3),
+            ]
+        );
+    }
+}
@@ -12,7 +12,7 @@ use std::sync::{Arc, Mutex};
 use lru::LruCache;
 use nostr::prelude::IntoNostrSigner;
 use nostr::{EventId, NostrSigner};
-use nostr_database::{IntoNostrDatabase, MemoryDatabase, NostrDatabase};
+use nostr_database::{IntoNostrDatabase, MemoryDatabase, NostrDatabase, NostrDatabaseOutbox};
 use tokio::sync::RwLock;
 
 use crate::monitor::Monitor;
@@ -49,6 +49,7 @@ pub struct SharedState {
     verification_cache: Arc<Mutex<LruCache<u64, ()>>>,
     pub(crate) admit_policy: Option<Arc<dyn AdmitPolicy>>,
     pub(crate) monitor: Option<Monitor>,
+    pub(crate) outbox: Option<Arc<dyn NostrDatabaseOutbox>>,
 }
 
 impl Default for SharedState {
@@ -60,6 +61,7 @@ impl Default for SharedState {
             None,
             true,
             None,
+            None,
         )
     }
 }
@@ -72,6 +74,7 @@ impl SharedState {
         admit_policy: Option<Arc<dyn AdmitPolicy>>,
         nip42_auto_authentication: bool,
         monitor: Option<Monitor>,
+        outbox: Option<Arc<dyn NostrDatabaseOutbox>>,
     ) -> Self {
         let max_verification_cache_size: NonZeroUsize =
             NonZeroUsize::new(MAX_VERIFICATION_CACHE_SIZE)
@@ -85,6 +88,7 @@ impl SharedState {
             verification_cache: Arc::new(Mutex::new(LruCache::new(max_verification_cache_size))),
             admit_policy,
             monitor,
+            outbox,
         }
     }
 
@@ -0,0 +1,389 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! In-process loopback transport
+//!
+//! Connect the relays directly to in-memory handlers (i.e. a `nostr-relay-builder` `LocalRelay` or a scripted fake),
+//! without binding any port.
+//! Latency, dropped messages and disconnections can be simulated to test the network faults deterministically.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_utility::{task, time};
+use async_wsocket::futures_util::{Sink, Stream};
+use async_wsocket::{ConnectionMode, Message};
+use nostr::util::BoxedFuture;
+use nostr::{RelayUrl, Url};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
+
+use super::error::TransportError;
+use super::websocket::{BoxSink, BoxStream, WebSocketTransport};
+
+type Handler = Arc<dyn Fn(LoopbackConnection) -> BoxedFuture<'static, ()> + Send + Sync>;
+
+/// Loopback error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopbackError {
+    /// No handler registered for the URL or connections refused
+    ConnectionRefused,
+    /// Connection closed
+    Disconnected,
+}
+
+impl std::error::Error for LoopbackError {}
+
+impl fmt::Display for LoopbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConnectionRefused => write!(f, "connection refused"),
+            Self::Disconnected => write!(f, "disconnected"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct InnerLoopbackTransport {
+    handlers: Mutex<HashMap<Url, Handler>>,
+    refused: Mutex<HashSet<Url>>,
+    connections: Mutex<HashMap<Url, Vec<watch::Sender<bool>>>>,
+    latency: Mutex<Option<Duration>>,
+    drop_next: AtomicUsize,
+}
+
+impl InnerLoopbackTransport {
+    fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        *Self::lock(&self.latency)
+    }
+
+    /// Check if the message must be dropped, consuming a drop
+    fn must_drop(&self) -> bool {
+        self.drop_next
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+/// In-process loopback transport
+///
+/// Every connection to a registered URL spawns a new instance of the handler, that receives a [`LoopbackConnection`].
+/// Connections to not registered URLs are refused.
+#[derive(Clone, Default)]
+pub struct LoopbackTransport {
+    inner: Arc<InnerLoopbackTransport>,
+}
+
+impl fmt::Debug for LoopbackTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let handlers = InnerLoopbackTransport::lock(&self.inner.handlers);
+        f.debug_struct("LoopbackTransport")
+            .field(
+                "relays",
+                &handlers.keys().map(|url| url.as_str()).collect::<Vec<_>>(),
+            )
+            .field("latency", &self.inner.latency())
+            .finish()
+    }
+}
+
+impl LoopbackTransport {
+    /// New loopback transport
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a handler for the relay URL
+    ///
+    /// The handler is called for every new connection.
+    /// Dropping the [`LoopbackConnection`] closes the connection.
+    pub fn add_relay<F, Fut>(&self, url: &RelayUrl, handler: F)
+    where
+        F: Fn(LoopbackConnection) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handler: Handler = Arc::new(move |conn| Box::pin(handler(conn)));
+        let mut handlers = InnerLoopbackTransport::lock(&self.inner.handlers);
+        handlers.insert(to_url(url), handler);
+    }
+
+    /// Remove the handler of the relay URL
+    ///
+    /// The open connections are closed.
+    pub fn remove_relay(&self, url: &RelayUrl) {
+        let mut handlers = InnerLoopbackTransport::lock(&self.inner.handlers);
+        handlers.remove(&to_url(url));
+        drop(handlers);
+
+        self.disconnect(url);
+    }
+
+    /// Set the latency applied to every message, in both directions
+    pub fn set_latency(&self, latency: Option<Duration>) {
+        let mut current = InnerLoopbackTransport::lock(&self.inner.latency);
+        *current = latency;
+    }
+
+    /// Silently drop the next `n` messages, in any direction
+    pub fn drop_next(&self, n: usize) {
+        self.inner.drop_next.store(n, Ordering::SeqCst);
+    }
+
+    /// Refuse (or accept again) the new connections to the relay URL
+    pub fn refuse_connections(&self, url: &RelayUrl, refuse: bool) {
+        let mut refused = InnerLoopbackTransport::lock(&self.inner.refused);
+        if refuse {
+            refused.insert(to_url(url));
+        } else {
+            refused.remove(&to_url(url));
+        }
+    }
+
+    /// Close all the open connections to the relay URL
+    ///
+    /// The relay is free to reconnect, unless new connections are refused (see [`LoopbackTransport::refuse_connections`]).
+    pub fn disconnect(&self, url: &RelayUrl) {
+        let mut connections = InnerLoopbackTransport::lock(&self.inner.connections);
+        if let Some(senders) = connections.remove(&to_url(url)) {
+            for sender in senders.into_iter() {
+                let _ = sender.send(true);
+            }
+        }
+    }
+
+    /// Get the number of open connections to the relay URL
+    pub fn connections(&self, url: &RelayUrl) -> usize {
+        let mut connections = InnerLoopbackTransport::lock(&self.inner.connections);
+        match connections.get_mut(&to_url(url)) {
+            Some(senders) => {
+                senders.retain(|s| !s.is_closed());
+                senders.len()
+            }
+            None => 0,
+        }
+    }
+
+    fn open(&self, url: &Url) -> Result<(BoxSink, BoxStream), TransportError> {
+        if InnerLoopbackTransport::lock(&self.inner.refused).contains(url) {
+            return Err(TransportError::backend(LoopbackError::ConnectionRefused));
+        }
+
+        let handler: Handler = match InnerLoopbackTransport::lock(&self.inner.handlers).get(url) {
+            Some(handler) => handler.clone(),
+            None => return Err(TransportError::backend(LoopbackError::ConnectionRefused)),
+        };
+
+        // Client -> transport -> server
+        let (client_tx, client_out) = mpsc::unbounded_channel::<Message>();
+        let (server_in, server_rx) = mpsc::unbounded_channel::<String>();
+
+        // Server -> transport -> client
+        let (server_tx, server_out) = mpsc::unbounded_channel::<String>();
+        let (client_in, client_rx) = mpsc::unbounded_channel::<Message>();
+
+        // Disconnection signal
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let mut connections = InnerLoopbackTransport::lock(&self.inner.connections);
+        let senders = connections.entry(url.clone()).or_default();
+        senders.retain(|s| !s.is_closed());
+        senders.push(shutdown_tx);
+        drop(connections);
+
+        // Forward client messages
+        task::spawn(forward(
+            self.inner.clone(),
+            client_out,
+            server_in,
+            shutdown_rx.clone(),
+            |msg| match msg {
+                Message::Text(json) => Forward::Send(json),
+                Message::Close(..) => Forward::Close,
+                _ => Forward::Skip,
+            },
+        ));
+
+        // Forward server messages
+        task::spawn(forward(
+            self.inner.clone(),
+            server_out,
+            client_in,
+            shutdown_rx,
+            |json| Forward::Send(Message::Text(json)),
+        ));
+
+        // Spawn handler
+        task::spawn(handler(LoopbackConnection {
+            tx: server_tx,
+            rx: server_rx,
+        }));
+
+        let sink: BoxSink = Box::new(LoopbackSink { tx: client_tx }) as BoxSink;
+        let stream: BoxStream = Box::new(LoopbackStream { rx: client_rx }) as BoxStream;
+        Ok((sink, stream))
+    }
+}
+
+impl WebSocketTransport for LoopbackTransport {
+    fn support_ping(&self) -> bool {
+        false
+    }
+
+    fn connect<'a>(
+        &'a self,
+        url: &'a Url,
+        _mode: &'a ConnectionMode,
+        _timeout: Duration,
+    ) -> BoxedFuture<'a, Result<(BoxSink, BoxStream), TransportError>> {
+        Box::pin(async move {
+            // Simulate the handshake
+            if let Some(latency) = self.inner.latency() {
+                time::sleep(latency).await;
+            }
+
+            self.open(url)
+        })
+    }
+}
+
+enum Forward<T> {
+    Send(T),
+    Skip,
+    Close,
+}
+
+async fn forward<I, O, F>(
+    inner: Arc<InnerLoopbackTransport>,
+    mut rx: UnboundedReceiver<I>,
+    tx: UnboundedSender<O>,
+    mut shutdown: watch::Receiver<bool>,
+    map: F,
+) where
+    F: Fn(I) -> Forward<O>,
+{
+    loop {
+        let item: I = tokio::select! {
+            item = rx.recv() => match item {
+                Some(item) => item,
+                None => break,
+            },
+            _ = shutdown.changed() => break,
+        };
+
+        let item: O = match map(item) {
+            Forward::Send(item) => item,
+            Forward::Skip => continue,
+            Forward::Close => break,
+        };
+
+        if let Some(latency) = inner.latency() {
+            tokio::select! {
+                _ = time::sleep(latency) => {},
+                _ = shutdown.changed() => break,
+            }
+        }
+
+        if inner.must_drop() {
+            continue;
+        }
+
+        if tx.send(item).is_err() {
+            break;
+        }
+    }
+}
+
+fn to_url(url: &RelayUrl) -> Url {
+    let url: &Url = url.into();
+    url.clone()
+}
+
+/// Server end of a loopback connection
+///
+/// Receive the JSON messages sent by the client and send the JSON messages to it.
+#[derive(Debug)]
+pub struct LoopbackConnection {
+    tx: UnboundedSender<String>,
+    rx: UnboundedReceiver<String>,
+}
+
+impl Stream for LoopbackConnection {
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Sink<String> for LoopbackConnection {
+    type Error = TransportError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: String) -> Result<(), Self::Error> {
+        self.tx
+            .send(item)
+            .map_err(|_| TransportError::backend(LoopbackError::Disconnected))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+struct LoopbackSink {
+    tx: UnboundedSender<Message>,
+}
+
+impl Sink<Message> for LoopbackSink {
+    type Error = TransportError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        self.tx
+            .send(item)
+            .map_err(|_| TransportError::backend(LoopbackError::Disconnected))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+struct LoopbackStream {
+    rx: UnboundedReceiver<Message>,
+}
+
+impl Stream for LoopbackStream {
+    type Item = Result<Message, TransportError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx).map(|msg| msg.map(Ok))
+    }
+}
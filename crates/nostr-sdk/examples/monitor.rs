@@ -9,7 +9,10 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
     let monitor = Monitor::new(4096);
-    let client = Client::builder().monitor(monitor).build();
+    let client = Client::builder()
+        .monitor(monitor)
+        .health_policy(RelayHealthPolicy::default())
+        .build();
 
     // Subscribe to monitor notifications
     let mut notifications = client.monitor().unwrap().subscribe();
@@ -25,6 +28,17 @@ async fn main() -> Result<()> {
             MonitorNotification::StatusChanged { relay_url, status } => {
                 println!("Relay status changed for {relay_url}: {status}")
             }
+            MonitorNotification::HealthDegraded {
+                relay_url,
+                action,
+                reason,
+            } => {
+                println!("Relay {relay_url} is unhealthy ({reason:?}): {action:?}")
+            }
+            MonitorNotification::HealthRestored { relay_url } => {
+                println!("Relay {relay_url} is healthy again")
+            }
+            notification => println!("{notification:?}"),
         }
     }
 
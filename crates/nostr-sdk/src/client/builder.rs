@@ -8,9 +8,12 @@ use std::sync::Arc;
 
 use nostr::signer::{IntoNostrSigner, NostrSigner};
 use nostr_database::memory::MemoryDatabase;
-use nostr_database::{IntoNostrDatabase, NostrDatabase};
+use nostr_database::{
+    IntoNostrDatabase, NostrDatabase, NostrDatabaseGossip, NostrDatabaseHealth, NostrDatabaseOutbox,
+};
 use nostr_relay_pool::monitor::Monitor;
 use nostr_relay_pool::policy::AdmitPolicy;
+use nostr_relay_pool::pool::health::RelayHealthPolicy;
 use nostr_relay_pool::transport::websocket::{
     DefaultWebsocketTransport, IntoWebSocketTransport, WebSocketTransport,
 };
@@ -28,8 +31,16 @@ pub struct ClientBuilder {
     pub admit_policy: Option<Arc<dyn AdmitPolicy>>,
     /// Database
     pub database: Arc<dyn NostrDatabase>,
+    /// Gossip store
+    pub gossip_store: Option<Arc<dyn NostrDatabaseGossip>>,
+    /// Outbox store
+    pub outbox: Option<Arc<dyn NostrDatabaseOutbox>>,
     /// Relay monitor
     pub monitor: Option<Monitor>,
+    /// Relay health policy
+    pub health_policy: Option<RelayHealthPolicy>,
+    /// Relay health scores store
+    pub health_scores: Option<Arc<dyn NostrDatabaseHealth>>,
     /// Client options
     pub opts: Options,
 }
@@ -41,7 +52,11 @@ impl Default for ClientBuilder {
             websocket_transport: Arc::new(DefaultWebsocketTransport),
             admit_policy: None,
             database: Arc::new(MemoryDatabase::default()),
+            gossip_store: None,
+            outbox: None,
             monitor: None,
+            health_policy: None,
+            health_scores: None,
             opts: Options::default(),
         }
     }
@@ -105,6 +120,24 @@ impl ClientBuilder {
         self
     }
 
+    /// Set gossip store
+    ///
+    /// Persists the gossip statuses of the public keys, so the relay lists aren't re-fetched after a restart.
+    #[inline]
+    pub fn gossip_store(mut self, store: Arc<dyn NostrDatabaseGossip>) -> Self {
+        self.gossip_store = Some(store);
+        self
+    }
+
+    /// Set outbox store
+    ///
+    /// Used by the relays with the offline outbox enabled (see [`RelayOptions::outbox`](crate::RelayOptions::outbox)).
+    #[inline]
+    pub fn outbox(mut self, store: Arc<dyn NostrDatabaseOutbox>) -> Self {
+        self.outbox = Some(store);
+        self
+    }
+
     /// Set monitor
     #[inline]
     pub fn monitor(mut self, monitor: Monitor) -> Self {
@@ -112,6 +145,22 @@ impl ClientBuilder {
         self
     }
 
+    /// Set relay health policy
+    #[inline]
+    pub fn health_policy(mut self, policy: RelayHealthPolicy) -> Self {
+        self.health_policy = Some(policy);
+        self
+    }
+
+    /// Set relay health scores store
+    ///
+    /// Persists the scores computed with the health policy, to restore them after a restart.
+    #[inline]
+    pub fn health_scores(mut self, store: Arc<dyn NostrDatabaseHealth>) -> Self {
+        self.health_scores = Some(store);
+        self
+    }
+
     /// Set opts
     #[inline]
     pub fn opts(mut self, opts: Options) -> Self {
@@ -27,6 +27,9 @@ pub enum Error {
     Json(serde_json::Error),
     /// Shared state error
     SharedState(SharedStateError),
+    /// NIP11
+    #[cfg(feature = "nip11")]
+    NIP11(nip11::Error),
     /// NIP59
     #[cfg(feature = "nip59")]
     NIP59(nip59::Error),
@@ -38,6 +41,8 @@ pub enum Error {
     GossipFiltersEmpty,
     /// Private message (NIP17) relays not found
     PrivateMsgRelaysNotFound,
+    /// Relay public key not found in the NIP11 document
+    RelayPublicKeyNotFound,
 }
 
 impl std::error::Error for Error {}
@@ -52,6 +57,8 @@ impl fmt::Display for Error {
             Self::EventBuilder(e) => write!(f, "{e}"),
             Self::Json(e) => write!(f, "{e}"),
             Self::SharedState(e) => write!(f, "{e}"),
+            #[cfg(feature = "nip11")]
+            Self::NIP11(e) => write!(f, "{e}"),
             #[cfg(feature = "nip59")]
             Self::NIP59(e) => write!(f, "{e}"),
             Self::EventNotFound(id) => {
@@ -64,6 +71,9 @@ impl fmt::Display for Error {
                 write!(f, "gossip broken down filters are empty")
             }
             Self::PrivateMsgRelaysNotFound => write!(f, "Private message relays not found. The user is not ready to receive private messages."),
+            Self::RelayPublicKeyNotFound => {
+                write!(f, "relay public key not found in the information document")
+            }
         }
     }
 }
@@ -110,6 +120,13 @@ impl From<SharedStateError> for Error {
     }
 }
 
+#[cfg(feature = "nip11")]
+impl From<nip11::Error> for Error {
+    fn from(e: nip11::Error) -> Self {
+        Self::NIP11(e)
+    }
+}
+
 #[cfg(feature = "nip59")]
 impl From<nip59::Error> for Error {
     fn from(e: nip59::Error) -> Self {
@@ -7,13 +7,21 @@
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::iter;
+#[cfg(feature = "nip11")]
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
+#[cfg(feature = "nip11")]
+use async_utility::task;
 use nostr::prelude::*;
 use nostr_database::prelude::*;
 use nostr_relay_pool::prelude::*;
 use tokio::sync::broadcast;
+#[cfg(feature = "nip11")]
+use tokio::sync::broadcast::error::RecvError;
+#[cfg(feature = "nip11")]
+use tokio::sync::mpsc;
 
 pub mod builder;
 mod error;
@@ -21,10 +29,10 @@ pub mod options;
 
 pub use self::builder::ClientBuilder;
 pub use self::error::Error;
-pub use self::options::Options;
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::options::{Connection, ConnectionTarget};
-use crate::gossip::{BrokenDownFilters, Gossip};
+pub use self::options::{GossipOptions, Options};
+use crate::gossip::{selection, BrokenDownFilters, Gossip};
 
 /// Nostr client
 #[derive(Debug, Clone)]
@@ -79,11 +87,21 @@ impl Client {
     }
 
     fn from_builder(builder: ClientBuilder) -> Self {
+        // Construct gossip graph
+        let gossip: Gossip = Gossip::new(
+            builder.database.clone(),
+            builder.gossip_store,
+            builder.opts.gossip_opts,
+        );
+
         // Construct relay pool builder
         let pool_builder: RelayPoolBuilder = RelayPoolBuilder {
             websocket_transport: builder.websocket_transport,
             admit_policy: builder.admit_policy,
             monitor: builder.monitor,
+            health_policy: builder.health_policy,
+            health_scores: builder.health_scores,
+            outbox: builder.outbox,
             opts: builder.opts.pool,
             __database: builder.database,
             __signer: builder.signer,
@@ -92,7 +110,7 @@ impl Client {
         // Construct client
         Self {
             pool: pool_builder.build(),
-            gossip: Gossip::new(),
+            gossip,
             opts: builder.opts,
         }
     }
@@ -705,6 +723,15 @@ impl Client {
         Ok(self.pool.sync_with(urls, filter, opts).await?)
     }
 
+    /// Construct a [`SyncScheduler`] to periodically sync filters with relays
+    ///
+    /// To resume from the last successful sync of each relay after a restart, set [`SyncScheduler::checkpoints`].
+    /// Check [`SyncScheduler`] for the other options.
+    #[inline]
+    pub fn sync_scheduler(&self) -> SyncScheduler {
+        SyncScheduler::new(&self.pool)
+    }
+
     /// Fetch events from relays
     ///
     /// # Overview
@@ -905,6 +932,52 @@ impl Client {
             .await?)
     }
 
+    /// Fetch events from relays, walking the history past the max limit of the relays
+    ///
+    /// # Overview
+    ///
+    /// The events are requested in pages, moving the `until` back to the oldest received event,
+    /// until the relays have no older events, the `since` of the filter is reached
+    /// or [`PaginationOptions::max_events`] events have been received.
+    /// Useful to backfill complete histories (i.e., all the notes of an author).
+    ///
+    /// The events are requested to the relays with [`RelayServiceFlags::READ`] flag.
+    /// To use specific relays, check [`RelayPool::fetch_paginated_events_from`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use nostr_sdk::prelude::*;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// # let client = Client::default();
+    /// # let public_key = Keys::generate().public_key();
+    /// let filter = Filter::new().author(public_key).kind(Kind::TextNote);
+    /// let opts = PaginationOptions::new().max_events(10_000);
+    /// let events: Events = client.fetch_paginated_events(filter, opts).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub async fn fetch_paginated_events(
+        &self,
+        filter: Filter,
+        opts: PaginationOptions,
+    ) -> Result<Events, Error> {
+        Ok(self.pool.fetch_paginated_events(filter, opts).await?)
+    }
+
+    /// Stream events from relays, walking the history past the max limit of the relays
+    ///
+    /// Check [`Client::fetch_paginated_events`] to learn more.
+    #[inline]
+    pub async fn stream_paginated_events(
+        &self,
+        filter: Filter,
+        opts: PaginationOptions,
+    ) -> Result<ReceiverStream<Event>, Error> {
+        Ok(self.pool.stream_paginated_events(filter, opts).await?)
+    }
+
     /// Send the client message to a **specific relays**
     #[inline]
     pub async fn send_msg_to<I, U>(
@@ -1319,6 +1392,155 @@ impl Client {
     }
 }
 
+// NIP29
+#[cfg(feature = "nip11")]
+impl Client {
+    /// Follow the state of a relay-based group
+    ///
+    /// Add and connect the group relay (if needed), load the state from the database
+    /// and subscribe to the group events published by the relay and to the moderation events.
+    /// The initial [`GroupState`] is sent immediately, then a new one every time it changes.
+    ///
+    /// The events published by the relay must be signed by the `pubkey` of its NIP11 document:
+    /// returns [`Error::RelayPublicKeyNotFound`] if the document doesn't include it.
+    ///
+    /// The subscriptions are closed when the returned stream is dropped.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    pub async fn follow_group(
+        &self,
+        address: &GroupAddress,
+    ) -> Result<ReceiverStream<GroupState>, Error> {
+        let relay_url: &RelayUrl = &address.relay_url;
+
+        // Add and connect the group relay
+        self.add_relay(relay_url).await?;
+        self.connect_relay(relay_url).await?;
+
+        let relay: Relay = self.relay(relay_url).await?;
+        let relay_public_key: PublicKey = get_relay_public_key(&relay).await?;
+
+        let filters: [Filter; 2] = [
+            Filter::new()
+                .kinds([
+                    Kind::GroupMetadata,
+                    Kind::GroupAdmins,
+                    Kind::GroupMembers,
+                    Kind::GroupRoles,
+                ])
+                .identifier(address.id.clone()),
+            Filter::new()
+                .kinds([
+                    Kind::GroupPutUser,
+                    Kind::GroupRemoveUser,
+                    Kind::GroupEditMetadata,
+                    Kind::GroupDelete,
+                ])
+                .group(address.id.clone()),
+        ];
+
+        // Listen for notifications before subscribing, to not miss any event
+        let mut notifications = relay.notifications();
+
+        // Load the state from the database
+        let database: Arc<dyn NostrDatabase> = self.database().clone();
+        let mut state: GroupState = GroupState::new(address.id.clone(), relay_public_key);
+        load_group_state(&database, &filters, &mut state).await?;
+
+        let mut ids: Vec<SubscriptionId> = Vec::with_capacity(filters.len());
+        for filter in filters.iter() {
+            let id: SubscriptionId = relay
+                .subscribe(filter.clone(), SubscribeOptions::default())
+                .await?;
+            ids.push(id);
+        }
+
+        let (tx, rx) = mpsc::channel(1024);
+        let _ = tx.send(state.clone()).await;
+
+        task::spawn(async move {
+            loop {
+                tokio::select! {
+                    notification = notifications.recv() => {
+                        let changed: bool = match notification {
+                            Ok(RelayNotification::Event {
+                                subscription_id,
+                                event,
+                            }) if ids.contains(&subscription_id) => state.apply(&event),
+                            Ok(RelayNotification::Shutdown) | Err(RecvError::Closed) => break,
+                            // Missed some events: reload the state from the database
+                            Err(RecvError::Lagged(..)) => {
+                                let prev: GroupState = state.clone();
+                                if let Err(e) =
+                                    load_group_state(&database, &filters, &mut state).await
+                                {
+                                    tracing::error!(error = %e, "Can't load group state.");
+                                }
+                                state != prev
+                            }
+                            Ok(..) => false,
+                        };
+
+                        if changed && tx.send(state.clone()).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = tx.closed() => break,
+                }
+            }
+
+            for id in ids.iter() {
+                if let Err(e) = relay.unsubscribe(id).await {
+                    tracing::error!(id = %id, error = %e, "Can't close group subscription.");
+                }
+            }
+        });
+
+        Ok(ReceiverStream::new(rx))
+    }
+}
+
+/// Get the relay public key from its NIP11 document
+#[cfg(feature = "nip11")]
+async fn get_relay_public_key(relay: &Relay) -> Result<PublicKey, Error> {
+    let mut document: RelayInformationDocument = relay.document().await;
+
+    // The document is fetched in background after the connection: may not be available yet
+    if document.pubkey.is_none() {
+        let proxy: Option<SocketAddr> = match relay.connection_mode() {
+            ConnectionMode::Direct => None,
+            #[cfg(not(target_arch = "wasm32"))]
+            ConnectionMode::Proxy(proxy) => Some(*proxy),
+            #[cfg(all(feature = "tor", not(target_arch = "wasm32")))]
+            ConnectionMode::Tor { .. } => return Err(Error::RelayPublicKeyNotFound),
+        };
+        document = RelayInformationDocument::get(relay.url().clone().into(), proxy).await?;
+    }
+
+    document
+        .pubkey
+        .and_then(|pubkey| PublicKey::from_hex(&pubkey).ok())
+        .ok_or(Error::RelayPublicKeyNotFound)
+}
+
+#[cfg(feature = "nip11")]
+async fn load_group_state(
+    database: &Arc<dyn NostrDatabase>,
+    filters: &[Filter],
+    state: &mut GroupState,
+) -> Result<(), Error> {
+    for filter in filters.iter() {
+        let events: Events = database.query(filter.clone()).await?;
+
+        // Oldest first
+        for event in events.into_iter().rev() {
+            state.apply(&event);
+        }
+    }
+
+    Ok(())
+}
+
 // Gossip
 impl Client {
     /// Check if there are outdated public keys and update them
@@ -1376,20 +1598,38 @@ impl Client {
         // Check and update outdated public keys
         self.check_and_update_gossip(public_keys).await?;
 
+        // Score the relays already in the pool
+        let gossip_opts: &GossipOptions = &self.opts.gossip_opts;
+        let scores: HashMap<RelayUrl, f64> =
+            if gossip_opts.relay_stats && gossip_opts.max_relays_per_pubkey.is_some() {
+                self.pool
+                    .all_relays()
+                    .await
+                    .into_iter()
+                    .map(|(url, relay)| {
+                        let score: f64 = selection::relay_score(relay.stats());
+                        (url, score)
+                    })
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
         // Broken-down filters
-        let filters: HashMap<RelayUrl, Filter> = match self.gossip.break_down_filter(filter).await {
-            BrokenDownFilters::Filters(filters) => filters,
-            BrokenDownFilters::Orphan(filter) | BrokenDownFilters::Other(filter) => {
-                // Get read relays
-                let read_relays: Vec<RelayUrl> = self.pool.__read_relay_urls().await;
-
-                let mut map = HashMap::with_capacity(read_relays.len());
-                for url in read_relays.into_iter() {
-                    map.insert(url, filter.clone());
+        let filters: HashMap<RelayUrl, Filter> =
+            match self.gossip.break_down_filter(filter, &scores).await {
+                BrokenDownFilters::Filters(filters) => filters,
+                BrokenDownFilters::Orphan(filter) | BrokenDownFilters::Other(filter) => {
+                    // Get read relays
+                    let read_relays: Vec<RelayUrl> = self.pool.__read_relay_urls().await;
+
+                    let mut map = HashMap::with_capacity(read_relays.len());
+                    for url in read_relays.into_iter() {
+                        map.insert(url, filter.clone());
+                    }
+                    map
                 }
-                map
-            }
-        };
+            };
 
         // Add gossip (outbox and inbox) relays
         for url in filters.keys() {
@@ -1558,3 +1798,134 @@ impl Client {
         Ok(self.pool.sync_targeted(filters, opts).await?)
     }
 }
+
+#[cfg(all(test, feature = "nip11"))]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use nostr_relay_builder::prelude::*;
+
+    use super::*;
+
+    async fn next_state(stream: &mut ReceiverStream<GroupState>) -> GroupState {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_follow_group() {
+        let relay_keys = Keys::generate();
+        let admin = Keys::generate();
+        let user = Keys::generate();
+        let stranger = Keys::generate();
+
+        let document = RelayInformationDocument {
+            pubkey: Some(relay_keys.public_key().to_hex()),
+            ..Default::default()
+        };
+        let mock = LocalRelay::run(RelayBuilder::default().nip11(document))
+            .await
+            .unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let publisher = Client::default();
+        publisher.add_relay(&url).await.unwrap();
+        publisher
+            .try_connect_relay(&url, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let now: Timestamp = Timestamp::now();
+        let event = |keys: &Keys, builder: EventBuilder, secs_ago: u64| -> Event {
+            builder
+                .custom_created_at(now - Duration::from_secs(secs_ago))
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+
+        // Group admins, published by the relay
+        let tags: Vec<Tag> = GroupAdmins {
+            id: String::from("pizza"),
+            admins: vec![GroupAdmin {
+                public_key: admin.public_key(),
+                roles: vec![String::from("ceo")],
+            }],
+        }
+        .into();
+        publisher
+            .send_event(&event(
+                &relay_keys,
+                EventBuilder::new(Kind::GroupAdmins, "").tags(tags),
+                30,
+            ))
+            .await
+            .unwrap();
+
+        let client = Client::default();
+        let mut stream = client
+            .follow_group(&GroupAddress::new(url, "pizza"))
+            .await
+            .unwrap();
+
+        // Initial state, then the snapshot received from the relay
+        let mut state: GroupState = next_state(&mut stream).await;
+        if !state.is_admin(&admin.public_key()) {
+            state = next_state(&mut stream).await;
+        }
+        assert!(state.is_admin(&admin.public_key()));
+
+        // Not signed by the relay or not authored by an admin: ignored
+        let tags: Vec<Tag> = GroupMembers {
+            id: String::from("pizza"),
+            members: BTreeSet::from([stranger.public_key()]),
+        }
+        .into();
+        publisher
+            .send_event(&event(
+                &stranger,
+                EventBuilder::new(Kind::GroupMembers, "").tags(tags),
+                25,
+            ))
+            .await
+            .unwrap();
+        publisher
+            .send_event(&event(
+                &stranger,
+                EventBuilder::group_put_user("pizza", stranger.public_key(), ["ceo"]),
+                25,
+            ))
+            .await
+            .unwrap();
+
+        // Put user
+        publisher
+            .send_event(&event(
+                &admin,
+                EventBuilder::group_put_user("pizza", user.public_key(), Vec::<String>::new()),
+                20,
+            ))
+            .await
+            .unwrap();
+
+        let state: GroupState = next_state(&mut stream).await;
+        assert!(state.is_member(&user.public_key()));
+        assert!(!state.is_member(&stranger.public_key()));
+        assert!(!state.is_admin(&stranger.public_key()));
+
+        // Remove user
+        publisher
+            .send_event(&event(
+                &admin,
+                EventBuilder::group_remove_user("pizza", user.public_key()),
+                10,
+            ))
+            .await
+            .unwrap();
+
+        let state: GroupState = next_state(&mut stream).await;
+        assert!(!state.is_member(&user.public_key()));
+        assert!(state.is_admin(&admin.public_key()));
+    }
+}
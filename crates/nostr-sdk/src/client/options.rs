@@ -17,6 +17,7 @@ use nostr_relay_pool::prelude::*;
 pub struct Options {
     pub(super) autoconnect: bool,
     pub(super) gossip: bool,
+    pub(super) gossip_opts: GossipOptions,
     #[cfg(not(target_arch = "wasm32"))]
     pub(super) connection: Connection,
     pub(super) relay_limits: RelayLimits,
@@ -71,6 +72,15 @@ impl Options {
         self
     }
 
+    /// Set gossip options
+    ///
+    /// Used only if gossip is enabled (see [`Options::gossip`]).
+    #[inline]
+    pub fn gossip_options(mut self, opts: GossipOptions) -> Self {
+        self.gossip_opts = opts;
+        self
+    }
+
     /// Connection mode and target
     #[inline]
     #[cfg(not(target_arch = "wasm32"))]
@@ -110,6 +120,52 @@ impl Options {
     }
 }
 
+/// Gossip options
+///
+/// Control how the relays are selected when a filter is broken down by the gossip model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GossipOptions {
+    pub(crate) max_relays_per_pubkey: Option<usize>,
+    pub(crate) relay_stats: bool,
+}
+
+impl Default for GossipOptions {
+    fn default() -> Self {
+        Self {
+            max_relays_per_pubkey: None,
+            relay_stats: true,
+        }
+    }
+}
+
+impl GossipOptions {
+    /// New default gossip options
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max number of relays to use for every public key (default: None)
+    ///
+    /// The relays shared by many public keys are preferred, to minimize the number of connections.
+    ///
+    /// `None` means no limits: all the relays of every public key will be used.
+    #[inline]
+    pub fn max_relays_per_pubkey(mut self, max: Option<usize>) -> Self {
+        self.max_relays_per_pubkey = max;
+        self
+    }
+
+    /// Penalize relays with a poor connection success rate or a high latency (default: true)
+    ///
+    /// Used only if [`GossipOptions::max_relays_per_pubkey`] is set.
+    #[inline]
+    pub fn penalize_unreliable_relays(mut self, enable: bool) -> Self {
+        self.relay_stats = enable;
+        self
+    }
+}
+
 /// Connection target
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
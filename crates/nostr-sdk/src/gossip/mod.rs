@@ -6,11 +6,16 @@ use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 
 use nostr::prelude::*;
-use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use nostr_database::{
+    DatabaseError, GossipPublicKeyStatus, NostrDatabase, NostrDatabaseGossip, NostrEventsDatabase,
+};
+use tokio::sync::{OnceCell, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 pub mod constant;
+pub mod selection;
 
 use self::constant::{CHECK_OUTDATED_INTERVAL, MAX_RELAYS_LIST, PUBKEY_METADATA_OUTDATED_AFTER};
+use crate::client::GossipOptions;
 
 const P_TAG: SingleLetterTag = SingleLetterTag::lowercase(Alphabet::P);
 
@@ -41,19 +46,115 @@ struct RelayLists {
     pub last_check: Timestamp,
 }
 
+impl RelayLists {
+    fn status(&self) -> GossipPublicKeyStatus {
+        GossipPublicKeyStatus {
+            last_check: self.last_check,
+            nip17_last_update: self.nip17.last_update,
+            nip65_last_update: self.nip65.last_update,
+        }
+    }
+}
+
 type PublicKeyMap = HashMap<PublicKey, RelayLists>;
 
 /// Gossip tracker
+///
+/// The graph is hydrated from the database on first use. If a gossip store is set, the statuses of the public keys
+/// are written back to it, so a warm start doesn't need to re-fetch the relay lists before routing the requests.
 #[derive(Debug, Clone)]
 pub struct Gossip {
     /// Keep track of seen public keys and of their NIP65
     public_keys: Arc<RwLock<PublicKeyMap>>,
+    database: Arc<dyn NostrDatabase>,
+    store: Option<Arc<dyn NostrDatabaseGossip>>,
+    loaded: Arc<OnceCell<()>>,
+    opts: GossipOptions,
 }
 
 impl Gossip {
-    pub fn new() -> Self {
+    pub fn new(
+        database: Arc<dyn NostrDatabase>,
+        store: Option<Arc<dyn NostrDatabaseGossip>>,
+        opts: GossipOptions,
+    ) -> Self {
         Self {
             public_keys: Arc::new(RwLock::new(HashMap::new())),
+            database,
+            store,
+            loaded: Arc::new(OnceCell::new()),
+            opts,
+        }
+    }
+
+    /// Load the graph from the database, if not already done.
+    async fn ensure_loaded(&self) {
+        self.loaded
+            .get_or_init(|| async {
+                if let Err(e) = self.load().await {
+                    tracing::error!(error = %e, "Failed to load gossip graph from database.");
+                }
+            })
+            .await;
+    }
+
+    async fn load(&self) -> Result<(), DatabaseError> {
+        // Get the relay lists stored in the database
+        let filter: Filter = Filter::new().kinds([Kind::RelayList, Kind::InboxRelays]);
+        let events = self.database.query(filter).await?;
+
+        // Get the timestamps of the last checks and updates
+        let statuses: HashMap<PublicKey, GossipPublicKeyStatus> = match &self.store {
+            Some(store) => store.gossip_statuses().await?,
+            None => HashMap::new(),
+        };
+
+        let mut public_keys = self.public_keys.write().await;
+
+        // The relay lists without a stored status are considered outdated
+        for event in events.into_iter() {
+            self.update_event(&mut public_keys, &event, Timestamp::zero());
+        }
+
+        for (public_key, status) in statuses.into_iter() {
+            let lists: &mut RelayLists = public_keys.entry(public_key).or_default();
+            lists.last_check = status.last_check;
+            lists.nip17.last_update = status.nip17_last_update;
+            lists.nip65.last_update = status.nip65_last_update;
+        }
+
+        tracing::debug!(
+            public_keys = public_keys.len(),
+            "Gossip graph loaded from database."
+        );
+
+        Ok(())
+    }
+
+    /// Write back the status of the public keys to the database
+    async fn save<I>(&self, public_keys: I)
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let store: &Arc<dyn NostrDatabaseGossip> = match &self.store {
+            Some(store) => store,
+            None => return,
+        };
+
+        let statuses: HashMap<PublicKey, GossipPublicKeyStatus> = {
+            let map = self.public_keys.read().await;
+            public_keys
+                .into_iter()
+                .filter_map(|p| Some((p, map.get(&p)?.status())))
+                .collect()
+        };
+
+        if statuses.is_empty() {
+            return;
+        }
+
+        if let Err(e) = store.save_gossip_statuses(statuses).await {
+            tracing::error!(error = %e, "Failed to save gossip statuses.");
         }
     }
 
@@ -64,11 +165,18 @@ impl Gossip {
             return;
         }
 
-        // Acquire write lock
-        let mut public_keys = self.public_keys.write().await;
+        self.ensure_loaded().await;
 
         // Update
-        self.update_event(&mut public_keys, event);
+        let updated: bool = {
+            // Acquire write lock
+            let mut public_keys = self.public_keys.write().await;
+            self.update_event(&mut public_keys, event, Timestamp::now())
+        };
+
+        if updated {
+            self.save([event.pubkey]).await;
+        }
     }
 
     /// Update graph
@@ -78,69 +186,65 @@ impl Gossip {
     where
         I: IntoIterator<Item = Event>,
     {
-        let mut public_keys = self.public_keys.write().await;
+        self.ensure_loaded().await;
 
-        for event in events.into_iter() {
-            self.update_event(&mut public_keys, &event);
+        let mut updated: HashSet<PublicKey> = HashSet::new();
+
+        {
+            let mut public_keys = self.public_keys.write().await;
+
+            for event in events.into_iter() {
+                if self.update_event(&mut public_keys, &event, Timestamp::now()) {
+                    updated.insert(event.pubkey);
+                }
+            }
         }
+
+        self.save(updated).await;
     }
 
-    fn update_event(&self, public_keys: &mut RwLockWriteGuard<PublicKeyMap>, event: &Event) {
+    /// Update the relay list of the event author
+    ///
+    /// Returns `true` if the graph has been updated.
+    fn update_event(
+        &self,
+        public_keys: &mut RwLockWriteGuard<PublicKeyMap>,
+        event: &Event,
+        last_update: Timestamp,
+    ) -> bool {
         if event.kind == Kind::RelayList {
-            public_keys
-                .entry(event.pubkey)
-                .and_modify(|lists| {
-                    // Update only if new metadata has more recent timestamp
-                    if event.created_at >= lists.nip65.event_created_at {
-                        lists.nip65 = RelayList {
-                            collection: nip65::extract_relay_list(event)
-                                .take(MAX_RELAYS_LIST)
-                                .map(|(u, m)| (u.clone(), *m))
-                                .collect(),
-                            event_created_at: event.created_at,
-                            last_update: Timestamp::now(),
-                        };
-                    }
-                })
-                .or_insert_with(|| RelayLists {
-                    nip65: RelayList {
-                        collection: nip65::extract_relay_list(event)
-                            .take(MAX_RELAYS_LIST)
-                            .map(|(u, m)| (u.clone(), *m))
-                            .collect(),
-                        event_created_at: event.created_at,
-                        last_update: Timestamp::now(),
-                    },
-                    ..Default::default()
-                });
+            let lists: &mut RelayLists = public_keys.entry(event.pubkey).or_default();
+
+            // Update only if new metadata has more recent timestamp
+            if event.created_at >= lists.nip65.event_created_at {
+                lists.nip65 = RelayList {
+                    collection: nip65::extract_relay_list(event)
+                        .take(MAX_RELAYS_LIST)
+                        .map(|(u, m)| (u.clone(), *m))
+                        .collect(),
+                    event_created_at: event.created_at,
+                    last_update,
+                };
+                return true;
+            }
         } else if event.kind == Kind::InboxRelays {
-            public_keys
-                .entry(event.pubkey)
-                .and_modify(|lists| {
-                    // Update only if new metadata has more recent timestamp
-                    if event.created_at >= lists.nip17.event_created_at {
-                        lists.nip17 = RelayList {
-                            collection: nip17::extract_relay_list(event)
-                                .take(MAX_RELAYS_LIST)
-                                .cloned()
-                                .collect(),
-                            event_created_at: event.created_at,
-                            last_update: Timestamp::now(),
-                        };
-                    }
-                })
-                .or_insert_with(|| RelayLists {
-                    nip17: RelayList {
-                        collection: nip17::extract_relay_list(event)
-                            .take(MAX_RELAYS_LIST)
-                            .cloned()
-                            .collect(),
-                        event_created_at: event.created_at,
-                        last_update: Timestamp::now(),
-                    },
-                    ..Default::default()
-                });
+            let lists: &mut RelayLists = public_keys.entry(event.pubkey).or_default();
+
+            // Update only if new metadata has more recent timestamp
+            if event.created_at >= lists.nip17.event_created_at {
+                lists.nip17 = RelayList {
+                    collection: nip17::extract_relay_list(event)
+                        .take(MAX_RELAYS_LIST)
+                        .cloned()
+                        .collect(),
+                    event_created_at: event.created_at,
+                    last_update,
+                };
+                return true;
+            }
         }
+
+        false
     }
 
     /// Check for what public keys the metadata are outdated or not existent (both for NIP17 and NIP65)
@@ -148,6 +252,8 @@ impl Gossip {
     where
         I: IntoIterator<Item = PublicKey>,
     {
+        self.ensure_loaded().await;
+
         let map = self.public_keys.read().await;
         let now = Timestamp::now();
 
@@ -187,19 +293,27 @@ impl Gossip {
     where
         I: IntoIterator<Item = PublicKey>,
     {
-        let mut map = self.public_keys.write().await;
-        let now = Timestamp::now();
-
-        for public_key in public_keys.into_iter() {
-            map.entry(public_key)
-                .and_modify(|lists| {
-                    lists.last_check = now;
-                })
-                .or_insert_with(|| RelayLists {
-                    last_check: now,
-                    ..Default::default()
-                });
+        self.ensure_loaded().await;
+
+        let public_keys: Vec<PublicKey> = public_keys.into_iter().collect();
+
+        {
+            let mut map = self.public_keys.write().await;
+            let now = Timestamp::now();
+
+            for public_key in public_keys.iter() {
+                map.entry(*public_key)
+                    .and_modify(|lists| {
+                        lists.last_check = now;
+                    })
+                    .or_insert_with(|| RelayLists {
+                        last_check: now,
+                        ..Default::default()
+                    });
+            }
         }
+
+        self.save(public_keys).await;
     }
 
     fn get_nip17_relays<'a, I>(
@@ -285,7 +399,7 @@ impl Gossip {
         &self,
         txn: &RwLockReadGuard<PublicKeyMap>,
         public_keys: I,
-        metadata: RelayMetadata,
+        metadata: Option<RelayMetadata>,
     ) -> HashMap<RelayUrl, BTreeSet<PublicKey>>
     where
         I: IntoIterator<Item = &'a PublicKey>,
@@ -296,7 +410,10 @@ impl Gossip {
             if let Some(lists) = txn.get(public_key) {
                 for (url, m) in lists.nip65.collection.iter() {
                     let insert: bool = match m {
-                        Some(val) => val == &metadata,
+                        Some(val) => match metadata {
+                            Some(metadata) => val == &metadata,
+                            None => true,
+                        },
                         None => true,
                     };
 
@@ -321,6 +438,8 @@ impl Gossip {
     where
         I: IntoIterator<Item = &'a PublicKey>,
     {
+        self.ensure_loaded().await;
+
         let txn = self.public_keys.read().await;
         self.get_nip65_relays(&txn, public_keys, Some(RelayMetadata::Write))
     }
@@ -331,6 +450,8 @@ impl Gossip {
     where
         I: IntoIterator<Item = &'a PublicKey>,
     {
+        self.ensure_loaded().await;
+
         let txn = self.public_keys.read().await;
         self.get_nip65_relays(&txn, public_keys, Some(RelayMetadata::Read))
     }
@@ -341,6 +462,8 @@ impl Gossip {
     where
         I: IntoIterator<Item = &'a PublicKey>,
     {
+        self.ensure_loaded().await;
+
         let txn = self.public_keys.read().await;
         self.get_nip17_relays(&txn, public_keys)
     }
@@ -355,7 +478,7 @@ impl Gossip {
     where
         I: IntoIterator<Item = &'a PublicKey>,
     {
-        self.map_nip65_relays(txn, public_keys, RelayMetadata::Write)
+        self.map_nip65_relays(txn, public_keys, Some(RelayMetadata::Write))
     }
 
     /// Map NIP65 inbox (read) relays for public keys
@@ -368,10 +491,29 @@ impl Gossip {
     where
         I: IntoIterator<Item = &'a PublicKey>,
     {
-        self.map_nip65_relays(txn, public_keys, RelayMetadata::Read)
+        self.map_nip65_relays(txn, public_keys, Some(RelayMetadata::Read))
     }
 
-    pub async fn break_down_filter(&self, filter: Filter) -> BrokenDownFilters {
+    /// Select the relays for the public keys, according to the options
+    #[inline]
+    fn select_relays(
+        &self,
+        candidates: HashMap<RelayUrl, BTreeSet<PublicKey>>,
+        scores: &HashMap<RelayUrl, f64>,
+    ) -> HashMap<RelayUrl, BTreeSet<PublicKey>> {
+        selection::select_relays(candidates, self.opts.max_relays_per_pubkey, scores)
+    }
+
+    /// Break down the filter by relay
+    ///
+    /// `scores` contains the score of the relays already known (see [`selection::relay_score`]).
+    pub async fn break_down_filter(
+        &self,
+        filter: Filter,
+        scores: &HashMap<RelayUrl, f64>,
+    ) -> BrokenDownFilters {
+        self.ensure_loaded().await;
+
         let txn = self.public_keys.read().await;
 
         // Extract `p` tag from generic tags and parse public key hex
@@ -389,13 +531,16 @@ impl Gossip {
                     self.map_nip65_outbox_relays(&txn, authors);
 
                 // Extend with NIP17 relays
-                outbox.extend(self.map_nip17_relays(&txn, authors));
+                merge_relay_maps(&mut outbox, self.map_nip17_relays(&txn, authors));
 
                 // No relay available for the authors
                 if outbox.is_empty() {
                     return BrokenDownFilters::Orphan(filter);
                 }
 
+                // Pick the relays to use
+                let outbox = self.select_relays(outbox, scores);
+
                 let mut map: HashMap<RelayUrl, Filter> = HashMap::with_capacity(outbox.len());
 
                 // Construct new filters
@@ -416,13 +561,16 @@ impl Gossip {
                     self.map_nip65_inbox_relays(&txn, p_public_keys);
 
                 // Extend with NIP17 relays
-                inbox.extend(self.map_nip17_relays(&txn, p_public_keys));
+                merge_relay_maps(&mut inbox, self.map_nip17_relays(&txn, p_public_keys));
 
                 // No relay available for the p tags
                 if inbox.is_empty() {
                     return BrokenDownFilters::Orphan(filter);
                 }
 
+                // Pick the relays to use
+                let inbox = self.select_relays(inbox, scores);
+
                 let mut map: HashMap<RelayUrl, Filter> = HashMap::with_capacity(inbox.len());
 
                 // Construct new filters
@@ -441,20 +589,26 @@ impl Gossip {
             }
             (Some(authors), Some(p_public_keys)) => {
                 // Get map of outbox and inbox relays
-                let mut relays: HashSet<RelayUrl> =
-                    self.get_nip65_relays(&txn, authors.union(p_public_keys), None);
+                let mut relays: HashMap<RelayUrl, BTreeSet<PublicKey>> =
+                    self.map_nip65_relays(&txn, authors.union(p_public_keys), None);
 
                 // Extend with NIP17 relays
-                relays.extend(self.get_nip17_relays(&txn, authors.union(p_public_keys)));
+                merge_relay_maps(
+                    &mut relays,
+                    self.map_nip17_relays(&txn, authors.union(p_public_keys)),
+                );
 
                 // No relay available for the authors and p tags
                 if relays.is_empty() {
                     return BrokenDownFilters::Orphan(filter);
                 }
 
+                // Pick the relays to use
+                let relays = self.select_relays(relays, scores);
+
                 let mut map: HashMap<RelayUrl, Filter> = HashMap::with_capacity(relays.len());
 
-                for relay in relays.into_iter() {
+                for relay in relays.into_keys() {
                     // Update map
                     map.insert(relay, filter.clone());
                 }
@@ -467,8 +621,19 @@ impl Gossip {
     }
 }
 
+fn merge_relay_maps(
+    map: &mut HashMap<RelayUrl, BTreeSet<PublicKey>>,
+    other: HashMap<RelayUrl, BTreeSet<PublicKey>>,
+) {
+    for (url, public_keys) in other.into_iter() {
+        map.entry(url).or_default().extend(public_keys);
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use nostr_database::{MemoryDatabase, MemoryDatabaseOptions};
+
     use super::*;
 
     const SECRET_KEY_A: &str = "nsec1j4c6269y9w0q2er2xjw8sv2ehyrtfxq3jwgdlxj6qfn8z4gjsq5qfvfk99"; // aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4
@@ -502,7 +667,11 @@ mod tests {
     }
 
     async fn setup_graph() -> Gossip {
-        let graph = Gossip::new();
+        setup_graph_with_opts(GossipOptions::default()).await
+    }
+
+    async fn setup_graph_with_opts(opts: GossipOptions) -> Gossip {
+        let graph = Gossip::new(Arc::new(MemoryDatabase::new()), None, opts);
 
         let events = vec![
             build_relay_list_event(SECRET_KEY_A, KEY_A_RELAYS.to_vec()),
@@ -531,7 +700,10 @@ mod tests {
 
         // Single author
         let filter = Filter::new().author(keys_a.public_key);
-        match graph.break_down_filter(filter.clone()).await {
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
             BrokenDownFilters::Filters(map) => {
                 assert_eq!(map.get(&damus_url).unwrap(), &filter);
                 assert_eq!(map.get(&nostr_bg_url).unwrap(), &filter);
@@ -543,7 +715,10 @@ mod tests {
 
         // Multiple authors
         let authors_filter = Filter::new().authors([keys_a.public_key, keys_b.public_key]);
-        match graph.break_down_filter(authors_filter.clone()).await {
+        match graph
+            .break_down_filter(authors_filter.clone(), &HashMap::new())
+            .await
+        {
             BrokenDownFilters::Filters(map) => {
                 assert_eq!(map.get(&damus_url).unwrap(), &authors_filter);
                 assert_eq!(
@@ -570,7 +745,10 @@ mod tests {
 
         // Other filter
         let search_filter = Filter::new().search("Test").limit(10);
-        match graph.break_down_filter(search_filter.clone()).await {
+        match graph
+            .break_down_filter(search_filter.clone(), &HashMap::new())
+            .await
+        {
             BrokenDownFilters::Other(filter) => {
                 assert_eq!(filter, search_filter);
             }
@@ -579,7 +757,10 @@ mod tests {
 
         // Single p tags
         let p_tag_filter = Filter::new().pubkey(keys_a.public_key);
-        match graph.break_down_filter(p_tag_filter.clone()).await {
+        match graph
+            .break_down_filter(p_tag_filter.clone(), &HashMap::new())
+            .await
+        {
             BrokenDownFilters::Filters(map) => {
                 assert_eq!(map.get(&damus_url).unwrap(), &p_tag_filter);
                 assert_eq!(map.get(&nostr_bg_url).unwrap(), &p_tag_filter);
@@ -596,7 +777,10 @@ mod tests {
         let filter = Filter::new()
             .author(keys_a.public_key)
             .pubkey(keys_b.public_key);
-        match graph.break_down_filter(filter.clone()).await {
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
             BrokenDownFilters::Filters(map) => {
                 assert_eq!(map.get(&damus_url).unwrap(), &filter);
                 assert_eq!(map.get(&nostr_bg_url).unwrap(), &filter);
@@ -612,11 +796,160 @@ mod tests {
         // test orphan filters
         let random_keys = Keys::generate();
         let filter = Filter::new().author(random_keys.public_key);
-        match graph.break_down_filter(filter.clone()).await {
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
             BrokenDownFilters::Orphan(f) => {
                 assert_eq!(f, filter);
             }
             _ => panic!("Expected filters"),
         }
     }
+
+    #[tokio::test]
+    async fn test_break_down_filter_max_relays_per_pubkey() {
+        let keys_a = Keys::parse(SECRET_KEY_A).unwrap();
+        let keys_b = Keys::parse(SECRET_KEY_B).unwrap();
+
+        let damus_url = RelayUrl::parse("wss://relay.damus.io").unwrap();
+        let nos_lol_url = RelayUrl::parse("wss://nos.lol").unwrap();
+
+        let opts = GossipOptions::default().max_relays_per_pubkey(Some(1));
+        let graph = setup_graph_with_opts(opts).await;
+
+        // A single relay is shared by both the authors
+        let filter = Filter::new().authors([keys_a.public_key, keys_b.public_key]);
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
+            BrokenDownFilters::Filters(map) => {
+                assert_eq!(map.len(), 1);
+                assert_eq!(map.get(&damus_url).unwrap(), &filter);
+            }
+            _ => panic!("Expected filters"),
+        }
+
+        // Avoid the unreliable relay
+        let mut scores: HashMap<RelayUrl, f64> = HashMap::new();
+        scores.insert(damus_url.clone(), 0.1);
+
+        let filter = Filter::new().author(keys_a.public_key);
+        match graph.break_down_filter(filter.clone(), &scores).await {
+            BrokenDownFilters::Filters(map) => {
+                assert_eq!(map.len(), 1);
+                assert!(!map.contains_key(&damus_url));
+                assert_eq!(map.get(&nos_lol_url).unwrap(), &filter);
+            }
+            _ => panic!("Expected filters"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_break_down_filter_max_relays_per_pubkey_p_tags() {
+        let keys_a = Keys::parse(SECRET_KEY_A).unwrap();
+        let keys_b = Keys::parse(SECRET_KEY_B).unwrap();
+
+        let damus_url = RelayUrl::parse("wss://relay.damus.io").unwrap();
+        let nostr_bg_url = RelayUrl::parse("wss://relay.nostr.bg").unwrap();
+        let nostr_mom_url = RelayUrl::parse("wss://nostr.mom").unwrap();
+
+        let opts = GossipOptions::default().max_relays_per_pubkey(Some(1));
+        let graph = setup_graph_with_opts(opts).await;
+
+        // Single inbox relay of the p tag
+        let filter = Filter::new().pubkey(keys_a.public_key);
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
+            BrokenDownFilters::Filters(map) => {
+                assert_eq!(map.len(), 1);
+                let (url, f) = map.into_iter().next().unwrap();
+                assert!([damus_url.clone(), nostr_bg_url, nostr_mom_url].contains(&url));
+                assert_eq!(f, filter);
+            }
+            _ => panic!("Expected filters"),
+        }
+
+        // Both author and p tag: a single relay is shared by both the public keys
+        let filter = Filter::new()
+            .author(keys_a.public_key)
+            .pubkey(keys_b.public_key);
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
+            BrokenDownFilters::Filters(map) => {
+                assert_eq!(map.len(), 1);
+                assert_eq!(map.get(&damus_url).unwrap(), &filter);
+            }
+            _ => panic!("Expected filters"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_from_database() {
+        let keys_a = Keys::parse(SECRET_KEY_A).unwrap();
+        let keys_b = Keys::parse(SECRET_KEY_B).unwrap();
+
+        let damus_url = RelayUrl::parse("wss://relay.damus.io").unwrap();
+        let nos_lol_url = RelayUrl::parse("wss://nos.lol").unwrap();
+
+        let database: Arc<MemoryDatabase> =
+            Arc::new(MemoryDatabase::with_opts(MemoryDatabaseOptions {
+                events: true,
+                ..Default::default()
+            }));
+
+        // Relay lists are stored as events
+        let event_a = build_relay_list_event(SECRET_KEY_A, KEY_A_RELAYS.to_vec());
+        let event_b = build_relay_list_event(SECRET_KEY_B, KEY_B_RELAYS.to_vec());
+        database.save_event(&event_a).await.unwrap();
+        database.save_event(&event_b).await.unwrap();
+
+        // Update the graph of the first instance
+        let graph = Gossip::new(
+            database.clone(),
+            Some(database.clone()),
+            GossipOptions::default(),
+        );
+        graph.update([event_a]).await;
+        graph.update_last_check([keys_a.public_key]).await;
+
+        // A new instance is hydrated from the database
+        let graph = Gossip::new(database.clone(), Some(database), GossipOptions::default());
+
+        let filter = Filter::new().author(keys_a.public_key);
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
+            BrokenDownFilters::Filters(map) => {
+                assert_eq!(map.get(&damus_url).unwrap(), &filter);
+                assert_eq!(map.get(&nos_lol_url).unwrap(), &filter);
+            }
+            _ => panic!("Expected filters"),
+        }
+
+        // Key A has been just checked, while the status of key B was never saved
+        let outdated = graph
+            .check_outdated([keys_a.public_key, keys_b.public_key])
+            .await;
+        assert!(!outdated.contains(&keys_a.public_key));
+        assert!(outdated.contains(&keys_b.public_key));
+
+        // Routing works also for key B, even if outdated
+        let filter = Filter::new().author(keys_b.public_key);
+        match graph
+            .break_down_filter(filter.clone(), &HashMap::new())
+            .await
+        {
+            BrokenDownFilters::Filters(map) => {
+                assert_eq!(map.get(&damus_url).unwrap(), &filter);
+            }
+            _ => panic!("Expected filters"),
+        }
+    }
 }
@@ -0,0 +1,195 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Gossip relay selection
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use nostr::{PublicKey, RelayUrl};
+use nostr_relay_pool::RelayConnectionStats;
+
+/// Lowest score a relay can have
+///
+/// Unreliable relays are penalized but never excluded: they may be the only ones available for some public key.
+const MIN_RELAY_SCORE: f64 = 0.05;
+
+/// Score a relay using its connection stats
+///
+/// Returns a value between [`MIN_RELAY_SCORE`] and `1.0`.
+/// Relays never tried are considered reliable.
+pub fn relay_score(stats: &RelayConnectionStats) -> f64 {
+    if stats.attempts() == 0 {
+        return 1.0;
+    }
+
+    #[allow(unused_mut)]
+    let mut score: f64 = stats.success_rate();
+
+    // Penalize slow relays
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(latency) = stats.latency() {
+        score /= 1.0 + latency.as_secs_f64();
+    }
+
+    score.max(MIN_RELAY_SCORE)
+}
+
+/// Select the relays to use for the public keys
+///
+/// Greedy set cover: at every step pick the relay that covers the highest number of public keys
+/// that still need a relay, weighted by the relay score. Each public key is assigned to at most `max_per_pubkey` relays.
+///
+/// The relays without a score are considered reliable.
+pub fn select_relays(
+    candidates: HashMap<RelayUrl, BTreeSet<PublicKey>>,
+    max_per_pubkey: Option<usize>,
+    scores: &HashMap<RelayUrl, f64>,
+) -> HashMap<RelayUrl, BTreeSet<PublicKey>> {
+    // No limits, use all relays
+    let max_per_pubkey: usize = match max_per_pubkey {
+        Some(max) if max > 0 => max,
+        _ => return candidates,
+    };
+
+    // Sort relays, to get always the same selection for the same input
+    let mut remaining: BTreeMap<RelayUrl, BTreeSet<PublicKey>> = candidates.into_iter().collect();
+
+    // Number of relays still needed by every public key
+    let mut needs: HashMap<PublicKey, usize> = HashMap::new();
+    for public_key in remaining.values().flatten() {
+        *needs.entry(*public_key).or_default() += 1;
+    }
+    for need in needs.values_mut() {
+        *need = (*need).min(max_per_pubkey);
+    }
+
+    let mut selected: HashMap<RelayUrl, BTreeSet<PublicKey>> = HashMap::new();
+
+    loop {
+        let mut best: Option<(&RelayUrl, f64)> = None;
+
+        for (url, public_keys) in remaining.iter() {
+            let covered: usize = public_keys
+                .iter()
+                .filter(|p| needs.get(p).copied().unwrap_or_default() > 0)
+                .count();
+
+            if covered == 0 {
+                continue;
+            }
+
+            let score: f64 = scores.get(url).copied().unwrap_or(1.0);
+            let weight: f64 = covered as f64 * score;
+
+            match best {
+                Some((_, best_weight)) if best_weight >= weight => {}
+                _ => best = Some((url, weight)),
+            }
+        }
+
+        // All public keys are covered
+        let url: RelayUrl = match best {
+            Some((url, ..)) => url.clone(),
+            None => break,
+        };
+
+        if let Some(public_keys) = remaining.remove(&url) {
+            let assigned: BTreeSet<PublicKey> = public_keys
+                .into_iter()
+                .filter(|p| match needs.get_mut(p) {
+                    Some(need) if *need > 0 => {
+                        *need -= 1;
+                        true
+                    }
+                    _ => false,
+                })
+                .collect();
+            selected.insert(url, assigned);
+        }
+    }
+
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::Keys;
+
+    use super::*;
+
+    fn url(url: &str) -> RelayUrl {
+        RelayUrl::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_select_relays_set_cover() {
+        let a = Keys::generate().public_key;
+        let b = Keys::generate().public_key;
+        let c = Keys::generate().public_key;
+
+        let mut candidates: HashMap<RelayUrl, BTreeSet<PublicKey>> = HashMap::new();
+        candidates.insert(url("wss://big.relay"), BTreeSet::from([a, b, c]));
+        candidates.insert(url("wss://a.relay"), BTreeSet::from([a]));
+        candidates.insert(url("wss://b.relay"), BTreeSet::from([b]));
+        candidates.insert(url("wss://c.relay"), BTreeSet::from([c]));
+
+        // A single relay covers all the public keys
+        let selected = select_relays(candidates.clone(), Some(1), &HashMap::new());
+        assert_eq!(selected.len(), 1);
+        assert_eq!(
+            selected.get(&url("wss://big.relay")),
+            Some(&BTreeSet::from([a, b, c]))
+        );
+
+        // Two relays for each public key
+        let selected = select_relays(candidates.clone(), Some(2), &HashMap::new());
+        assert_eq!(selected.len(), 4);
+        for public_key in [a, b, c] {
+            let count: usize = selected
+                .values()
+                .filter(|s| s.contains(&public_key))
+                .count();
+            assert_eq!(count, 2);
+        }
+
+        // No limits
+        let selected = select_relays(candidates.clone(), None, &HashMap::new());
+        assert_eq!(selected, candidates);
+    }
+
+    #[test]
+    fn test_select_relays_penalize_unreliable() {
+        let a = Keys::generate().public_key;
+        let b = Keys::generate().public_key;
+
+        let mut candidates: HashMap<RelayUrl, BTreeSet<PublicKey>> = HashMap::new();
+        candidates.insert(url("wss://bad.relay"), BTreeSet::from([a, b]));
+        candidates.insert(url("wss://a.relay"), BTreeSet::from([a]));
+        candidates.insert(url("wss://b.relay"), BTreeSet::from([b]));
+        candidates.insert(url("wss://only.relay"), BTreeSet::from([b]));
+
+        let mut scores: HashMap<RelayUrl, f64> = HashMap::new();
+        scores.insert(url("wss://bad.relay"), 0.1);
+        scores.insert(url("wss://only.relay"), MIN_RELAY_SCORE);
+
+        let selected = select_relays(candidates, Some(1), &scores);
+        assert_eq!(selected.len(), 2);
+        assert!(!selected.contains_key(&url("wss://bad.relay")));
+        assert!(!selected.contains_key(&url("wss://only.relay")));
+        assert_eq!(
+            selected.get(&url("wss://a.relay")),
+            Some(&BTreeSet::from([a]))
+        );
+        assert_eq!(
+            selected.get(&url("wss://b.relay")),
+            Some(&BTreeSet::from([b]))
+        );
+    }
+
+    #[test]
+    fn test_relay_score_never_tried() {
+        let stats = RelayConnectionStats::default();
+        assert_eq!(relay_score(&stats), 1.0);
+    }
+}
@@ -33,6 +33,9 @@ pub use nostr_relay_pool::{
     RelayPoolNotification, RelayPoolOptions, RelayServiceFlags, RelayStatus,
     SubscribeAutoCloseOptions, SubscribeOptions, SyncDirection, SyncOptions,
 };
+#[doc(hidden)]
+#[cfg(feature = "sqlite")]
+pub use nostr_sqlite::NostrSqlite;
 
 pub mod client;
 mod gossip;
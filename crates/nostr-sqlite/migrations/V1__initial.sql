@@ -0,0 +1,49 @@
+-- Events
+CREATE TABLE IF NOT EXISTS events (
+    id BLOB PRIMARY KEY NOT NULL,
+    pubkey BLOB NOT NULL,
+    created_at INTEGER NOT NULL,
+    kind INTEGER NOT NULL,
+    -- `d` tag of the addressable events (empty for the replaceable ones)
+    identifier TEXT,
+    content TEXT NOT NULL,
+    json TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS events_created_at_idx ON events(created_at DESC, id);
+CREATE INDEX IF NOT EXISTS events_pubkey_created_at_idx ON events(pubkey, created_at DESC);
+CREATE INDEX IF NOT EXISTS events_kind_created_at_idx ON events(kind, created_at DESC);
+CREATE INDEX IF NOT EXISTS events_pubkey_kind_created_at_idx ON events(pubkey, kind, created_at DESC);
+CREATE INDEX IF NOT EXISTS events_coordinate_idx ON events(kind, pubkey, identifier) WHERE identifier IS NOT NULL;
+
+-- Single-letter tags (first value only)
+CREATE TABLE IF NOT EXISTS tags (
+    event_id BLOB NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (name, value, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS tags_event_id_idx ON tags(event_id);
+
+-- Deleted event IDs
+CREATE TABLE IF NOT EXISTS deleted_ids (
+    event_id BLOB PRIMARY KEY NOT NULL
+);
+
+-- Deleted coordinates
+CREATE TABLE IF NOT EXISTS deleted_coordinates (
+    kind INTEGER NOT NULL,
+    pubkey BLOB NOT NULL,
+    identifier TEXT NOT NULL,
+    deleted_at INTEGER NOT NULL,
+    PRIMARY KEY (kind, pubkey, identifier)
+);
+
+-- Gossip statuses
+CREATE TABLE IF NOT EXISTS gossip (
+    pubkey BLOB PRIMARY KEY NOT NULL,
+    last_check INTEGER NOT NULL,
+    nip17_last_update INTEGER NOT NULL,
+    nip65_last_update INTEGER NOT NULL
+);
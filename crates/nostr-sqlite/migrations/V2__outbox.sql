@@ -0,0 +1,8 @@
+-- Events waiting to be sent to a relay
+CREATE TABLE IF NOT EXISTS outbox (
+    relay_url TEXT NOT NULL,
+    event_id BLOB NOT NULL,
+    created_at INTEGER NOT NULL,
+    json TEXT NOT NULL,
+    PRIMARY KEY (relay_url, event_id)
+);
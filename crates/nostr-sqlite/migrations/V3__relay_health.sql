@@ -0,0 +1,5 @@
+-- Relay health scores computed by the relay pool
+CREATE TABLE IF NOT EXISTS relay_health (
+    relay_url TEXT PRIMARY KEY NOT NULL,
+    score REAL NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Time of the last successful negentropy sync of a filter with a relay
+CREATE TABLE IF NOT EXISTS sync_checkpoints (
+    relay_url TEXT NOT NULL,
+    filter TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (relay_url, filter)
+);
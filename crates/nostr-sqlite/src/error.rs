@@ -0,0 +1,68 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::{fmt, io};
+
+use async_utility::tokio::task::JoinError;
+use nostr::event;
+
+#[derive(Debug)]
+pub enum Error {
+    /// An upstream I/O error
+    Io(io::Error),
+    /// SQLite error
+    Sqlite(rusqlite::Error),
+    /// Migration error
+    Migration(refinery::Error),
+    /// Event error
+    Event(event::Error),
+    Thread(JoinError),
+    /// The connection mutex is poisoned
+    MutexPoisoned,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Sqlite(e) => write!(f, "{e}"),
+            Self::Migration(e) => write!(f, "{e}"),
+            Self::Event(e) => write!(f, "{e}"),
+            Self::Thread(e) => write!(f, "{e}"),
+            Self::MutexPoisoned => write!(f, "mutex poisoned"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Sqlite(e)
+    }
+}
+
+impl From<refinery::Error> for Error {
+    fn from(e: refinery::Error) -> Self {
+        Self::Migration(e)
+    }
+}
+
+impl From<event::Error> for Error {
+    fn from(e: event::Error) -> Self {
+        Self::Event(e)
+    }
+}
+
+impl From<JoinError> for Error {
+    fn from(e: JoinError) -> Self {
+        Self::Thread(e)
+    }
+}
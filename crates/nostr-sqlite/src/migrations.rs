@@ -0,0 +1,25 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use rusqlite::Connection;
+
+use crate::error::Error;
+
+// Embed the SQL migrations
+refinery::embed_migrations!("migrations");
+
+/// Run database migrations to set up or upgrade the database schema.
+pub(crate) fn run_migrations(conn: &mut Connection) -> Result<(), Error> {
+    let report = migrations::runner().run(conn)?;
+
+    for migration in report.applied_migrations() {
+        tracing::info!(
+            "Applied migration: {} (version: {})",
+            migration.name(),
+            migration.version()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,676 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_utility::task;
+use nostr_database::prelude::*;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Transaction};
+
+use crate::error::Error;
+use crate::migrations;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Store {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Store {
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path: &Path = path.as_ref();
+
+        // Create the parent directory if it doesn't exist
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let conn: Connection = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
+
+        Self::init(conn)
+    }
+
+    pub fn in_memory() -> Result<Self, Error> {
+        let conn: Connection = Connection::open_in_memory()?;
+        Self::init(conn)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, Error> {
+        // Needed to remove the tags when an event is deleted
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+
+        // Apply migrations
+        migrations::run_migrations(&mut conn)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    async fn interact<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Connection) -> Result<R, Error> + Send + 'static,
+        R: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| Error::MutexPoisoned)?;
+            f(&mut conn)
+        })
+        .await?
+    }
+
+    /// Store an event.
+    pub async fn save_event(&self, event: &Event) -> Result<SaveEventStatus, Error> {
+        let event: Event = event.clone();
+        self.interact(move |conn| save_event(conn, &event)).await
+    }
+
+    pub async fn check_id(&self, id: &EventId) -> Result<DatabaseEventStatus, Error> {
+        let id: EventId = *id;
+        self.interact(move |conn| {
+            if is_deleted(conn, &id)? {
+                Ok(DatabaseEventStatus::Deleted)
+            } else if has_event(conn, &id)? {
+                Ok(DatabaseEventStatus::Saved)
+            } else {
+                Ok(DatabaseEventStatus::NotExistent)
+            }
+        })
+        .await
+    }
+
+    pub async fn when_is_coordinate_deleted(
+        &self,
+        coordinate: &CoordinateBorrow<'_>,
+    ) -> Result<Option<Timestamp>, Error> {
+        let kind: Kind = *coordinate.kind;
+        let public_key: PublicKey = *coordinate.public_key;
+        let identifier: String = coordinate_identifier(&kind, coordinate.identifier).to_string();
+        self.interact(move |conn| when_is_coordinate_deleted(conn, &kind, &public_key, &identifier))
+            .await
+    }
+
+    pub async fn get_event_by_id(&self, id: &EventId) -> Result<Option<Event>, Error> {
+        let id: EventId = *id;
+        self.interact(move |conn| {
+            let json: Option<String> = conn
+                .query_row(
+                    "SELECT json FROM events WHERE id = ?1",
+                    params![id.as_bytes()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            match json {
+                Some(json) => Ok(Some(Event::from_json(json)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    pub async fn count(&self, filter: Filter) -> Result<usize, Error> {
+        self.interact(move |conn| {
+            let (sql, values) = build_query("SELECT id FROM events", &filter);
+            let sql: String = format!("SELECT COUNT(*) FROM ({sql})");
+            let count: i64 = conn.query_row(&sql, params_from_iter(values), |row| row.get(0))?;
+            Ok(usize::try_from(count).unwrap_or_default())
+        })
+        .await
+    }
+
+    // Lookup ID: EVENT_ORD_IMPL
+    pub async fn query(&self, filter: Filter) -> Result<Events, Error> {
+        self.interact(move |conn| {
+            let mut events: Events = Events::new(&filter);
+
+            let (sql, values) = build_query("SELECT json FROM events", &filter);
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let mut rows = stmt.query(params_from_iter(values))?;
+            while let Some(row) = rows.next()? {
+                let json: String = row.get(0)?;
+                events.insert(Event::from_json(json)?);
+            }
+
+            Ok(events)
+        })
+        .await
+    }
+
+    pub async fn negentropy_items(
+        &self,
+        filter: Filter,
+    ) -> Result<Vec<(EventId, Timestamp)>, Error> {
+        self.interact(move |conn| {
+            let (sql, values) = build_query("SELECT id, created_at FROM events", &filter);
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let mut rows = stmt.query(params_from_iter(values))?;
+
+            let mut items: Vec<(EventId, Timestamp)> = Vec::new();
+            while let Some(row) = rows.next()? {
+                let id: [u8; 32] = row.get(0)?;
+                let created_at: i64 = row.get(1)?;
+                items.push((EventId::from_byte_array(id), from_sql_timestamp(created_at)));
+            }
+
+            Ok(items)
+        })
+        .await
+    }
+
+    pub async fn delete(&self, filter: Filter) -> Result<(), Error> {
+        self.interact(move |conn| {
+            let (sql, values) = build_query("SELECT id FROM events", &filter);
+            let sql: String = format!("DELETE FROM events WHERE id IN ({sql})");
+            conn.execute(&sql, params_from_iter(values))?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn gossip_statuses(
+        &self,
+    ) -> Result<HashMap<PublicKey, GossipPublicKeyStatus>, Error> {
+        self.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT pubkey, last_check, nip17_last_update, nip65_last_update FROM gossip",
+            )?;
+            let mut rows = stmt.query([])?;
+
+            let mut statuses: HashMap<PublicKey, GossipPublicKeyStatus> = HashMap::new();
+            while let Some(row) = rows.next()? {
+                let public_key: [u8; 32] = row.get(0)?;
+
+                // Skip invalid public keys
+                let public_key: PublicKey = match PublicKey::from_slice(&public_key) {
+                    Ok(public_key) => public_key,
+                    Err(..) => continue,
+                };
+
+                statuses.insert(
+                    public_key,
+                    GossipPublicKeyStatus {
+                        last_check: from_sql_timestamp(row.get(1)?),
+                        nip17_last_update: from_sql_timestamp(row.get(2)?),
+                        nip65_last_update: from_sql_timestamp(row.get(3)?),
+                    },
+                );
+            }
+
+            Ok(statuses)
+        })
+        .await
+    }
+
+    pub async fn save_gossip_statuses(
+        &self,
+        statuses: HashMap<PublicKey, GossipPublicKeyStatus>,
+    ) -> Result<(), Error> {
+        self.interact(move |conn| {
+            let tx: Transaction = conn.transaction()?;
+
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO gossip (pubkey, last_check, nip17_last_update, nip65_last_update) VALUES (?1, ?2, ?3, ?4)",
+                )?;
+
+                for (public_key, status) in statuses.into_iter() {
+                    stmt.execute(params![
+                        public_key.as_bytes(),
+                        to_sql_timestamp(&status.last_check),
+                        to_sql_timestamp(&status.nip17_last_update),
+                        to_sql_timestamp(&status.nip65_last_update),
+                    ])?;
+                }
+            }
+
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn sync_checkpoint(
+        &self,
+        relay_url: RelayUrl,
+        filter: Filter,
+    ) -> Result<Option<Timestamp>, Error> {
+        self.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT created_at FROM sync_checkpoints WHERE relay_url = ?1 AND filter = ?2",
+            )?;
+            let mut rows = stmt.query([relay_url.as_str(), &filter.as_json()])?;
+
+            match rows.next()? {
+                Some(row) => Ok(Some(Timestamp::from_secs(row.get(0)?))),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    pub async fn save_sync_checkpoint(
+        &self,
+        relay_url: RelayUrl,
+        filter: Filter,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        self.interact(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO sync_checkpoints (relay_url, filter, created_at) VALUES (?1, ?2, ?3)",
+                params![relay_url.as_str(), filter.as_json(), timestamp.as_u64()],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn relay_health_score(&self, relay_url: RelayUrl) -> Result<Option<f64>, Error> {
+        self.interact(move |conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT score FROM relay_health WHERE relay_url = ?1")?;
+            let mut rows = stmt.query([relay_url.as_str()])?;
+
+            match rows.next()? {
+                Some(row) => Ok(Some(row.get(0)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    pub async fn save_relay_health_score(
+        &self,
+        relay_url: RelayUrl,
+        score: f64,
+    ) -> Result<(), Error> {
+        self.interact(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO relay_health (relay_url, score) VALUES (?1, ?2)",
+                params![relay_url.as_str(), score],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn outbox_events(&self, relay_url: RelayUrl) -> Result<Vec<Event>, Error> {
+        self.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT json FROM outbox WHERE relay_url = ?1 ORDER BY created_at ASC, event_id ASC",
+            )?;
+            let mut rows = stmt.query([relay_url.as_str()])?;
+
+            let mut events: Vec<Event> = Vec::new();
+            while let Some(row) = rows.next()? {
+                let json: String = row.get(0)?;
+                events.push(Event::from_json(json)?);
+            }
+
+            Ok(events)
+        })
+        .await
+    }
+
+    pub async fn save_outbox_event(&self, relay_url: RelayUrl, event: Event) -> Result<(), Error> {
+        self.interact(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO outbox (relay_url, event_id, created_at, json) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    relay_url.as_str(),
+                    event.id.as_bytes(),
+                    to_sql_timestamp(&event.created_at),
+                    event.as_json(),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn remove_outbox_event(
+        &self,
+        relay_url: RelayUrl,
+        event_id: EventId,
+    ) -> Result<(), Error> {
+        self.interact(move |conn| {
+            conn.execute(
+                "DELETE FROM outbox WHERE relay_url = ?1 AND event_id = ?2",
+                params![relay_url.as_str(), event_id.as_bytes()],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn wipe(&self) -> Result<(), Error> {
+        self.interact(move |conn| {
+            conn.execute_batch(
+                "BEGIN;
+                DELETE FROM tags;
+                DELETE FROM events;
+                DELETE FROM deleted_ids;
+                DELETE FROM deleted_coordinates;
+                DELETE FROM sync_checkpoints;
+                DELETE FROM gossip;
+                DELETE FROM relay_health;
+                DELETE FROM outbox;
+                COMMIT;",
+            )?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+fn save_event(conn: &mut Connection, event: &Event) -> Result<SaveEventStatus, Error> {
+    if event.kind.is_ephemeral() {
+        return Ok(SaveEventStatus::Rejected(RejectedReason::Ephemeral));
+    }
+
+    // The transaction is rolled back if not committed
+    let tx: Transaction = conn.transaction()?;
+
+    // Already exists
+    if has_event(&tx, &event.id)? {
+        return Ok(SaveEventStatus::Rejected(RejectedReason::Duplicate));
+    }
+
+    // Reject event if ID was deleted
+    if is_deleted(&tx, &event.id)? {
+        return Ok(SaveEventStatus::Rejected(RejectedReason::Deleted));
+    }
+
+    if let Some(identifier) = event_identifier(event) {
+        // Reject event if ADDR was deleted after it's created_at date
+        // (non-parameterized or parameterized)
+        if let Some(time) = when_is_coordinate_deleted(&tx, &event.kind, &event.pubkey, identifier)?
+        {
+            if event.created_at <= time {
+                return Ok(SaveEventStatus::Rejected(RejectedReason::Deleted));
+            }
+        }
+
+        // Find the replaceable/addressable event being replaced
+        let stored: Option<(i64, [u8; 32])> = tx
+            .query_row(
+                "SELECT created_at, id FROM events WHERE kind = ?1 AND pubkey = ?2 AND identifier = ?3 ORDER BY created_at DESC, id ASC LIMIT 1",
+                params![event.kind.as_u16(), event.pubkey.as_bytes(), identifier],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        if let Some((created_at, id)) = stored {
+            let created_at: Timestamp = from_sql_timestamp(created_at);
+
+            // In case of same timestamp, the event with the lowest ID is retained
+            if created_at > event.created_at
+                || (created_at == event.created_at && &id < event.id.as_bytes())
+            {
+                return Ok(SaveEventStatus::Rejected(RejectedReason::Replaced));
+            }
+
+            tx.execute(
+                "DELETE FROM events WHERE kind = ?1 AND pubkey = ?2 AND identifier = ?3",
+                params![event.kind.as_u16(), event.pubkey.as_bytes(), identifier],
+            )?;
+        }
+    }
+
+    // Handle deletion events
+    if let Kind::EventDeletion = event.kind {
+        let invalid: bool = handle_deletion_event(&tx, event)?;
+
+        if invalid {
+            return Ok(SaveEventStatus::Rejected(RejectedReason::InvalidDelete));
+        }
+    }
+
+    // Store and index the event
+    insert_event(&tx, event)?;
+
+    tx.commit()?;
+
+    Ok(SaveEventStatus::Success)
+}
+
+/// Returns `true` if the deletion event is invalid
+fn handle_deletion_event(tx: &Transaction, event: &Event) -> Result<bool, Error> {
+    for id in event.tags.event_ids() {
+        let author: Option<[u8; 32]> = tx
+            .query_row(
+                "SELECT pubkey FROM events WHERE id = ?1",
+                params![id.as_bytes()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if let Some(author) = author {
+            // Author must match
+            if &author != event.pubkey.as_bytes() {
+                return Ok(true);
+            }
+
+            // Mark as deleted and remove event
+            tx.execute(
+                "INSERT OR IGNORE INTO deleted_ids (event_id) VALUES (?1)",
+                params![id.as_bytes()],
+            )?;
+            tx.execute("DELETE FROM events WHERE id = ?1", params![id.as_bytes()])?;
+        }
+    }
+
+    for coordinate in event.tags.coordinates() {
+        // Author must match
+        if coordinate.public_key != event.pubkey {
+            return Ok(true);
+        }
+
+        let identifier: &str =
+            coordinate_identifier(&coordinate.kind, Some(coordinate.identifier.as_str()));
+        let deleted_at: i64 = to_sql_timestamp(&event.created_at);
+
+        // Mark deleted
+        tx.execute(
+            "INSERT INTO deleted_coordinates (kind, pubkey, identifier, deleted_at) VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (kind, pubkey, identifier) DO UPDATE SET deleted_at = MAX(deleted_at, excluded.deleted_at)",
+            params![
+                coordinate.kind.as_u16(),
+                coordinate.public_key.as_bytes(),
+                identifier,
+                deleted_at
+            ],
+        )?;
+
+        // Remove events (up to the created_at of the deletion event)
+        tx.execute(
+            "DELETE FROM events WHERE kind = ?1 AND pubkey = ?2 AND identifier = ?3 AND created_at <= ?4",
+            params![
+                coordinate.kind.as_u16(),
+                coordinate.public_key.as_bytes(),
+                identifier,
+                deleted_at
+            ],
+        )?;
+    }
+
+    Ok(false)
+}
+
+fn insert_event(tx: &Transaction, event: &Event) -> Result<(), Error> {
+    tx.execute(
+        "INSERT INTO events (id, pubkey, created_at, kind, identifier, content, json) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            event.id.as_bytes(),
+            event.pubkey.as_bytes(),
+            to_sql_timestamp(&event.created_at),
+            event.kind.as_u16(),
+            event_identifier(event),
+            event.content,
+            event.as_json(),
+        ],
+    )?;
+
+    // Index the single-letter tags
+    let mut stmt = tx
+        .prepare_cached("INSERT OR IGNORE INTO tags (event_id, name, value) VALUES (?1, ?2, ?3)")?;
+    for tag in event.tags.iter() {
+        if let (Some(name), Some(value)) = (tag.single_letter_tag(), tag.content()) {
+            stmt.execute(params![event.id.as_bytes(), name.as_str(), value])?;
+        }
+    }
+
+    Ok(())
+}
+
+fn has_event(conn: &Connection, id: &EventId) -> Result<bool, Error> {
+    let mut stmt = conn.prepare_cached("SELECT 1 FROM events WHERE id = ?1")?;
+    Ok(stmt.exists(params![id.as_bytes()])?)
+}
+
+fn is_deleted(conn: &Connection, id: &EventId) -> Result<bool, Error> {
+    let mut stmt = conn.prepare_cached("SELECT 1 FROM deleted_ids WHERE event_id = ?1")?;
+    Ok(stmt.exists(params![id.as_bytes()])?)
+}
+
+fn when_is_coordinate_deleted(
+    conn: &Connection,
+    kind: &Kind,
+    public_key: &PublicKey,
+    identifier: &str,
+) -> Result<Option<Timestamp>, Error> {
+    let deleted_at: Option<i64> = conn
+        .query_row(
+            "SELECT deleted_at FROM deleted_coordinates WHERE kind = ?1 AND pubkey = ?2 AND identifier = ?3",
+            params![kind.as_u16(), public_key.as_bytes(), identifier],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(deleted_at.map(from_sql_timestamp))
+}
+
+/// Get the identifier used to index the replaceable and addressable events
+///
+/// Returns `None` for all the other kinds.
+fn event_identifier(event: &Event) -> Option<&str> {
+    if event.kind.is_replaceable() || event.kind.is_addressable() {
+        return Some(coordinate_identifier(&event.kind, event.tags.identifier()));
+    }
+
+    None
+}
+
+/// The identifier is ignored for the replaceable events
+#[inline]
+fn coordinate_identifier<'a>(kind: &Kind, identifier: Option<&'a str>) -> &'a str {
+    if kind.is_replaceable() {
+        ""
+    } else {
+        identifier.unwrap_or_default()
+    }
+}
+
+/// Build the query for the filter
+///
+/// The events are sorted by `created_at` (descending) and by ID (ascending).
+fn build_query(select: &str, filter: &Filter) -> (String, Vec<Value>) {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(ids) = &filter.ids {
+        if !ids.is_empty() {
+            conditions.push(format!("id IN ({})", placeholders(ids.len())));
+            values.extend(ids.iter().map(|id| Value::Blob(id.to_bytes().to_vec())));
+        }
+    }
+
+    if let Some(authors) = &filter.authors {
+        if !authors.is_empty() {
+            conditions.push(format!("pubkey IN ({})", placeholders(authors.len())));
+            values.extend(
+                authors
+                    .iter()
+                    .map(|public_key| Value::Blob(public_key.to_bytes().to_vec())),
+            );
+        }
+    }
+
+    if let Some(kinds) = &filter.kinds {
+        if !kinds.is_empty() {
+            conditions.push(format!("kind IN ({})", placeholders(kinds.len())));
+            values.extend(
+                kinds
+                    .iter()
+                    .map(|kind| Value::Integer(i64::from(kind.as_u16()))),
+            );
+        }
+    }
+
+    if let Some(since) = &filter.since {
+        conditions.push(String::from("created_at >= ?"));
+        values.push(Value::Integer(to_sql_timestamp(since)));
+    }
+
+    if let Some(until) = &filter.until {
+        conditions.push(String::from("created_at <= ?"));
+        values.push(Value::Integer(to_sql_timestamp(until)));
+    }
+
+    for (name, tag_values) in filter.generic_tags.iter() {
+        if tag_values.is_empty() {
+            continue;
+        }
+
+        conditions.push(format!(
+            "id IN (SELECT event_id FROM tags WHERE name = ? AND value IN ({}))",
+            placeholders(tag_values.len())
+        ));
+        values.push(Value::Text(name.as_str().to_string()));
+        values.extend(tag_values.iter().cloned().map(Value::Text));
+    }
+
+    if let Some(search) = &filter.search {
+        // Case-insensitive (ASCII only) full text search
+        conditions.push(String::from("instr(lower(content), ?) > 0"));
+        values.push(Value::Text(search.to_ascii_lowercase()));
+    }
+
+    let mut sql: String = String::from(select);
+
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+
+    sql.push_str(" ORDER BY created_at DESC, id ASC");
+
+    if let Some(limit) = filter.limit {
+        sql.push_str(" LIMIT ?");
+        values.push(Value::Integer(i64::try_from(limit).unwrap_or(i64::MAX)));
+    }
+
+    (sql, values)
+}
+
+#[inline]
+fn placeholders(len: usize) -> String {
+    vec!["?"; len].join(", ")
+}
+
+#[inline]
+fn to_sql_timestamp(timestamp: &Timestamp) -> i64 {
+    i64::try_from(timestamp.as_u64()).unwrap_or(i64::MAX)
+}
+
+#[inline]
+fn from_sql_timestamp(timestamp: i64) -> Timestamp {
+    Timestamp::from_secs(u64::try_from(timestamp).unwrap_or_default())
+}
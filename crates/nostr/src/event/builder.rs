@@ -52,6 +52,8 @@ pub enum Error {
     /// NIP44 error
     #[cfg(all(feature = "std", feature = "nip44"))]
     NIP44(nip44::Error),
+    /// NIP52 error
+    NIP52(nip52::Error),
     /// NIP58 error
     NIP58(nip58::Error),
     /// NIP59 error
@@ -82,6 +84,7 @@ impl fmt::Display for Error {
             Self::NIP04(e) => write!(f, "{e}"),
             #[cfg(all(feature = "std", feature = "nip44"))]
             Self::NIP44(e) => write!(f, "{e}"),
+            Self::NIP52(e) => write!(f, "{e}"),
             Self::NIP58(e) => write!(f, "{e}"),
             #[cfg(all(feature = "std", feature = "nip59"))]
             Self::NIP59(e) => write!(f, "{e}"),
@@ -132,6 +135,12 @@ impl From<nip44::Error> for Error {
     }
 }
 
+impl From<nip52::Error> for Error {
+    fn from(e: nip52::Error) -> Self {
+        Self::NIP52(e)
+    }
+}
+
 impl From<nip58::Error> for Error {
     fn from(e: nip58::Error) -> Self {
         Self::NIP58(e)
@@ -923,6 +932,225 @@ impl EventBuilder {
         ))
     }
 
+    /// Date-based calendar event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    #[inline]
+    pub fn date_based_calendar_event<S>(description: S, event: DateBasedCalendarEvent) -> Self
+    where
+        S: Into<String>,
+    {
+        let tags: Vec<Tag> = event.into();
+        Self::new(Kind::DateBasedCalendarEvent, description).tags(tags)
+    }
+
+    /// Time-based calendar event
+    ///
+    /// Returns an error if the event spans too many days (see [`TimeBasedCalendarEvent::days`]).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    #[inline]
+    pub fn time_based_calendar_event<S>(
+        description: S,
+        event: TimeBasedCalendarEvent,
+    ) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let tags: Vec<Tag> = event.try_into()?;
+        Ok(Self::new(Kind::TimeBasedCalendarEvent, description).tags(tags))
+    }
+
+    /// Calendar
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    #[inline]
+    pub fn calendar<S>(description: S, calendar: Calendar) -> Self
+    where
+        S: Into<String>,
+    {
+        let tags: Vec<Tag> = calendar.into();
+        Self::new(Kind::Calendar, description).tags(tags)
+    }
+
+    /// Calendar event RSVP
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    #[inline]
+    pub fn calendar_event_rsvp<S>(note: S, rsvp: CalendarEventRsvp) -> Self
+    where
+        S: Into<String>,
+    {
+        let tags: Vec<Tag> = rsvp.into();
+        Self::new(Kind::CalendarEventRsvp, note).tags(tags)
+    }
+
+    /// Community definition
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/72.md>
+    #[inline]
+    pub fn community_definition(community: CommunityDefinition) -> Self {
+        let tags: Vec<Tag> = community.into();
+        Self::new(Kind::CommunityDefinition, "").tags(tags)
+    }
+
+    /// Community post approval
+    ///
+    /// The approved post is included in the content.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/72.md>
+    pub fn community_post_approval(community: Coordinate, post: &Event) -> Self {
+        let tags: Vec<Tag> = CommunityPostApproval::new(community, post).into();
+        Self::new(Kind::CommunityPostApproval, post.as_json()).tags(tags)
+    }
+
+    /// Handler information
+    ///
+    /// The application metadata, if any, is included in the content.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub fn handler_information(metadata: Option<&Metadata>, handler: HandlerInformation) -> Self {
+        let content: String = metadata.map(|m| m.as_json()).unwrap_or_default();
+        let tags: Vec<Tag> = handler.into();
+        Self::new(Kind::HandlerInformation, content).tags(tags)
+    }
+
+    /// Handler recommendation
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    #[inline]
+    pub fn handler_recommendation(recommendation: HandlerRecommendation) -> Self {
+        let tags: Vec<Tag> = recommendation.into();
+        Self::new(Kind::HandlerRecommendation, "").tags(tags)
+    }
+
+    /// Group join request
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    pub fn group_join_request<S, R, C>(group_id: S, reason: R, invite_code: Option<C>) -> Self
+    where
+        S: Into<String>,
+        R: Into<String>,
+        C: Into<String>,
+    {
+        let mut tags: Vec<Tag> = Vec::with_capacity(2);
+        tags.push(nip29::group_id_tag(group_id));
+
+        if let Some(code) = invite_code {
+            tags.push(Tag::custom(TagKind::custom("code"), [code]));
+        }
+
+        Self::new(Kind::GroupJoinRequest, reason).tags(tags)
+    }
+
+    /// Group leave request
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn group_leave_request<S>(group_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(Kind::GroupLeaveRequest, "").tag(nip29::group_id_tag(group_id))
+    }
+
+    /// Add a user to a group, with optional roles
+    ///
+    /// Must be signed by a group admin.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    pub fn group_put_user<S, I, R>(group_id: S, public_key: PublicKey, roles: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = R>,
+        R: Into<String>,
+    {
+        let admin = GroupAdmin {
+            public_key,
+            roles: roles.into_iter().map(|r| r.into()).collect(),
+        };
+        Self::new(Kind::GroupPutUser, "").tags([nip29::group_id_tag(group_id), admin.into()])
+    }
+
+    /// Remove a user from a group
+    ///
+    /// Must be signed by a group admin.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn group_remove_user<S>(group_id: S, public_key: PublicKey) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(Kind::GroupRemoveUser, "")
+            .tags([nip29::group_id_tag(group_id), Tag::public_key(public_key)])
+    }
+
+    /// Edit the metadata of a group
+    ///
+    /// Must be signed by a group admin.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    pub fn group_edit_metadata(metadata: GroupMetadata) -> Self {
+        let mut tags: Vec<Tag> = metadata.to_tags();
+        tags.insert(0, nip29::group_id_tag(metadata.id));
+        Self::new(Kind::GroupEditMetadata, "").tags(tags)
+    }
+
+    /// Delete an event from a group
+    ///
+    /// Must be signed by a group admin.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn group_delete_event<S>(group_id: S, event_id: EventId) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(Kind::GroupDeleteEvent, "")
+            .tags([nip29::group_id_tag(group_id), Tag::event(event_id)])
+    }
+
+    /// Create a group
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn group_create<S>(group_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(Kind::GroupCreate, "").tag(nip29::group_id_tag(group_id))
+    }
+
+    /// Delete a group
+    ///
+    /// Must be signed by a group admin.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn group_delete<S>(group_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(Kind::GroupDelete, "").tag(nip29::group_id_tag(group_id))
+    }
+
+    /// Create an invite code for a closed group
+    ///
+    /// Must be signed by a group admin.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    pub fn group_create_invite<S, C>(group_id: S, code: C) -> Self
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        Self::new(Kind::GroupCreateInvite, "").tags([
+            nip29::group_id_tag(group_id),
+            Tag::custom(TagKind::custom("code"), [code]),
+        ])
+    }
+
     /// Reporting
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/56.md>
@@ -91,6 +91,19 @@ kind_variants! {
     PublicChatReserved47 => 47, "Public Chat Reserved", "<https://github.com/nostr-protocol/nips/blob/master/28.md>",
     PublicChatReserved48 => 48, "Public Chat Reserved", "<https://github.com/nostr-protocol/nips/blob/master/28.md>",
     PublicChatReserved49 => 49, "Public Chat Reserved", "<https://github.com/nostr-protocol/nips/blob/master/28.md>",
+    GroupPutUser => 9000, "Group Put User", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupRemoveUser => 9001, "Group Remove User", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupEditMetadata => 9002, "Group Edit Metadata", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupDeleteEvent => 9005, "Group Delete Event", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupCreate => 9007, "Group Create", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupDelete => 9008, "Group Delete", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupCreateInvite => 9009, "Group Create Invite", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupJoinRequest => 9021, "Group Join Request", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupLeaveRequest => 9022, "Group Leave Request", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupMetadata => 39000, "Group Metadata", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupAdmins => 39001, "Group Admins", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupMembers => 39002, "Group Members", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
+    GroupRoles => 39003, "Group Roles", "<https://github.com/nostr-protocol/nips/blob/master/29.md>",
     MlsKeyPackage => 443, "MLS Key Package", "<https://github.com/nostr-protocol/nips/blob/master/104.md>",
     MlsWelcome => 444, "MLS Welcome", "<https://github.com/nostr-protocol/nips/blob/master/104.md>",
     MlsGroupMessage => 445, "MLS Group Message", "<https://github.com/nostr-protocol/nips/blob/master/104.md>",
@@ -126,6 +139,10 @@ kind_variants! {
     NostrConnect => 24133, "Nostr Connect", "<https://github.com/nostr-protocol/nips/blob/master/47.md>",
     LiveEvent => 30311, "Live Event", "<https://github.com/nostr-protocol/nips/blob/master/53.md>",
     LiveEventMessage => 1311, "Live Event Message", "<https://github.com/nostr-protocol/nips/blob/master/53.md>",
+    DateBasedCalendarEvent => 31922, "Date-Based Calendar Event", "<https://github.com/nostr-protocol/nips/blob/master/52.md>",
+    TimeBasedCalendarEvent => 31923, "Time-Based Calendar Event", "<https://github.com/nostr-protocol/nips/blob/master/52.md>",
+    Calendar => 31924, "Calendar", "<https://github.com/nostr-protocol/nips/blob/master/52.md>",
+    CalendarEventRsvp => 31925, "Calendar Event RSVP", "<https://github.com/nostr-protocol/nips/blob/master/52.md>",
     ProfileBadges => 30008, "Profile Badges", "<https://github.com/nostr-protocol/nips/blob/master/58.md>",
     BadgeDefinition => 30009, "Badge Definition", "<https://github.com/nostr-protocol/nips/blob/master/58.md>",
     Seal => 13, "Seal", "<https://github.com/nostr-protocol/nips/blob/master/59.md>",
@@ -134,6 +151,8 @@ kind_variants! {
     SetStall => 30017, "Set stall", "<https://github.com/nostr-protocol/nips/blob/master/15.md>",
     SetProduct => 30018, "Set product", "<https://github.com/nostr-protocol/nips/blob/master/15.md>",
     JobFeedback => 7000, "Job Feedback", "<https://github.com/nostr-protocol/nips/blob/master/90.md>",
+    HandlerRecommendation => 31989, "Handler Recommendation", "<https://github.com/nostr-protocol/nips/blob/master/89.md>",
+    HandlerInformation => 31990, "Handler Information", "<https://github.com/nostr-protocol/nips/blob/master/89.md>",
     FollowSet => 30000, "Follow Set", "<https://github.com/nostr-protocol/nips/blob/master/51.md>",
     RelaySet => 30002, "Relay Set", "<https://github.com/nostr-protocol/nips/blob/master/51.md>",
     BookmarkSet => 30003, "Bookmark Set", "<https://github.com/nostr-protocol/nips/blob/master/51.md>",
@@ -151,6 +170,8 @@ kind_variants! {
     Torrent => 2003, "Torrent", "<https://github.com/nostr-protocol/nips/blob/master/35.md>",
     TorrentComment => 2004, "Torrent Comment", "<https://github.com/nostr-protocol/nips/blob/master/35.md>",
     PeerToPeerOrder => 38383, "Peer-to-peer Order events", "<https://github.com/nostr-protocol/nips/blob/master/69.md>",
+    CommunityDefinition => 34550, "Community Definition", "<https://github.com/nostr-protocol/nips/blob/master/72.md>",
+    CommunityPostApproval => 4550, "Community Post Approval", "<https://github.com/nostr-protocol/nips/blob/master/72.md>",
     RequestToVanish => 62, "Request to Vanish", "<https://github.com/nostr-protocol/nips/blob/master/62.md>",
     UserStatus => 30315, "User Status", "<https://github.com/nostr-protocol/nips/blob/master/38.md>",
     CashuWallet => 17375, "Cashu Wallet", "<https://github.com/nostr-protocol/nips/blob/master/60.md>",
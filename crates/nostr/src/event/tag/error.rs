@@ -9,7 +9,7 @@ use hashes::hex::HexToArrayError;
 
 #[cfg(feature = "nip98")]
 use crate::nips::nip98;
-use crate::nips::{nip01, nip10, nip26, nip39, nip53, nip65};
+use crate::nips::{nip01, nip10, nip26, nip39, nip52, nip53, nip65};
 use crate::types::image;
 use crate::types::url::{Error as RelayUrlError, ParseError};
 use crate::{key, secp256k1};
@@ -37,6 +37,8 @@ pub enum Error {
     NIP26(nip26::Error),
     /// NIP39 error
     NIP39(nip39::Error),
+    /// NIP52 error
+    NIP52(nip52::Error),
     /// NIP53 error
     NIP53(nip53::Error),
     /// NIP65 error
@@ -72,6 +74,7 @@ impl fmt::Display for Error {
             Self::NIP10(e) => write!(f, "{e}"),
             Self::NIP26(e) => write!(f, "{e}"),
             Self::NIP39(e) => write!(f, "{e}"),
+            Self::NIP52(e) => write!(f, "{e}"),
             Self::NIP53(e) => write!(f, "{e}"),
             Self::NIP65(e) => write!(f, "{e}"),
             #[cfg(feature = "nip98")]
@@ -145,6 +148,12 @@ impl From<nip39::Error> for Error {
     }
 }
 
+impl From<nip52::Error> for Error {
+    fn from(e: nip52::Error) -> Self {
+        Self::NIP52(e)
+    }
+}
+
 impl From<nip53::Error> for Error {
     fn from(e: nip53::Error) -> Self {
         Self::NIP53(e)
@@ -59,6 +59,14 @@ pub enum TagKind<'a> {
     Emoji,
     /// Encrypted
     Encrypted,
+    /// End
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    End,
+    /// End timezone
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    EndTzid,
     /// Ends
     Ends,
     /// Expiration
@@ -71,6 +79,10 @@ pub enum TagKind<'a> {
     Extension,
     /// File
     File,
+    /// Free/busy
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    FreeBusy,
     /// Image
     Image,
     /// License of the shared content
@@ -79,6 +91,10 @@ pub enum TagKind<'a> {
     License,
     /// Lnurl
     Lnurl,
+    /// Location
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    Location,
     /// Magnet
     Magnet,
     /// Maintainers
@@ -129,6 +145,14 @@ pub enum TagKind<'a> {
     Server,
     /// Size of the file in bytes
     Size,
+    /// Start
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    Start,
+    /// Start timezone
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    StartTzid,
     /// Starts
     Starts,
     /// Status
@@ -309,13 +333,17 @@ impl<'a> TagKind<'a> {
             Self::Dim => "dim",
             Self::Emoji => "emoji",
             Self::Encrypted => "encrypted",
+            Self::End => "end",
+            Self::EndTzid => "end_tzid",
             Self::Ends => "ends",
             Self::Expiration => "expiration",
             Self::Extension => "extension",
             Self::File => "file",
+            Self::FreeBusy => "fb",
             Self::Image => "image",
             Self::License => "license",
             Self::Lnurl => "lnurl",
+            Self::Location => "location",
             Self::Magnet => "magnet",
             Self::Maintainers => "maintainers",
             Self::Method => "method",
@@ -337,6 +365,8 @@ impl<'a> TagKind<'a> {
             Self::Runtime => "runtime",
             Self::Server => "server",
             Self::Size => "size",
+            Self::Start => "start",
+            Self::StartTzid => "start_tzid",
             Self::Starts => "starts",
             Self::Status => "status",
             Self::Streaming => "streaming",
@@ -383,13 +413,17 @@ impl<'a> From<&'a str> for TagKind<'a> {
             "dim" => Self::Dim,
             "emoji" => Self::Emoji,
             "encrypted" => Self::Encrypted,
+            "end" => Self::End,
+            "end_tzid" => Self::EndTzid,
             "ends" => Self::Ends,
             "expiration" => Self::Expiration,
             "extension" => Self::Extension,
+            "fb" => Self::FreeBusy,
             "file" => Self::File,
             "image" => Self::Image,
             "license" => Self::License,
             "lnurl" => Self::Lnurl,
+            "location" => Self::Location,
             "magnet" => Self::Magnet,
             "maintainers" => Self::Maintainers,
             "method" => Self::Method,
@@ -410,6 +444,8 @@ impl<'a> From<&'a str> for TagKind<'a> {
             "runtime" => Self::Runtime,
             "server" => Self::Server,
             "size" => Self::Size,
+            "start" => Self::Start,
+            "start_tzid" => Self::StartTzid,
             "starts" => Self::Starts,
             "status" => Self::Status,
             "streaming" => Self::Streaming,
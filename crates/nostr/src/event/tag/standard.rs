@@ -20,6 +20,7 @@ use crate::nips::nip26::Conditions;
 use crate::nips::nip34::EUC;
 use crate::nips::nip39::Identity;
 use crate::nips::nip48::Protocol;
+use crate::nips::nip52::{CalendarEventTime, FreeBusy};
 use crate::nips::nip53::{LiveEventMarker, LiveEventStatus};
 use crate::nips::nip56::Report;
 use crate::nips::nip65::RelayMetadata;
@@ -199,6 +200,30 @@ pub enum TagStandard {
     Starts(Timestamp),
     Ends(Timestamp),
     LiveEventStatus(LiveEventStatus),
+    /// Start of a calendar event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    Start(CalendarEventTime),
+    /// End of a calendar event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    End(CalendarEventTime),
+    /// Time zone of the start of a calendar event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    StartTzid(String),
+    /// Time zone of the end of a calendar event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    EndTzid(String),
+    /// Location of a calendar event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    Location(String),
+    /// Free/busy status of a calendar event RSVP
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/52.md>
+    FreeBusy(FreeBusy),
     CurrentParticipants(u64),
     TotalParticipants(u64),
     AbsoluteURL(Url),
@@ -294,16 +319,16 @@ impl TagStandard {
                 // Parse `a` tag
                 SingleLetterTag {
                     character: Alphabet::A,
-                    uppercase: false,
+                    uppercase,
                 } => {
-                    return parse_a_tag(tag);
+                    return parse_a_tag(tag, uppercase);
                 }
                 // Parse `e` tag
                 SingleLetterTag {
                     character: Alphabet::E,
-                    uppercase: false,
+                    uppercase,
                 } => {
-                    return parse_e_tag(tag);
+                    return parse_e_tag(tag, uppercase);
                 }
                 // Parse `i` tag
                 SingleLetterTag {
@@ -312,6 +337,13 @@ impl TagStandard {
                 } => {
                     return parse_i_tag(tag, uppercase);
                 }
+                // Parse `k` tag
+                SingleLetterTag {
+                    character: Alphabet::K,
+                    uppercase,
+                } => {
+                    return parse_k_tag(tag, uppercase);
+                }
                 // Parse `l` tag
                 SingleLetterTag {
                     character: Alphabet::L,
@@ -446,6 +478,12 @@ impl TagStandard {
                 TagKind::Recording => Ok(Self::Recording(Url::parse(tag_1)?)),
                 TagKind::Starts => Ok(Self::Starts(Timestamp::from_str(tag_1)?)),
                 TagKind::Ends => Ok(Self::Ends(Timestamp::from_str(tag_1)?)),
+                TagKind::Start => Ok(Self::Start(CalendarEventTime::parse(tag_1)?)),
+                TagKind::End => Ok(Self::End(CalendarEventTime::parse(tag_1)?)),
+                TagKind::StartTzid => Ok(Self::StartTzid(tag_1.to_string())),
+                TagKind::EndTzid => Ok(Self::EndTzid(tag_1.to_string())),
+                TagKind::Location => Ok(Self::Location(tag_1.to_string())),
+                TagKind::FreeBusy => Ok(Self::FreeBusy(FreeBusy::from_str(tag_1)?)),
                 TagKind::Status => match DataVendingMachineStatus::from_str(tag_1) {
                     Ok(status) => Ok(Self::DataVendingMachineStatus {
                         status,
@@ -654,6 +692,12 @@ impl TagStandard {
             Self::Recording(..) => TagKind::Recording,
             Self::Starts(..) => TagKind::Starts,
             Self::Ends(..) => TagKind::Ends,
+            Self::Start(..) => TagKind::Start,
+            Self::End(..) => TagKind::End,
+            Self::StartTzid(..) => TagKind::StartTzid,
+            Self::EndTzid(..) => TagKind::EndTzid,
+            Self::Location(..) => TagKind::Location,
+            Self::FreeBusy(..) => TagKind::FreeBusy,
             Self::LiveEventStatus(..) | Self::DataVendingMachineStatus { .. } => TagKind::Status,
             Self::CurrentParticipants(..) => TagKind::CurrentParticipants,
             Self::TotalParticipants(..) => TagKind::TotalParticipants,
@@ -944,6 +988,12 @@ impl From<TagStandard> for Vec<String> {
             TagStandard::Ends(timestamp) => {
                 vec![tag_kind, timestamp.to_string()]
             }
+            TagStandard::Start(time) | TagStandard::End(time) => {
+                vec![tag_kind, time.to_string()]
+            }
+            TagStandard::StartTzid(tzid) | TagStandard::EndTzid(tzid) => vec![tag_kind, tzid],
+            TagStandard::Location(location) => vec![tag_kind, location],
+            TagStandard::FreeBusy(fb) => vec![tag_kind, fb.to_string()],
             TagStandard::LiveEventStatus(s) => {
                 vec![tag_kind, s.to_string()]
             }
@@ -1009,7 +1059,7 @@ impl From<TagStandard> for Vec<String> {
     }
 }
 
-fn parse_a_tag<S>(tag: &[S]) -> Result<TagStandard, Error>
+fn parse_a_tag<S>(tag: &[S], uppercase: bool) -> Result<TagStandard, Error>
 where
     S: AsRef<str>,
 {
@@ -1020,14 +1070,14 @@ where
                 Some(url) if !url.is_empty() => Some(RelayUrl::parse(url)?),
                 _ => None,
             },
-            uppercase: false,
+            uppercase,
         })
     } else {
         Err(Error::UnknownStandardizedTag)
     }
 }
 
-fn parse_e_tag<S>(tag: &[S]) -> Result<TagStandard, Error>
+fn parse_e_tag<S>(tag: &[S], uppercase: bool) -> Result<TagStandard, Error>
 where
     S: AsRef<str>,
 {
@@ -1044,8 +1094,8 @@ where
     // Check if it's a report
     if let Some(tag_2) = tag_2 {
         return match Report::from_str(tag_2) {
-            Ok(report) => Ok(TagStandard::EventReport(event_id, report)),
-            Err(_) => {
+            Ok(report) if !uppercase => Ok(TagStandard::EventReport(event_id, report)),
+            _ => {
                 // Check if 3rd arg is a marker or a public key
                 let (marker, public_key) = match (tag_3, tag_4) {
                     (Some(marker), Some(public_key)) => {
@@ -1086,13 +1136,33 @@ where
                     },
                     marker,
                     public_key,
-                    uppercase: false,
+                    uppercase,
                 })
             }
         };
     }
 
-    Ok(TagStandard::event(event_id))
+    Ok(TagStandard::Event {
+        event_id,
+        relay_url: None,
+        marker: None,
+        public_key: None,
+        uppercase,
+    })
+}
+
+fn parse_k_tag<S>(tag: &[S], uppercase: bool) -> Result<TagStandard, Error>
+where
+    S: AsRef<str>,
+{
+    // ["k", "<kind>"]
+
+    let kind: &str = tag.get(1).ok_or(Error::UnknownStandardizedTag)?.as_ref();
+
+    Ok(TagStandard::Kind {
+        kind: Kind::from_str(kind)?,
+        uppercase,
+    })
 }
 
 fn parse_i_tag<S>(tag: &[S], uppercase: bool) -> Result<TagStandard, Error>
@@ -1442,6 +1512,7 @@ where
 mod tests {
     use super::*;
     use crate::nips::nip39::ExternalIdentity;
+    use crate::nips::nip52::{self, CalendarDate};
 
     #[test]
     fn test_tag_standard_is_reply() {
@@ -1477,6 +1548,27 @@ mod tests {
     fn test_tag_standard_serialization() {
         assert_eq!(vec!["-"], TagStandard::Protected.to_vec());
 
+        assert_eq!(
+            vec!["start", "2024-08-19"],
+            TagStandard::Start(CalendarEventTime::Date(
+                CalendarDate::new(2024, 8, 19).unwrap()
+            ))
+            .to_vec()
+        );
+
+        assert_eq!(
+            vec!["end", "1724119200"],
+            TagStandard::End(CalendarEventTime::Timestamp(Timestamp::from_secs(
+                1724119200
+            )))
+            .to_vec()
+        );
+
+        assert_eq!(
+            vec!["fb", "busy"],
+            TagStandard::FreeBusy(FreeBusy::Busy).to_vec()
+        );
+
         assert_eq!(
             vec!["alt", "something"],
             TagStandard::Alt(String::from("something")).to_vec()
@@ -2063,6 +2155,40 @@ mod tests {
     fn test_tag_standard_parsing() {
         assert_eq!(TagStandard::parse(&["-"]).unwrap(), TagStandard::Protected);
 
+        assert_eq!(
+            TagStandard::parse(&["start", "2024-08-19"]).unwrap(),
+            TagStandard::Start(CalendarEventTime::Date(
+                CalendarDate::new(2024, 8, 19).unwrap()
+            ))
+        );
+
+        assert_eq!(
+            TagStandard::parse(&["end", "1724119200"]).unwrap(),
+            TagStandard::End(CalendarEventTime::Timestamp(Timestamp::from_secs(
+                1724119200
+            )))
+        );
+
+        assert_eq!(
+            TagStandard::parse(&["start_tzid", "Europe/Rome"]).unwrap(),
+            TagStandard::StartTzid(String::from("Europe/Rome"))
+        );
+
+        assert_eq!(
+            TagStandard::parse(&["location", "Rome"]).unwrap(),
+            TagStandard::Location(String::from("Rome"))
+        );
+
+        assert_eq!(
+            TagStandard::parse(&["fb", "free"]).unwrap(),
+            TagStandard::FreeBusy(FreeBusy::Free)
+        );
+
+        assert_eq!(
+            TagStandard::parse(&["fb", "maybe"]).unwrap_err(),
+            Error::NIP52(nip52::Error::UnknownFreeBusy(String::from("maybe")))
+        );
+
         assert_eq!(
             TagStandard::parse(&["alt", "something"]).unwrap(),
             TagStandard::Alt(String::from("something"))
@@ -2184,6 +2310,39 @@ mod tests {
             TagStandard::Reference(String::from("https://example.com"))
         );
 
+        assert_eq!(
+            TagStandard::parse(&[
+                "E",
+                "378f145897eea948952674269945e88612420db35791784abf0616b4fed56ef7",
+                "wss://relay.damus.io",
+                "13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"
+            ])
+            .unwrap(),
+            TagStandard::Event {
+                event_id: EventId::from_hex(
+                    "378f145897eea948952674269945e88612420db35791784abf0616b4fed56ef7"
+                )
+                .unwrap(),
+                relay_url: Some(RelayUrl::parse("wss://relay.damus.io").unwrap()),
+                marker: None,
+                public_key: Some(
+                    PublicKey::from_hex(
+                        "13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"
+                    )
+                    .unwrap()
+                ),
+                uppercase: true,
+            }
+        );
+
+        assert_eq!(
+            TagStandard::parse(&["K", "1"]).unwrap(),
+            TagStandard::Kind {
+                kind: Kind::TextNote,
+                uppercase: true,
+            }
+        );
+
         assert_eq!(
             TagStandard::parse(&["i", "isbn:9780765382030"]).unwrap(),
             TagStandard::ExternalContent {
@@ -595,6 +595,45 @@ impl Filter {
         self.remove_custom_tags(SingleLetterTag::lowercase(Alphabet::D), identifiers)
     }
 
+    /// Add group ID
+    ///
+    /// Query for `h` tag.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn group<S>(self, group_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.custom_tag(SingleLetterTag::lowercase(Alphabet::H), group_id)
+    }
+
+    /// Add group IDs
+    ///
+    /// Query for `h` tag.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn groups<I, S>(self, group_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.custom_tags(SingleLetterTag::lowercase(Alphabet::H), group_ids)
+    }
+
+    /// Remove group IDs
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/29.md>
+    #[inline]
+    pub fn remove_groups<I, S>(self, group_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.remove_custom_tags(SingleLetterTag::lowercase(Alphabet::H), group_ids)
+    }
+
     /// Add coordinate
     ///
     /// Query for `a` tag.
@@ -134,7 +134,10 @@ impl ClientMessage<'_> {
     /// Check if is an `REQ` message
     #[inline]
     pub fn is_req(&self) -> bool {
-        matches!(self, ClientMessage::Req { .. })
+        matches!(
+            self,
+            ClientMessage::Req { .. } | ClientMessage::ReqMultiFilter { .. }
+        )
     }
 
     /// Check if is an `CLOSE` message
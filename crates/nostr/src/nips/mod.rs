@@ -18,7 +18,7 @@ pub mod nip06;
 pub mod nip07;
 pub mod nip09;
 pub mod nip10;
-#[cfg(all(feature = "std", feature = "nip11"))]
+#[cfg(feature = "std")]
 pub mod nip11;
 pub mod nip13;
 pub mod nip15;
@@ -27,6 +27,7 @@ pub mod nip19;
 pub mod nip21;
 pub mod nip22;
 pub mod nip26;
+pub mod nip29;
 pub mod nip34;
 pub mod nip35;
 pub mod nip38;
@@ -42,6 +43,7 @@ pub mod nip48;
 #[cfg(feature = "nip49")]
 pub mod nip49;
 pub mod nip51;
+pub mod nip52;
 pub mod nip53;
 pub mod nip56;
 #[cfg(feature = "nip57")]
@@ -51,7 +53,11 @@ pub mod nip58;
 pub mod nip59;
 pub mod nip62;
 pub mod nip65;
+pub mod nip72;
 pub mod nip73;
+#[cfg(feature = "std")]
+pub mod nip86;
+pub mod nip89;
 pub mod nip90;
 pub mod nip94;
 #[cfg(all(feature = "std", feature = "nip96"))]
@@ -6,9 +6,12 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/10.md>
 
+use alloc::vec::Vec;
 use core::fmt;
 use core::str::FromStr;
 
+use crate::{Event, EventId, Kind, TagKind, TagStandard};
+
 /// NIP10 error
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
@@ -62,3 +65,126 @@ impl FromStr for Marker {
         }
     }
 }
+
+/// Extract the ID of the root event of the thread
+///
+/// Supports both the marked `e` tags and the deprecated positional ones.
+pub fn extract_root(event: &Event) -> Option<&EventId> {
+    extract_data(event, true)
+}
+
+/// Extract the ID of the event replied to
+///
+/// If the event is a direct reply to the root, the root is returned.
+/// Supports both the marked `e` tags and the deprecated positional ones.
+pub fn extract_reply(event: &Event) -> Option<&EventId> {
+    extract_data(event, false)
+}
+
+fn extract_data(event: &Event, is_root: bool) -> Option<&EventId> {
+    if event.kind != Kind::TextNote {
+        return None;
+    }
+
+    let tags: Vec<(&EventId, Option<&Marker>)> = event
+        .tags
+        .filter_standardized(TagKind::e())
+        .filter_map(|tag| match tag {
+            TagStandard::Event {
+                event_id, marker, ..
+            } => Some((event_id, marker.as_ref())),
+            _ => None,
+        })
+        .collect();
+
+    // Deprecated positional `e` tags: the first is the root, the last the event replied to
+    if tags.iter().all(|(_, marker)| marker.is_none()) {
+        return if is_root {
+            tags.first().map(|(event_id, ..)| *event_id)
+        } else {
+            tags.last().map(|(event_id, ..)| *event_id)
+        };
+    }
+
+    let find = |target: Marker| -> Option<&EventId> {
+        tags.iter()
+            .find(|(_, marker)| *marker == Some(&target))
+            .map(|(event_id, ..)| *event_id)
+    };
+
+    let root: Option<&EventId> = find(Marker::Root);
+
+    if is_root {
+        return root;
+    }
+
+    // A direct reply to the root has only the `root` marker
+    find(Marker::Reply).or(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Tag};
+
+    #[test]
+    fn test_extract_marked() {
+        let keys = Keys::generate();
+        let root = EventBuilder::text_note("root")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let reply = EventBuilder::text_note_reply("reply", &root, None, None)
+            .sign_with_keys(&keys)
+            .unwrap();
+        let nested = EventBuilder::text_note_reply("nested", &reply, Some(&root), None)
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        assert_eq!(extract_root(&root), None);
+        assert_eq!(extract_reply(&root), None);
+
+        assert_eq!(extract_reply(&reply), Some(&root.id));
+
+        assert_eq!(extract_root(&nested), Some(&root.id));
+        assert_eq!(extract_reply(&nested), Some(&reply.id));
+    }
+
+    #[test]
+    fn test_extract_positional() {
+        let keys = Keys::generate();
+        let root = EventId::from_byte_array([0; 32]);
+        let mention = EventId::from_byte_array([1; 32]);
+        let reply_to = EventId::from_byte_array([2; 32]);
+
+        let event = EventBuilder::text_note("reply")
+            .tag(Tag::event(root))
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(extract_root(&event), Some(&root));
+        assert_eq!(extract_reply(&event), Some(&root));
+
+        let event = EventBuilder::text_note("nested")
+            .tags([Tag::event(root), Tag::event(mention), Tag::event(reply_to)])
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(extract_root(&event), Some(&root));
+        assert_eq!(extract_reply(&event), Some(&reply_to));
+    }
+
+    #[test]
+    fn test_extract_mention_only() {
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("mention")
+            .tag(Tag::from_standardized_without_cell(TagStandard::Event {
+                event_id: EventId::from_byte_array([0; 32]),
+                relay_url: None,
+                marker: Some(Marker::Mention),
+                public_key: None,
+                uppercase: false,
+            }))
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(extract_root(&event), None);
+        assert_eq!(extract_reply(&event), None);
+    }
+}
@@ -10,18 +10,23 @@
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt;
+#[cfg(feature = "nip11")]
 use std::net::SocketAddr;
 
+#[cfg(feature = "nip11")]
 use reqwest::Client;
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "nip11", not(target_arch = "wasm32")))]
 use reqwest::Proxy;
 
-use crate::{Timestamp, Url};
+use crate::Timestamp;
+#[cfg(feature = "nip11")]
+use crate::Url;
 
 /// `NIP11` error
 #[derive(Debug)]
 pub enum Error {
     /// Reqwest error
+    #[cfg(feature = "nip11")]
     Reqwest(reqwest::Error),
     /// The relay information document is invalid
     InvalidInformationDocument,
@@ -36,6 +41,7 @@ impl std::error::Error for Error {}
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "nip11")]
             Self::Reqwest(e) => write!(f, "{e}"),
             Self::InvalidInformationDocument => {
                 write!(f, "The relay information document is invalid")
@@ -48,6 +54,7 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "nip11")]
 impl From<reqwest::Error> for Error {
     fn from(e: reqwest::Error) -> Self {
         Self::Reqwest(e)
@@ -189,6 +196,7 @@ impl RelayInformationDocument {
     /// Get Relay Information Document
     ///
     /// **Proxy is ignored for WASM targets!**
+    #[cfg(feature = "nip11")]
     pub async fn get(mut url: Url, _proxy: Option<SocketAddr>) -> Result<Self, Error> {
         #[cfg(not(target_arch = "wasm32"))]
         let client: Client = {
@@ -219,6 +227,7 @@ impl RelayInformationDocument {
 
     /// Returns new URL with scheme substituted to HTTP(S) if WS(S) was provided,
     /// other schemes leaves untouched.
+    #[cfg(feature = "nip11")]
     fn with_http_scheme(url: &mut Url) -> Result<&str, Error> {
         match url.scheme() {
             "wss" => url.set_scheme("https").map_err(|_| Error::InvalidScheme)?,
@@ -8,7 +8,9 @@
 
 use crate::nips::nip01::Coordinate;
 use crate::nips::nip73::ExternalContentId;
-use crate::{Event, EventId, Kind, PublicKey, RelayUrl, TagKind, TagStandard, Url};
+use crate::{
+    Alphabet, Event, EventId, Kind, PublicKey, RelayUrl, SingleLetterTag, TagKind, TagStandard, Url,
+};
 
 /// Borrowed comment extracted data
 pub enum Comment<'a> {
@@ -82,6 +84,14 @@ fn extract_data(event: &Event, is_root: bool) -> Option<Comment> {
     None
 }
 
+/// Uppercase tag kind for the root, lowercase for the parent
+fn tag_kind(character: Alphabet, is_root: bool) -> TagKind<'static> {
+    TagKind::SingleLetter(SingleLetterTag {
+        character,
+        uppercase: is_root,
+    })
+}
+
 fn check_return<T>(val: T, is_root: bool, uppercase: bool) -> Option<T> {
     if (is_root && uppercase) || (!is_root && !uppercase) {
         return Some(val);
@@ -98,7 +108,7 @@ fn check_return<T>(val: T, is_root: bool, uppercase: bool) -> Option<T> {
 fn extract_kind(event: &Event, is_root: bool) -> Option<&Kind> {
     event
         .tags
-        .filter_standardized(TagKind::k())
+        .filter_standardized(tag_kind(Alphabet::K, is_root))
         .find_map(|tag| match tag {
             TagStandard::Kind { kind, uppercase } => check_return(kind, is_root, *uppercase),
             _ => None,
@@ -116,7 +126,7 @@ fn extract_event(
 ) -> Option<(&EventId, Option<&RelayUrl>, Option<&PublicKey>)> {
     event
         .tags
-        .filter_standardized(TagKind::e())
+        .filter_standardized(tag_kind(Alphabet::E, is_root))
         .find_map(|tag| match tag {
             TagStandard::Event {
                 event_id,
@@ -141,7 +151,7 @@ fn extract_event(
 fn extract_coordinate(event: &Event, is_root: bool) -> Option<(&Coordinate, Option<&RelayUrl>)> {
     event
         .tags
-        .filter_standardized(TagKind::a())
+        .filter_standardized(tag_kind(Alphabet::A, is_root))
         .find_map(|tag| match tag {
             TagStandard::Coordinate {
                 coordinate,
@@ -161,7 +171,7 @@ fn extract_coordinate(event: &Event, is_root: bool) -> Option<(&Coordinate, Opti
 fn extract_external(event: &Event, is_root: bool) -> Option<(&ExternalContentId, Option<&Url>)> {
     event
         .tags
-        .filter_standardized(TagKind::i())
+        .filter_standardized(tag_kind(Alphabet::I, is_root))
         .find_map(|tag| match tag {
             TagStandard::ExternalContent {
                 content,
@@ -171,3 +181,41 @@ fn extract_external(event: &Event, is_root: bool) -> Option<(&ExternalContentId,
             _ => None,
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_extract_comment() {
+        let keys = Keys::generate();
+        let root = EventBuilder::text_note("root")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let comment = EventBuilder::comment("comment", &root, Some(&root), None)
+            .sign_with_keys(&keys)
+            .unwrap();
+        let reply = EventBuilder::comment("reply", &comment, Some(&root), None)
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        match extract_root(&reply) {
+            Some(Comment::Event { id, kind, .. }) => {
+                assert_eq!(id, &root.id);
+                assert_eq!(kind, Some(&Kind::TextNote));
+            }
+            _ => panic!("expected root event"),
+        }
+
+        match extract_parent(&reply) {
+            Some(Comment::Event { id, kind, .. }) => {
+                assert_eq!(id, &comment.id);
+                assert_eq!(kind, Some(&Kind::Comment));
+            }
+            _ => panic!("expected parent event"),
+        }
+
+        assert!(extract_root(&root).is_none());
+    }
+}
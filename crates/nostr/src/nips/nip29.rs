@@ -0,0 +1,903 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP29: Relay-based Groups
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/29.md>
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::types::url::{Error as RelayUrlError, RelayUrl, Url};
+use crate::{Event, EventId, Kind, PublicKey, Tag, TagKind, Timestamp};
+
+const PUBLIC: &str = "public";
+const PRIVATE: &str = "private";
+const OPEN: &str = "open";
+const CLOSED: &str = "closed";
+const ABOUT: &str = "about";
+const PICTURE: &str = "picture";
+const ROLE: &str = "role";
+
+/// NIP29 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Relay URL error
+    RelayUrl(RelayUrlError),
+    /// Invalid group address
+    InvalidGroupAddress,
+    /// Group ID (`d` tag) missing
+    GroupIdMissing,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RelayUrl(e) => write!(f, "{e}"),
+            Self::InvalidGroupAddress => write!(f, "Invalid group address"),
+            Self::GroupIdMissing => write!(f, "Group ID missing"),
+        }
+    }
+}
+
+impl From<RelayUrlError> for Error {
+    fn from(e: RelayUrlError) -> Self {
+        Self::RelayUrl(e)
+    }
+}
+
+/// Group address
+///
+/// Format: `<host>'<group-id>` (i.e., `groups.nostr.com'abcdef`)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupAddress {
+    /// Relay hosting the group
+    pub relay_url: RelayUrl,
+    /// Group ID
+    pub id: String,
+}
+
+impl GroupAddress {
+    /// New group address
+    #[inline]
+    pub fn new<S>(relay_url: RelayUrl, id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            relay_url,
+            id: id.into(),
+        }
+    }
+
+    /// Parse group address
+    ///
+    /// If the host has no scheme, `wss://` is used.
+    pub fn parse(address: &str) -> Result<Self, Error> {
+        let (host, id) = address
+            .rsplit_once('\'')
+            .ok_or(Error::InvalidGroupAddress)?;
+
+        if host.is_empty() || id.is_empty() {
+            return Err(Error::InvalidGroupAddress);
+        }
+
+        let relay_url: RelayUrl = if host.contains("://") {
+            RelayUrl::parse(host)?
+        } else {
+            RelayUrl::parse(&format!("wss://{host}"))?
+        };
+
+        Ok(Self::new(relay_url, id))
+    }
+}
+
+impl FromStr for GroupAddress {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        Self::parse(address)
+    }
+}
+
+impl fmt::Display for GroupAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let url: &str = self.relay_url.as_str_without_trailing_slash();
+        let host: &str = url.strip_prefix("wss://").unwrap_or(url);
+        write!(f, "{host}'{}", self.id)
+    }
+}
+
+/// Group metadata
+///
+/// Published by the relay with kind [`Kind::GroupMetadata`] and
+/// edited by the admins with kind [`Kind::GroupEditMetadata`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupMetadata {
+    /// Group ID
+    pub id: String,
+    /// Name
+    pub name: Option<String>,
+    /// Picture
+    pub picture: Option<Url>,
+    /// About
+    pub about: Option<String>,
+    /// Only the members can read the group messages
+    pub private: bool,
+    /// The join requests are ignored (invite only)
+    pub closed: bool,
+}
+
+impl GroupMetadata {
+    /// New group metadata
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            name: None,
+            picture: None,
+            about: None,
+            private: false,
+            closed: false,
+        }
+    }
+
+    /// Metadata tags, without the group ID
+    pub(crate) fn to_tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = Vec::with_capacity(5);
+
+        if let Some(name) = &self.name {
+            tags.push(Tag::custom(TagKind::Name, [name]));
+        }
+
+        if let Some(picture) = &self.picture {
+            tags.push(Tag::custom(TagKind::custom(PICTURE), [picture.as_str()]));
+        }
+
+        if let Some(about) = &self.about {
+            tags.push(Tag::custom(TagKind::custom(ABOUT), [about]));
+        }
+
+        let privacy: &str = if self.private { PRIVATE } else { PUBLIC };
+        tags.push(Tag::custom(TagKind::custom(privacy), Vec::<String>::new()));
+
+        let access: &str = if self.closed { CLOSED } else { OPEN };
+        tags.push(Tag::custom(TagKind::custom(access), Vec::<String>::new()));
+
+        tags
+    }
+
+    /// Update the metadata with the fields included in the tags
+    ///
+    /// Returns `true` if something changed.
+    fn update(&mut self, tags: &[Tag]) -> bool {
+        let prev: Self = self.clone();
+
+        for tag in tags.iter() {
+            let value: Option<&str> = tag.content();
+            match (tag.kind(), value) {
+                (TagKind::Name, Some(name)) => self.name = Some(name.to_string()),
+                (TagKind::Custom(kind), value) => match (kind.as_ref(), value) {
+                    (PICTURE, Some(picture)) => {
+                        // Ignore invalid URLs
+                        if let Ok(url) = Url::parse(picture) {
+                            self.picture = Some(url);
+                        }
+                    }
+                    (ABOUT, Some(about)) => self.about = Some(about.to_string()),
+                    (PUBLIC, ..) => self.private = false,
+                    (PRIVATE, ..) => self.private = true,
+                    (OPEN, ..) => self.closed = false,
+                    (CLOSED, ..) => self.closed = true,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        *self != prev
+    }
+}
+
+impl From<GroupMetadata> for Vec<Tag> {
+    fn from(metadata: GroupMetadata) -> Self {
+        let mut tags: Vec<Tag> = Vec::with_capacity(6);
+        tags.push(Tag::identifier(metadata.id.clone()));
+        tags.extend(metadata.to_tags());
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for GroupMetadata {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let mut metadata = Self::new(extract_identifier(&tags)?);
+        metadata.update(&tags);
+        Ok(metadata)
+    }
+}
+
+/// Group admin
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupAdmin {
+    /// Public key
+    pub public_key: PublicKey,
+    /// Roles (see [`GroupRoles`])
+    pub roles: Vec<String>,
+}
+
+impl From<GroupAdmin> for Tag {
+    fn from(admin: GroupAdmin) -> Self {
+        let mut values: Vec<String> = Vec::with_capacity(1 + admin.roles.len());
+        values.push(admin.public_key.to_hex());
+        values.extend(admin.roles);
+        Self::custom(TagKind::p(), values)
+    }
+}
+
+/// Group admins
+///
+/// Published by the relay with kind [`Kind::GroupAdmins`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupAdmins {
+    /// Group ID
+    pub id: String,
+    /// Admins
+    pub admins: Vec<GroupAdmin>,
+}
+
+impl From<GroupAdmins> for Vec<Tag> {
+    fn from(admins: GroupAdmins) -> Self {
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + admins.admins.len());
+        tags.push(Tag::identifier(admins.id));
+        tags.extend(admins.admins.into_iter().map(Tag::from));
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for GroupAdmins {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: extract_identifier(&tags)?,
+            admins: extract_users(&tags)
+                .map(|(public_key, roles)| GroupAdmin { public_key, roles })
+                .collect(),
+        })
+    }
+}
+
+/// Group members
+///
+/// Published by the relay with kind [`Kind::GroupMembers`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupMembers {
+    /// Group ID
+    pub id: String,
+    /// Members
+    pub members: BTreeSet<PublicKey>,
+}
+
+impl From<GroupMembers> for Vec<Tag> {
+    fn from(members: GroupMembers) -> Self {
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + members.members.len());
+        tags.push(Tag::identifier(members.id));
+        tags.extend(members.members.into_iter().map(Tag::public_key));
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for GroupMembers {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: extract_identifier(&tags)?,
+            members: extract_users(&tags)
+                .map(|(public_key, ..)| public_key)
+                .collect(),
+        })
+    }
+}
+
+/// Group role
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupRole {
+    /// Name
+    pub name: String,
+    /// Description
+    pub description: Option<String>,
+}
+
+/// Group roles
+///
+/// Published by the relay with kind [`Kind::GroupRoles`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupRoles {
+    /// Group ID
+    pub id: String,
+    /// Roles
+    pub roles: Vec<GroupRole>,
+}
+
+impl From<GroupRoles> for Vec<Tag> {
+    fn from(roles: GroupRoles) -> Self {
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + roles.roles.len());
+        tags.push(Tag::identifier(roles.id));
+        tags.extend(roles.roles.into_iter().map(|role| {
+            let mut values: Vec<String> = Vec::with_capacity(2);
+            values.push(role.name);
+            values.extend(role.description);
+            Tag::custom(TagKind::custom(ROLE), values)
+        }));
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for GroupRoles {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: extract_identifier(&tags)?,
+            roles: tags
+                .iter()
+                .filter(|tag| tag.kind() == TagKind::custom(ROLE))
+                .filter_map(|tag| {
+                    let values: &[String] = tag.as_slice();
+                    Some(GroupRole {
+                        name: values.get(1)?.clone(),
+                        description: values.get(2).cloned(),
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Group state
+///
+/// Built by applying, in any order, the events published by the relay
+/// ([`Kind::GroupMetadata`], [`Kind::GroupAdmins`], [`Kind::GroupMembers`] and [`Kind::GroupRoles`])
+/// and the moderation events ([`Kind::GroupPutUser`], [`Kind::GroupRemoveUser`],
+/// [`Kind::GroupEditMetadata`] and [`Kind::GroupDelete`]).
+///
+/// The events published by the relay are accepted only if signed by the relay public key
+/// (the `pubkey` of its NIP11 document).
+/// The state is the last snapshot published by the relay, with the moderation events
+/// not older than the snapshot replayed on top, in chronological order.
+/// A moderation event is replayed only if its author is an admin at that point (or the relay).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupState {
+    /// Group ID
+    pub id: String,
+    /// Relay public key
+    pub relay_public_key: PublicKey,
+    /// Metadata
+    pub metadata: Option<GroupMetadata>,
+    /// Admins
+    pub admins: Vec<GroupAdmin>,
+    /// Members
+    pub members: BTreeSet<PublicKey>,
+    /// Roles
+    pub roles: Vec<GroupRole>,
+    /// The group has been deleted
+    pub deleted: bool,
+    metadata_snapshot: Option<Snapshot<GroupMetadata>>,
+    admins_snapshot: Option<Snapshot<Vec<GroupAdmin>>>,
+    members_snapshot: Option<Snapshot<BTreeSet<PublicKey>>>,
+    roles_snapshot: Option<Snapshot<Vec<GroupRole>>>,
+    moderation: BTreeMap<(Timestamp, EventId), Event>,
+}
+
+/// State published by the relay
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot<T> {
+    created_at: Timestamp,
+    value: T,
+}
+
+impl GroupState {
+    /// New empty group state
+    pub fn new<S>(id: S, relay_public_key: PublicKey) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            relay_public_key,
+            metadata: None,
+            admins: Vec::new(),
+            members: BTreeSet::new(),
+            roles: Vec::new(),
+            deleted: false,
+            metadata_snapshot: None,
+            admins_snapshot: None,
+            members_snapshot: None,
+            roles_snapshot: None,
+            moderation: BTreeMap::new(),
+        }
+    }
+
+    /// Apply an event to the state
+    ///
+    /// Returns `true` if the state changed.
+    pub fn apply(&mut self, event: &Event) -> bool {
+        if extract_group_id(event) != Some(self.id.as_str()) {
+            return false;
+        }
+
+        let created_at: Timestamp = event.created_at;
+        let tags: &[Tag] = event.tags.as_slice();
+
+        let updated: bool = match event.kind {
+            Kind::GroupMetadata | Kind::GroupAdmins | Kind::GroupMembers | Kind::GroupRoles
+                if event.pubkey != self.relay_public_key =>
+            {
+                false
+            }
+            Kind::GroupMetadata => {
+                let mut metadata = GroupMetadata::new(self.id.clone());
+                metadata.update(tags);
+                update_snapshot(&mut self.metadata_snapshot, created_at, metadata)
+            }
+            Kind::GroupAdmins => {
+                let admins: Vec<GroupAdmin> = extract_users(tags)
+                    .map(|(public_key, roles)| GroupAdmin { public_key, roles })
+                    .collect();
+                update_snapshot(&mut self.admins_snapshot, created_at, admins)
+            }
+            Kind::GroupMembers => {
+                let members: BTreeSet<PublicKey> = extract_users(tags)
+                    .map(|(public_key, ..)| public_key)
+                    .collect();
+                update_snapshot(&mut self.members_snapshot, created_at, members)
+            }
+            Kind::GroupRoles => match GroupRoles::try_from(tags.to_vec()) {
+                Ok(roles) => update_snapshot(&mut self.roles_snapshot, created_at, roles.roles),
+                Err(..) => false,
+            },
+            Kind::GroupPutUser
+            | Kind::GroupRemoveUser
+            | Kind::GroupEditMetadata
+            | Kind::GroupDelete => self
+                .moderation
+                .insert((created_at, event.id), event.clone())
+                .is_none(),
+            _ => false,
+        };
+
+        updated && self.rebuild()
+    }
+
+    /// Rebuild the state from the snapshots and the moderation events
+    ///
+    /// Returns `true` if the state changed.
+    fn rebuild(&mut self) -> bool {
+        let mut metadata: Option<GroupMetadata> = snapshot_value(&self.metadata_snapshot);
+        let mut admins: Vec<GroupAdmin> = snapshot_value(&self.admins_snapshot).unwrap_or_default();
+        let mut members: BTreeSet<PublicKey> =
+            snapshot_value(&self.members_snapshot).unwrap_or_default();
+        let roles: Vec<GroupRole> = snapshot_value(&self.roles_snapshot).unwrap_or_default();
+        let mut deleted: bool = false;
+
+        for event in self.moderation.values() {
+            // Only the admins (or the relay) can moderate the group
+            if event.pubkey != self.relay_public_key
+                && !admins.iter().any(|a| a.public_key == event.pubkey)
+            {
+                continue;
+            }
+
+            let created_at: Timestamp = event.created_at;
+            let tags: &[Tag] = event.tags.as_slice();
+
+            match event.kind {
+                Kind::GroupPutUser => {
+                    for (public_key, roles) in extract_users(tags) {
+                        if is_not_older(&self.members_snapshot, created_at) {
+                            members.insert(public_key);
+                        }
+
+                        if !roles.is_empty() && is_not_older(&self.admins_snapshot, created_at) {
+                            let admin = GroupAdmin { public_key, roles };
+                            match admins.iter_mut().find(|a| a.public_key == admin.public_key) {
+                                Some(current) => *current = admin,
+                                None => admins.push(admin),
+                            }
+                        }
+                    }
+                }
+                Kind::GroupRemoveUser => {
+                    for (public_key, ..) in extract_users(tags) {
+                        if is_not_older(&self.members_snapshot, created_at) {
+                            members.remove(&public_key);
+                        }
+
+                        if is_not_older(&self.admins_snapshot, created_at) {
+                            admins.retain(|a| a.public_key != public_key);
+                        }
+                    }
+                }
+                Kind::GroupEditMetadata => {
+                    if is_not_older(&self.metadata_snapshot, created_at) {
+                        metadata
+                            .get_or_insert_with(|| GroupMetadata::new(self.id.clone()))
+                            .update(tags);
+                    }
+                }
+                Kind::GroupDelete => deleted = true,
+                _ => {}
+            }
+        }
+
+        let mut changed: bool = replace(&mut self.metadata, metadata);
+        changed |= replace(&mut self.admins, admins);
+        changed |= replace(&mut self.members, members);
+        changed |= replace(&mut self.roles, roles);
+        changed |= replace(&mut self.deleted, deleted);
+        changed
+    }
+
+    /// Check if the public key is a member (or an admin) of the group
+    pub fn is_member(&self, public_key: &PublicKey) -> bool {
+        self.members.contains(public_key) || self.is_admin(public_key)
+    }
+
+    /// Check if the public key is an admin of the group
+    pub fn is_admin(&self, public_key: &PublicKey) -> bool {
+        self.admins.iter().any(|a| &a.public_key == public_key)
+    }
+}
+
+/// Compose `h` tag
+///
+/// JSON: `["h", "<group-id>"]`
+pub(crate) fn group_id_tag<S>(id: S) -> Tag
+where
+    S: Into<String>,
+{
+    Tag::custom(TagKind::h(), [id])
+}
+
+/// Extract the group ID
+///
+/// Returns the `d` tag for the events published by the relay
+/// ([`Kind::GroupMetadata`], [`Kind::GroupAdmins`], [`Kind::GroupMembers`] and [`Kind::GroupRoles`])
+/// and the `h` tag for all the others.
+pub fn extract_group_id(event: &Event) -> Option<&str> {
+    match event.kind {
+        Kind::GroupMetadata | Kind::GroupAdmins | Kind::GroupMembers | Kind::GroupRoles => {
+            event.tags.identifier()
+        }
+        _ => event.tags.find(TagKind::h()).and_then(|tag| tag.content()),
+    }
+}
+
+fn extract_identifier(tags: &[Tag]) -> Result<String, Error> {
+    tags.iter()
+        .find(|tag| tag.kind() == TagKind::d())
+        .and_then(|tag| tag.content())
+        .map(|id| id.to_string())
+        .ok_or(Error::GroupIdMissing)
+}
+
+/// Extract the valid `p` tags, with the roles
+fn extract_users(tags: &[Tag]) -> impl Iterator<Item = (PublicKey, Vec<String>)> + '_ {
+    tags.iter()
+        .filter(|tag| tag.kind() == TagKind::p())
+        .filter_map(|tag| {
+            let values: &[String] = tag.as_slice();
+            let public_key: PublicKey = PublicKey::from_hex(values.get(1)?).ok()?;
+            let roles: Vec<String> = values.iter().skip(2).cloned().collect();
+            Some((public_key, roles))
+        })
+}
+
+/// Replace the snapshot if the new one isn't older, returning `true` if changed
+fn update_snapshot<T>(snapshot: &mut Option<Snapshot<T>>, created_at: Timestamp, value: T) -> bool
+where
+    T: PartialEq,
+{
+    match snapshot {
+        Some(current) if created_at < current.created_at => false,
+        _ => replace(snapshot, Some(Snapshot { created_at, value })),
+    }
+}
+
+/// Get a copy of the snapshot value
+fn snapshot_value<T>(snapshot: &Option<Snapshot<T>>) -> Option<T>
+where
+    T: Clone,
+{
+    snapshot.as_ref().map(|s| s.value.clone())
+}
+
+/// Check if the moderation event isn't older than the snapshot
+fn is_not_older<T>(snapshot: &Option<Snapshot<T>>, created_at: Timestamp) -> bool {
+    match snapshot {
+        Some(snapshot) => created_at >= snapshot.created_at,
+        None => true,
+    }
+}
+
+/// Replace the value, returning `true` if changed
+fn replace<T>(current: &mut T, new: T) -> bool
+where
+    T: PartialEq,
+{
+    if *current == new {
+        return false;
+    }
+
+    *current = new;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_group_address() {
+        let address = GroupAddress::parse("groups.nostr.com'abcdef").unwrap();
+        assert_eq!(
+            address.relay_url,
+            RelayUrl::parse("wss://groups.nostr.com").unwrap()
+        );
+        assert_eq!(address.id, "abcdef");
+        assert_eq!(address.to_string(), "groups.nostr.com'abcdef");
+
+        let address = GroupAddress::parse("ws://127.0.0.1:7777'abc").unwrap();
+        assert_eq!(address.to_string(), "ws://127.0.0.1:7777'abc");
+
+        assert_eq!(
+            GroupAddress::parse("groups.nostr.com").unwrap_err(),
+            Error::InvalidGroupAddress
+        );
+        assert_eq!(
+            GroupAddress::parse("groups.nostr.com'").unwrap_err(),
+            Error::InvalidGroupAddress
+        );
+    }
+
+    #[test]
+    fn test_group_metadata_tags() {
+        let metadata = GroupMetadata {
+            id: String::from("abcdef"),
+            name: Some(String::from("Pizza Lovers")),
+            picture: Some(Url::parse("https://pizza.com/pizza.png").unwrap()),
+            about: Some(String::from("a group for people who love pizza")),
+            private: true,
+            closed: false,
+        };
+
+        let tags: Vec<Tag> = metadata.clone().into();
+        assert_eq!(tags[0], Tag::identifier("abcdef"));
+        assert!(tags.contains(&Tag::parse(["private"]).unwrap()));
+        assert!(tags.contains(&Tag::parse(["open"]).unwrap()));
+
+        assert_eq!(GroupMetadata::try_from(tags).unwrap(), metadata);
+
+        assert_eq!(
+            GroupMetadata::try_from(vec![Tag::parse(["name", "test"]).unwrap()]).unwrap_err(),
+            Error::GroupIdMissing
+        );
+    }
+
+    #[test]
+    fn test_group_admins_and_roles_tags() {
+        let public_key = Keys::generate().public_key();
+
+        let admins = GroupAdmins {
+            id: String::from("abcdef"),
+            admins: vec![GroupAdmin {
+                public_key,
+                roles: vec![String::from("ceo"), String::from("gardener")],
+            }],
+        };
+        let tags: Vec<Tag> = admins.clone().into();
+        assert_eq!(GroupAdmins::try_from(tags).unwrap(), admins);
+
+        let roles = GroupRoles {
+            id: String::from("abcdef"),
+            roles: vec![
+                GroupRole {
+                    name: String::from("ceo"),
+                    description: Some(String::from("The boss")),
+                },
+                GroupRole {
+                    name: String::from("gardener"),
+                    description: None,
+                },
+            ],
+        };
+        let tags: Vec<Tag> = roles.clone().into();
+        assert_eq!(GroupRoles::try_from(tags).unwrap(), roles);
+    }
+
+    #[test]
+    fn test_group_state() {
+        let relay = Keys::generate();
+        let admin = Keys::generate();
+        let user = Keys::generate();
+        let alice = Keys::generate();
+
+        let event = |keys: &Keys, builder: EventBuilder, created_at: u64| -> Event {
+            builder
+                .custom_created_at(Timestamp::from_secs(created_at))
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+        let members = |created_at: u64, members: &[&Keys]| -> Event {
+            let tags: Vec<Tag> = GroupMembers {
+                id: String::from("abcdef"),
+                members: members.iter().map(|k| k.public_key()).collect(),
+            }
+            .into();
+            event(
+                &relay,
+                EventBuilder::new(Kind::GroupMembers, "").tags(tags),
+                created_at,
+            )
+        };
+
+        let mut state = GroupState::new("abcdef", relay.public_key());
+
+        // Relay snapshots
+        let mut metadata = GroupMetadata::new("abcdef");
+        metadata.name = Some(String::from("Pizza Lovers"));
+        let tags: Vec<Tag> = metadata.into();
+        assert!(state.apply(&event(
+            &relay,
+            EventBuilder::new(Kind::GroupMetadata, "").tags(tags),
+            10
+        )));
+        assert_eq!(
+            state.metadata.as_ref().unwrap().name.as_deref(),
+            Some("Pizza Lovers")
+        );
+
+        let tags: Vec<Tag> = GroupAdmins {
+            id: String::from("abcdef"),
+            admins: vec![GroupAdmin {
+                public_key: admin.public_key(),
+                roles: vec![String::from("ceo")],
+            }],
+        }
+        .into();
+        assert!(state.apply(&event(
+            &relay,
+            EventBuilder::new(Kind::GroupAdmins, "").tags(tags),
+            10
+        )));
+        assert!(state.is_admin(&admin.public_key()));
+
+        let snapshot: Event = members(10, &[&admin]);
+        assert!(state.apply(&snapshot));
+
+        // Same snapshot again
+        assert!(!state.apply(&snapshot));
+
+        // Snapshot not signed by the relay
+        let tags: Vec<Tag> = GroupMembers {
+            id: String::from("abcdef"),
+            members: BTreeSet::new(),
+        }
+        .into();
+        assert!(!state.apply(&event(
+            &admin,
+            EventBuilder::new(Kind::GroupMembers, "").tags(tags),
+            11
+        )));
+        assert!(state.is_member(&admin.public_key()));
+
+        // Put user
+        assert!(state.apply(&event(
+            &admin,
+            EventBuilder::group_put_user("abcdef", user.public_key(), ["moderator"]),
+            20
+        )));
+        assert!(state.is_member(&user.public_key()));
+        assert!(state.is_admin(&user.public_key()));
+
+        // Not an admin
+        assert!(!state.apply(&event(
+            &alice,
+            EventBuilder::group_put_user("abcdef", alice.public_key(), ["ceo"]),
+            21
+        )));
+        assert!(!state.is_member(&alice.public_key()));
+
+        // Snapshot received late: the newer moderation events are replayed on top
+        assert!(state.apply(&members(15, &[&admin, &alice])));
+        assert!(state.is_member(&alice.public_key()));
+        assert!(state.members.contains(&user.public_key()));
+
+        // Older snapshot: ignored
+        assert!(!state.apply(&members(5, &[])));
+        assert!(state.is_member(&alice.public_key()));
+
+        // Edit metadata
+        let mut metadata = GroupMetadata::new("abcdef");
+        metadata.closed = true;
+        assert!(state.apply(&event(
+            &admin,
+            EventBuilder::group_edit_metadata(metadata),
+            30
+        )));
+        let metadata = state.metadata.as_ref().unwrap();
+        assert!(metadata.closed);
+        assert_eq!(metadata.name.as_deref(), Some("Pizza Lovers"));
+
+        // Remove user
+        assert!(state.apply(&event(
+            &admin,
+            EventBuilder::group_remove_user("abcdef", user.public_key()),
+            40
+        )));
+        assert!(!state.is_member(&user.public_key()));
+
+        // Another group
+        assert!(!state.apply(&event(
+            &admin,
+            EventBuilder::group_remove_user("other", admin.public_key()),
+            50
+        )));
+        assert!(state.is_member(&admin.public_key()));
+    }
+
+    #[test]
+    fn test_group_state_any_order() {
+        let relay = Keys::generate();
+        let admin = Keys::generate();
+        let user = Keys::generate();
+
+        let put_user: Event =
+            EventBuilder::group_put_user("abcdef", user.public_key(), Vec::<String>::new())
+                .custom_created_at(Timestamp::from_secs(20))
+                .sign_with_keys(&admin)
+                .unwrap();
+        let tags: Vec<Tag> = GroupAdmins {
+            id: String::from("abcdef"),
+            admins: vec![GroupAdmin {
+                public_key: admin.public_key(),
+                roles: vec![String::from("ceo")],
+            }],
+        }
+        .into();
+        let admins: Event = EventBuilder::new(Kind::GroupAdmins, "")
+            .tags(tags)
+            .custom_created_at(Timestamp::from_secs(10))
+            .sign_with_keys(&relay)
+            .unwrap();
+
+        let mut state = GroupState::new("abcdef", relay.public_key());
+
+        // The author isn't an admin yet
+        assert!(!state.apply(&put_user));
+        assert!(!state.is_member(&user.public_key()));
+
+        assert!(state.apply(&admins));
+        assert!(state.is_member(&user.public_key()));
+
+        let mut other = GroupState::new("abcdef", relay.public_key());
+        other.apply(&admins);
+        other.apply(&put_user);
+        assert_eq!(state, other);
+    }
+}
@@ -0,0 +1,1206 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP52: Calendar Events
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/52.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::RangeInclusive;
+use core::str::FromStr;
+
+use super::nip01::{self, Coordinate};
+use crate::types::{RelayUrl, Url};
+use crate::{Alphabet, Event, EventId, Kind, PublicKey, SingleLetterTag, Tag, TagKind, Timestamp};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+/// Max number of days spanned by a time-based calendar event (one `D` tag per day)
+const MAX_DAYS: u64 = 366;
+
+/// NIP52 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// NIP01 error
+    NIP01(nip01::Error),
+    /// Invalid date: expected `YYYY-MM-DD` format
+    InvalidDate(String),
+    /// Invalid timestamp: expected unix seconds
+    InvalidTimestamp(String),
+    /// The end isn't after the start
+    InvalidRange,
+    /// The time-based event spans too many days
+    TooManyDays(u64),
+    /// Unexpected event kind
+    UnexpectedKind(Kind),
+    /// Unknown RSVP status
+    UnknownRsvpStatus(String),
+    /// Unknown free/busy value
+    UnknownFreeBusy(String),
+    /// Identifier (`d` tag) missing
+    IdentifierMissing,
+    /// Title missing
+    TitleMissing,
+    /// Start missing
+    StartMissing,
+    /// Calendar event coordinate (`a` tag) missing
+    CoordinateMissing,
+    /// RSVP status missing
+    StatusMissing,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NIP01(e) => write!(f, "{e}"),
+            Self::InvalidDate(date) => write!(f, "Invalid date: {date}"),
+            Self::InvalidTimestamp(timestamp) => write!(f, "Invalid timestamp: {timestamp}"),
+            Self::InvalidRange => write!(f, "The end must be after the start"),
+            Self::TooManyDays(days) => {
+                write!(f, "The event spans {days} days (max {MAX_DAYS})")
+            }
+            Self::UnexpectedKind(kind) => write!(f, "Unexpected kind: {kind}"),
+            Self::UnknownRsvpStatus(status) => write!(f, "Unknown RSVP status: {status}"),
+            Self::UnknownFreeBusy(fb) => write!(f, "Unknown free/busy value: {fb}"),
+            Self::IdentifierMissing => write!(f, "Identifier missing"),
+            Self::TitleMissing => write!(f, "Title missing"),
+            Self::StartMissing => write!(f, "Start missing"),
+            Self::CoordinateMissing => write!(f, "Calendar event coordinate missing"),
+            Self::StatusMissing => write!(f, "RSVP status missing"),
+        }
+    }
+}
+
+impl From<nip01::Error> for Error {
+    fn from(e: nip01::Error) -> Self {
+        Self::NIP01(e)
+    }
+}
+
+/// Calendar date, in ISO 8601 format (`YYYY-MM-DD`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl CalendarDate {
+    /// Construct a new date
+    ///
+    /// Returns an error if the day doesn't exist (i.e., `2025-02-29`).
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self, Error> {
+        if year > 9999 || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month)
+        {
+            let date = Self { year, month, day };
+            return Err(Error::InvalidDate(date.to_string()));
+        }
+
+        Ok(Self { year, month, day })
+    }
+
+    /// Parse date from `YYYY-MM-DD` format
+    pub fn parse(date: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidDate(date.to_string());
+
+        let bytes: &[u8] = date.as_bytes();
+        if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+            return Err(invalid());
+        }
+
+        if !bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let year: u16 = date[0..4].parse().map_err(|_| invalid())?;
+        let month: u8 = date[5..7].parse().map_err(|_| invalid())?;
+        let day: u8 = date[8..10].parse().map_err(|_| invalid())?;
+
+        Self::new(year, month, day)
+    }
+
+    /// Year
+    #[inline]
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Month (`1..=12`)
+    #[inline]
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Day of the month (`1..=31`)
+    #[inline]
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl FromStr for CalendarDate {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(date: &str) -> Result<Self, Self::Err> {
+        Self::parse(date)
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Start or end of a calendar event
+///
+/// A date for the date-based events and a timestamp for the time-based ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CalendarEventTime {
+    /// Date (`YYYY-MM-DD`)
+    Date(CalendarDate),
+    /// Unix timestamp, in seconds
+    Timestamp(Timestamp),
+}
+
+impl CalendarEventTime {
+    /// Parse from a `YYYY-MM-DD` date or a unix timestamp
+    pub fn parse(time: &str) -> Result<Self, Error> {
+        if time.contains('-') {
+            Ok(Self::Date(CalendarDate::parse(time)?))
+        } else {
+            Ok(Self::Timestamp(parse_timestamp(time)?))
+        }
+    }
+}
+
+impl FromStr for CalendarEventTime {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(time: &str) -> Result<Self, Self::Err> {
+        Self::parse(time)
+    }
+}
+
+impl fmt::Display for CalendarEventTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Date(date) => write!(f, "{date}"),
+            Self::Timestamp(timestamp) => write!(f, "{timestamp}"),
+        }
+    }
+}
+
+/// Calendar event participant
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarEventParticipant {
+    /// Public key
+    pub public_key: PublicKey,
+    /// Recommended relay URL
+    pub relay_url: Option<RelayUrl>,
+    /// Role in the meeting (i.e., `organizer`, `speaker`)
+    pub role: Option<String>,
+}
+
+impl From<CalendarEventParticipant> for Tag {
+    fn from(participant: CalendarEventParticipant) -> Self {
+        let mut values: Vec<String> = Vec::with_capacity(3);
+        values.push(participant.public_key.to_hex());
+
+        match (participant.relay_url, participant.role) {
+            (relay_url, Some(role)) => {
+                values.push(relay_url.map(|u| u.to_string()).unwrap_or_default());
+                values.push(role);
+            }
+            (Some(relay_url), None) => values.push(relay_url.to_string()),
+            (None, None) => {}
+        }
+
+        Self::custom(TagKind::p(), values)
+    }
+}
+
+/// Date-based calendar event
+///
+/// All-day or multi-day event, published with kind [`Kind::DateBasedCalendarEvent`].
+/// The description of the event goes in the content.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateBasedCalendarEvent {
+    /// Unique identifier
+    pub id: String,
+    /// Title
+    pub title: String,
+    /// Start date
+    pub start: CalendarDate,
+    /// End date (exclusive)
+    ///
+    /// If `None`, the event ends on the same date as the start.
+    pub end: Option<CalendarDate>,
+    /// Summary
+    pub summary: Option<String>,
+    /// Image
+    pub image: Option<Url>,
+    /// Locations (i.e., address, coordinates or URL of a call)
+    pub locations: Vec<String>,
+    /// Geohash
+    pub geohash: Option<String>,
+    /// Participants
+    pub participants: Vec<CalendarEventParticipant>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+    /// References (i.e., web pages, documents)
+    pub references: Vec<String>,
+}
+
+impl DateBasedCalendarEvent {
+    /// New date-based calendar event
+    pub fn new<S1, S2>(id: S1, title: S2, start: CalendarDate) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            start,
+            end: None,
+            summary: None,
+            image: None,
+            locations: Vec::new(),
+            geohash: None,
+            participants: Vec::new(),
+            hashtags: Vec::new(),
+            references: Vec::new(),
+        }
+    }
+}
+
+impl From<DateBasedCalendarEvent> for Vec<Tag> {
+    fn from(event: DateBasedCalendarEvent) -> Self {
+        let DateBasedCalendarEvent {
+            id,
+            title,
+            start,
+            end,
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        } = event;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(4);
+
+        tags.push(Tag::identifier(id));
+        tags.push(Tag::title(title));
+        tags.push(Tag::custom(TagKind::Start, [start.to_string()]));
+
+        if let Some(end) = end {
+            tags.push(Tag::custom(TagKind::End, [end.to_string()]));
+        }
+
+        CommonFields {
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        }
+        .push_tags(&mut tags);
+
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for DateBasedCalendarEvent {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let (id, title) = extract_identifier_and_title(&tags)?;
+
+        let start: CalendarDate = match find_content(&tags, TagKind::Start) {
+            Some(start) => CalendarDate::parse(start)?,
+            None => return Err(Error::StartMissing),
+        };
+
+        let end: Option<CalendarDate> = match find_content(&tags, TagKind::End) {
+            Some(end) => Some(CalendarDate::parse(end)?),
+            None => None,
+        };
+
+        check_range(&start, end.as_ref())?;
+
+        let CommonFields {
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        } = CommonFields::parse(&tags);
+
+        Ok(Self {
+            id,
+            title,
+            start,
+            end,
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        })
+    }
+}
+
+impl TryFrom<&Event> for DateBasedCalendarEvent {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        check_kind(event, Kind::DateBasedCalendarEvent)?;
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Time-based calendar event
+///
+/// Event between two moments in time, published with kind [`Kind::TimeBasedCalendarEvent`].
+/// The description of the event goes in the content.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeBasedCalendarEvent {
+    /// Unique identifier
+    pub id: String,
+    /// Title
+    pub title: String,
+    /// Start
+    pub start: Timestamp,
+    /// End (exclusive)
+    ///
+    /// If `None`, the event ends at the start (i.e., a moment in time).
+    pub end: Option<Timestamp>,
+    /// Time zone of the start (IANA Time Zone Database identifier, i.e., `America/Costa_Rica`)
+    pub start_tzid: Option<String>,
+    /// Time zone of the end (IANA Time Zone Database identifier)
+    ///
+    /// If `None`, the time zone of the start is used.
+    pub end_tzid: Option<String>,
+    /// Summary
+    pub summary: Option<String>,
+    /// Image
+    pub image: Option<Url>,
+    /// Locations (i.e., address, coordinates or URL of a call)
+    pub locations: Vec<String>,
+    /// Geohash
+    pub geohash: Option<String>,
+    /// Participants
+    pub participants: Vec<CalendarEventParticipant>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+    /// References (i.e., web pages, documents)
+    pub references: Vec<String>,
+}
+
+impl TimeBasedCalendarEvent {
+    /// New time-based calendar event
+    pub fn new<S1, S2>(id: S1, title: S2, start: Timestamp) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            start,
+            end: None,
+            start_tzid: None,
+            end_tzid: None,
+            summary: None,
+            image: None,
+            locations: Vec::new(),
+            geohash: None,
+            participants: Vec::new(),
+            hashtags: Vec::new(),
+            references: Vec::new(),
+        }
+    }
+
+    /// Days (number of days since the unix epoch) spanned by the event
+    ///
+    /// Used for the `D` tags, to allow querying the events by day.
+    /// Returns an error if the event spans more than 366 days.
+    pub fn days(&self) -> Result<RangeInclusive<u64>, Error> {
+        let first: u64 = self.start.as_u64() / SECONDS_PER_DAY;
+        let last: u64 = match self.end {
+            // The end is exclusive
+            Some(end) if end > self.start => (end.as_u64() - 1) / SECONDS_PER_DAY,
+            _ => first,
+        };
+
+        let days: u64 = last - first + 1;
+        if days > MAX_DAYS {
+            return Err(Error::TooManyDays(days));
+        }
+
+        Ok(first..=last)
+    }
+}
+
+impl TryFrom<TimeBasedCalendarEvent> for Vec<Tag> {
+    type Error = Error;
+
+    fn try_from(event: TimeBasedCalendarEvent) -> Result<Self, Self::Error> {
+        let days: RangeInclusive<u64> = event.days()?;
+
+        let TimeBasedCalendarEvent {
+            id,
+            title,
+            start,
+            end,
+            start_tzid,
+            end_tzid,
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        } = event;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(4 + days.clone().count());
+
+        tags.push(Tag::identifier(id));
+        tags.push(Tag::title(title));
+        tags.push(Tag::custom(TagKind::Start, [start.to_string()]));
+
+        if let Some(end) = end {
+            tags.push(Tag::custom(TagKind::End, [end.to_string()]));
+        }
+
+        if let Some(start_tzid) = start_tzid {
+            tags.push(Tag::custom(TagKind::StartTzid, [start_tzid]));
+        }
+
+        if let Some(end_tzid) = end_tzid {
+            tags.push(Tag::custom(TagKind::EndTzid, [end_tzid]));
+        }
+
+        for day in days {
+            tags.push(Tag::custom(
+                TagKind::SingleLetter(SingleLetterTag::uppercase(Alphabet::D)),
+                [day.to_string()],
+            ));
+        }
+
+        CommonFields {
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        }
+        .push_tags(&mut tags);
+
+        Ok(tags)
+    }
+}
+
+impl TryFrom<Vec<Tag>> for TimeBasedCalendarEvent {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let (id, title) = extract_identifier_and_title(&tags)?;
+
+        let start: Timestamp = match find_content(&tags, TagKind::Start) {
+            Some(start) => parse_timestamp(start)?,
+            None => return Err(Error::StartMissing),
+        };
+
+        let end: Option<Timestamp> = match find_content(&tags, TagKind::End) {
+            Some(end) => Some(parse_timestamp(end)?),
+            None => None,
+        };
+
+        check_range(&start, end.as_ref())?;
+
+        let CommonFields {
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        } = CommonFields::parse(&tags);
+
+        Ok(Self {
+            id,
+            title,
+            start,
+            end,
+            start_tzid: find_content(&tags, TagKind::StartTzid).map(|s| s.to_string()),
+            end_tzid: find_content(&tags, TagKind::EndTzid).map(|s| s.to_string()),
+            summary,
+            image,
+            locations,
+            geohash,
+            participants,
+            hashtags,
+            references,
+        })
+    }
+}
+
+impl TryFrom<&Event> for TimeBasedCalendarEvent {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        check_kind(event, Kind::TimeBasedCalendarEvent)?;
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Calendar
+///
+/// Collection of calendar events, published with kind [`Kind::Calendar`].
+/// The description of the calendar goes in the content.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Calendar {
+    /// Unique identifier
+    pub id: String,
+    /// Title
+    pub title: String,
+    /// Calendar events
+    pub events: Vec<(Coordinate, Option<RelayUrl>)>,
+}
+
+impl Calendar {
+    /// New calendar
+    pub fn new<S1, S2>(id: S1, title: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl From<Calendar> for Vec<Tag> {
+    fn from(calendar: Calendar) -> Self {
+        let mut tags: Vec<Tag> = Vec::with_capacity(2 + calendar.events.len());
+        tags.push(Tag::identifier(calendar.id));
+        tags.push(Tag::title(calendar.title));
+        tags.extend(
+            calendar
+                .events
+                .into_iter()
+                .map(|(coordinate, relay_url)| Tag::coordinate(coordinate, relay_url)),
+        );
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for Calendar {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let (id, title) = extract_identifier_and_title(&tags)?;
+        Ok(Self {
+            id,
+            title,
+            events: extract_coordinates(&tags).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<&Event> for Calendar {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        check_kind(event, Kind::Calendar)?;
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Calendar event RSVP status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CalendarEventRsvpStatus {
+    /// Accepted
+    Accepted,
+    /// Declined
+    Declined,
+    /// Tentative
+    Tentative,
+}
+
+impl CalendarEventRsvpStatus {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Declined => "declined",
+            Self::Tentative => "tentative",
+        }
+    }
+}
+
+impl fmt::Display for CalendarEventRsvpStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for CalendarEventRsvpStatus {
+    type Err = Error;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "accepted" => Ok(Self::Accepted),
+            "declined" => Ok(Self::Declined),
+            "tentative" => Ok(Self::Tentative),
+            status => Err(Error::UnknownRsvpStatus(status.to_string())),
+        }
+    }
+}
+
+/// Free/busy status of the RSVP author during the calendar event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FreeBusy {
+    /// Free
+    Free,
+    /// Busy
+    Busy,
+}
+
+impl FreeBusy {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Free => "free",
+            Self::Busy => "busy",
+        }
+    }
+}
+
+impl fmt::Display for FreeBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for FreeBusy {
+    type Err = Error;
+
+    fn from_str(fb: &str) -> Result<Self, Self::Err> {
+        match fb {
+            "free" => Ok(Self::Free),
+            "busy" => Ok(Self::Busy),
+            fb => Err(Error::UnknownFreeBusy(fb.to_string())),
+        }
+    }
+}
+
+/// Calendar event RSVP
+///
+/// Response to a calendar event, published with kind [`Kind::CalendarEventRsvp`].
+/// The content can include a note.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarEventRsvp {
+    /// Unique identifier
+    pub id: String,
+    /// Coordinate of the calendar event
+    pub coordinate: Coordinate,
+    /// Recommended relay URL of the calendar event
+    pub relay_url: Option<RelayUrl>,
+    /// ID of the specific revision of the calendar event
+    pub event_id: Option<EventId>,
+    /// Status
+    pub status: CalendarEventRsvpStatus,
+    /// Free/busy status
+    ///
+    /// Ignored if the status is [`CalendarEventRsvpStatus::Declined`].
+    pub free_busy: Option<FreeBusy>,
+    /// Author of the calendar event
+    pub author: Option<PublicKey>,
+}
+
+impl CalendarEventRsvp {
+    /// New calendar event RSVP
+    pub fn new<S>(id: S, coordinate: Coordinate, status: CalendarEventRsvpStatus) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            coordinate,
+            relay_url: None,
+            event_id: None,
+            status,
+            free_busy: None,
+            author: None,
+        }
+    }
+}
+
+impl From<CalendarEventRsvp> for Vec<Tag> {
+    fn from(rsvp: CalendarEventRsvp) -> Self {
+        let CalendarEventRsvp {
+            id,
+            coordinate,
+            relay_url,
+            event_id,
+            status,
+            free_busy,
+            author,
+        } = rsvp;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(6);
+
+        tags.push(Tag::identifier(id));
+        tags.push(Tag::coordinate(coordinate, relay_url));
+
+        if let Some(event_id) = event_id {
+            tags.push(Tag::event(event_id));
+        }
+
+        tags.push(Tag::custom(TagKind::Status, [status.as_str()]));
+
+        match (status, free_busy) {
+            (CalendarEventRsvpStatus::Declined, ..) | (.., None) => {}
+            (.., Some(free_busy)) => {
+                tags.push(Tag::custom(TagKind::FreeBusy, [free_busy.as_str()]))
+            }
+        }
+
+        if let Some(author) = author {
+            tags.push(Tag::public_key(author));
+        }
+
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for CalendarEventRsvp {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let id: String = find_content(&tags, TagKind::d())
+            .ok_or(Error::IdentifierMissing)?
+            .to_string();
+
+        let (coordinate, relay_url) = extract_coordinates(&tags)
+            .next()
+            .ok_or(Error::CoordinateMissing)??;
+
+        let status: CalendarEventRsvpStatus = match find_content(&tags, TagKind::Status) {
+            Some(status) => CalendarEventRsvpStatus::from_str(status)?,
+            None => return Err(Error::StatusMissing),
+        };
+
+        let free_busy: Option<FreeBusy> = match (status, find_content(&tags, TagKind::FreeBusy)) {
+            (CalendarEventRsvpStatus::Declined, ..) | (.., None) => None,
+            (.., Some(fb)) => Some(FreeBusy::from_str(fb)?),
+        };
+
+        Ok(Self {
+            id,
+            coordinate,
+            relay_url,
+            event_id: find_content(&tags, TagKind::e()).and_then(|id| EventId::from_hex(id).ok()),
+            status,
+            free_busy,
+            author: find_content(&tags, TagKind::p()).and_then(|pk| PublicKey::from_hex(pk).ok()),
+        })
+    }
+}
+
+impl TryFrom<&Event> for CalendarEventRsvp {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        check_kind(event, Kind::CalendarEventRsvp)?;
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Optional fields shared by the date-based and time-based calendar events
+struct CommonFields {
+    summary: Option<String>,
+    image: Option<Url>,
+    locations: Vec<String>,
+    geohash: Option<String>,
+    participants: Vec<CalendarEventParticipant>,
+    hashtags: Vec<String>,
+    references: Vec<String>,
+}
+
+impl CommonFields {
+    /// Parse the fields, skipping the invalid tags
+    fn parse(tags: &[Tag]) -> Self {
+        let mut fields = Self {
+            summary: None,
+            image: None,
+            locations: Vec::new(),
+            geohash: None,
+            participants: Vec::new(),
+            hashtags: Vec::new(),
+            references: Vec::new(),
+        };
+
+        for tag in tags.iter() {
+            let values: &[String] = tag.as_slice();
+            let Some(value) = values.get(1) else {
+                continue;
+            };
+
+            match tag.kind() {
+                TagKind::Summary => fields.summary = Some(value.clone()),
+                TagKind::Image => fields.image = Url::parse(value).ok(),
+                TagKind::Location => fields.locations.push(value.clone()),
+                TagKind::SingleLetter(SingleLetterTag {
+                    character: Alphabet::G,
+                    uppercase: false,
+                }) => fields.geohash = Some(value.clone()),
+                TagKind::SingleLetter(SingleLetterTag {
+                    character: Alphabet::P,
+                    uppercase: false,
+                }) => {
+                    if let Ok(public_key) = PublicKey::from_hex(value) {
+                        fields.participants.push(CalendarEventParticipant {
+                            public_key,
+                            relay_url: values.get(2).and_then(|u| RelayUrl::parse(u).ok()),
+                            role: values.get(3).filter(|r| !r.is_empty()).cloned(),
+                        });
+                    }
+                }
+                TagKind::SingleLetter(SingleLetterTag {
+                    character: Alphabet::T,
+                    uppercase: false,
+                }) => fields.hashtags.push(value.clone()),
+                TagKind::SingleLetter(SingleLetterTag {
+                    character: Alphabet::R,
+                    uppercase: false,
+                }) => fields.references.push(value.clone()),
+                _ => {}
+            }
+        }
+
+        fields
+    }
+
+    fn push_tags(self, tags: &mut Vec<Tag>) {
+        if let Some(summary) = self.summary {
+            tags.push(Tag::custom(TagKind::Summary, [summary]));
+        }
+
+        if let Some(image) = self.image {
+            tags.push(Tag::image(image, None));
+        }
+
+        for location in self.locations.into_iter() {
+            tags.push(Tag::custom(TagKind::Location, [location]));
+        }
+
+        if let Some(geohash) = self.geohash {
+            tags.push(Tag::custom(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::G)),
+                [geohash],
+            ));
+        }
+
+        tags.extend(self.participants.into_iter().map(Tag::from));
+        tags.extend(self.hashtags.into_iter().map(Tag::hashtag));
+        tags.extend(self.references.into_iter().map(Tag::reference));
+    }
+}
+
+fn find_content<'a>(tags: &'a [Tag], kind: TagKind) -> Option<&'a str> {
+    tags.iter()
+        .find(|tag| tag.kind() == kind)
+        .and_then(|tag| tag.content())
+}
+
+fn extract_identifier_and_title(tags: &[Tag]) -> Result<(String, String), Error> {
+    let id: &str = find_content(tags, TagKind::d()).ok_or(Error::IdentifierMissing)?;
+    let title: &str = find_content(tags, TagKind::Title).ok_or(Error::TitleMissing)?;
+    Ok((id.to_string(), title.to_string()))
+}
+
+fn extract_coordinates(
+    tags: &[Tag],
+) -> impl Iterator<Item = Result<(Coordinate, Option<RelayUrl>), Error>> + '_ {
+    tags.iter()
+        .filter(|tag| tag.kind() == TagKind::a())
+        .filter_map(|tag| {
+            let values: &[String] = tag.as_slice();
+            let coordinate: &str = values.get(1)?;
+            Some(
+                Coordinate::parse(coordinate)
+                    .map(|c| (c, values.get(2).and_then(|u| RelayUrl::parse(u).ok())))
+                    .map_err(Error::from),
+            )
+        })
+}
+
+fn parse_timestamp(timestamp: &str) -> Result<Timestamp, Error> {
+    timestamp
+        .parse::<u64>()
+        .map(Timestamp::from_secs)
+        .map_err(|_| Error::InvalidTimestamp(timestamp.to_string()))
+}
+
+fn check_range<T>(start: &T, end: Option<&T>) -> Result<(), Error>
+where
+    T: PartialOrd,
+{
+    match end {
+        Some(end) if end <= start => Err(Error::InvalidRange),
+        _ => Ok(()),
+    }
+}
+
+fn check_kind(event: &Event, kind: Kind) -> Result<(), Error> {
+    if event.kind != kind {
+        return Err(Error::UnexpectedKind(event.kind));
+    }
+
+    Ok(())
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_calendar_date() {
+        let date = CalendarDate::parse("2024-02-29").unwrap();
+        assert_eq!(date.year(), 2024);
+        assert_eq!(date.month(), 2);
+        assert_eq!(date.day(), 29);
+        assert_eq!(date.to_string(), "2024-02-29");
+
+        assert!(CalendarDate::parse("2024-01-01").unwrap() < date);
+
+        for invalid in [
+            "2025-02-29",
+            "1900-02-29",
+            "2024-04-31",
+            "2024-13-01",
+            "2024-00-10",
+            "2024-1-01",
+            "2024/01/01",
+            "+024-01-01",
+            "2024-01-01T00:00:00Z",
+            "",
+        ] {
+            assert_eq!(
+                CalendarDate::parse(invalid).unwrap_err(),
+                Error::InvalidDate(invalid.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_date_based_calendar_event() {
+        let keys = Keys::generate();
+
+        let mut calendar_event = DateBasedCalendarEvent::new(
+            "nostrasia",
+            "Nostrasia",
+            CalendarDate::parse("2024-08-19").unwrap(),
+        );
+        calendar_event.end = Some(CalendarDate::parse("2024-08-21").unwrap());
+        calendar_event.locations.push(String::from("Hong Kong"));
+        calendar_event.geohash = Some(String::from("wecnv"));
+        calendar_event.hashtags.push(String::from("nostr"));
+        calendar_event.participants.push(CalendarEventParticipant {
+            public_key: Keys::generate().public_key(),
+            relay_url: None,
+            role: Some(String::from("speaker")),
+        });
+
+        let event = EventBuilder::date_based_calendar_event("Conference", calendar_event.clone())
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::DateBasedCalendarEvent);
+        assert_eq!(
+            event.tags.find(TagKind::Start).unwrap().content(),
+            Some("2024-08-19")
+        );
+        assert_eq!(
+            DateBasedCalendarEvent::try_from(&event).unwrap(),
+            calendar_event
+        );
+
+        // Time-based start
+        let tags = vec![
+            Tag::identifier("id"),
+            Tag::title("Title"),
+            Tag::custom(TagKind::Start, ["1724025600"]),
+        ];
+        assert_eq!(
+            DateBasedCalendarEvent::try_from(tags).unwrap_err(),
+            Error::InvalidDate(String::from("1724025600"))
+        );
+
+        // End before start
+        let tags = vec![
+            Tag::identifier("id"),
+            Tag::title("Title"),
+            Tag::custom(TagKind::Start, ["2024-08-19"]),
+            Tag::custom(TagKind::End, ["2024-08-19"]),
+        ];
+        assert_eq!(
+            DateBasedCalendarEvent::try_from(tags).unwrap_err(),
+            Error::InvalidRange
+        );
+
+        // Wrong kind
+        let event = EventBuilder::text_note("").sign_with_keys(&keys).unwrap();
+        assert_eq!(
+            DateBasedCalendarEvent::try_from(&event).unwrap_err(),
+            Error::UnexpectedKind(Kind::TextNote)
+        );
+    }
+
+    #[test]
+    fn test_time_based_calendar_event() {
+        let keys = Keys::generate();
+
+        // 2024-08-19T22:00:00Z -> 2024-08-20T02:00:00Z
+        let mut calendar_event =
+            TimeBasedCalendarEvent::new("meetup", "Nostr meetup", Timestamp::from_secs(1724104800));
+        calendar_event.end = Some(Timestamp::from_secs(1724119200));
+        calendar_event.start_tzid = Some(String::from("Europe/Rome"));
+        calendar_event
+            .references
+            .push(String::from("https://example.com"));
+        assert_eq!(calendar_event.days().unwrap(), 19954..=19955);
+
+        let event = EventBuilder::time_based_calendar_event("", calendar_event.clone())
+            .unwrap()
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::TimeBasedCalendarEvent);
+        assert_eq!(
+            event
+                .tags
+                .filter(TagKind::SingleLetter(SingleLetterTag::uppercase(
+                    Alphabet::D
+                )))
+                .count(),
+            2
+        );
+        assert_eq!(
+            TimeBasedCalendarEvent::try_from(&event).unwrap(),
+            calendar_event
+        );
+
+        // Ending at midnight
+        calendar_event.end = Some(Timestamp::from_secs(19955 * SECONDS_PER_DAY));
+        assert_eq!(calendar_event.days().unwrap(), 19954..=19954);
+
+        // Spanning too many days
+        calendar_event.end = Some(Timestamp::from_secs(
+            19954 * SECONDS_PER_DAY + 400 * SECONDS_PER_DAY,
+        ));
+        assert_eq!(calendar_event.days().unwrap_err(), Error::TooManyDays(400));
+        calendar_event.end = Some(Timestamp::from_secs(u64::MAX));
+        assert!(EventBuilder::time_based_calendar_event("", calendar_event.clone()).is_err());
+
+        // Date-based start
+        let tags = vec![
+            Tag::identifier("id"),
+            Tag::title("Title"),
+            Tag::custom(TagKind::Start, ["2024-08-19"]),
+        ];
+        assert_eq!(
+            TimeBasedCalendarEvent::try_from(tags).unwrap_err(),
+            Error::InvalidTimestamp(String::from("2024-08-19"))
+        );
+
+        // Missing start
+        let tags = vec![Tag::identifier("id"), Tag::title("Title")];
+        assert_eq!(
+            TimeBasedCalendarEvent::try_from(tags).unwrap_err(),
+            Error::StartMissing
+        );
+    }
+
+    #[test]
+    fn test_calendar_and_rsvp() {
+        let keys = Keys::generate();
+        let coordinate =
+            Coordinate::new(Kind::TimeBasedCalendarEvent, keys.public_key()).identifier("meetup");
+
+        let mut calendar = Calendar::new("events", "My events");
+        calendar.events.push((coordinate.clone(), None));
+        let event = EventBuilder::calendar("", calendar.clone())
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::Calendar);
+        assert_eq!(Calendar::try_from(&event).unwrap(), calendar);
+
+        let mut rsvp = CalendarEventRsvp::new(
+            "rsvp",
+            coordinate.clone(),
+            CalendarEventRsvpStatus::Accepted,
+        );
+        rsvp.free_busy = Some(FreeBusy::Busy);
+        rsvp.author = Some(Keys::generate().public_key());
+        let event = EventBuilder::calendar_event_rsvp("See you there!", rsvp.clone())
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::CalendarEventRsvp);
+        assert_eq!(CalendarEventRsvp::try_from(&event).unwrap(), rsvp);
+
+        // The free/busy is omitted when declined
+        rsvp.status = CalendarEventRsvpStatus::Declined;
+        let tags: Vec<Tag> = rsvp.into();
+        assert!(!tags.iter().any(|t| t.kind() == TagKind::FreeBusy));
+
+        let tags = vec![
+            Tag::identifier("rsvp"),
+            Tag::coordinate(coordinate, None),
+            Tag::custom(TagKind::Status, ["maybe"]),
+        ];
+        assert_eq!(
+            CalendarEventRsvp::try_from(tags).unwrap_err(),
+            Error::UnknownRsvpStatus(String::from("maybe"))
+        );
+    }
+}
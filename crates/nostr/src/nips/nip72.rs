@@ -0,0 +1,571 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP72: Moderated Communities
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/72.md>
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use super::nip01::Coordinate;
+use crate::types::{RelayUrl, Url};
+use crate::{Event, EventId, ImageDimensions, Kind, PublicKey, Tag, TagKind, TagStandard};
+
+const MODERATOR: &str = "moderator";
+const RULES: &str = "rules";
+
+/// NIP72 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Unexpected event kind
+    UnexpectedKind(Kind),
+    /// Unknown community relay marker
+    UnknownRelayMarker(String),
+    /// Community ID (`d` tag) missing
+    IdentifierMissing,
+    /// Community coordinate missing
+    CommunityMissing,
+    /// Approved post (`e` or `a` tag) missing
+    PostMissing,
+    /// Approved post author missing
+    PostAuthorMissing,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedKind(kind) => write!(f, "Unexpected kind: {kind}"),
+            Self::UnknownRelayMarker(marker) => write!(f, "Unknown relay marker: {marker}"),
+            Self::IdentifierMissing => write!(f, "Community ID missing"),
+            Self::CommunityMissing => write!(f, "Community coordinate missing"),
+            Self::PostMissing => write!(f, "Approved post missing"),
+            Self::PostAuthorMissing => write!(f, "Approved post author missing"),
+        }
+    }
+}
+
+/// Community relay marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CommunityRelayMarker {
+    /// Relay hosting the events of the community author
+    Author,
+    /// Relay where to send the post requests
+    Requests,
+    /// Relay where to send the approvals
+    Approvals,
+}
+
+impl CommunityRelayMarker {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Author => "author",
+            Self::Requests => "requests",
+            Self::Approvals => "approvals",
+        }
+    }
+}
+
+impl fmt::Display for CommunityRelayMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for CommunityRelayMarker {
+    type Err = Error;
+
+    fn from_str(marker: &str) -> Result<Self, Self::Err> {
+        match marker {
+            "author" => Ok(Self::Author),
+            "requests" => Ok(Self::Requests),
+            "approvals" => Ok(Self::Approvals),
+            marker => Err(Error::UnknownRelayMarker(marker.to_string())),
+        }
+    }
+}
+
+/// Community definition
+///
+/// Published by the community owner with kind [`Kind::CommunityDefinition`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommunityDefinition {
+    /// Community ID
+    pub id: String,
+    /// Name
+    pub name: Option<String>,
+    /// Description
+    pub description: Option<String>,
+    /// Image
+    pub image: Option<(Url, Option<ImageDimensions>)>,
+    /// Rules
+    pub rules: Option<String>,
+    /// Moderators
+    pub moderators: Vec<(PublicKey, Option<RelayUrl>)>,
+    /// Relays
+    pub relays: Vec<(RelayUrl, Option<CommunityRelayMarker>)>,
+}
+
+impl CommunityDefinition {
+    /// New community definition
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            name: None,
+            description: None,
+            image: None,
+            rules: None,
+            moderators: Vec::new(),
+            relays: Vec::new(),
+        }
+    }
+
+    /// Check if the public key is a moderator
+    pub fn is_moderator(&self, public_key: &PublicKey) -> bool {
+        self.moderators.iter().any(|(pk, ..)| pk == public_key)
+    }
+}
+
+impl From<CommunityDefinition> for Vec<Tag> {
+    fn from(community: CommunityDefinition) -> Self {
+        let CommunityDefinition {
+            id,
+            name,
+            description,
+            image,
+            rules,
+            moderators,
+            relays,
+        } = community;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + moderators.len() + relays.len());
+
+        tags.push(Tag::identifier(id));
+
+        if let Some(name) = name {
+            tags.push(Tag::custom(TagKind::Name, [name]));
+        }
+
+        if let Some(description) = description {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::Description(description),
+            ));
+        }
+
+        if let Some((image, dimensions)) = image {
+            tags.push(Tag::image(image, dimensions));
+        }
+
+        if let Some(rules) = rules {
+            tags.push(Tag::custom(TagKind::custom(RULES), [rules]));
+        }
+
+        for (public_key, relay_url) in moderators.into_iter() {
+            tags.push(Tag::custom(
+                TagKind::p(),
+                [
+                    public_key.to_hex(),
+                    relay_url.map(|u| u.to_string()).unwrap_or_default(),
+                    MODERATOR.to_string(),
+                ],
+            ));
+        }
+
+        for (relay_url, marker) in relays.into_iter() {
+            let mut values: Vec<String> = Vec::with_capacity(2);
+            values.push(relay_url.to_string());
+
+            if let Some(marker) = marker {
+                values.push(marker.to_string());
+            }
+
+            tags.push(Tag::custom(TagKind::Relay, values));
+        }
+
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for CommunityDefinition {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let id: &str = tags
+            .iter()
+            .find(|tag| tag.kind() == TagKind::d())
+            .and_then(|tag| tag.content())
+            .ok_or(Error::IdentifierMissing)?;
+
+        let mut community = Self::new(id);
+
+        for tag in tags.iter() {
+            let values: &[String] = tag.as_slice();
+            let Some(value) = values.get(1) else {
+                continue;
+            };
+
+            match tag.kind() {
+                TagKind::Name => community.name = Some(value.clone()),
+                TagKind::Description => community.description = Some(value.clone()),
+                TagKind::Image => {
+                    if let Ok(url) = Url::parse(value) {
+                        let dimensions = values
+                            .get(2)
+                            .and_then(|d| ImageDimensions::from_str(d).ok());
+                        community.image = Some((url, dimensions));
+                    }
+                }
+                TagKind::Relay => {
+                    // Ignore invalid URLs and unknown markers
+                    if let Ok(relay_url) = RelayUrl::parse(value) {
+                        let marker = values
+                            .get(2)
+                            .and_then(|m| CommunityRelayMarker::from_str(m).ok());
+                        community.relays.push((relay_url, marker));
+                    }
+                }
+                TagKind::Custom(kind) if kind == RULES => community.rules = Some(value.clone()),
+                kind if kind == TagKind::p() => {
+                    let is_moderator: bool = values.get(3).map(|m| m.as_str()) == Some(MODERATOR);
+                    if let (true, Ok(public_key)) = (is_moderator, PublicKey::from_hex(value)) {
+                        let relay_url = values.get(2).and_then(|u| RelayUrl::parse(u).ok());
+                        community.moderators.push((public_key, relay_url));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(community)
+    }
+}
+
+impl TryFrom<&Event> for CommunityDefinition {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind != Kind::CommunityDefinition {
+            return Err(Error::UnexpectedKind(event.kind));
+        }
+
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Community post approval
+///
+/// Published by a moderator with kind [`Kind::CommunityPostApproval`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommunityPostApproval {
+    /// Community coordinate
+    pub community: Coordinate,
+    /// ID of the approved post
+    pub post_id: Option<EventId>,
+    /// Coordinate of the approved post
+    ///
+    /// Used for the replaceable posts, to approve all their versions.
+    pub post_coordinate: Option<Coordinate>,
+    /// Author of the approved post
+    pub post_author: PublicKey,
+    /// Kind of the approved post
+    pub post_kind: Option<Kind>,
+}
+
+impl CommunityPostApproval {
+    /// Compose the approval of a post
+    ///
+    /// The replaceable and addressable posts are approved by coordinate too.
+    pub fn new(community: Coordinate, post: &Event) -> Self {
+        Self {
+            community,
+            post_id: Some(post.id),
+            post_coordinate: post.coordinate().map(|c| c.into_owned()),
+            post_author: post.pubkey,
+            post_kind: Some(post.kind),
+        }
+    }
+
+    /// Check if the post is approved
+    pub fn approves(&self, post: &Event) -> bool {
+        if self.post_id == Some(post.id) {
+            return true;
+        }
+
+        match (&self.post_coordinate, post.coordinate()) {
+            (Some(approved), Some(coordinate)) => {
+                approved.kind == *coordinate.kind
+                    && approved.public_key == *coordinate.public_key
+                    && approved.identifier == coordinate.identifier.unwrap_or_default()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<CommunityPostApproval> for Vec<Tag> {
+    fn from(approval: CommunityPostApproval) -> Self {
+        let CommunityPostApproval {
+            community,
+            post_id,
+            post_coordinate,
+            post_author,
+            post_kind,
+        } = approval;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(5);
+
+        tags.push(Tag::coordinate(community, None));
+
+        if let Some(post_id) = post_id {
+            tags.push(Tag::event(post_id));
+        }
+
+        if let Some(post_coordinate) = post_coordinate {
+            tags.push(Tag::coordinate(post_coordinate, None));
+        }
+
+        tags.push(Tag::public_key(post_author));
+
+        if let Some(post_kind) = post_kind {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Kind {
+                kind: post_kind,
+                uppercase: false,
+            }));
+        }
+
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for CommunityPostApproval {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let mut community: Option<Coordinate> = None;
+        let mut post_id: Option<EventId> = None;
+        let mut post_coordinate: Option<Coordinate> = None;
+        let mut post_author: Option<PublicKey> = None;
+        let mut post_kind: Option<Kind> = None;
+
+        for tag in tags.into_iter() {
+            let Some(tag) = tag.to_standardized() else {
+                continue;
+            };
+
+            match tag {
+                // The `a` tags point to the community or to the approved replaceable post
+                TagStandard::Coordinate {
+                    coordinate,
+                    uppercase: false,
+                    ..
+                } => match coordinate.kind {
+                    Kind::CommunityDefinition if community.is_none() => {
+                        community = Some(coordinate)
+                    }
+                    _ => post_coordinate = Some(coordinate),
+                },
+                TagStandard::Event { event_id, .. } => post_id = Some(event_id),
+                TagStandard::PublicKey {
+                    public_key,
+                    uppercase: false,
+                    ..
+                } => post_author = Some(public_key),
+                TagStandard::Kind {
+                    kind,
+                    uppercase: false,
+                } => post_kind = Some(kind),
+                _ => {}
+            }
+        }
+
+        if post_id.is_none() && post_coordinate.is_none() {
+            return Err(Error::PostMissing);
+        }
+
+        Ok(Self {
+            community: community.ok_or(Error::CommunityMissing)?,
+            post_id,
+            post_coordinate,
+            post_author: post_author.ok_or(Error::PostAuthorMissing)?,
+            post_kind,
+        })
+    }
+}
+
+impl TryFrom<&Event> for CommunityPostApproval {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind != Kind::CommunityPostApproval {
+            return Err(Error::UnexpectedKind(event.kind));
+        }
+
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Get the posts approved by a current moderator of the community
+///
+/// Only the approvals published by the moderators listed in the community definition are considered:
+/// the approvals of removed moderators are ignored.
+/// The approvals of other communities and the invalid ones are skipped.
+/// The posts are returned in the same order as received.
+pub fn approved_posts<'a, 'b, I1, I2>(
+    community: &Event,
+    approvals: I1,
+    posts: I2,
+) -> Result<Vec<&'a Event>, Error>
+where
+    I1: IntoIterator<Item = &'b Event>,
+    I2: IntoIterator<Item = &'a Event>,
+{
+    let definition: CommunityDefinition = CommunityDefinition::try_from(community)?;
+    let coordinate: Coordinate =
+        Coordinate::new(Kind::CommunityDefinition, community.pubkey).identifier(&definition.id);
+
+    let approvals: Vec<CommunityPostApproval> = approvals
+        .into_iter()
+        .filter(|event| {
+            event.kind == Kind::CommunityPostApproval && definition.is_moderator(&event.pubkey)
+        })
+        .filter_map(|event| CommunityPostApproval::try_from(event).ok())
+        .filter(|approval| approval.community == coordinate)
+        .collect();
+
+    // Index the approvals by ID, to avoid checking all the approvals for each post
+    let approved_ids: BTreeSet<EventId> = approvals.iter().filter_map(|a| a.post_id).collect();
+
+    Ok(posts
+        .into_iter()
+        .filter(|post| {
+            approved_ids.contains(&post.id)
+                || approvals
+                    .iter()
+                    .any(|a| a.post_coordinate.is_some() && a.approves(post))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, JsonUtil, Keys};
+
+    #[test]
+    fn test_community_definition_tags() {
+        let moderator = Keys::generate().public_key();
+
+        let mut community = CommunityDefinition::new("rust");
+        community.name = Some(String::from("Rust"));
+        community.description = Some(String::from("Rust programming language"));
+        community.rules = Some(String::from("Be kind"));
+        community.moderators.push((moderator, None));
+        community.relays.push((
+            RelayUrl::parse("wss://relay.example.com").unwrap(),
+            Some(CommunityRelayMarker::Requests),
+        ));
+
+        let tags: Vec<Tag> = community.clone().into();
+        assert_eq!(
+            tags[4].as_slice(),
+            &[
+                String::from("p"),
+                moderator.to_hex(),
+                String::new(),
+                String::from("moderator")
+            ]
+        );
+        assert_eq!(CommunityDefinition::try_from(tags).unwrap(), community);
+        assert!(community.is_moderator(&moderator));
+
+        // Not a moderator
+        let tags = vec![Tag::identifier("rust"), Tag::public_key(moderator)];
+        let community = CommunityDefinition::try_from(tags).unwrap();
+        assert!(community.moderators.is_empty());
+    }
+
+    #[test]
+    fn test_approved_posts() {
+        let owner = Keys::generate();
+        let moderator = Keys::generate();
+        let former_moderator = Keys::generate();
+        let author = Keys::generate();
+
+        let mut definition = CommunityDefinition::new("rust");
+        definition.moderators.push((moderator.public_key(), None));
+        let community = EventBuilder::community_definition(definition)
+            .sign_with_keys(&owner)
+            .unwrap();
+        let coordinate =
+            Coordinate::new(Kind::CommunityDefinition, owner.public_key()).identifier("rust");
+
+        let post = |content: &str| {
+            EventBuilder::text_note(content)
+                .tag(Tag::coordinate(coordinate.clone(), None))
+                .sign_with_keys(&author)
+                .unwrap()
+        };
+        let approved = post("approved");
+        let by_former_moderator = post("approved by a former moderator");
+        let other_community = post("approved in another community");
+        let pending = post("pending");
+        let article = EventBuilder::long_form_text_note("article")
+            .tag(Tag::identifier("article"))
+            .sign_with_keys(&author)
+            .unwrap();
+
+        let approve = |keys: &Keys, community: Coordinate, post: &Event| {
+            EventBuilder::community_post_approval(community, post)
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+        let other_coordinate =
+            Coordinate::new(Kind::CommunityDefinition, owner.public_key()).identifier("other");
+        let approvals = vec![
+            approve(&moderator, coordinate.clone(), &approved),
+            approve(&former_moderator, coordinate.clone(), &by_former_moderator),
+            approve(&moderator, other_coordinate, &other_community),
+            approve(&moderator, coordinate.clone(), &article),
+        ];
+
+        let parsed = CommunityPostApproval::try_from(&approvals[3]).unwrap();
+        assert_eq!(parsed.community, coordinate);
+        assert_eq!(parsed.post_kind, Some(Kind::LongFormTextNote));
+        assert!(parsed.post_coordinate.is_some());
+        assert_eq!(approvals[0].content, approved.as_json());
+
+        // New version of the article, approved by coordinate
+        let article_v2 = EventBuilder::long_form_text_note("article v2")
+            .tag(Tag::identifier("article"))
+            .custom_created_at(article.created_at + 1)
+            .sign_with_keys(&author)
+            .unwrap();
+
+        let posts = vec![
+            approved.clone(),
+            by_former_moderator,
+            other_community,
+            pending,
+            article_v2.clone(),
+        ];
+        let result = approved_posts(&community, &approvals, &posts).unwrap();
+        assert_eq!(result, vec![&approved, &article_v2]);
+
+        // Not a community definition
+        assert_eq!(
+            approved_posts(&approved, &approvals, &posts).unwrap_err(),
+            Error::UnexpectedKind(Kind::TextNote)
+        );
+    }
+}
@@ -0,0 +1,542 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP86: Relay Management API
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/86.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use std::net::IpAddr;
+
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{EventId, JsonUtil, Kind, PublicKey, Url};
+
+/// Content type of the NIP86 requests
+pub const CONTENT_TYPE: &str = "application/nostr+json+rpc";
+
+/// NIP86 error
+#[derive(Debug)]
+pub enum Error {
+    /// JSON error
+    Json(serde_json::Error),
+    /// Unknown method
+    UnknownMethod(String),
+    /// Missing param
+    MissingParam(usize),
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "{e}"),
+            Self::UnknownMethod(method) => write!(f, "Unknown method: {method}"),
+            Self::MissingParam(index) => write!(f, "Missing param at index {index}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// NIP86 method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Method {
+    /// Supported methods
+    SupportedMethods,
+    /// Ban public key
+    BanPubkey,
+    /// List banned public keys
+    ListBannedPubkeys,
+    /// Allow public key
+    AllowPubkey,
+    /// List allowed public keys
+    ListAllowedPubkeys,
+    /// List events needing moderation
+    ListEventsNeedingModeration,
+    /// Allow event
+    AllowEvent,
+    /// Ban event
+    BanEvent,
+    /// List banned events
+    ListBannedEvents,
+    /// Change relay name
+    ChangeRelayName,
+    /// Change relay description
+    ChangeRelayDescription,
+    /// Change relay icon
+    ChangeRelayIcon,
+    /// Allow kind
+    AllowKind,
+    /// Disallow kind
+    DisallowKind,
+    /// List allowed kinds
+    ListAllowedKinds,
+    /// Block IP
+    BlockIp,
+    /// Unblock IP
+    UnblockIp,
+    /// List blocked IPs
+    ListBlockedIps,
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Method {
+    /// All the methods
+    pub const ALL: [Self; 18] = [
+        Self::SupportedMethods,
+        Self::BanPubkey,
+        Self::ListBannedPubkeys,
+        Self::AllowPubkey,
+        Self::ListAllowedPubkeys,
+        Self::ListEventsNeedingModeration,
+        Self::AllowEvent,
+        Self::BanEvent,
+        Self::ListBannedEvents,
+        Self::ChangeRelayName,
+        Self::ChangeRelayDescription,
+        Self::ChangeRelayIcon,
+        Self::AllowKind,
+        Self::DisallowKind,
+        Self::ListAllowedKinds,
+        Self::BlockIp,
+        Self::UnblockIp,
+        Self::ListBlockedIps,
+    ];
+
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::SupportedMethods => "supportedmethods",
+            Self::BanPubkey => "banpubkey",
+            Self::ListBannedPubkeys => "listbannedpubkeys",
+            Self::AllowPubkey => "allowpubkey",
+            Self::ListAllowedPubkeys => "listallowedpubkeys",
+            Self::ListEventsNeedingModeration => "listeventsneedingmoderation",
+            Self::AllowEvent => "allowevent",
+            Self::BanEvent => "banevent",
+            Self::ListBannedEvents => "listbannedevents",
+            Self::ChangeRelayName => "changerelayname",
+            Self::ChangeRelayDescription => "changerelaydescription",
+            Self::ChangeRelayIcon => "changerelayicon",
+            Self::AllowKind => "allowkind",
+            Self::DisallowKind => "disallowkind",
+            Self::ListAllowedKinds => "listallowedkinds",
+            Self::BlockIp => "blockip",
+            Self::UnblockIp => "unblockip",
+            Self::ListBlockedIps => "listblockedips",
+        }
+    }
+}
+
+impl FromStr for Method {
+    type Err = Error;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|m| m.as_str() == method)
+            .ok_or_else(|| Error::UnknownMethod(method.to_string()))
+    }
+}
+
+/// NIP86 request
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Request {
+    /// Get the list of the supported methods
+    SupportedMethods,
+    /// Ban public key
+    BanPubkey {
+        /// Public key
+        public_key: PublicKey,
+        /// Optional reason
+        reason: Option<String>,
+    },
+    /// List banned public keys
+    ListBannedPubkeys,
+    /// Allow public key
+    AllowPubkey {
+        /// Public key
+        public_key: PublicKey,
+        /// Optional reason
+        reason: Option<String>,
+    },
+    /// List allowed public keys
+    ListAllowedPubkeys,
+    /// List events needing moderation
+    ListEventsNeedingModeration,
+    /// Allow event
+    AllowEvent {
+        /// Event ID
+        id: EventId,
+        /// Optional reason
+        reason: Option<String>,
+    },
+    /// Ban event
+    BanEvent {
+        /// Event ID
+        id: EventId,
+        /// Optional reason
+        reason: Option<String>,
+    },
+    /// List banned events
+    ListBannedEvents,
+    /// Change relay name
+    ChangeRelayName(String),
+    /// Change relay description
+    ChangeRelayDescription(String),
+    /// Change relay icon
+    ChangeRelayIcon(Url),
+    /// Allow kind
+    AllowKind(Kind),
+    /// Disallow kind
+    DisallowKind(Kind),
+    /// List allowed kinds
+    ListAllowedKinds,
+    /// Block IP
+    BlockIp {
+        /// IP address
+        ip: IpAddr,
+        /// Optional reason
+        reason: Option<String>,
+    },
+    /// Unblock IP
+    UnblockIp(IpAddr),
+    /// List blocked IPs
+    ListBlockedIps,
+}
+
+#[derive(Deserialize)]
+struct RequestTemplate {
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+impl Request {
+    /// Get request method
+    pub fn method(&self) -> Method {
+        match self {
+            Self::SupportedMethods => Method::SupportedMethods,
+            Self::BanPubkey { .. } => Method::BanPubkey,
+            Self::ListBannedPubkeys => Method::ListBannedPubkeys,
+            Self::AllowPubkey { .. } => Method::AllowPubkey,
+            Self::ListAllowedPubkeys => Method::ListAllowedPubkeys,
+            Self::ListEventsNeedingModeration => Method::ListEventsNeedingModeration,
+            Self::AllowEvent { .. } => Method::AllowEvent,
+            Self::BanEvent { .. } => Method::BanEvent,
+            Self::ListBannedEvents => Method::ListBannedEvents,
+            Self::ChangeRelayName(..) => Method::ChangeRelayName,
+            Self::ChangeRelayDescription(..) => Method::ChangeRelayDescription,
+            Self::ChangeRelayIcon(..) => Method::ChangeRelayIcon,
+            Self::AllowKind(..) => Method::AllowKind,
+            Self::DisallowKind(..) => Method::DisallowKind,
+            Self::ListAllowedKinds => Method::ListAllowedKinds,
+            Self::BlockIp { .. } => Method::BlockIp,
+            Self::UnblockIp(..) => Method::UnblockIp,
+            Self::ListBlockedIps => Method::ListBlockedIps,
+        }
+    }
+
+    fn params(&self) -> Vec<Value> {
+        match self {
+            Self::BanPubkey { public_key, reason } | Self::AllowPubkey { public_key, reason } => {
+                with_reason(Value::String(public_key.to_hex()), reason)
+            }
+            Self::AllowEvent { id, reason } | Self::BanEvent { id, reason } => {
+                with_reason(Value::String(id.to_hex()), reason)
+            }
+            Self::ChangeRelayName(value) | Self::ChangeRelayDescription(value) => {
+                vec![Value::String(value.clone())]
+            }
+            Self::ChangeRelayIcon(url) => vec![Value::String(url.to_string())],
+            Self::AllowKind(kind) | Self::DisallowKind(kind) => vec![Value::from(kind.as_u16())],
+            Self::BlockIp { ip, reason } => with_reason(Value::String(ip.to_string()), reason),
+            Self::UnblockIp(ip) => vec![Value::String(ip.to_string())],
+            Self::SupportedMethods
+            | Self::ListBannedPubkeys
+            | Self::ListAllowedPubkeys
+            | Self::ListEventsNeedingModeration
+            | Self::ListBannedEvents
+            | Self::ListAllowedKinds
+            | Self::ListBlockedIps => Vec::new(),
+        }
+    }
+
+    /// Deserialize from [`Value`]
+    pub fn from_value(value: Value) -> Result<Self, Error> {
+        let template: RequestTemplate = serde_json::from_value(value)?;
+        let params: &[Value] = &template.params;
+
+        match Method::from_str(&template.method)? {
+            Method::SupportedMethods => Ok(Self::SupportedMethods),
+            Method::BanPubkey => Ok(Self::BanPubkey {
+                public_key: param(params, 0)?,
+                reason: optional_param(params, 1)?,
+            }),
+            Method::ListBannedPubkeys => Ok(Self::ListBannedPubkeys),
+            Method::AllowPubkey => Ok(Self::AllowPubkey {
+                public_key: param(params, 0)?,
+                reason: optional_param(params, 1)?,
+            }),
+            Method::ListAllowedPubkeys => Ok(Self::ListAllowedPubkeys),
+            Method::ListEventsNeedingModeration => Ok(Self::ListEventsNeedingModeration),
+            Method::AllowEvent => Ok(Self::AllowEvent {
+                id: param(params, 0)?,
+                reason: optional_param(params, 1)?,
+            }),
+            Method::BanEvent => Ok(Self::BanEvent {
+                id: param(params, 0)?,
+                reason: optional_param(params, 1)?,
+            }),
+            Method::ListBannedEvents => Ok(Self::ListBannedEvents),
+            Method::ChangeRelayName => Ok(Self::ChangeRelayName(param(params, 0)?)),
+            Method::ChangeRelayDescription => Ok(Self::ChangeRelayDescription(param(params, 0)?)),
+            Method::ChangeRelayIcon => Ok(Self::ChangeRelayIcon(param(params, 0)?)),
+            Method::AllowKind => Ok(Self::AllowKind(param(params, 0)?)),
+            Method::DisallowKind => Ok(Self::DisallowKind(param(params, 0)?)),
+            Method::ListAllowedKinds => Ok(Self::ListAllowedKinds),
+            Method::BlockIp => Ok(Self::BlockIp {
+                ip: param(params, 0)?,
+                reason: optional_param(params, 1)?,
+            }),
+            Method::UnblockIp => Ok(Self::UnblockIp(param(params, 0)?)),
+            Method::ListBlockedIps => Ok(Self::ListBlockedIps),
+        }
+    }
+}
+
+fn with_reason(value: Value, reason: &Option<String>) -> Vec<Value> {
+    let mut params: Vec<Value> = vec![value];
+    if let Some(reason) = reason {
+        params.push(Value::String(reason.clone()));
+    }
+    params
+}
+
+fn param<T>(params: &[Value], index: usize) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let value: &Value = params.get(index).ok_or(Error::MissingParam(index))?;
+    Ok(serde_json::from_value(value.clone())?)
+}
+
+fn optional_param<T>(params: &[Value], index: usize) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    match params.get(index) {
+        Some(Value::Null) | None => Ok(None),
+        Some(value) => Ok(Some(serde_json::from_value(value.clone())?)),
+    }
+}
+
+impl Serialize for Request {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Request", 2)?;
+        state.serialize_field("method", self.method().as_str())?;
+        state.serialize_field("params", &self.params())?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Request {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: Value = Value::deserialize(deserializer)?;
+        Self::from_value(value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonUtil for Request {
+    type Err = Error;
+
+    /// Deserialize [`Request`] from JSON string
+    fn from_json<T>(json: T) -> Result<Self, Self::Err>
+    where
+        T: AsRef<[u8]>,
+    {
+        let value: Value = serde_json::from_slice(json.as_ref())?;
+        Self::from_value(value)
+    }
+}
+
+/// Public key with optional reason
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PublicKeyWithReason {
+    /// Public key
+    pub pubkey: PublicKey,
+    /// Reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Event ID with optional reason
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EventIdWithReason {
+    /// Event ID
+    pub id: EventId,
+    /// Reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// IP address with optional reason
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct IpAddrWithReason {
+    /// IP address
+    pub ip: IpAddr,
+    /// Reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// NIP86 response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    /// Result
+    #[serde(default)]
+    pub result: Value,
+    /// Error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    /// Successful response
+    pub fn ok<T>(result: T) -> Result<Self, Error>
+    where
+        T: Serialize,
+    {
+        Ok(Self {
+            result: serde_json::to_value(result)?,
+            error: None,
+        })
+    }
+
+    /// Error response
+    pub fn error<S>(error: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            result: Value::Null,
+            error: Some(error.into()),
+        }
+    }
+}
+
+impl JsonUtil for Response {
+    type Err = Error;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_serialization() {
+        let public_key =
+            PublicKey::from_hex("aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4")
+                .unwrap();
+
+        let req = Request::BanPubkey {
+            public_key,
+            reason: Some(String::from("spam")),
+        };
+        assert_eq!(
+            req.as_json(),
+            r#"{"method":"banpubkey","params":["aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4","spam"]}"#
+        );
+        assert_eq!(Request::from_json(req.as_json()).unwrap(), req);
+
+        let req = Request::AllowKind(Kind::TextNote);
+        assert_eq!(req.as_json(), r#"{"method":"allowkind","params":[1]}"#);
+        assert_eq!(Request::from_json(req.as_json()).unwrap(), req);
+
+        let req = Request::SupportedMethods;
+        assert_eq!(
+            req.as_json(),
+            r#"{"method":"supportedmethods","params":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_request_deserialization() {
+        let json = r#"{"method":"banevent","params":["b7b1fb52ad8461a03e949820ae29a9ea07e35bcd79c95c4b59b0254944f62805"]}"#;
+        let req = Request::from_json(json).unwrap();
+        assert_eq!(
+            req,
+            Request::BanEvent {
+                id: EventId::from_hex(
+                    "b7b1fb52ad8461a03e949820ae29a9ea07e35bcd79c95c4b59b0254944f62805"
+                )
+                .unwrap(),
+                reason: None
+            }
+        );
+
+        let json = r#"{"method":"blockip","params":["127.0.0.1","abuse"]}"#;
+        let req = Request::from_json(json).unwrap();
+        assert_eq!(
+            req,
+            Request::BlockIp {
+                ip: IpAddr::from([127, 0, 0, 1]),
+                reason: Some(String::from("abuse"))
+            }
+        );
+
+        // Missing param
+        let json = r#"{"method":"banpubkey","params":[]}"#;
+        assert!(matches!(
+            Request::from_json(json).unwrap_err(),
+            Error::MissingParam(0)
+        ));
+
+        // Invalid param
+        let json = r#"{"method":"banpubkey","params":["invalid"]}"#;
+        assert!(matches!(
+            Request::from_json(json).unwrap_err(),
+            Error::Json(..)
+        ));
+
+        // Unknown method
+        let json = r#"{"method":"unknown","params":[]}"#;
+        assert!(matches!(
+            Request::from_json(json).unwrap_err(),
+            Error::UnknownMethod(..)
+        ));
+    }
+
+    #[test]
+    fn test_response() {
+        let res = Response::ok(true).unwrap();
+        assert_eq!(res.as_json(), r#"{"result":true}"#);
+
+        let res = Response::error("unauthorized");
+        assert_eq!(res.as_json(), r#"{"result":null,"error":"unauthorized"}"#);
+    }
+}
@@ -0,0 +1,726 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP89: Recommended Application Handlers
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/89.md>
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use super::nip01::Coordinate;
+use super::nip19::{self, Nip19, ToBech32};
+use crate::types::RelayUrl;
+use crate::{Event, Kind, PublicKey, Tag, TagKind, TagStandard, Timestamp};
+
+/// Placeholder of the NIP19 entity in the handler URL templates
+pub const BECH32_PLACEHOLDER: &str = "<bech32>";
+
+/// NIP89 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// NIP19 error
+    NIP19(nip19::Error),
+    /// Unexpected event kind
+    UnexpectedKind(Kind),
+    /// Invalid kind
+    InvalidKind(String),
+    /// The entity can't be handled by an application (i.e., a secret key)
+    UnsupportedEntity,
+    /// Identifier (`d` tag) missing
+    IdentifierMissing,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NIP19(e) => write!(f, "{e}"),
+            Self::UnexpectedKind(kind) => write!(f, "Unexpected kind: {kind}"),
+            Self::InvalidKind(kind) => write!(f, "Invalid kind: {kind}"),
+            Self::UnsupportedEntity => write!(f, "Unsupported entity"),
+            Self::IdentifierMissing => write!(f, "Identifier missing"),
+        }
+    }
+}
+
+impl From<nip19::Error> for Error {
+    fn from(e: nip19::Error) -> Self {
+        Self::NIP19(e)
+    }
+}
+
+/// Handler URL template
+///
+/// JSON: `["<platform>", "<url-template>", "<nip19-entity>"]` (i.e., `["web", "https://example.com/a/<bech32>", "nevent"]`)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandlerUrl {
+    /// Platform (i.e., `web`, `ios`, `android`)
+    pub platform: String,
+    /// URL template, including the [`BECH32_PLACEHOLDER`]
+    pub template: String,
+    /// NIP19 entity handled by the URL (i.e., `nevent`, `naddr`)
+    ///
+    /// If `None`, all the entities are handled.
+    pub entity: Option<String>,
+}
+
+impl HandlerUrl {
+    /// Replace the [`BECH32_PLACEHOLDER`] with the entity
+    #[inline]
+    pub fn resolve(&self, bech32: &str) -> String {
+        self.template.replace(BECH32_PLACEHOLDER, bech32)
+    }
+}
+
+impl From<HandlerUrl> for Tag {
+    fn from(url: HandlerUrl) -> Self {
+        let mut values: Vec<String> = Vec::with_capacity(2);
+        values.push(url.template);
+
+        if let Some(entity) = url.entity {
+            values.push(entity);
+        }
+
+        Self::custom(TagKind::from(url.platform.as_str()), values)
+    }
+}
+
+/// Handler information
+///
+/// Published by the application with kind [`Kind::HandlerInformation`].
+/// The content can include the application metadata (same format of [`Kind::Metadata`]).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandlerInformation {
+    /// Unique identifier
+    pub id: String,
+    /// Supported kinds
+    pub kinds: Vec<Kind>,
+    /// URL templates
+    pub urls: Vec<HandlerUrl>,
+}
+
+impl HandlerInformation {
+    /// New handler information
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            kinds: Vec::new(),
+            urls: Vec::new(),
+        }
+    }
+
+    /// Check if the kind is supported
+    #[inline]
+    pub fn supports(&self, kind: &Kind) -> bool {
+        self.kinds.contains(kind)
+    }
+
+    /// Get the URL templates able to handle the entity, one per platform
+    ///
+    /// The URLs dedicated to the entity are preferred to the generic ones.
+    pub fn urls_for(&self, entity: &str) -> Vec<&HandlerUrl> {
+        let mut urls: Vec<&HandlerUrl> = Vec::new();
+
+        for url in self.urls.iter() {
+            let dedicated: bool = match url.entity.as_deref() {
+                Some(e) if e == entity => true,
+                Some(..) => continue,
+                None => false,
+            };
+
+            match urls.iter_mut().find(|u| u.platform == url.platform) {
+                Some(current) => {
+                    if dedicated && current.entity.is_none() {
+                        *current = url;
+                    }
+                }
+                None => urls.push(url),
+            }
+        }
+
+        urls
+    }
+}
+
+impl From<HandlerInformation> for Vec<Tag> {
+    fn from(handler: HandlerInformation) -> Self {
+        let HandlerInformation { id, kinds, urls } = handler;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + kinds.len() + urls.len());
+
+        tags.push(Tag::identifier(id));
+
+        for kind in kinds.into_iter() {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Kind {
+                kind,
+                uppercase: false,
+            }));
+        }
+
+        tags.extend(urls.into_iter().map(Tag::from));
+
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for HandlerInformation {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let id: &str = tags
+            .iter()
+            .find(|tag| tag.kind() == TagKind::d())
+            .and_then(|tag| tag.content())
+            .ok_or(Error::IdentifierMissing)?;
+
+        let mut handler = Self::new(id);
+
+        for tag in tags.iter() {
+            let values: &[String] = tag.as_slice();
+            let Some(value) = values.get(1) else {
+                continue;
+            };
+
+            if tag.kind() == TagKind::k() {
+                // Ignore invalid kinds
+                if let Ok(kind) = Kind::from_str(value) {
+                    handler.kinds.push(kind);
+                }
+            } else if tag.single_letter_tag().is_none() && value.contains(BECH32_PLACEHOLDER) {
+                handler.urls.push(HandlerUrl {
+                    platform: tag.kind().to_string(),
+                    template: value.clone(),
+                    entity: values.get(2).filter(|e| !e.is_empty()).cloned(),
+                });
+            }
+        }
+
+        Ok(handler)
+    }
+}
+
+impl TryFrom<&Event> for HandlerInformation {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind != Kind::HandlerInformation {
+            return Err(Error::UnexpectedKind(event.kind));
+        }
+
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Recommended handler
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RecommendedHandler {
+    /// Coordinate of the handler information
+    pub coordinate: Coordinate,
+    /// Relay URL where to find the handler information
+    pub relay_url: Option<RelayUrl>,
+    /// Platform
+    ///
+    /// If `None`, the handler is recommended for all its platforms.
+    pub platform: Option<String>,
+}
+
+impl From<RecommendedHandler> for Tag {
+    fn from(handler: RecommendedHandler) -> Self {
+        let mut values: Vec<String> = Vec::with_capacity(3);
+        values.push(handler.coordinate.to_string());
+
+        match (handler.relay_url, handler.platform) {
+            (relay_url, Some(platform)) => {
+                values.push(relay_url.map(|u| u.to_string()).unwrap_or_default());
+                values.push(platform);
+            }
+            (Some(relay_url), None) => values.push(relay_url.to_string()),
+            (None, None) => {}
+        }
+
+        Self::custom(TagKind::a(), values)
+    }
+}
+
+/// Handler recommendation
+///
+/// Published by the users with kind [`Kind::HandlerRecommendation`],
+/// to recommend the handlers of a kind.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandlerRecommendation {
+    /// Recommended kind
+    pub kind: Kind,
+    /// Recommended handlers
+    pub handlers: Vec<RecommendedHandler>,
+}
+
+impl HandlerRecommendation {
+    /// New handler recommendation
+    #[inline]
+    pub fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            handlers: Vec::new(),
+        }
+    }
+}
+
+impl From<HandlerRecommendation> for Vec<Tag> {
+    fn from(recommendation: HandlerRecommendation) -> Self {
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + recommendation.handlers.len());
+        tags.push(Tag::identifier(recommendation.kind.to_string()));
+        tags.extend(recommendation.handlers.into_iter().map(Tag::from));
+        tags
+    }
+}
+
+impl TryFrom<Vec<Tag>> for HandlerRecommendation {
+    type Error = Error;
+
+    fn try_from(tags: Vec<Tag>) -> Result<Self, Self::Error> {
+        let kind: &str = tags
+            .iter()
+            .find(|tag| tag.kind() == TagKind::d())
+            .and_then(|tag| tag.content())
+            .ok_or(Error::IdentifierMissing)?;
+        let kind: Kind = Kind::from_str(kind).map_err(|_| Error::InvalidKind(kind.to_string()))?;
+
+        let mut recommendation = Self::new(kind);
+
+        for tag in tags.iter().filter(|tag| tag.kind() == TagKind::a()) {
+            let values: &[String] = tag.as_slice();
+
+            // Ignore the invalid coordinates and the ones not pointing to a handler
+            let coordinate: Coordinate = match values.get(1).map(|c| Coordinate::parse(c)) {
+                Some(Ok(coordinate)) if coordinate.kind == Kind::HandlerInformation => coordinate,
+                _ => continue,
+            };
+
+            recommendation.handlers.push(RecommendedHandler {
+                coordinate,
+                relay_url: values.get(2).and_then(|u| RelayUrl::parse(u).ok()),
+                platform: values.get(3).filter(|p| !p.is_empty()).cloned(),
+            });
+        }
+
+        Ok(recommendation)
+    }
+}
+
+impl TryFrom<&Event> for HandlerRecommendation {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind != Kind::HandlerRecommendation {
+            return Err(Error::UnexpectedKind(event.kind));
+        }
+
+        Self::try_from(event.tags.as_slice().to_vec())
+    }
+}
+
+/// Handler candidate
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandlerCandidate {
+    /// Coordinate of the handler information
+    pub handler: Coordinate,
+    /// Platform
+    pub platform: String,
+    /// URL, with the entity already substituted
+    pub url: String,
+    /// Number of followed public keys recommending the handler
+    pub recommendations: usize,
+}
+
+/// Resolve the handlers of an entity
+///
+/// Returns the URLs of the handlers supporting the kind, for each of their platforms,
+/// ranked by the number of followed public keys recommending them.
+/// The handlers with the same number of recommendations keep the order in which they were received.
+///
+/// Only the latest version of each handler information and recommendation is used.
+pub fn resolve_handlers<'a, 'b, I1, I2>(
+    kind: Kind,
+    entity: &Nip19,
+    handlers: I1,
+    recommendations: I2,
+    follows: &BTreeSet<PublicKey>,
+) -> Result<Vec<HandlerCandidate>, Error>
+where
+    I1: IntoIterator<Item = &'a Event>,
+    I2: IntoIterator<Item = &'b Event>,
+{
+    let prefix: &str = entity_prefix(entity).ok_or(Error::UnsupportedEntity)?;
+    let bech32: String = entity.to_bech32()?;
+
+    // Latest recommendation of the kind, for each followed public key.
+    // The recommendations are addressable by kind (`d` tag): filter them before deduplicating,
+    // to not replace the recommendation of the kind with a newer one of another kind.
+    let mut latest: BTreeMap<PublicKey, (Timestamp, HandlerRecommendation)> = BTreeMap::new();
+    for event in recommendations.into_iter() {
+        if !follows.contains(&event.pubkey) {
+            continue;
+        }
+
+        let recommendation: HandlerRecommendation = match HandlerRecommendation::try_from(event) {
+            Ok(recommendation) if recommendation.kind == kind => recommendation,
+            _ => continue,
+        };
+
+        match latest.get(&event.pubkey) {
+            Some((created_at, ..)) if *created_at >= event.created_at => {}
+            _ => {
+                latest.insert(event.pubkey, (event.created_at, recommendation));
+            }
+        }
+    }
+
+    // Recommended handlers, with the public key recommending them
+    let recommended: Vec<(PublicKey, RecommendedHandler)> = latest
+        .into_iter()
+        .flat_map(|(public_key, (_, recommendation))| {
+            recommendation
+                .handlers
+                .into_iter()
+                .map(move |handler| (public_key, handler))
+        })
+        .collect();
+
+    // Latest version of each handler supporting the kind
+    let mut handlers_list: Vec<(Coordinate, Timestamp, HandlerInformation)> = Vec::new();
+    for event in handlers.into_iter() {
+        let Ok(handler) = HandlerInformation::try_from(event) else {
+            continue;
+        };
+
+        let coordinate: Coordinate =
+            Coordinate::new(Kind::HandlerInformation, event.pubkey).identifier(&handler.id);
+
+        match handlers_list.iter_mut().find(|(c, ..)| c == &coordinate) {
+            Some((_, created_at, current)) => {
+                if event.created_at > *created_at {
+                    *created_at = event.created_at;
+                    *current = handler;
+                }
+            }
+            None => handlers_list.push((coordinate, event.created_at, handler)),
+        }
+    }
+
+    let mut candidates: Vec<HandlerCandidate> = Vec::new();
+    for (coordinate, _, handler) in handlers_list.iter() {
+        if !handler.supports(&kind) {
+            continue;
+        }
+
+        for url in handler.urls_for(prefix).into_iter() {
+            // Count the public keys, not the tags: the same handler may be listed more than once
+            let recommendations: usize = recommended
+                .iter()
+                .filter(|(_, r)| {
+                    &r.coordinate == coordinate
+                        && r.platform.as_ref().map_or(true, |p| p == &url.platform)
+                })
+                .map(|(public_key, ..)| public_key)
+                .collect::<BTreeSet<&PublicKey>>()
+                .len();
+
+            candidates.push(HandlerCandidate {
+                handler: coordinate.clone(),
+                platform: url.platform.clone(),
+                url: url.resolve(&bech32),
+                recommendations,
+            });
+        }
+    }
+
+    // Stable sort: keep the order of the handlers with the same recommendations
+    candidates.sort_by(|a, b| b.recommendations.cmp(&a.recommendations));
+
+    Ok(candidates)
+}
+
+/// Get the NIP19 prefix of the entities that can be passed to an application
+fn entity_prefix(entity: &Nip19) -> Option<&'static str> {
+    match entity {
+        Nip19::Pubkey(..) => Some(nip19::PREFIX_BECH32_PUBLIC_KEY),
+        Nip19::Profile(..) => Some(nip19::PREFIX_BECH32_PROFILE),
+        Nip19::EventId(..) => Some(nip19::PREFIX_BECH32_NOTE_ID),
+        Nip19::Event(..) => Some(nip19::PREFIX_BECH32_EVENT),
+        Nip19::Coordinate(..) => Some(nip19::PREFIX_BECH32_COORDINATE),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Metadata};
+
+    fn web_url(template: &str, entity: Option<&str>) -> HandlerUrl {
+        HandlerUrl {
+            platform: String::from("web"),
+            template: template.to_string(),
+            entity: entity.map(|e| e.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_handler_information_tags() {
+        let mut handler = HandlerInformation::new("app");
+        handler.kinds.push(Kind::LongFormTextNote);
+        handler
+            .urls
+            .push(web_url("https://example.com/a/<bech32>", Some("naddr")));
+        handler
+            .urls
+            .push(web_url("https://example.com/e/<bech32>", None));
+        handler.urls.push(HandlerUrl {
+            platform: String::from("ios"),
+            template: String::from("example:<bech32>"),
+            entity: None,
+        });
+
+        let tags: Vec<Tag> = handler.clone().into();
+        assert_eq!(
+            tags[2].as_slice(),
+            &["web", "https://example.com/a/<bech32>", "naddr"]
+        );
+        assert_eq!(HandlerInformation::try_from(tags).unwrap(), handler);
+
+        // The dedicated URL is preferred
+        let urls = handler.urls_for("naddr");
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[0].template, "https://example.com/a/<bech32>");
+        assert_eq!(urls[1].platform, "ios");
+
+        let urls = handler.urls_for("nevent");
+        assert_eq!(urls[0].template, "https://example.com/e/<bech32>");
+    }
+
+    #[test]
+    fn test_handler_recommendation_tags() {
+        let keys = Keys::generate();
+        let mut recommendation = HandlerRecommendation::new(Kind::LongFormTextNote);
+        recommendation.handlers.push(RecommendedHandler {
+            coordinate: Coordinate::new(Kind::HandlerInformation, keys.public_key())
+                .identifier("app"),
+            relay_url: None,
+            platform: Some(String::from("web")),
+        });
+
+        let tags: Vec<Tag> = recommendation.clone().into();
+        assert_eq!(tags[0].content(), Some("30023"));
+        assert_eq!(
+            HandlerRecommendation::try_from(tags).unwrap(),
+            recommendation
+        );
+
+        let tags = vec![Tag::identifier("abc")];
+        assert_eq!(
+            HandlerRecommendation::try_from(tags).unwrap_err(),
+            Error::InvalidKind(String::from("abc"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_handlers() {
+        let app1 = Keys::generate();
+        let app2 = Keys::generate();
+        let friend1 = Keys::generate();
+        let friend2 = Keys::generate();
+        let stranger = Keys::generate();
+
+        let handler = |keys: &Keys, template: &str, created_at: u64| {
+            let mut handler = HandlerInformation::new("app");
+            handler.kinds.push(Kind::LongFormTextNote);
+            handler.urls.push(web_url(template, None));
+            EventBuilder::handler_information(Some(&Metadata::new().name("App")), handler)
+                .custom_created_at(Timestamp::from_secs(created_at))
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+        let handlers = vec![
+            handler(&app1, "https://one.example.com/old/<bech32>", 1),
+            handler(&app1, "https://one.example.com/<bech32>", 2),
+            handler(&app2, "https://two.example.com/<bech32>", 1),
+        ];
+
+        let recommend = |keys: &Keys, app: &Keys| {
+            let mut recommendation = HandlerRecommendation::new(Kind::LongFormTextNote);
+            recommendation.handlers.push(RecommendedHandler {
+                coordinate: Coordinate::new(Kind::HandlerInformation, app.public_key())
+                    .identifier("app"),
+                relay_url: None,
+                platform: None,
+            });
+            EventBuilder::handler_recommendation(recommendation)
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+        let recommendations = vec![
+            recommend(&friend1, &app2),
+            recommend(&friend2, &app2),
+            recommend(&stranger, &app1),
+        ];
+
+        let follows: BTreeSet<PublicKey> = [friend1.public_key(), friend2.public_key()]
+            .into_iter()
+            .collect();
+
+        let entity = Nip19::Coordinate(nip19::Nip19Coordinate {
+            coordinate: Coordinate::new(Kind::LongFormTextNote, app1.public_key())
+                .identifier("article"),
+            relays: Vec::new(),
+        });
+        let bech32: String = entity.to_bech32().unwrap();
+
+        let candidates = resolve_handlers(
+            Kind::LongFormTextNote,
+            &entity,
+            &handlers,
+            &recommendations,
+            &follows,
+        )
+        .unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            candidates[0].url,
+            format!("https://two.example.com/{bech32}")
+        );
+        assert_eq!(candidates[0].recommendations, 2);
+        assert_eq!(
+            candidates[1].url,
+            format!("https://one.example.com/{bech32}")
+        );
+        assert_eq!(candidates[1].recommendations, 0);
+
+        // Kind not supported
+        let candidates = resolve_handlers(
+            Kind::TextNote,
+            &entity,
+            &handlers,
+            &recommendations,
+            &follows,
+        )
+        .unwrap();
+        assert!(candidates.is_empty());
+
+        // Secret key
+        let entity = Nip19::Secret(Keys::generate().secret_key().clone());
+        assert_eq!(
+            resolve_handlers(
+                Kind::TextNote,
+                &entity,
+                &handlers,
+                &recommendations,
+                &follows
+            )
+            .unwrap_err(),
+            Error::UnsupportedEntity
+        );
+    }
+
+    #[test]
+    fn test_resolve_handlers_recommending_multiple_kinds() {
+        let app = Keys::generate();
+        let friend = Keys::generate();
+
+        let mut handler = HandlerInformation::new("app");
+        handler.kinds.push(Kind::TextNote);
+        handler.kinds.push(Kind::LongFormTextNote);
+        handler
+            .urls
+            .push(web_url("https://app.example.com/<bech32>", None));
+        let handlers = vec![EventBuilder::handler_information(None, handler)
+            .sign_with_keys(&app)
+            .unwrap()];
+
+        // The same follower recommends the app for two kinds
+        let recommend = |kind: Kind, created_at: u64| {
+            let mut recommendation = HandlerRecommendation::new(kind);
+            recommendation.handlers.push(RecommendedHandler {
+                coordinate: Coordinate::new(Kind::HandlerInformation, app.public_key())
+                    .identifier("app"),
+                relay_url: None,
+                platform: None,
+            });
+            EventBuilder::handler_recommendation(recommendation)
+                .custom_created_at(Timestamp::from_secs(created_at))
+                .sign_with_keys(&friend)
+                .unwrap()
+        };
+        let recommendations = vec![
+            recommend(Kind::LongFormTextNote, 1),
+            recommend(Kind::TextNote, 2),
+        ];
+
+        let follows: BTreeSet<PublicKey> = [friend.public_key()].into_iter().collect();
+        let entity = Nip19::Pubkey(friend.public_key());
+
+        for kind in [Kind::LongFormTextNote, Kind::TextNote] {
+            let candidates =
+                resolve_handlers(kind, &entity, &handlers, &recommendations, &follows).unwrap();
+            assert_eq!(candidates.len(), 1);
+            assert_eq!(candidates[0].recommendations, 1);
+        }
+    }
+
+    #[test]
+    fn test_resolve_handlers_duplicated_recommendation() {
+        let app = Keys::generate();
+        let friend = Keys::generate();
+
+        let mut handler = HandlerInformation::new("app");
+        handler.kinds.push(Kind::TextNote);
+        handler
+            .urls
+            .push(web_url("https://app.example.com/<bech32>", None));
+        let handlers = vec![EventBuilder::handler_information(None, handler)
+            .sign_with_keys(&app)
+            .unwrap()];
+
+        // The same handler listed twice, with and without platform
+        let coordinate =
+            Coordinate::new(Kind::HandlerInformation, app.public_key()).identifier("app");
+        let mut recommendation = HandlerRecommendation::new(Kind::TextNote);
+        recommendation.handlers.push(RecommendedHandler {
+            coordinate: coordinate.clone(),
+            relay_url: None,
+            platform: None,
+        });
+        recommendation.handlers.push(RecommendedHandler {
+            coordinate,
+            relay_url: None,
+            platform: Some(String::from("web")),
+        });
+        let recommendations = vec![EventBuilder::handler_recommendation(recommendation)
+            .sign_with_keys(&friend)
+            .unwrap()];
+        assert_eq!(recommendations[0].tags.coordinates().count(), 2);
+
+        let follows: BTreeSet<PublicKey> = [friend.public_key()].into_iter().collect();
+        let entity = Nip19::Pubkey(friend.public_key());
+
+        let candidates = resolve_handlers(
+            Kind::TextNote,
+            &entity,
+            &handlers,
+            &recommendations,
+            &follows,
+        )
+        .unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].recommendations, 1);
+    }
+}
@@ -40,7 +40,7 @@ pub use crate::nips::nip06::{self, *};
 pub use crate::nips::nip07::{self, *};
 pub use crate::nips::nip09::{self, *};
 pub use crate::nips::nip10::{self, *};
-#[cfg(all(feature = "std", feature = "nip11"))]
+#[cfg(feature = "std")]
 pub use crate::nips::nip11::{self, *};
 pub use crate::nips::nip13::{self, *};
 pub use crate::nips::nip15::{self, *};
@@ -48,6 +48,7 @@ pub use crate::nips::nip17::{self, *};
 pub use crate::nips::nip19::{self, *};
 pub use crate::nips::nip21::{self, *};
 pub use crate::nips::nip26::{self, *};
+pub use crate::nips::nip29::{self, *};
 pub use crate::nips::nip34::{self, *};
 pub use crate::nips::nip35::{self, *};
 pub use crate::nips::nip38::{self, *};
@@ -63,6 +64,7 @@ pub use crate::nips::nip48::{self, *};
 #[cfg(feature = "nip49")]
 pub use crate::nips::nip49::{self, *};
 pub use crate::nips::nip51::{self, *};
+pub use crate::nips::nip52::{self, *};
 pub use crate::nips::nip53::{self, *};
 pub use crate::nips::nip56::{self, *};
 #[cfg(feature = "nip57")]
@@ -72,6 +74,10 @@ pub use crate::nips::nip58;
 pub use crate::nips::nip59::{self, *};
 pub use crate::nips::nip62::{self, *};
 pub use crate::nips::nip65::{self, *};
+pub use crate::nips::nip72::{self, *};
+#[cfg(feature = "std")]
+pub use crate::nips::nip86;
+pub use crate::nips::nip89::{self, *};
 pub use crate::nips::nip90::{self, *};
 pub use crate::nips::nip94::{self, *};
 #[cfg(all(feature = "std", feature = "nip96"))]